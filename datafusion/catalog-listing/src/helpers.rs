@@ -21,12 +21,14 @@ use std::mem;
 use std::sync::Arc;
 
 use datafusion_catalog::Session;
-use datafusion_common::internal_err;
+use datafusion_common::{exec_datafusion_err, internal_err, plan_err};
 use datafusion_common::{HashMap, Result, ScalarValue};
 use datafusion_datasource::ListingTableUrl;
 use datafusion_datasource::PartitionedFile;
 use datafusion_expr::{BinaryExpr, Operator};
 
+use chrono::{NaiveDate, NaiveDateTime};
+
 use arrow::{
     array::{Array, ArrayRef, AsArray, StringBuilder},
     compute::{and, cast, prep_null_mask_filter},
@@ -243,6 +245,7 @@ async fn prune_partitions(
     partitions: Vec<Partition>,
     filters: &[Expr],
     partition_cols: &[(String, DataType)],
+    partition_value_formats: &std::collections::HashMap<String, String>,
 ) -> Result<Vec<Partition>> {
     if filters.is_empty() {
         return Ok(partitions);
@@ -258,8 +261,17 @@ async fn prune_partitions(
             .unwrap_or_default();
 
         let mut builders = builders.iter_mut();
-        for (p, b) in parsed.iter().zip(&mut builders) {
-            b.append_value(p);
+        for ((p, (name, data_type)), b) in
+            parsed.iter().zip(partition_cols).zip(&mut builders)
+        {
+            // values not matching the format are null, as values failing the cast
+            match partition_value_formats.get(name) {
+                Some(format) => match normalize_partition_value(p, data_type, format) {
+                    Ok(value) => b.append_value(value),
+                    Err(_) => b.append_null(),
+                },
+                None => b.append_value(p),
+            }
         }
         builders.for_each(|b| b.append_null());
     }
@@ -413,6 +425,7 @@ pub async fn pruned_partition_list<'a>(
     filters: &'a [Expr],
     file_extension: &'a str,
     partition_cols: &'a [(String, DataType)],
+    partition_value_formats: &'a std::collections::HashMap<String, String>,
 ) -> Result<BoxStream<'a, Result<PartitionedFile>>> {
     // if no partition col => simply list all the files
     if partition_cols.is_empty() {
@@ -437,8 +450,14 @@ pub async fn pruned_partition_list<'a>(
             .await?;
     debug!("Listed {} partitions", partitions.len());
 
-    let pruned =
-        prune_partitions(table_path, partitions, filters, partition_cols).await?;
+    let pruned = prune_partitions(
+        table_path,
+        partitions,
+        filters,
+        partition_cols,
+        partition_value_formats,
+    )
+    .await?;
 
     debug!("Pruning yielded {} partitions", pruned.len());
 
@@ -451,8 +470,9 @@ pub async fn pruned_partition_list<'a>(
                 .into_iter()
                 .flatten()
                 .zip(partition_cols)
-                .map(|(parsed, (_, datatype))| {
-                    ScalarValue::try_from_string(parsed.to_string(), datatype)
+                .map(|(parsed, (name, datatype))| {
+                    let format = partition_value_formats.get(name).map(String::as_str);
+                    parse_partition_value(parsed, datatype, format)
                 })
                 .collect::<Result<Vec<_>>>()?;
 
@@ -478,6 +498,13 @@ pub async fn pruned_partition_list<'a>(
                     statistics: None,
                     extensions: None,
                     metadata_size_hint: None,
+                    object_store_url: None,
+                    sub_files: vec![],
+                    file_schema_override: None,
+                    row_group_statistics: None,
+                    ranges: vec![],
+                    tombstoned: false,
+                    expected_checksum: None,
                 })
             }));
 
@@ -489,6 +516,57 @@ pub async fn pruned_partition_list<'a>(
     Ok(stream)
 }
 
+/// Parses the `value` of a partition column of type `data_type` from a file path.
+///
+/// Values are cast from strings with the Arrow cast kernels, e.g. `2024-01-31`
+/// for `Date32`, `2024-01-31T10:00:00` for timestamps or `1 day` for
+/// intervals. For date and timestamp columns, `format` is an optional
+/// [`chrono` format string] to parse values in other formats, e.g. `%Y%m%d`
+/// for `20240131`.
+///
+/// [`chrono` format string]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+pub fn parse_partition_value(
+    value: &str,
+    data_type: &DataType,
+    format: Option<&str>,
+) -> Result<ScalarValue> {
+    let value = match format {
+        Some(format) => normalize_partition_value(value, data_type, format)?,
+        None => value.to_string(),
+    };
+    ScalarValue::try_from_string(value, data_type)
+}
+
+/// Parses `value` with the date or timestamp `format` and returns it in the
+/// format expected by the Arrow cast kernels
+fn normalize_partition_value(
+    value: &str,
+    data_type: &DataType,
+    format: &str,
+) -> Result<String> {
+    let value_type = match data_type {
+        DataType::Dictionary(_, value_type) => value_type.as_ref(),
+        data_type => data_type,
+    };
+    let normalized = match value_type {
+        DataType::Date32 | DataType::Date64 => {
+            NaiveDate::parse_from_str(value, format).map(|date| date.to_string())
+        }
+        DataType::Timestamp(_, _) => NaiveDateTime::parse_from_str(value, format)
+            .map(|ts| ts.format("%Y-%m-%dT%H:%M:%S%.f").to_string()),
+        _ => {
+            return plan_err!(
+                "Partition value formats are only supported for date and timestamp columns, got {data_type}"
+            )
+        }
+    };
+    normalized.map_err(|e| {
+        exec_datafusion_err!(
+            "Failed to parse partition value '{value}' with format '{format}': {e}"
+        )
+    })
+}
+
 /// Extract the partition values for the given `file_path` (in the given `table_path`)
 /// associated to the partitions defined by `table_partition_cols`
 pub fn parse_partitions_for_path<'a, I>(
@@ -530,6 +608,7 @@ pub fn describe_partition(partition: &Partition) -> (&str, usize, Vec<&str>) {
 
 #[cfg(test)]
 mod tests {
+    use arrow::datatypes::TimeUnit;
     use async_trait::async_trait;
     use datafusion_common::config::TableOptions;
     use datafusion_datasource::file_groups::FileGroup;
@@ -603,6 +682,7 @@ mod tests {
             &[filter],
             ".parquet",
             &[(String::from("mypartition"), DataType::Utf8)],
+            &Default::default(),
         )
         .await
         .expect("partition pruning failed")
@@ -628,6 +708,7 @@ mod tests {
             &[filter],
             ".parquet",
             &[(String::from("mypartition"), DataType::Utf8)],
+            &Default::default(),
         )
         .await
         .expect("partition pruning failed")
@@ -671,6 +752,7 @@ mod tests {
                 (String::from("part1"), DataType::Utf8),
                 (String::from("part2"), DataType::Utf8),
             ],
+            &Default::default(),
         )
         .await
         .expect("partition pruning failed")
@@ -699,6 +781,72 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_pruned_partition_list_temporal() {
+        let (store, state) = make_test_store_and_state(&[
+            (
+                "tablepath/day=2024-01-31/ts=20240131T1030/file.parquet",
+                100,
+            ),
+            (
+                "tablepath/day=2024-02-01/ts=20240201T0000/file.parquet",
+                100,
+            ),
+        ]);
+        let partition_cols = [
+            (String::from("day"), DataType::Date32),
+            (
+                String::from("ts"),
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+            ),
+        ];
+        let formats = std::collections::HashMap::from([(
+            "ts".to_string(),
+            "%Y%m%dT%H%M".to_string(),
+        )]);
+        let filter = Expr::eq(
+            col("ts"),
+            lit(ScalarValue::TimestampMicrosecond(
+                Some(1706697000000000),
+                None,
+            )),
+        );
+        let pruned = pruned_partition_list(
+            state.as_ref(),
+            store.as_ref(),
+            &ListingTableUrl::parse("file:///tablepath/").unwrap(),
+            &[filter],
+            ".parquet",
+            &partition_cols,
+            &formats,
+        )
+        .await
+        .expect("partition pruning failed")
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(
+            pruned[0].partition_values,
+            vec![
+                ScalarValue::Date32(Some(19753)),
+                ScalarValue::TimestampMicrosecond(Some(1706697000000000), None),
+            ]
+        );
+
+        // values not matching the format are an error
+        let err = parse_partition_value(
+            "2024-01-31",
+            &partition_cols[1].1,
+            Some("%Y%m%dT%H%M"),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Failed to parse partition value '2024-01-31'"));
+    }
+
     #[tokio::test]
     async fn test_list_partition() {
         let (store, _) = make_test_store_and_state(&[
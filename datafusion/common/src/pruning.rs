@@ -295,6 +295,8 @@ impl PrunableStatistics {
         get_stat: impl Fn(&ColumnStatistics) -> &Precision<ScalarValue>,
     ) -> Option<ArrayRef> {
         let index = self.schema.index_of(column.name()).ok()?;
+        // missing values must be nulls of the column type to build an array
+        let null = ScalarValue::try_from(self.schema.field(index).data_type()).ok()?;
         let mut has_value = false;
         match ScalarValue::iter_to_array(self.statistics.iter().map(|s| {
             s.column_statistics
@@ -307,7 +309,7 @@ impl PrunableStatistics {
                         None
                     }
                 })
-                .unwrap_or(ScalarValue::Null)
+                .unwrap_or_else(|| null.clone())
         })) {
             // If there is any non-null value and no errors, return the array
             Ok(array) => has_value.then_some(array),
@@ -761,6 +763,41 @@ mod tests {
         assert!(pruning_stats.contained(&column_a, &values).is_none());
     }
 
+    #[test]
+    fn test_statistics_pruning_statistics_partially_missing() {
+        let statistics = vec![
+            Arc::new(
+                Statistics::default().add_column_statistics(
+                    ColumnStatistics::new_unknown()
+                        .with_min_value(Precision::Exact(ScalarValue::from(1i32)))
+                        .with_max_value(Precision::Exact(ScalarValue::from(10i32))),
+                ),
+            ),
+            // the statistics of the column are not known for this container
+            Arc::new(
+                Statistics::default()
+                    .add_column_statistics(ColumnStatistics::new_unknown()),
+            ),
+        ];
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let pruning_stats = PrunableStatistics::new(statistics, schema);
+
+        // the missing values are nulls of the column type
+        let column_a = Column::new_unqualified("a");
+        let min_values = pruning_stats.min_values(&column_a).unwrap();
+        let min_values = as_int32_array(&min_values).unwrap();
+        assert_eq!(
+            min_values.into_iter().collect::<Vec<_>>(),
+            vec![Some(1), None]
+        );
+        let max_values = pruning_stats.max_values(&column_a).unwrap();
+        let max_values = as_int32_array(&max_values).unwrap();
+        assert_eq!(
+            max_values.into_iter().collect::<Vec<_>>(),
+            vec![Some(10), None]
+        );
+    }
+
     #[test]
     fn test_composite_pruning_statistics_partition_and_file() {
         // Create partition statistics
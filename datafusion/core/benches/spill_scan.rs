@@ -0,0 +1,108 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Benchmarks reloading spilled batches with
+//! `ArrowSource::spill_file_scan_config`, compared to decoding the spill files
+//! sequentially with an Arrow IPC `StreamReader`
+
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+use arrow::ipc::reader::StreamReader;
+use criterion::{criterion_group, criterion_main, Criterion};
+use datafusion::datasource::physical_plan::ArrowSource;
+use datafusion::physical_plan::collect_partitioned;
+use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, SpillMetrics};
+use datafusion::physical_plan::SpillManager;
+use datafusion::prelude::SessionContext;
+use datafusion_datasource::source::DataSourceExec;
+use datafusion_datasource::PartitionedFile;
+use datafusion_execution::disk_manager::RefCountedTempFile;
+use tokio::runtime::Runtime;
+
+const NUM_FILES: usize = 8;
+const BATCHES_PER_FILE: usize = 64;
+const ROWS_PER_BATCH: usize = 8192;
+
+fn batch(offset: usize) -> RecordBatch {
+    let values = (offset..offset + ROWS_PER_BATCH).map(|v| v as i64);
+    let ids: ArrayRef = Arc::new(Int64Array::from_iter_values(values.clone()));
+    let names: ArrayRef = Arc::new(StringArray::from_iter_values(
+        values.map(|v| format!("name-{v}")),
+    ));
+    RecordBatch::try_from_iter(vec![("id", ids), ("name", names)]).unwrap()
+}
+
+fn spill_files(spill_manager: &SpillManager) -> Vec<RefCountedTempFile> {
+    (0..NUM_FILES)
+        .map(|file| {
+            let batches = (0..BATCHES_PER_FILE)
+                .map(|i| batch((file * BATCHES_PER_FILE + i) * ROWS_PER_BATCH))
+                .collect::<Vec<_>>();
+            spill_manager
+                .spill_record_batch_and_finish(&batches, "spill_scan")
+                .unwrap()
+                .unwrap()
+        })
+        .collect()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let ctx = SessionContext::new();
+    let schema = batch(0).schema();
+    let spill_manager = SpillManager::new(
+        ctx.runtime_env(),
+        SpillMetrics::new(&ExecutionPlanMetricsSet::new(), 0),
+        Arc::clone(&schema),
+    );
+    let spill_files = spill_files(&spill_manager);
+    let files = spill_files
+        .iter()
+        .map(|file| {
+            let path = file.path();
+            let size = std::fs::metadata(path).unwrap().len();
+            PartitionedFile::new(path.to_str().unwrap(), size)
+        })
+        .collect::<Vec<_>>();
+
+    c.bench_function("spill_file_scan_config", |b| {
+        b.iter(|| {
+            let config =
+                ArrowSource::spill_file_scan_config(Arc::clone(&schema), files.clone());
+            let exec = DataSourceExec::from_data_source(config);
+            rt.block_on(collect_partitioned(exec, ctx.task_ctx()))
+                .unwrap()
+        })
+    });
+
+    c.bench_function("arrow_stream_reader", |b| {
+        b.iter(|| {
+            for file in &spill_files {
+                let reader = BufReader::new(File::open(file.path()).unwrap());
+                for batch in StreamReader::try_new(reader, None).unwrap() {
+                    batch.unwrap();
+                }
+            }
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
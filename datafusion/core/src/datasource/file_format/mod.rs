@@ -77,6 +77,13 @@ pub(crate) mod test_util {
             statistics: None,
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         }]
         .into()];
 
@@ -343,6 +343,9 @@ pub struct ListingOptions {
     ///       multiple equivalent orderings, the outer `Vec` will have a
     ///       single element.
     pub file_sort_order: Vec<Vec<SortExpr>>,
+    /// Formats of the values of partition columns in the file paths, by
+    /// column name. See [Self::with_partition_value_format] for details
+    pub partition_value_formats: HashMap<String, String>,
 }
 
 impl ListingOptions {
@@ -360,6 +363,7 @@ impl ListingOptions {
             collect_stat: false,
             target_partitions: 1,
             file_sort_order: vec![],
+            partition_value_formats: HashMap::new(),
         }
     }
 
@@ -489,6 +493,41 @@ impl ListingOptions {
         self
     }
 
+    /// Set the format of the values of the date or timestamp partition
+    /// column `column` in the file paths, and returns self.
+    ///
+    /// By default, values are cast from the path with the Arrow cast kernels,
+    /// which expect e.g. `2024-01-31` for dates and `2024-01-31T10:30:00` for
+    /// timestamps. `format` is a [`chrono` format string] to parse other
+    /// formats, such as `%Y%m%d` for paths like `/mnt/data/day=20240131/`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use arrow::datatypes::DataType;
+    /// # use datafusion::datasource::{listing::ListingOptions, file_format::parquet::ParquetFormat};
+    ///
+    /// let listing_options = ListingOptions::new(Arc::new(
+    ///     ParquetFormat::default()
+    ///   ))
+    ///   .with_table_partition_cols(vec![("day".to_string(), DataType::Date32)])
+    ///   .with_partition_value_format("day", "%Y%m%d");
+    ///
+    /// assert_eq!(listing_options.partition_value_formats["day"], "%Y%m%d");
+    /// ```
+    ///
+    /// [`chrono` format string]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+    pub fn with_partition_value_format(
+        mut self,
+        column: impl Into<String>,
+        format: impl Into<String>,
+    ) -> Self {
+        self.partition_value_formats
+            .insert(column.into(), format.into());
+        self
+    }
+
     /// Set stat collection on [`ListingOptions`] and returns self.
     ///
     /// ```
@@ -1080,6 +1119,7 @@ impl TableProvider for ListingTable {
             &[],
             &self.options.file_extension,
             &self.options.table_partition_cols,
+            &self.options.partition_value_formats,
         )
         .await?;
 
@@ -1139,6 +1179,7 @@ impl ListingTable {
                 filters,
                 &self.options.file_extension,
                 &self.options.table_partition_cols,
+                &self.options.partition_value_formats,
             )
         }))
         .await?;
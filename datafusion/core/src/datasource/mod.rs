@@ -115,6 +115,13 @@ mod tests {
             statistics: None,
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         };
 
         let f1 = Field::new("id", DataType::Int32, true);
@@ -16,6 +16,7 @@
 // under the License.
 
 use std::any::Any;
+use std::io::{BufReader, Cursor, Read};
 use std::sync::Arc;
 
 use crate::datasource::physical_plan::{FileMeta, FileOpenFuture, FileOpener};
@@ -25,11 +26,14 @@ use datafusion_datasource::schema_adapter::SchemaAdapterFactory;
 
 use arrow::buffer::Buffer;
 use arrow::datatypes::SchemaRef;
-use arrow_ipc::reader::FileDecoder;
-use datafusion_common::Statistics;
+use arrow_ipc::reader::{FileDecoder, StreamReader};
+use datafusion_common::{exec_err, Statistics};
 use datafusion_datasource::file::FileSource;
-use datafusion_datasource::file_scan_config::FileScanConfig;
+use datafusion_datasource::file_compression_type::FileCompressionType;
+use datafusion_datasource::file_groups::FileGroup;
+use datafusion_datasource::file_scan_config::{FileScanConfig, FileScanConfigBuilder};
 use datafusion_datasource::PartitionedFile;
+use datafusion_execution::object_store::ObjectStoreUrl;
 use datafusion_physical_plan::metrics::ExecutionPlanMetricsSet;
 
 use futures::StreamExt;
@@ -43,6 +47,48 @@ pub struct ArrowSource {
     metrics: ExecutionPlanMetricsSet,
     projected_statistics: Option<Statistics>,
     schema_adapter_factory: Option<Arc<dyn SchemaAdapterFactory>>,
+    /// Read files in the Arrow IPC stream format rather than the IPC file format
+    ipc_stream_format: bool,
+}
+
+impl ArrowSource {
+    /// Read files in the Arrow IPC stream format, which has no footer, rather
+    /// than the IPC file format.
+    ///
+    /// Streams can not be split into ranges, so each file is read as a whole.
+    pub fn with_ipc_stream_format(mut self, ipc_stream_format: bool) -> Self {
+        self.ipc_stream_format = ipc_stream_format;
+        self
+    }
+
+    /// Create a [`FileScanConfig`] reading back `files` spilled to the local
+    /// disk by DataFusion, e.g. by a [`SpillManager`], whose batches all have
+    /// the given `schema`.
+    ///
+    /// This is a fast path for reloading spilled data: the files are read as
+    /// uncompressed Arrow IPC streams with exactly `schema`, without schema
+    /// inference or adaptation and without partition columns. Each file is
+    /// read, in order, by its own output partition.
+    ///
+    /// [`SpillManager`]: datafusion_physical_plan::spill::SpillManager
+    pub fn spill_file_scan_config(
+        schema: SchemaRef,
+        files: Vec<PartitionedFile>,
+    ) -> FileScanConfig {
+        let source = Self::default().with_ipc_stream_format(true);
+        FileScanConfigBuilder::new(
+            ObjectStoreUrl::local_filesystem(),
+            schema,
+            Arc::new(source),
+        )
+        .with_file_groups(
+            files
+                .into_iter()
+                .map(|file| FileGroup::new(vec![file]))
+                .collect(),
+        )
+        .build()
+    }
 }
 
 impl From<ArrowSource> for Arc<dyn FileSource> {
@@ -61,6 +107,8 @@ impl FileSource for ArrowSource {
         Arc::new(ArrowOpener {
             object_store,
             projection: base_config.file_column_projection_indices(),
+            file_compression_type: base_config.file_compression_type,
+            ipc_stream_format: self.ipc_stream_format,
         })
     }
 
@@ -119,6 +167,12 @@ impl FileSource for ArrowSource {
 pub struct ArrowOpener {
     pub object_store: Arc<dyn ObjectStore>,
     pub projection: Option<Vec<usize>>,
+    /// Compression of the whole file, as opposed to the per batch compression
+    /// supported by the IPC format itself
+    pub file_compression_type: FileCompressionType,
+    /// Read the file in the Arrow IPC stream format rather than the IPC file
+    /// format
+    pub ipc_stream_format: bool,
 }
 
 impl FileOpener for ArrowOpener {
@@ -129,8 +183,53 @@ impl FileOpener for ArrowOpener {
     ) -> Result<FileOpenFuture> {
         let object_store = Arc::clone(&self.object_store);
         let projection = self.projection.clone();
+        let file_compression_type = self.file_compression_type;
+        let ipc_stream_format = self.ipc_stream_format;
         Ok(Box::pin(async move {
             let range = file_meta.range.clone();
+            if ipc_stream_format {
+                if range.is_some() {
+                    return exec_err!(
+                        "Reading a range of an Arrow IPC stream is not supported"
+                    );
+                }
+                // streams are read sequentially, so there is no need to
+                // buffer the whole file
+                let r = object_store.get(file_meta.location()).await?;
+                let reader: Box<dyn Read + Send> = match r.payload {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    GetResultPayload::File(file, _) => Box::new(BufReader::new(file)),
+                    GetResultPayload::Stream(_) => {
+                        Box::new(Cursor::new(r.bytes().await?))
+                    }
+                };
+                let reader = file_compression_type.convert_read(reader)?;
+                let arrow_reader = StreamReader::try_new(reader, projection)?;
+                return Ok(futures::stream::iter(arrow_reader).boxed());
+            }
+            if file_compression_type.is_compressed() {
+                if range.is_some() {
+                    return exec_err!(
+                        "Reading a range of a compressed Arrow IPC file is not supported"
+                    );
+                }
+                // IPC files need to be seekable, so decompress the whole file
+                // before parsing it
+                let bytes = object_store
+                    .get(file_meta.location())
+                    .await?
+                    .bytes()
+                    .await?;
+                let mut decoder =
+                    file_compression_type.convert_read(Cursor::new(bytes))?;
+                let mut decompressed = vec![];
+                decoder.read_to_end(&mut decompressed)?;
+                let arrow_reader = arrow::ipc::reader::FileReader::try_new(
+                    Cursor::new(decompressed),
+                    projection,
+                )?;
+                return Ok(futures::stream::iter(arrow_reader).boxed());
+            }
             match range {
                 None => {
                     let r = object_store.get(file_meta.location()).await?;
@@ -144,7 +243,7 @@ impl FileOpener for ArrowOpener {
                         }
                         GetResultPayload::Stream(_) => {
                             let bytes = r.bytes().await?;
-                            let cursor = std::io::Cursor::new(bytes);
+                            let cursor = Cursor::new(bytes);
                             let arrow_reader = arrow::ipc::reader::FileReader::try_new(
                                 cursor, projection,
                             )?;
@@ -254,3 +353,113 @@ impl FileOpener for ArrowOpener {
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_read_gzip_compressed_arrow_file() -> Result<()> {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use futures::TryStreamExt;
+        use object_store::{memory::InMemory, path::Path};
+        use std::io::Write;
+
+        let bytes = std::fs::read("tests/data/example.arrow")?;
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&bytes)?;
+        let compressed = encoder.finish()?;
+
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        store
+            .put(&Path::from("example.arrow"), bytes.into())
+            .await?;
+        store
+            .put(&Path::from("example.arrow.gz"), compressed.into())
+            .await?;
+
+        let mut results = vec![];
+        for (name, file_compression_type) in [
+            ("example.arrow", FileCompressionType::UNCOMPRESSED),
+            ("example.arrow.gz", FileCompressionType::GZIP),
+        ] {
+            let object_meta = store.head(&Path::from(name)).await?;
+            let opener = ArrowOpener {
+                object_store: Arc::clone(&store),
+                projection: None,
+                file_compression_type,
+                ipc_stream_format: false,
+            };
+            let file = PartitionedFile::from(object_meta.clone());
+            let stream = opener.open(object_meta.into(), file)?.await?;
+            results.push(stream.try_collect::<Vec<_>>().await?);
+        }
+
+        // the gzip wrapped file decodes to the same batches as the plain one
+        assert!(!results[0].is_empty());
+        assert_eq!(results[0], results[1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_spill_file_round_trip() -> Result<()> {
+        use crate::prelude::SessionContext;
+        use arrow::array::{Int32Array, RecordBatch, StringArray};
+        use arrow::datatypes::{DataType, Field, Schema};
+        use datafusion_datasource::source::DataSourceExec;
+        use datafusion_physical_plan::metrics::SpillMetrics;
+        use datafusion_physical_plan::SpillManager;
+        use datafusion_physical_plan::{collect_partitioned, ExecutionPlan};
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = |start: i32| {
+            RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![
+                    Arc::new(Int32Array::from_iter_values(start..start + 3)),
+                    Arc::new(StringArray::from(vec![Some("x"), None, Some("z")])),
+                ],
+            )
+            .unwrap()
+        };
+        let spilled = vec![vec![batch(0), batch(3)], vec![batch(6)]];
+
+        let ctx = SessionContext::new();
+        let spill_manager = SpillManager::new(
+            ctx.runtime_env(),
+            SpillMetrics::new(&ExecutionPlanMetricsSet::new(), 0),
+            Arc::clone(&schema),
+        );
+        // the temporary files are deleted once dropped
+        let spill_files = spilled
+            .iter()
+            .map(|batches| {
+                Ok(spill_manager
+                    .spill_record_batch_and_finish(batches, "test")?
+                    .unwrap())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let files = spill_files
+            .iter()
+            .map(|file| {
+                let path = file.path();
+                let size = std::fs::metadata(path)?.len();
+                Ok(PartitionedFile::new(path.to_str().unwrap(), size))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let config = ArrowSource::spill_file_scan_config(Arc::clone(&schema), files);
+        let exec = DataSourceExec::from_data_source(config);
+        assert_eq!(exec.properties().output_partitioning().partition_count(), 2);
+
+        // each spill file is read back, in order, by its own partition
+        let results = collect_partitioned(exec, ctx.task_ctx()).await?;
+        assert_eq!(results, spilled);
+        Ok(())
+    }
+}
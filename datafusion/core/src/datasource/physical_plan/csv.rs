@@ -44,8 +44,11 @@ mod tests {
 
     #[cfg(feature = "compression")]
     use datafusion_datasource::file_compression_type::FileCompressionType;
+    use datafusion_datasource::file_groups::FileGroup;
+    use datafusion_datasource::PartitionedFile;
     use datafusion_datasource_csv::partitioned_csv_config;
     use datafusion_datasource_csv::source::CsvSource;
+    use datafusion_execution::object_store::ObjectStoreUrl;
     use futures::{StreamExt, TryStreamExt};
 
     use arrow::datatypes::*;
@@ -410,6 +413,52 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn csv_exec_without_projection_pushdown() -> Result<()> {
+        use datafusion_common::ScalarValue;
+        use datafusion_datasource::file_compression_type::FileCompressionType;
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let file_schema = aggr_test_schema();
+        let path = format!("{}/csv", arrow_test_data());
+        let tmp_dir = TempDir::new()?;
+        let mut file_groups = partitioned_file_groups(
+            path.as_str(),
+            "aggregate_test_100.csv",
+            1,
+            Arc::new(CsvFormat::default()),
+            FileCompressionType::UNCOMPRESSED,
+            tmp_dir.path(),
+        )?;
+        file_groups[0][0].partition_values = vec![ScalarValue::from("2021-10-26")];
+
+        let source = Arc::new(CsvSource::new(true, b',', b'"'));
+        let builder = FileScanConfigBuilder::from(partitioned_csv_config(
+            file_schema,
+            file_groups,
+            source,
+        ))
+        .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+        // mixed order projection including the partition column
+        .with_projection(Some(vec![4, 13, 0]))
+        .with_limit(Some(10));
+
+        let mut results = vec![];
+        for projection_pushdown in [true, false] {
+            let config = builder
+                .clone()
+                .with_projection_pushdown(projection_pushdown)
+                .build();
+            let csv = DataSourceExec::from_data_source(config);
+            results.push(collect(csv, session_ctx.task_ctx()).await?);
+        }
+
+        assert_eq!(results[0][0].num_columns(), 3);
+        assert_eq!(results[0], results[1]);
+        Ok(())
+    }
+
     /// Generate CSV partitions within the supplied directory
     fn populate_csv_partitions(
         tmp_dir: &TempDir,
@@ -576,6 +625,856 @@ mod tests {
         assert_eq!(e.strip_backtrace(), "Arrow error: Csv error: incorrect number of fields for line 1, expected 2 got more than 2")
     }
 
+    #[tokio::test]
+    async fn test_per_file_object_store() -> Result<()> {
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let mut files = vec![];
+        for (url, name, data) in [
+            ("memory://", "a.csv", "a,b\n1,2\n"),
+            ("remote://", "b.csv", "a,b\n3,4\n"),
+        ] {
+            let store = Arc::new(object_store::memory::InMemory::new());
+            let path = object_store::path::Path::from(name);
+            store.put(&path, Bytes::from(data).into()).await?;
+            let file = PartitionedFile::from(store.head(&path).await?);
+            session_ctx.register_object_store(&Url::parse(url).unwrap(), store);
+            files.push(file.with_object_store_url(ObjectStoreUrl::parse(url)?));
+        }
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ]));
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("memory://")?,
+            file_schema,
+            Arc::new(CsvSource::new(true, b',', b'"')),
+        )
+        .with_file_group(FileGroup::new(files))
+        .build();
+        let exec = DataSourceExec::from_data_source(config);
+
+        let result = collect(exec, session_ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+---+
+            | a | b |
+            +---+---+
+            | 1 | 2 |
+            | 3 | 4 |
+            +---+---+
+        "###);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sort_merge_on_read() -> Result<()> {
+        use datafusion_physical_expr::expressions::col;
+        use datafusion_physical_expr_common::sort_expr::{LexOrdering, PhysicalSortExpr};
+        use datafusion_physical_plan::{collect, ExecutionPlanProperties};
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        let mut files = vec![];
+        // every file is sorted, but they fully overlap
+        for (name, data) in [
+            (
+                "a.csv",
+                "a,b
+1,a
+4,a
+7,a
+",
+            ),
+            (
+                "b.csv",
+                "a,b
+2,b
+5,b
+8,b
+",
+            ),
+            (
+                "c.csv",
+                "a,b
+3,c
+6,c
+9,c
+",
+            ),
+        ] {
+            let path = object_store::path::Path::from(name);
+            store.put(&path, Bytes::from(data).into()).await?;
+            files.push(PartitionedFile::from(store.head(&path).await?));
+        }
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let ordering = LexOrdering::new(vec![PhysicalSortExpr::new_default(col(
+            "a",
+            &file_schema,
+        )?)])
+        .unwrap();
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("memory://")?,
+            Arc::clone(&file_schema),
+            Arc::new(CsvSource::new(true, b',', b'"')),
+        )
+        .with_file_groups(vec![
+            FileGroup::new(files[..2].to_vec()),
+            FileGroup::new(files[2..].to_vec()),
+        ])
+        .with_projection(Some(vec![0, 1]))
+        .with_output_ordering(vec![ordering.clone()])
+        .with_sort_merge_on_read(true)
+        .with_batch_size(Some(2))
+        .build();
+        let exec: Arc<dyn ExecutionPlan> = DataSourceExec::from_data_source(config);
+        assert_eq!(exec.output_partitioning().partition_count(), 1);
+        assert_eq!(exec.output_ordering(), Some(&ordering));
+
+        let result = collect(exec, session_ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+---+
+            | a | b |
+            +---+---+
+            | 1 | a |
+            | 2 | b |
+            | 3 | c |
+            | 4 | a |
+            | 5 | b |
+            | 6 | c |
+            | 7 | a |
+            | 8 | b |
+            | 9 | c |
+            +---+---+
+        "###);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_schema_override() -> Result<()> {
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        let mut files = vec![];
+        // every file has its own schema, e.g. recorded in a manifest
+        for (name, data, schema) in [
+            (
+                "a.csv",
+                "a,b\n1,x\n2,y\n",
+                Schema::new(vec![
+                    Field::new("a", DataType::Int64, true),
+                    Field::new("b", DataType::Utf8, true),
+                ]),
+            ),
+            (
+                "b.csv",
+                "c,a\n0.5,3\n1.5,4\n",
+                Schema::new(vec![
+                    Field::new("c", DataType::Float64, true),
+                    Field::new("a", DataType::Int32, true),
+                ]),
+            ),
+        ] {
+            let path = object_store::path::Path::from(name);
+            store.put(&path, Bytes::from(data).into()).await?;
+            let file = PartitionedFile::from(store.head(&path).await?);
+            files.push(file.with_file_schema_override(Arc::new(schema)));
+        }
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+            Field::new("c", DataType::Float64, true),
+        ]));
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("memory://")?,
+            file_schema,
+            Arc::new(CsvSource::new(true, b',', b'"')),
+        )
+        .with_file_group(FileGroup::new(files))
+        .with_projection(Some(vec![2, 0, 1]))
+        .build();
+        let exec = DataSourceExec::from_data_source(config);
+        let result = collect(exec, session_ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+        +-----+---+---+
+        | c   | a | b |
+        +-----+---+---+
+        |     | 1 | x |
+        |     | 2 | y |
+        | 0.5 | 3 |   |
+        | 1.5 | 4 |   |
+        +-----+---+---+
+        "###);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_force_nullable() -> Result<()> {
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        // the producer wrote a null into the non-nullable column `b`
+        let path = object_store::path::Path::from("nulls.csv");
+        store
+            .put(&path, Bytes::from("a,b\n1,x\n2,\n").into())
+            .await?;
+        let file = PartitionedFile::from(store.head(&path).await?);
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Utf8, false),
+        ]));
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("memory://")?,
+            file_schema,
+            Arc::new(CsvSource::new(true, b',', b'"')),
+        )
+        .with_file(file);
+
+        let strict = DataSourceExec::from_data_source(builder.clone().build());
+        let err = collect(strict, session_ctx.task_ctx()).await.unwrap_err();
+        assert!(err.to_string().contains("contains null values"), "{err}");
+
+        let lenient =
+            DataSourceExec::from_data_source(builder.with_force_nullable(true).build());
+        assert!(lenient.schema().fields().iter().all(|f| f.is_nullable()));
+        let result = collect(lenient, session_ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+---+
+            | a | b |
+            +---+---+
+            | 1 | x |
+            | 2 |   |
+            +---+---+
+        "###);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_ordering() -> Result<()> {
+        use datafusion_physical_expr::expressions::col;
+        use datafusion_physical_expr_common::sort_expr::{LexOrdering, PhysicalSortExpr};
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        let mut files = vec![];
+        // each batch of `unsorted.csv` is sorted, but not the file
+        for (name, data) in [
+            ("sorted.csv", "a\n1\n2\n3\n4\n"),
+            ("unsorted.csv", "a\n1\n3\n2\n4\n"),
+        ] {
+            let path = object_store::path::Path::from(name);
+            store.put(&path, Bytes::from(data).into()).await?;
+            files.push(PartitionedFile::from(store.head(&path).await?));
+        }
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let ordering = LexOrdering::new(vec![PhysicalSortExpr::new_default(col(
+            "a",
+            &file_schema,
+        )?)])
+        .unwrap();
+        let scan = |file: &PartitionedFile| {
+            let config = FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("memory://").unwrap(),
+                Arc::clone(&file_schema),
+                Arc::new(CsvSource::new(true, b',', b'"')),
+            )
+            .with_file(file.clone())
+            .with_output_ordering(vec![ordering.clone()])
+            .with_batch_size(Some(2))
+            .with_verify_ordering(true)
+            .build();
+            DataSourceExec::from_data_source(config)
+        };
+
+        let result = collect(scan(&files[0]), session_ctx.task_ctx()).await?;
+        assert_eq!(result.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+
+        let result = collect(scan(&files[1]), session_ctx.task_ctx()).await;
+        if cfg!(debug_assertions) {
+            let err = result.unwrap_err();
+            assert!(
+                err.to_string().contains(
+                    "Batch is not sorted by the declared output ordering [a@0 ASC]"
+                ),
+                "{err}"
+            );
+        } else {
+            // the verification is skipped in release builds
+            assert_eq!(result?.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_partition_column_source() -> Result<()> {
+        use datafusion_common::{assert_contains, ScalarValue};
+        use datafusion_datasource::file_scan_config::PartitionColumnSource;
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        let mut files = vec![];
+        // the partition column is also stored in the files, and disagrees
+        // with the path for the second row of `b.csv`
+        for (name, date, data) in [
+            (
+                "date=2024-01-01/a.csv",
+                "2024-01-01",
+                "a,date\n1,2024-01-01\n2,\n",
+            ),
+            (
+                "date=2024-01-02/b.csv",
+                "2024-01-02",
+                "a,date\n3,2024-01-02\n4,2024-01-03\n",
+            ),
+        ] {
+            let path = object_store::path::Path::from(name);
+            store.put(&path, Bytes::from(data).into()).await?;
+            let mut file = PartitionedFile::from(store.head(&path).await?);
+            file.partition_values = vec![ScalarValue::from(date)];
+            files.push(file);
+        }
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let scan = |files: &[PartitionedFile], source: PartitionColumnSource| {
+            let config = FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("memory://").unwrap(),
+                Arc::clone(&file_schema),
+                Arc::new(CsvSource::new(true, b',', b'"')),
+            )
+            .with_file_group(FileGroup::new(files.to_vec()))
+            .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+            .with_partition_column_source(source)
+            .build();
+            DataSourceExec::from_data_source(config)
+        };
+
+        // the file agrees with its path, a null value is not checked
+        let exec = scan(&files[..1], PartitionColumnSource::Validate);
+        let result = collect(exec, session_ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+------------+
+            | a | date       |
+            +---+------------+
+            | 1 | 2024-01-01 |
+            | 2 | 2024-01-01 |
+            +---+------------+
+        "###);
+
+        let exec = scan(&files, PartitionColumnSource::Validate);
+        let err = collect(exec, session_ctx.task_ctx()).await.unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "Partition column date has the value 2024-01-03 in the file, but 2024-01-02 in its partition values"
+        );
+
+        // the values of the file are used, unless null
+        let exec = scan(&files, PartitionColumnSource::FilePreferred);
+        let result = collect(exec, session_ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+------------+
+            | a | date       |
+            +---+------------+
+            | 1 | 2024-01-01 |
+            | 2 | 2024-01-01 |
+            | 3 | 2024-01-02 |
+            | 4 | 2024-01-03 |
+            +---+------------+
+        "###);
+
+        // the columns of the files are not read by default
+        let exec = scan(&files, PartitionColumnSource::PathOnly);
+        let err = collect(exec, session_ctx.task_ctx()).await.unwrap_err();
+        assert_contains!(err.to_string(), "incorrect number of fields");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_csv_dialect() -> Result<()> {
+        use arrow::array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+        use arrow::compute::concat_batches;
+        use datafusion_datasource::file_scan_config::CsvDialect;
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        // the second value of the second record contains an escaped quote,
+        // a delimiter and a line terminator
+        let data = "a;b\n1;x\n2;'p\\'q\nr;s'\n3;'t;u'\n";
+        let path = object_store::path::Path::from("dialect.csv");
+        store.put(&path, Bytes::from(data).into()).await?;
+        let file = PartitionedFile::from(store.head(&path).await?);
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let expected = RecordBatch::try_new(
+            Arc::clone(&file_schema),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef,
+                Arc::new(StringArray::from(vec!["x", "p'q\nr;s", "t;u"])),
+            ],
+        )?;
+        let scan = |files: Vec<PartitionedFile>| {
+            let config = FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("memory://").unwrap(),
+                Arc::clone(&file_schema),
+                Arc::new(CsvSource::new(true, b',', b'"')),
+            )
+            .with_file_group(FileGroup::new(files))
+            .with_newlines_in_values(true)
+            .with_csv_dialect(CsvDialect {
+                delimiter: b';',
+                quote: b'\'',
+                escape: Some(b'\\'),
+                comment: None,
+            })
+            .build();
+            DataSourceExec::from_data_source(config)
+        };
+
+        let result = collect(scan(vec![file.clone()]), session_ctx.task_ctx()).await?;
+        assert_eq!(concat_batches(&file_schema, &result)?, expected);
+
+        // the second range starts inside the quoted value, the record
+        // boundaries are found with the quote and escape of the dialect
+        let ranges = vec![
+            file.clone().with_range(0, 12),
+            file.clone().with_range(12, data.len() as i64),
+        ];
+        let result = collect(scan(ranges), session_ctx.task_ctx()).await?;
+        assert_eq!(concat_batches(&file_schema, &result)?, expected);
+        Ok(())
+    }
+
+    #[rstest(max_probe_bytes, case(0), case(10), case(20), case(1000))]
+    #[tokio::test]
+    async fn test_quoted_range_splitting(max_probe_bytes: u64) -> Result<()> {
+        use arrow::array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+        use arrow::compute::concat_batches;
+        use datafusion_datasource::file::FileSource;
+        use datafusion_physical_plan::collect_partitioned;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        let data = "a,b\n1,\"x\ny\"\n2,z\n3,\"p\nq\nr\"\n4,w\n";
+        let path = object_store::path::Path::from("quoted.csv");
+        store.put(&path, Bytes::from(data).into()).await?;
+        let file = PartitionedFile::from(store.head(&path).await?);
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let expected = RecordBatch::try_new(
+            Arc::clone(&file_schema),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])) as ArrayRef,
+                Arc::new(StringArray::from(vec!["x\ny", "z", "p\nq\nr", "w"])),
+            ],
+        )?;
+        let config = |source: &CsvSource| {
+            FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("memory://").unwrap(),
+                Arc::clone(&file_schema),
+                Arc::new(source.clone()),
+            )
+            .with_file(file.clone())
+            .with_newlines_in_values(true)
+            .build()
+        };
+
+        // files with newlines in values are only split if enabled
+        let source = CsvSource::new(true, b',', b'"');
+        assert!(source
+            .repartitioned(4, 0, None, &config(&source))?
+            .is_none());
+
+        let source = source.with_quoted_range_splitting(max_probe_bytes);
+        let config = source
+            .repartitioned(4, 0, None, &config(&source))?
+            .expect("file is split");
+        assert_eq!(config.file_groups.len(), 4);
+
+        // no record is split across partitions, nor read twice
+        let exec = DataSourceExec::from_data_source(config);
+        let result = collect_partitioned(exec, session_ctx.task_ctx()).await?;
+        let result = result.into_iter().flatten().collect::<Vec<_>>();
+        assert_eq!(concat_batches(&file_schema, &result)?, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_partition_index_column() -> Result<()> {
+        use datafusion_physical_plan::collect_partitioned;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        let mut file_groups = vec![];
+        for (name, data) in [("a.csv", "a\n1\n2\n"), ("b.csv", "a\n3\n")] {
+            let path = object_store::path::Path::from(name);
+            store.put(&path, Bytes::from(data).into()).await?;
+            let file = PartitionedFile::from(store.head(&path).await?);
+            file_groups.push(FileGroup::new(vec![file]));
+        }
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("memory://").unwrap(),
+            Arc::clone(&file_schema),
+            Arc::new(CsvSource::new(true, b',', b'"')),
+        )
+        .with_file_groups(file_groups)
+        .with_partition_index_column("_partition_index")
+        .build();
+        let exec = DataSourceExec::from_data_source(config);
+        assert_eq!(
+            exec.schema().field(1),
+            &Field::new("_partition_index", DataType::UInt64, false)
+        );
+
+        let result = collect_partitioned(exec, session_ctx.task_ctx()).await?;
+        assert_eq!(result.len(), 2);
+        assert_snapshot!(batches_to_string(&result[0]),@r###"
+            +---+------------------+
+            | a | _partition_index |
+            +---+------------------+
+            | 1 | 0                |
+            | 2 | 0                |
+            +---+------------------+
+        "###);
+        assert_snapshot!(batches_to_string(&result[1]),@r###"
+            +---+------------------+
+            | a | _partition_index |
+            +---+------------------+
+            | 3 | 1                |
+            +---+------------------+
+        "###);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_constant_column() -> Result<()> {
+        use datafusion_common::ScalarValue;
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        let path = object_store::path::Path::from("a.csv");
+        store
+            .put(&path, Bytes::from("a,b\n1,x\n2,y\n").into())
+            .await?;
+        let file = PartitionedFile::from(store.head(&path).await?);
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("memory://").unwrap(),
+            Arc::clone(&file_schema),
+            Arc::new(CsvSource::new(true, b',', b'"')),
+        )
+        .with_file(file)
+        .with_projection(Some(vec![1]))
+        .with_constant_column("dataset", ScalarValue::from("sales"))
+        .build();
+        let exec = DataSourceExec::from_data_source(config);
+        assert_eq!(
+            exec.schema().field(1),
+            &Field::new("dataset", DataType::Utf8, false)
+        );
+
+        let result = collect(exec, session_ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+---------+
+            | b | dataset |
+            +---+---------+
+            | x | sales   |
+            | y | sales   |
+            +---+---------+
+        "###);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_name_based_column_matching() -> Result<()> {
+        use datafusion_common::assert_contains;
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        let mut files = vec![];
+        // the columns of the second file are reordered
+        for (name, data) in [("a.csv", "a,b\n1,x\n"), ("b.csv", "b,a\ny,2\nz,3\n")] {
+            let path = object_store::path::Path::from(name);
+            store.put(&path, Bytes::from(data).into()).await?;
+            files.push(PartitionedFile::from(store.head(&path).await?));
+        }
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let scan = |name_based_column_matching: bool, projection: Option<Vec<usize>>| {
+            let config = FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("memory://").unwrap(),
+                Arc::clone(&file_schema),
+                Arc::new(CsvSource::new(true, b',', b'"')),
+            )
+            .with_file_group(FileGroup::new(files.clone()))
+            .with_projection(projection)
+            .with_name_based_column_matching(name_based_column_matching)
+            .build();
+            collect(
+                DataSourceExec::from_data_source(config),
+                session_ctx.task_ctx(),
+            )
+        };
+
+        // the columns are matched by position by default
+        let err = scan(false, None).await.unwrap_err();
+        assert_contains!(err.to_string(), "Error while parsing value 'y'");
+
+        let result = scan(true, None).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+---+
+            | a | b |
+            +---+---+
+            | 1 | x |
+            | 2 | y |
+            | 3 | z |
+            +---+---+
+        "###);
+
+        let result = scan(true, Some(vec![1])).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+
+            | b |
+            +---+
+            | x |
+            | y |
+            | z |
+            +---+
+        "###);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_ranges() -> Result<()> {
+        use datafusion_datasource::FileRange;
+        use datafusion_physical_plan::collect;
+
+        let session_ctx = SessionContext::new();
+        let store = Arc::new(object_store::memory::InMemory::new());
+        // every line is 4 bytes long, the header included
+        let data = "a,b\n1,x\n2,y\n3,z\n4,w\n5,v\n";
+        let path = object_store::path::Path::from("indexed.csv");
+        store.put(&path, Bytes::from(data).into()).await?;
+        let file = PartitionedFile::from(store.head(&path).await?);
+        session_ctx.register_object_store(&Url::parse("memory://").unwrap(), store);
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        // the lines starting in the ranges are read: the header and the
+        // first line, then the fourth line
+        let file = file.with_ranges(vec![
+            FileRange { start: 0, end: 8 },
+            FileRange { start: 14, end: 18 },
+        ]);
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("memory://").unwrap(),
+            Arc::clone(&file_schema),
+            Arc::new(CsvSource::new(true, b',', b'"')),
+        )
+        .with_file(file)
+        .build();
+        let exec = DataSourceExec::from_data_source(config);
+
+        let result = collect(exec, session_ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+---+
+            | a | b |
+            +---+---+
+            | 1 | x |
+            | 4 | w |
+            +---+---+
+        "###);
+        Ok(())
+    }
+
+    /// Object store counting the read requests sent to the inner store
+    #[derive(Debug)]
+    struct RequestCountingObjectStore {
+        inner: Arc<dyn ObjectStore>,
+        requests: std::sync::atomic::AtomicUsize,
+    }
+
+    impl std::fmt::Display for RequestCountingObjectStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "RequestCountingObjectStore({})", self.inner)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for RequestCountingObjectStore {
+        async fn put_opts(
+            &self,
+            location: &object_store::path::Path,
+            payload: object_store::PutPayload,
+            opts: object_store::PutOptions,
+        ) -> object_store::Result<object_store::PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &object_store::path::Path,
+            opts: object_store::PutMultipartOpts,
+        ) -> object_store::Result<Box<dyn object_store::MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &object_store::path::Path,
+            options: object_store::GetOptions,
+        ) -> object_store::Result<object_store::GetResult> {
+            self.requests
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.get_opts(location, options).await
+        }
+
+        async fn delete(
+            &self,
+            location: &object_store::path::Path,
+        ) -> object_store::Result<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&object_store::path::Path>,
+        ) -> futures::stream::BoxStream<
+            'static,
+            object_store::Result<object_store::ObjectMeta>,
+        > {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&object_store::path::Path>,
+        ) -> object_store::Result<object_store::ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(
+            &self,
+            from: &object_store::path::Path,
+            to: &object_store::path::Path,
+        ) -> object_store::Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(
+            &self,
+            from: &object_store::path::Path,
+            to: &object_store::path::Path,
+        ) -> object_store::Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    #[rstest(coalesce, case(false), case(true))]
+    #[tokio::test]
+    async fn test_file_ranges_coalesced(coalesce: bool) -> Result<()> {
+        use datafusion_datasource::FileRange;
+        use datafusion_physical_plan::collect;
+        use std::sync::atomic::Ordering;
+
+        let session_ctx = SessionContext::new();
+        let inner = Arc::new(object_store::memory::InMemory::new());
+        // every line is 4 bytes long, the header included
+        let data = "a,b\n1,x\n2,y\n3,z\n4,w\n5,v\n";
+        let path = object_store::path::Path::from("indexed.csv");
+        inner.put(&path, Bytes::from(data).into()).await?;
+        let file = PartitionedFile::from(inner.head(&path).await?);
+        let store = Arc::new(RequestCountingObjectStore {
+            inner,
+            requests: Default::default(),
+        });
+        session_ctx.register_object_store(
+            &Url::parse("memory://").unwrap(),
+            Arc::clone(&store) as Arc<dyn ObjectStore>,
+        );
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        // the header and the first line, then the fourth line
+        let file = file.with_ranges(vec![
+            FileRange { start: 0, end: 8 },
+            FileRange { start: 16, end: 20 },
+        ]);
+        let mut builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("memory://").unwrap(),
+            Arc::clone(&file_schema),
+            Arc::new(CsvSource::new(true, b',', b'"')),
+        )
+        .with_file(file);
+        if coalesce {
+            builder = builder.with_range_coalescing(1024);
+        }
+        let exec = DataSourceExec::from_data_source(builder.build());
+
+        let result = collect(exec, session_ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&result),@r###"
+            +---+---+
+            | a | b |
+            +---+---+
+            | 1 | x |
+            | 4 | w |
+            +---+---+
+        "###);
+
+        // the end of the first range and both ends of the second range are
+        // searched for a newline with a request each. The lines of the ranges
+        // are read with a request per range, or a single request for both
+        // ranges when they are coalesced
+        let expected_requests = if coalesce { 4 } else { 5 };
+        assert_eq!(store.requests.load(Ordering::SeqCst), expected_requests);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_create_external_table_with_terminator() -> Result<()> {
         let ctx = SessionContext::new();
@@ -48,7 +48,7 @@ mod tests {
     use bytes::{BufMut, BytesMut};
     use datafusion_common::config::TableParquetOptions;
     use datafusion_common::test_util::{batches_to_sort_string, batches_to_string};
-    use datafusion_common::{assert_contains, Result, ScalarValue};
+    use datafusion_common::{assert_contains, DataFusionError, Result, ScalarValue};
     use datafusion_datasource::file_format::FileFormat;
     use datafusion_datasource::file_meta::FileMeta;
     use datafusion_datasource::file_scan_config::FileScanConfigBuilder;
@@ -1469,6 +1469,13 @@ mod tests {
                 statistics: None,
                 extensions: None,
                 metadata_size_hint: None,
+                object_store_url: None,
+                sub_files: vec![],
+                file_schema_override: None,
+                row_group_statistics: None,
+                ranges: vec![],
+                tombstoned: false,
+                expected_checksum: None,
             }
         }
 
@@ -1566,6 +1573,13 @@ mod tests {
             statistics: None,
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         };
 
         let expected_schema = Schema::new(vec![
@@ -1656,6 +1670,13 @@ mod tests {
             statistics: None,
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         };
 
         let file_schema = Arc::new(Schema::empty());
@@ -2203,6 +2224,13 @@ mod tests {
                     statistics: None,
                     extensions: None,
                     metadata_size_hint: None,
+                    object_store_url: None,
+                    sub_files: vec![],
+                    file_schema_override: None,
+                    row_group_statistics: None,
+                    ranges: vec![],
+                    tombstoned: false,
+                    expected_checksum: None,
                 }
                 .with_metadata_size_hint(123),
             )
@@ -2219,6 +2247,13 @@ mod tests {
                 statistics: None,
                 extensions: None,
                 metadata_size_hint: None,
+                object_store_url: None,
+                sub_files: vec![],
+                file_schema_override: None,
+                row_group_statistics: None,
+                ranges: vec![],
+                tombstoned: false,
+                expected_checksum: None,
             })
             .build();
 
@@ -2231,4 +2266,112 @@ mod tests {
         assert_eq!(calls.len(), 2);
         assert_eq!(calls, vec![Some(123), Some(456)]);
     }
+
+    #[tokio::test]
+    async fn parquet_exec_with_sub_files() -> Result<()> {
+        let parquet_bytes = |values: Vec<i32>| {
+            let c1: ArrayRef = Arc::new(Int32Array::from(values));
+            let batch = create_batch(vec![("c1", c1)]);
+            let mut writer =
+                ArrowWriter::try_new(BytesMut::new().writer(), batch.schema(), None)?;
+            writer.write(&batch)?;
+            let bytes = writer.into_inner()?.into_inner().freeze();
+            Ok::<_, DataFusionError>((batch.schema(), bytes))
+        };
+        let (schema, file_1) = parquet_bytes(vec![1, 2])?;
+        let (_, file_2) = parquet_bytes(vec![3])?;
+
+        // pack both files into a single object, after a header
+        let header = b"PACKED".as_slice();
+        let packed = [header, &file_1, &file_2].concat();
+        let file_1_start = header.len() as i64;
+        let file_2_start = file_1_start + file_1.len() as i64;
+        let sub_files = vec![
+            FileRange {
+                start: file_1_start,
+                end: file_2_start,
+            },
+            FileRange {
+                start: file_2_start,
+                end: packed.len() as i64,
+            },
+        ];
+
+        let store =
+            Arc::new(object_store::memory::InMemory::new()) as Arc<dyn ObjectStore>;
+        let store_url = ObjectStoreUrl::parse("memory://test").unwrap();
+        let ctx = SessionContext::new();
+        ctx.register_object_store(store_url.as_ref(), Arc::clone(&store));
+        let path = Path::from("packed.bin");
+        store.put(&path, packed.into()).await?;
+        let file =
+            PartitionedFile::from(store.head(&path).await?).with_sub_files(sub_files);
+
+        let config = FileScanConfigBuilder::new(
+            store_url,
+            schema,
+            Arc::new(ParquetSource::default()),
+        )
+        .with_file(file)
+        .build();
+        let exec = DataSourceExec::from_data_source(config);
+        let batches = collect(exec, ctx.task_ctx()).await?;
+        assert_snapshot!(batches_to_string(&batches), @r"
+        +----+
+        | c1 |
+        +----+
+        | 1  |
+        | 2  |
+        | 3  |
+        +----+
+        ");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn parquet_exec_with_trailing_bytes() -> Result<()> {
+        let c1: ArrayRef = Arc::new(Int32Array::from(vec![1, 2]));
+        let batch = create_batch(vec![("c1", c1)]);
+        let mut writer =
+            ArrowWriter::try_new(BytesMut::new().writer(), batch.schema(), None)?;
+        writer.write(&batch)?;
+        let bytes = writer.into_inner()?.into_inner().freeze();
+        // padding and garbage appended after the footer
+        let padded = [&bytes, [0; 100].as_slice(), b"garbage"].concat();
+
+        let store =
+            Arc::new(object_store::memory::InMemory::new()) as Arc<dyn ObjectStore>;
+        let store_url = ObjectStoreUrl::parse("memory://test").unwrap();
+        let ctx = SessionContext::new();
+        ctx.register_object_store(store_url.as_ref(), Arc::clone(&store));
+        let path = Path::from("padded.parquet");
+        store.put(&path, padded.into()).await?;
+        let file = PartitionedFile::from(store.head(&path).await?);
+
+        let scan = |tolerate_trailing_bytes: bool| {
+            let config = FileScanConfigBuilder::new(
+                store_url.clone(),
+                batch.schema(),
+                Arc::new(ParquetSource::default()),
+            )
+            .with_file(file.clone())
+            .with_tolerate_trailing_bytes(tolerate_trailing_bytes)
+            .build();
+            collect(DataSourceExec::from_data_source(config), ctx.task_ctx())
+        };
+
+        let err = scan(false).await.unwrap_err();
+        assert_contains!(err.to_string(), "Invalid Parquet file");
+
+        let batches = scan(true).await?;
+        assert_snapshot!(batches_to_string(&batches), @r"
+        +----+
+        | c1 |
+        +----+
+        | 1  |
+        | 2  |
+        +----+
+        ");
+        Ok(())
+    }
 }
@@ -169,6 +169,13 @@ impl TestParquetFile {
             statistics: None,
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         });
 
         let df_schema = Arc::clone(&self.schema).to_dfschema_ref()?;
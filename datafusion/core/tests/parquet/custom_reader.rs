@@ -76,6 +76,13 @@ async fn route_data_access_ops_to_parquet_file_reader_factory() {
             statistics: None,
             extensions: Some(Arc::new(String::from(EXPECTED_USER_DEFINED_METADATA))),
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         })
         .collect();
 
@@ -0,0 +1,116 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Tests for reading Parquet files with encrypted columns
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Int64Array, StringArray};
+use arrow::record_batch::RecordBatch;
+use datafusion::datasource::listing::PartitionedFile;
+use datafusion::datasource::object_store::ObjectStoreUrl;
+use datafusion::datasource::physical_plan::ParquetSource;
+use datafusion::physical_plan::collect;
+use datafusion::prelude::SessionContext;
+use datafusion_common::test_util::batches_to_string;
+use datafusion_common::Result;
+use datafusion_datasource::column_encryption::ColumnKeyProvider;
+use datafusion_datasource::file_scan_config::FileScanConfigBuilder;
+use datafusion_datasource::source::DataSourceExec;
+use insta::assert_snapshot;
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use parquet::arrow::ArrowWriter;
+use parquet::encryption::encrypt::FileEncryptionProperties;
+use parquet::file::properties::WriterProperties;
+
+const FOOTER_KEY: &[u8] = b"0123456789012345";
+const SECRET_KEY: &[u8] = b"1234567890123450";
+
+/// Provides the key of the `secret` column
+#[derive(Debug)]
+struct SecretKeyProvider;
+
+impl ColumnKeyProvider for SecretKeyProvider {
+    fn footer_key(&self, _file: &PartitionedFile) -> Result<Option<Vec<u8>>> {
+        Ok(Some(FOOTER_KEY.to_vec()))
+    }
+
+    fn column_key(
+        &self,
+        _file: &PartitionedFile,
+        column_name: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        Ok((column_name == "secret").then(|| SECRET_KEY.to_vec()))
+    }
+}
+
+#[tokio::test]
+async fn read_encrypted_column() -> Result<()> {
+    let id: ArrayRef = Arc::new(Int64Array::from(vec![1, 2]));
+    let secret: ArrayRef = Arc::new(StringArray::from(vec!["a", "b"]));
+    let batch = RecordBatch::try_from_iter(vec![("id", id), ("secret", secret)])?;
+
+    // only the `secret` column is encrypted, the footer is in plaintext
+    let encryption = FileEncryptionProperties::builder(FOOTER_KEY.to_vec())
+        .with_plaintext_footer(true)
+        .with_column_key("secret", SECRET_KEY.to_vec())
+        .build()?;
+    let props = WriterProperties::builder()
+        .with_file_encryption_properties(encryption)
+        .build();
+    let mut buffer = vec![];
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    let path = Path::from("encrypted.parquet");
+    store.put(&path, buffer.into()).await?;
+    let file = PartitionedFile::from(store.head(&path).await?);
+    let ctx = SessionContext::new();
+    ctx.register_object_store(ObjectStoreUrl::parse("memory://")?.as_ref(), store);
+
+    let read = |provider: Option<Arc<dyn ColumnKeyProvider>>| {
+        let mut builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("memory://").unwrap(),
+            batch.schema(),
+            Arc::new(ParquetSource::default()),
+        )
+        .with_file(file.clone());
+        if let Some(provider) = provider {
+            builder = builder.with_column_key_provider(provider);
+        }
+        let exec = DataSourceExec::from_data_source(builder.build());
+        collect(exec, ctx.task_ctx())
+    };
+
+    let batches = read(Some(Arc::new(SecretKeyProvider))).await?;
+    assert_snapshot!(batches_to_string(&batches), @r"
+    +----+--------+
+    | id | secret |
+    +----+--------+
+    | 1  | a      |
+    | 2  | b      |
+    +----+--------+
+    ");
+
+    // the encrypted column can not be read without its key
+    assert!(read(None).await.is_err());
+    Ok(())
+}
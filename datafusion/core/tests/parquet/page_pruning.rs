@@ -69,6 +69,13 @@ async fn get_parquet_exec(state: &SessionState, filter: Expr) -> DataSourceExec
         statistics: None,
         extensions: None,
         metadata_size_hint: None,
+        object_store_url: None,
+        sub_files: vec![],
+        file_schema_override: None,
+        row_group_statistics: None,
+        ranges: vec![],
+        tombstoned: false,
+        expected_checksum: None,
     };
 
     let df_schema = schema.clone().to_dfschema().unwrap();
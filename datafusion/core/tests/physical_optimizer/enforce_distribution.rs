@@ -1743,7 +1743,7 @@ fn merge_does_not_need_sort() -> Result<()> {
     let expected = &[
         "SortPreservingMergeExec: [a@0 ASC]",
         "  CoalesceBatchesExec: target_batch_size=4096",
-        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_type=parquet",
+        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     let test_config = TestConfig::default();
     test_config.run(expected, exec.clone(), &DISTRIB_DISTRIB_SORT)?;
@@ -1758,7 +1758,7 @@ fn merge_does_not_need_sort() -> Result<()> {
         "SortExec: expr=[a@0 ASC], preserve_partitioning=[false]",
         "  CoalescePartitionsExec",
         "    CoalesceBatchesExec: target_batch_size=4096",
-        "      DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_type=parquet",
+        "      DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     test_config.run(expected_first_sort_enforcement, exec, &SORT_DISTRIB_DISTRIB)?;
 
@@ -2070,7 +2070,7 @@ fn repartition_ignores_sort_preserving_merge() -> Result<()> {
     // should not repartition, since increased parallelism is not beneficial for SortPReservingMerge
     let expected = &[
         "SortPreservingMergeExec: [c@2 ASC]",
-        "  DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_type=parquet",
+        "  DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     let test_config = TestConfig::default();
     test_config.run(expected, plan.clone(), &DISTRIB_DISTRIB_SORT)?;
@@ -2079,7 +2079,7 @@ fn repartition_ignores_sort_preserving_merge() -> Result<()> {
     let expected_first_sort_enforcement = &[
         "SortExec: expr=[c@2 ASC], preserve_partitioning=[false]",
         "  CoalescePartitionsExec",
-        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_type=parquet",
+        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     test_config.run(expected_first_sort_enforcement, plan, &SORT_DISTRIB_DISTRIB)?;
 
@@ -2275,7 +2275,7 @@ fn repartition_ignores_transitively_with_projection() -> Result<()> {
         "SortRequiredExec: [c@2 ASC]",
         // Since this projection is trivial, increasing parallelism is not beneficial
         "  ProjectionExec: expr=[a@0 as a, b@1 as b, c@2 as c]",
-        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_type=parquet",
+        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
 
     let test_config = TestConfig::default();
@@ -2420,7 +2420,7 @@ fn parallelization_single_partition() -> Result<()> {
         "AggregateExec: mode=FinalPartitioned, gby=[a@0 as a], aggr=[]",
         "  RepartitionExec: partitioning=Hash([a@0], 2), input_partitions=2",
         "    AggregateExec: mode=Partial, gby=[a@0 as a], aggr=[]",
-        "      DataSourceExec: file_groups={2 groups: [[x:0..50], [x:50..100]]}, projection=[a, b, c, d, e], file_type=parquet",
+        "      DataSourceExec: file_groups={2 groups: [[x:0..50], [x:50..100]]}, projection=[a, b, c, d, e], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 100, avg_bytes_per_group: 50, min_bytes_per_group: 50, max_bytes_per_group: 50}, file_type=parquet",
     ];
     test_config.run(
         &expected_parquet,
@@ -2434,7 +2434,7 @@ fn parallelization_single_partition() -> Result<()> {
         "AggregateExec: mode=FinalPartitioned, gby=[a@0 as a], aggr=[]",
         "  RepartitionExec: partitioning=Hash([a@0], 2), input_partitions=2",
         "    AggregateExec: mode=Partial, gby=[a@0 as a], aggr=[]",
-        "      DataSourceExec: file_groups={2 groups: [[x:0..50], [x:50..100]]}, projection=[a, b, c, d, e], file_type=csv, has_header=false",
+        "      DataSourceExec: file_groups={2 groups: [[x:0..50], [x:50..100]]}, projection=[a, b, c, d, e], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 100, avg_bytes_per_group: 50, min_bytes_per_group: 50, max_bytes_per_group: 50}, file_type=csv, has_header=false",
     ];
     test_config.run(&expected_csv, plan_csv.clone(), &DISTRIB_DISTRIB_SORT)?;
     test_config.run(&expected_csv, plan_csv, &SORT_DISTRIB_DISTRIB)?;
@@ -2464,7 +2464,7 @@ fn parallelization_multiple_files() -> Result<()> {
     let expected_with_3_target_partitions = [
         "SortRequiredExec: [a@0 ASC]",
         "  FilterExec: c@2 = 0",
-        "    DataSourceExec: file_groups={3 groups: [[x:0..50], [y:0..100], [x:50..100]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_type=parquet",
+        "    DataSourceExec: file_groups={3 groups: [[x:0..50], [y:0..100], [x:50..100]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_group_balance={groups: 3, files_per_group: {1 file: 3 groups}, total_bytes: 200, avg_bytes_per_group: 66, min_bytes_per_group: 50, max_bytes_per_group: 100}, file_type=parquet",
     ];
     let test_config_concurrency_3 =
         test_config.clone().with_query_execution_partitions(3);
@@ -2482,7 +2482,7 @@ fn parallelization_multiple_files() -> Result<()> {
     let expected_with_8_target_partitions = [
         "SortRequiredExec: [a@0 ASC]",
         "  FilterExec: c@2 = 0",
-        "    DataSourceExec: file_groups={8 groups: [[x:0..25], [y:0..25], [x:25..50], [y:25..50], [x:50..75], [y:50..75], [x:75..100], [y:75..100]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_type=parquet",
+        "    DataSourceExec: file_groups={8 groups: [[x:0..25], [y:0..25], [x:25..50], [y:25..50], [x:50..75], [y:50..75], [x:75..100], [y:75..100]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_group_balance={groups: 8, files_per_group: {1 file: 8 groups}, total_bytes: 200, avg_bytes_per_group: 25, min_bytes_per_group: 25, max_bytes_per_group: 25}, file_type=parquet",
     ];
     let test_config_concurrency_8 = test_config.with_query_execution_partitions(8);
     test_config_concurrency_8.run(
@@ -2523,7 +2523,7 @@ fn parallelization_compressed_csv() -> Result<()> {
         "AggregateExec: mode=FinalPartitioned, gby=[a@0 as a], aggr=[]",
         "  RepartitionExec: partitioning=Hash([a@0], 2), input_partitions=2",
         "    AggregateExec: mode=Partial, gby=[a@0 as a], aggr=[]",
-        "      DataSourceExec: file_groups={2 groups: [[x:0..50], [x:50..100]]}, projection=[a, b, c, d, e], file_type=csv, has_header=false",
+        "      DataSourceExec: file_groups={2 groups: [[x:0..50], [x:50..100]]}, projection=[a, b, c, d, e], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 100, avg_bytes_per_group: 50, min_bytes_per_group: 50, max_bytes_per_group: 50}, file_type=csv, has_header=false",
     ];
 
     for compression_type in compression_types {
@@ -2571,7 +2571,7 @@ fn parallelization_two_partitions() -> Result<()> {
         "  RepartitionExec: partitioning=Hash([a@0], 2), input_partitions=2",
         "    AggregateExec: mode=Partial, gby=[a@0 as a], aggr=[]",
         // Plan already has two partitions
-        "      DataSourceExec: file_groups={2 groups: [[x:0..100], [y:0..100]]}, projection=[a, b, c, d, e], file_type=parquet",
+        "      DataSourceExec: file_groups={2 groups: [[x:0..100], [y:0..100]]}, projection=[a, b, c, d, e], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     test_config.run(
         &expected_parquet,
@@ -2586,7 +2586,7 @@ fn parallelization_two_partitions() -> Result<()> {
         "  RepartitionExec: partitioning=Hash([a@0], 2), input_partitions=2",
         "    AggregateExec: mode=Partial, gby=[a@0 as a], aggr=[]",
         // Plan already has two partitions
-        "      DataSourceExec: file_groups={2 groups: [[x:0..100], [y:0..100]]}, projection=[a, b, c, d, e], file_type=csv, has_header=false",
+        "      DataSourceExec: file_groups={2 groups: [[x:0..100], [y:0..100]]}, projection=[a, b, c, d, e], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=csv, has_header=false",
     ];
     test_config.run(&expected_csv, plan_csv.clone(), &DISTRIB_DISTRIB_SORT)?;
     test_config.run(&expected_csv, plan_csv, &SORT_DISTRIB_DISTRIB)?;
@@ -2610,7 +2610,7 @@ fn parallelization_two_partitions_into_four() -> Result<()> {
         "  RepartitionExec: partitioning=Hash([a@0], 4), input_partitions=4",
         "    AggregateExec: mode=Partial, gby=[a@0 as a], aggr=[]",
         // Multiple source files splitted across partitions
-        "      DataSourceExec: file_groups={4 groups: [[x:0..50], [x:50..100], [y:0..50], [y:50..100]]}, projection=[a, b, c, d, e], file_type=parquet",
+        "      DataSourceExec: file_groups={4 groups: [[x:0..50], [x:50..100], [y:0..50], [y:50..100]]}, projection=[a, b, c, d, e], file_group_balance={groups: 4, files_per_group: {1 file: 4 groups}, total_bytes: 200, avg_bytes_per_group: 50, min_bytes_per_group: 50, max_bytes_per_group: 50}, file_type=parquet",
     ];
     test_config.run(
         &expected_parquet,
@@ -2625,7 +2625,7 @@ fn parallelization_two_partitions_into_four() -> Result<()> {
         "  RepartitionExec: partitioning=Hash([a@0], 4), input_partitions=4",
         "    AggregateExec: mode=Partial, gby=[a@0 as a], aggr=[]",
         // Multiple source files splitted across partitions
-        "      DataSourceExec: file_groups={4 groups: [[x:0..50], [x:50..100], [y:0..50], [y:50..100]]}, projection=[a, b, c, d, e], file_type=csv, has_header=false",
+        "      DataSourceExec: file_groups={4 groups: [[x:0..50], [x:50..100], [y:0..50], [y:50..100]]}, projection=[a, b, c, d, e], file_group_balance={groups: 4, files_per_group: {1 file: 4 groups}, total_bytes: 200, avg_bytes_per_group: 50, min_bytes_per_group: 50, max_bytes_per_group: 50}, file_type=csv, has_header=false",
     ];
     test_config.run(&expected_csv, plan_csv.clone(), &DISTRIB_DISTRIB_SORT)?;
     test_config.run(&expected_csv, plan_csv, &SORT_DISTRIB_DISTRIB)?;
@@ -3136,7 +3136,7 @@ fn remove_unnecessary_spm_after_filter() -> Result<()> {
         "CoalescePartitionsExec",
         "  FilterExec: c@2 = 0",
         "    RepartitionExec: partitioning=RoundRobinBatch(10), input_partitions=2, preserve_order=true, sort_exprs=c@2 ASC",
-        "      DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_type=parquet",
+        "      DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
 
     test_config.run(expected, physical_plan.clone(), &DISTRIB_DISTRIB_SORT)?;
@@ -3164,7 +3164,7 @@ fn preserve_ordering_through_repartition() -> Result<()> {
         "SortPreservingMergeExec: [d@3 ASC]",
         "  FilterExec: c@2 = 0",
         "    RepartitionExec: partitioning=RoundRobinBatch(10), input_partitions=2, preserve_order=true, sort_exprs=d@3 ASC",
-        "      DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[d@3 ASC], file_type=parquet",
+        "      DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[d@3 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     test_config.run(expected, physical_plan.clone(), &DISTRIB_DISTRIB_SORT)?;
     test_config.run(expected, physical_plan, &SORT_DISTRIB_DISTRIB)?;
@@ -3191,7 +3191,7 @@ fn do_not_preserve_ordering_through_repartition() -> Result<()> {
         "  SortExec: expr=[a@0 ASC], preserve_partitioning=[true]",
         "    FilterExec: c@2 = 0",
         "      RepartitionExec: partitioning=RoundRobinBatch(10), input_partitions=2",
-        "        DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_type=parquet",
+        "        DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     test_config.run(expected, physical_plan.clone(), &DISTRIB_DISTRIB_SORT)?;
 
@@ -3201,7 +3201,7 @@ fn do_not_preserve_ordering_through_repartition() -> Result<()> {
         "  CoalescePartitionsExec",
         "    FilterExec: c@2 = 0",
         "      RepartitionExec: partitioning=RoundRobinBatch(10), input_partitions=2",
-        "        DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_type=parquet",
+        "        DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     test_config.run(
         expected_first_sort_enforcement,
@@ -3229,7 +3229,7 @@ fn no_need_for_sort_after_filter() -> Result<()> {
         // Since after this stage c is constant. c@2 ASC ordering is already satisfied.
         "  FilterExec: c@2 = 0",
         "    RepartitionExec: partitioning=RoundRobinBatch(10), input_partitions=2",
-        "      DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_type=parquet",
+        "      DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     let test_config = TestConfig::default();
     test_config.run(expected, physical_plan.clone(), &DISTRIB_DISTRIB_SORT)?;
@@ -3263,7 +3263,7 @@ fn do_not_preserve_ordering_through_repartition2() -> Result<()> {
         "  SortExec: expr=[a@0 ASC], preserve_partitioning=[true]",
         "    FilterExec: c@2 = 0",
         "      RepartitionExec: partitioning=RoundRobinBatch(10), input_partitions=2",
-        "        DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_type=parquet",
+        "        DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     test_config.run(expected, physical_plan.clone(), &DISTRIB_DISTRIB_SORT)?;
 
@@ -3274,7 +3274,7 @@ fn do_not_preserve_ordering_through_repartition2() -> Result<()> {
         "    SortExec: expr=[a@0 ASC], preserve_partitioning=[true]",
         "      FilterExec: c@2 = 0",
         "        RepartitionExec: partitioning=RoundRobinBatch(10), input_partitions=2",
-        "          DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_type=parquet",
+        "          DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     test_config.run(
         expected_first_sort_enforcement,
@@ -3299,7 +3299,7 @@ fn do_not_preserve_ordering_through_repartition3() -> Result<()> {
     let expected = &[
         "FilterExec: c@2 = 0",
         "  RepartitionExec: partitioning=RoundRobinBatch(10), input_partitions=2",
-        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_type=parquet",
+        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     let test_config = TestConfig::default();
     test_config.run(expected, physical_plan.clone(), &DISTRIB_DISTRIB_SORT)?;
@@ -3362,7 +3362,7 @@ fn put_sort_when_input_is_valid() -> Result<()> {
         // by existing ordering at the source.
         "SortRequiredExec: [a@0 ASC]",
         "  FilterExec: c@2 = 0",
-        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_type=parquet",
+        "    DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     assert_plan_txt!(expected, physical_plan);
 
@@ -3371,7 +3371,7 @@ fn put_sort_when_input_is_valid() -> Result<()> {
         // EnforceDistribution rule satisfy this requirement also.
         "SortRequiredExec: [a@0 ASC]",
         "  FilterExec: c@2 = 0",
-        "    DataSourceExec: file_groups={10 groups: [[x:0..20], [y:0..20], [x:20..40], [y:20..40], [x:40..60], [y:40..60], [x:60..80], [y:60..80], [x:80..100], [y:80..100]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_type=parquet",
+        "    DataSourceExec: file_groups={10 groups: [[x:0..20], [y:0..20], [x:20..40], [y:20..40], [x:40..60], [y:40..60], [x:60..80], [y:60..80], [x:80..100], [y:80..100]]}, projection=[a, b, c, d, e], output_ordering=[a@0 ASC], file_group_balance={groups: 10, files_per_group: {1 file: 10 groups}, total_bytes: 200, avg_bytes_per_group: 20, min_bytes_per_group: 20, max_bytes_per_group: 20}, file_type=parquet",
     ];
 
     let mut config = ConfigOptions::new();
@@ -3437,7 +3437,7 @@ fn do_not_add_unnecessary_hash2() -> Result<()> {
         "      RepartitionExec: partitioning=Hash([a@0], 4), input_partitions=4",
         "        AggregateExec: mode=Partial, gby=[a@0 as a], aggr=[]",
         "          RepartitionExec: partitioning=RoundRobinBatch(4), input_partitions=2",
-        "            DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_type=parquet",
+        "            DataSourceExec: file_groups={2 groups: [[x], [y]]}, projection=[a, b, c, d, e], output_ordering=[c@2 ASC], file_group_balance={groups: 2, files_per_group: {1 file: 2 groups}, total_bytes: 200, avg_bytes_per_group: 100, min_bytes_per_group: 100, max_bytes_per_group: 100}, file_type=parquet",
     ];
     test_config.run(expected, physical_plan.clone(), &DISTRIB_DISTRIB_SORT)?;
     test_config.run(expected, physical_plan, &SORT_DISTRIB_DISTRIB)?;
@@ -3518,7 +3518,7 @@ async fn test_distribute_sort_parquet() -> Result<()> {
     let expected = &[
         "SortExec: expr=[c@2 ASC], preserve_partitioning=[false]",
         "  CoalescePartitionsExec",
-        "    DataSourceExec: file_groups={10 groups: [[x:0..8192000], [x:8192000..16384000], [x:16384000..24576000], [x:24576000..32768000], [x:32768000..40960000], [x:40960000..49152000], [x:49152000..57344000], [x:57344000..65536000], [x:65536000..73728000], [x:73728000..81920000]]}, projection=[a, b, c, d, e], file_type=parquet",
+        "    DataSourceExec: file_groups={10 groups: [[x:0..8192000], [x:8192000..16384000], [x:16384000..24576000], [x:24576000..32768000], [x:32768000..40960000], [x:40960000..49152000], [x:49152000..57344000], [x:57344000..65536000], [x:65536000..73728000], [x:73728000..81920000]]}, projection=[a, b, c, d, e], file_group_balance={groups: 10, files_per_group: {1 file: 10 groups}, total_bytes: 81920000, avg_bytes_per_group: 8192000, min_bytes_per_group: 8192000, max_bytes_per_group: 8192000}, file_type=parquet",
     ];
     test_config.run(expected, physical_plan.clone(), &[Run::Distribution])?;
 
@@ -3526,7 +3526,7 @@ async fn test_distribute_sort_parquet() -> Result<()> {
     let expected = &[
         "SortPreservingMergeExec: [c@2 ASC]",
         "  SortExec: expr=[c@2 ASC], preserve_partitioning=[true]",
-        "    DataSourceExec: file_groups={10 groups: [[x:0..8192000], [x:8192000..16384000], [x:16384000..24576000], [x:24576000..32768000], [x:32768000..40960000], [x:40960000..49152000], [x:49152000..57344000], [x:57344000..65536000], [x:65536000..73728000], [x:73728000..81920000]]}, projection=[a, b, c, d, e], file_type=parquet",
+        "    DataSourceExec: file_groups={10 groups: [[x:0..8192000], [x:8192000..16384000], [x:16384000..24576000], [x:24576000..32768000], [x:32768000..40960000], [x:40960000..49152000], [x:49152000..57344000], [x:57344000..65536000], [x:65536000..73728000], [x:73728000..81920000]]}, projection=[a, b, c, d, e], file_group_balance={groups: 10, files_per_group: {1 file: 10 groups}, total_bytes: 81920000, avg_bytes_per_group: 8192000, min_bytes_per_group: 8192000, max_bytes_per_group: 8192000}, file_type=parquet",
     ];
     test_config.run(expected, physical_plan, &[Run::Distribution, Run::Sorting])?;
     Ok(())
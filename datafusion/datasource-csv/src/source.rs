@@ -24,13 +24,15 @@ use std::io::{Read, Seek, SeekFrom};
 use std::sync::Arc;
 use std::task::Poll;
 
-use datafusion_datasource::decoder::{deserialize_stream, DecoderDeserializer};
+use datafusion_datasource::decoder::{
+    deserialize_stream, strip_bom_read, strip_bom_stream, DecoderDeserializer,
+};
 use datafusion_datasource::file_compression_type::FileCompressionType;
 use datafusion_datasource::file_meta::FileMeta;
 use datafusion_datasource::file_stream::{FileOpenFuture, FileOpener};
 use datafusion_datasource::{
-    as_file_source, calculate_range, FileRange, ListingTableUrl, PartitionedFile,
-    RangeCalculation,
+    as_file_source, calculate_quoted_range, calculate_range, FileRange, ListingTableUrl,
+    PartitionedFile, RangeCalculation,
 };
 
 use arrow::csv;
@@ -38,14 +40,17 @@ use arrow::datatypes::SchemaRef;
 use datafusion_common::{DataFusionError, Result, Statistics};
 use datafusion_common_runtime::JoinSet;
 use datafusion_datasource::file::FileSource;
+use datafusion_datasource::file_groups::FileGroupPartitioner;
 use datafusion_datasource::file_scan_config::FileScanConfig;
 use datafusion_execution::TaskContext;
+use datafusion_physical_expr_common::sort_expr::LexOrdering;
 use datafusion_physical_plan::metrics::ExecutionPlanMetricsSet;
 use datafusion_physical_plan::{
     DisplayFormatType, ExecutionPlan, ExecutionPlanProperties,
 };
 
 use crate::file_format::CsvDecoder;
+use futures::future::BoxFuture;
 use futures::{StreamExt, TryStreamExt};
 use object_store::buffered::BufWriter;
 use object_store::{GetOptions, GetResultPayload, ObjectStore};
@@ -91,9 +96,11 @@ pub struct CsvSource {
     terminator: Option<u8>,
     escape: Option<u8>,
     comment: Option<u8>,
+    keep_bom: bool,
     metrics: ExecutionPlanMetricsSet,
     projected_statistics: Option<Statistics>,
     schema_adapter_factory: Option<Arc<dyn SchemaAdapterFactory>>,
+    quoted_range_probe_limit: Option<u64>,
 }
 
 impl CsvSource {
@@ -136,6 +143,11 @@ impl CsvSource {
         self.escape
     }
 
+    /// true if a leading byte order mark is stripped from each file
+    pub fn strip_bom(&self) -> bool {
+        !self.keep_bom
+    }
+
     /// Initialize a CsvSource with escape
     pub fn with_escape(&self, escape: Option<u8>) -> Self {
         let mut conf = self.clone();
@@ -156,9 +168,45 @@ impl CsvSource {
         conf.comment = comment;
         conf
     }
+
+    /// Initialize a CsvSource with whether to strip a leading UTF-8 byte
+    /// order mark from each file (the default). Files starting with a UTF-16
+    /// byte order mark are rejected, as only UTF-8 is supported.
+    pub fn with_strip_bom(&self, strip_bom: bool) -> Self {
+        let mut conf = self.clone();
+        conf.keep_bom = !strip_bom;
+        conf
+    }
+
+    /// Initialize a CsvSource that also splits files whose values contain
+    /// newlines into byte ranges when repartitioning, which is disabled by
+    /// default as each range scans the file from its start to find the
+    /// record boundaries, see [`calculate_quoted_range`].
+    ///
+    /// The scan of each range is bounded by `max_probe_bytes`: the range
+    /// containing that offset of a file reads the rest of it, and the ranges
+    /// after it read nothing.
+    pub fn with_quoted_range_splitting(&self, max_probe_bytes: u64) -> Self {
+        let mut conf = self.clone();
+        conf.quoted_range_probe_limit = Some(max_probe_bytes);
+        conf
+    }
 }
 
 impl CsvSource {
+    /// Returns this source with the [`FileScanConfig::csv_dialect`] of
+    /// `config`, if any
+    fn with_dialect_of(&self, config: &FileScanConfig) -> Self {
+        let mut source = self.clone();
+        if let Some(dialect) = config.csv_dialect {
+            source.delimiter = dialect.delimiter;
+            source.quote = dialect.quote;
+            source.escape = dialect.escape;
+            source.comment = dialect.comment;
+        }
+        source
+    }
+
     fn open<R: Read>(&self, reader: R) -> Result<csv::Reader<R>> {
         Ok(self.builder().build(reader)?)
     }
@@ -198,6 +246,7 @@ pub struct CsvOpener {
     config: Arc<CsvSource>,
     file_compression_type: FileCompressionType,
     object_store: Arc<dyn ObjectStore>,
+    newlines_in_values: bool,
 }
 
 impl CsvOpener {
@@ -211,8 +260,17 @@ impl CsvOpener {
             config,
             file_compression_type,
             object_store,
+            newlines_in_values: false,
         }
     }
+
+    /// Set whether quoted values may contain newlines, in which case the
+    /// byte range of a partial file scan is aligned with the record
+    /// boundaries using a quote aware scan, see [`calculate_quoted_range`]
+    pub fn with_newlines_in_values(mut self, newlines_in_values: bool) -> Self {
+        self.newlines_in_values = newlines_in_values;
+        self
+    }
 }
 
 impl From<CsvSource> for Arc<dyn FileSource> {
@@ -229,9 +287,10 @@ impl FileSource for CsvSource {
         _partition: usize,
     ) -> Arc<dyn FileOpener> {
         Arc::new(CsvOpener {
-            config: Arc::new(self.clone()),
+            config: Arc::new(self.with_dialect_of(base_config)),
             file_compression_type: base_config.file_compression_type,
             object_store,
+            newlines_in_values: base_config.new_lines_in_values,
         })
     }
 
@@ -284,6 +343,36 @@ impl FileSource for CsvSource {
         }
     }
 
+    /// Unlike the default implementation, files whose values contain newlines
+    /// are also split into byte ranges if enabled with
+    /// [`CsvSource::with_quoted_range_splitting`], as [`CsvOpener`] aligns the
+    /// ranges with record boundaries taking quotes into account
+    fn repartitioned(
+        &self,
+        target_partitions: usize,
+        repartition_file_min_size: usize,
+        output_ordering: Option<LexOrdering>,
+        config: &FileScanConfig,
+    ) -> Result<Option<FileScanConfig>> {
+        if config.file_compression_type.is_compressed()
+            || (config.new_lines_in_values && self.quoted_range_probe_limit.is_none())
+        {
+            return Ok(None);
+        }
+
+        let repartitioned_file_groups_option = FileGroupPartitioner::new()
+            .with_target_partitions(target_partitions)
+            .with_repartition_file_min_size(repartition_file_min_size)
+            .with_preserve_order_within_groups(output_ordering.is_some())
+            .repartition_file_groups(&config.file_groups);
+
+        Ok(repartitioned_file_groups_option.map(|file_groups| {
+            let mut source = config.clone();
+            source.file_groups = file_groups;
+            source
+        }))
+    }
+
     fn with_schema_adapter_factory(
         &self,
         schema_adapter_factory: Arc<dyn SchemaAdapterFactory>,
@@ -297,6 +386,62 @@ impl FileSource for CsvSource {
     fn schema_adapter_factory(&self) -> Option<Arc<dyn SchemaAdapterFactory>> {
         self.schema_adapter_factory.clone()
     }
+
+    /// Reads the names of the columns from the header of `file`, if it has
+    /// one
+    fn physical_column_names(
+        &self,
+        object_store: Arc<dyn ObjectStore>,
+        file: &PartitionedFile,
+        config: &FileScanConfig,
+    ) -> Option<BoxFuture<'static, Result<Vec<String>>>> {
+        if !self.has_header {
+            return None;
+        }
+        let source = self.with_dialect_of(config);
+        let mut format = csv::reader::Format::default()
+            .with_header(true)
+            .with_delimiter(source.delimiter)
+            .with_quote(source.quote);
+        if let Some(escape) = source.escape {
+            format = format.with_escape(escape);
+        }
+        if let Some(comment) = source.comment {
+            format = format.with_comment(comment);
+        }
+        if let Some(terminator) = source.terminator {
+            format = format.with_terminator(terminator);
+        }
+        let terminator = source.terminator.unwrap_or(b'\n');
+        let file_compression_type = config.file_compression_type;
+        let location = file.object_meta.location.clone();
+
+        Some(Box::pin(async move {
+            let stream = object_store
+                .get(&location)
+                .await?
+                .into_stream()
+                .map_err(DataFusionError::from);
+            let mut input = file_compression_type.convert_stream(stream.boxed())?;
+            if source.strip_bom() {
+                input = strip_bom_stream(input);
+            }
+            // the rest of the file is not read
+            let mut header = vec![];
+            while let Some(bytes) = input.try_next().await? {
+                header.extend_from_slice(&bytes);
+                if bytes.contains(&terminator) {
+                    break;
+                }
+            }
+            let (schema, _) = format.infer_schema(header.as_slice(), Some(0))?;
+            Ok(schema
+                .fields()
+                .iter()
+                .map(|field| field.name().clone())
+                .collect())
+        }))
+    }
 }
 
 impl FileOpener for CsvOpener {
@@ -352,14 +497,36 @@ impl FileOpener for CsvOpener {
             );
         }
 
+        // A byte order mark can only appear at the very start of the file
+        let strip_bom = self.config.strip_bom()
+            && file_meta
+                .range
+                .as_ref()
+                .is_none_or(|range| range.start == 0);
+
         let store = Arc::clone(&self.object_store);
         let terminator = self.config.terminator;
+        let quote = self.config.quote;
+        let escape = self.config.escape;
+        let newlines_in_values = self.newlines_in_values;
+        let max_probe_bytes = self.config.quoted_range_probe_limit;
 
         Ok(Box::pin(async move {
             // Current partition contains bytes [start_byte, end_byte) (might contain incomplete lines at boundaries)
 
-            let calculated_range =
-                calculate_range(&file_meta, &store, terminator).await?;
+            let calculated_range = if newlines_in_values {
+                calculate_quoted_range(
+                    &file_meta,
+                    &store,
+                    terminator,
+                    quote,
+                    escape,
+                    max_probe_bytes,
+                )
+                .await?
+            } else {
+                calculate_range(&file_meta, &store, terminator).await?
+            };
 
             let range = match calculated_range {
                 RangeCalculation::Range(None) => None,
@@ -391,16 +558,24 @@ impl FileOpener for CsvOpener {
                             file.take((result.range.end - result.range.start) as u64),
                         )?
                     };
+                    let decoder = if strip_bom {
+                        strip_bom_read(decoder)?
+                    } else {
+                        decoder
+                    };
 
                     Ok(futures::stream::iter(config.open(decoder)?).boxed())
                 }
                 GetResultPayload::Stream(s) => {
                     let decoder = config.builder().build_decoder();
                     let s = s.map_err(DataFusionError::from);
-                    let input = file_compression_type.convert_stream(s.boxed())?.fuse();
+                    let mut input = file_compression_type.convert_stream(s.boxed())?;
+                    if strip_bom {
+                        input = strip_bom_stream(input);
+                    }
 
                     Ok(deserialize_stream(
-                        input,
+                        input.fuse(),
                         DecoderDeserializer::new(CsvDecoder::new(decoder)),
                     ))
                 }
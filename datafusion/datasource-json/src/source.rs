@@ -26,7 +26,9 @@ use crate::file_format::JsonDecoder;
 
 use datafusion_common::error::{DataFusionError, Result};
 use datafusion_common_runtime::JoinSet;
-use datafusion_datasource::decoder::{deserialize_stream, DecoderDeserializer};
+use datafusion_datasource::decoder::{
+    deserialize_stream, strip_bom_read, strip_bom_stream, DecoderDeserializer,
+};
 use datafusion_datasource::file_compression_type::FileCompressionType;
 use datafusion_datasource::file_meta::FileMeta;
 use datafusion_datasource::file_stream::{FileOpenFuture, FileOpener};
@@ -55,6 +57,7 @@ pub struct JsonOpener {
     projected_schema: SchemaRef,
     file_compression_type: FileCompressionType,
     object_store: Arc<dyn ObjectStore>,
+    keep_bom: bool,
 }
 
 impl JsonOpener {
@@ -70,8 +73,16 @@ impl JsonOpener {
             projected_schema,
             file_compression_type,
             object_store,
+            keep_bom: false,
         }
     }
+
+    /// Set whether to strip a leading UTF-8 byte order mark from each file,
+    /// see [`JsonSource::with_strip_bom`]
+    pub fn with_strip_bom(mut self, strip_bom: bool) -> Self {
+        self.keep_bom = !strip_bom;
+        self
+    }
 }
 
 /// JsonSource holds the extra configuration that is necessary for [`JsonOpener`]
@@ -81,6 +92,7 @@ pub struct JsonSource {
     metrics: ExecutionPlanMetricsSet,
     projected_statistics: Option<Statistics>,
     schema_adapter_factory: Option<Arc<dyn SchemaAdapterFactory>>,
+    keep_bom: bool,
 }
 
 impl JsonSource {
@@ -88,6 +100,20 @@ impl JsonSource {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Initialize a JsonSource with whether to strip a leading UTF-8 byte
+    /// order mark from each file (the default). Files starting with a UTF-16
+    /// byte order mark are rejected, as only UTF-8 is supported.
+    pub fn with_strip_bom(&self, strip_bom: bool) -> Self {
+        let mut conf = self.clone();
+        conf.keep_bom = !strip_bom;
+        conf
+    }
+
+    /// true if a leading byte order mark is stripped from each file
+    pub fn strip_bom(&self) -> bool {
+        !self.keep_bom
+    }
 }
 
 impl From<JsonSource> for Arc<dyn FileSource> {
@@ -110,6 +136,7 @@ impl FileSource for JsonSource {
             projected_schema: base_config.projected_file_schema(),
             file_compression_type: base_config.file_compression_type,
             object_store,
+            keep_bom: self.keep_bom,
         })
     }
 
@@ -185,6 +212,12 @@ impl FileOpener for JsonOpener {
         let schema = Arc::clone(&self.projected_schema);
         let batch_size = self.batch_size;
         let file_compression_type = self.file_compression_type.to_owned();
+        // A byte order mark can only appear at the very start of the file
+        let strip_bom = !self.keep_bom
+            && file_meta
+                .range
+                .as_ref()
+                .is_none_or(|range| range.start == 0);
 
         Ok(Box::pin(async move {
             let calculated_range = calculate_range(&file_meta, &store, None).await?;
@@ -217,6 +250,11 @@ impl FileOpener for JsonOpener {
                             file_compression_type.convert_read(file.take(limit as u64))?
                         }
                     };
+                    let bytes = if strip_bom {
+                        strip_bom_read(bytes)?
+                    } else {
+                        bytes
+                    };
 
                     let reader = ReaderBuilder::new(schema)
                         .with_batch_size(batch_size)
@@ -230,10 +268,13 @@ impl FileOpener for JsonOpener {
                     let decoder = ReaderBuilder::new(schema)
                         .with_batch_size(batch_size)
                         .build_decoder()?;
-                    let input = file_compression_type.convert_stream(s.boxed())?.fuse();
+                    let mut input = file_compression_type.convert_stream(s.boxed())?;
+                    if strip_bom {
+                        input = strip_bom_stream(input);
+                    }
 
                     Ok(deserialize_stream(
-                        input,
+                        input.fuse(),
                         DecoderDeserializer::new(JsonDecoder::new(decoder)),
                     ))
                 }
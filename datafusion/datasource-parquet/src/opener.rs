@@ -36,17 +36,22 @@ use datafusion_common::pruning::{
     PruningStatistics,
 };
 use datafusion_common::{exec_err, Result};
+use datafusion_datasource::column_encryption::ColumnKeyProvider;
 use datafusion_datasource::PartitionedFile;
 use datafusion_physical_expr_common::physical_expr::PhysicalExpr;
 use datafusion_physical_optimizer::pruning::PruningPredicate;
 use datafusion_physical_plan::metrics::{Count, ExecutionPlanMetricsSet, MetricBuilder};
 
+#[cfg(feature = "parquet_encryption")]
+use arrow::datatypes::{DataType, Field, Fields};
 use futures::{StreamExt, TryStreamExt};
 use itertools::Itertools;
 use log::debug;
 use parquet::arrow::arrow_reader::{ArrowReaderMetadata, ArrowReaderOptions};
 use parquet::arrow::async_reader::AsyncFileReader;
 use parquet::arrow::{ParquetRecordBatchStreamBuilder, ProjectionMask};
+#[cfg(feature = "parquet_encryption")]
+use parquet::encryption::decrypt::FileDecryptionProperties;
 use parquet::file::metadata::ParquetMetaDataReader;
 
 /// Implements [`FileOpener`] for a parquet file
@@ -90,6 +95,8 @@ pub(super) struct ParquetOpener {
     pub enable_row_group_stats_pruning: bool,
     /// Coerce INT96 timestamps to specific TimeUnit
     pub coerce_int96: Option<TimeUnit>,
+    /// Keys to decrypt the encrypted columns of the files, if any
+    pub column_key_provider: Option<Arc<dyn ColumnKeyProvider>>,
 }
 
 impl FileOpener for ParquetOpener {
@@ -133,6 +140,25 @@ impl FileOpener for ParquetOpener {
 
         let enable_page_index = self.enable_page_index;
 
+        #[cfg(feature = "parquet_encryption")]
+        let file_decryption_properties = self
+            .column_key_provider
+            .as_ref()
+            .map(|provider| {
+                file_decryption_properties(
+                    provider.as_ref(),
+                    &file,
+                    &self.logical_file_schema,
+                )
+            })
+            .transpose()?;
+        #[cfg(not(feature = "parquet_encryption"))]
+        if self.column_key_provider.is_some() {
+            return datafusion_common::not_impl_err!(
+                "Reading encrypted Parquet columns requires the parquet_encryption feature"
+            );
+        }
+
         Ok(Box::pin(async move {
             // Prune this file using the file level statistics.
             // Since dynamic filters may have been updated since planning it is possible that we are able
@@ -200,6 +226,11 @@ impl FileOpener for ParquetOpener {
             // pruning predicates. Thus default to not requesting if from the
             // underlying reader.
             let mut options = ArrowReaderOptions::new().with_page_index(false);
+            #[cfg(feature = "parquet_encryption")]
+            if let Some(file_decryption_properties) = file_decryption_properties {
+                options =
+                    options.with_file_decryption_properties(file_decryption_properties);
+            }
             let mut metadata_timer = file_metrics.metadata_load_time.timer();
 
             // Begin by loading the metadata from the underlying reader (note
@@ -316,6 +347,15 @@ impl FileOpener for ParquetOpener {
             // track which row groups to actually read
             let access_plan =
                 create_initial_plan(&file_name, extensions, rg_metadata.len())?;
+            if let Some(statistics) = &file.row_group_statistics {
+                if statistics.len() != rg_metadata.len() {
+                    return exec_err!(
+                        "Invalid row group statistics for {file_name}. Specified {} row groups, but file has {}",
+                        statistics.len(),
+                        rg_metadata.len()
+                    );
+                }
+            }
             let mut row_groups = RowGroupAccessPlanFilter::new(access_plan);
             // if there is a range restricting what parts of the file to read
             if let Some(range) = file_range.as_ref() {
@@ -324,13 +364,21 @@ impl FileOpener for ParquetOpener {
             // If there is a predicate that can be evaluated against the metadata
             if let Some(predicate) = predicate.as_ref() {
                 if enable_row_group_stats_pruning {
-                    row_groups.prune_by_statistics(
-                        &physical_file_schema,
-                        builder.parquet_schema(),
-                        rg_metadata,
-                        predicate,
-                        &file_metrics,
-                    );
+                    match &file.row_group_statistics {
+                        Some(statistics) => row_groups.prune_by_row_group_statistics(
+                            &logical_file_schema,
+                            statistics,
+                            predicate,
+                            &file_metrics,
+                        ),
+                        None => row_groups.prune_by_statistics(
+                            &physical_file_schema,
+                            builder.parquet_schema(),
+                            rg_metadata,
+                            predicate,
+                            &file_metrics,
+                        ),
+                    }
                 }
 
                 if enable_bloom_filter && !row_groups.is_empty() {
@@ -481,6 +529,71 @@ pub(crate) fn build_pruning_predicates(
     (pruning_predicate, Some(page_pruning_predicate))
 }
 
+/// Returns the properties to decrypt `file` with the keys of `provider` for
+/// the columns of `file_schema`
+#[cfg(feature = "parquet_encryption")]
+fn file_decryption_properties(
+    provider: &dyn ColumnKeyProvider,
+    file: &PartitionedFile,
+    file_schema: &Schema,
+) -> Result<FileDecryptionProperties> {
+    let footer_key = provider.footer_key(file)?;
+    let mut builder =
+        FileDecryptionProperties::builder(footer_key.clone().unwrap_or_default());
+    if footer_key.is_none() {
+        builder = builder.disable_footer_signature_verification();
+    }
+
+    let mut column_names = vec![];
+    collect_column_names(file_schema.fields(), "", &mut column_names);
+    for column_name in column_names {
+        if let Some(key) = provider.column_key(file, &column_name)? {
+            builder = builder.with_column_key(&column_name, key);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Collects the dot separated paths of the leaf columns of `fields`, the
+/// fields of a struct are prefixed by `prefix`
+#[cfg(feature = "parquet_encryption")]
+fn collect_column_names(fields: &Fields, prefix: &str, column_names: &mut Vec<String>) {
+    for field in fields {
+        collect_field_column_names(
+            field,
+            &format!("{prefix}{}", field.name()),
+            column_names,
+        );
+    }
+}
+
+/// Collects the dot separated paths of the leaf columns of `field` stored at
+/// `path`. The elements of a list are stored in a repeated `list` group, and
+/// the entries of a map in a repeated group named after the entries field, as
+/// written by the [`ArrowWriter`](parquet::arrow::ArrowWriter) unless it
+/// coerces the types to the names of the Parquet specification
+#[cfg(feature = "parquet_encryption")]
+fn collect_field_column_names(field: &Field, path: &str, column_names: &mut Vec<String>) {
+    match field.data_type() {
+        DataType::Struct(children) => {
+            collect_column_names(children, &format!("{path}."), column_names)
+        }
+        DataType::List(element)
+        | DataType::LargeList(element)
+        | DataType::FixedSizeList(element, _) => collect_field_column_names(
+            element,
+            &format!("{path}.list.{}", element.name()),
+            column_names,
+        ),
+        DataType::Map(entries, _) => collect_field_column_names(
+            entries,
+            &format!("{path}.{}", entries.name()),
+            column_names,
+        ),
+        _ => column_names.push(path.to_string()),
+    }
+}
+
 /// Returns a `ArrowReaderMetadata` with the page index loaded, loading
 /// it from the underlying `AsyncFileReader` if necessary.
 async fn load_page_index<T: AsyncFileReader>(
@@ -544,6 +657,7 @@ mod test {
     use futures::{Stream, StreamExt};
     use object_store::{memory::InMemory, path::Path, ObjectMeta, ObjectStore};
     use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
 
     use crate::{opener::ParquetOpener, DefaultParquetFileReaderFactory};
 
@@ -636,6 +750,7 @@ mod test {
                 schema_adapter_factory: Arc::new(DefaultSchemaAdapterFactory),
                 enable_row_group_stats_pruning: true,
                 coerce_int96: None,
+                column_key_provider: None,
             }
         };
 
@@ -720,6 +835,7 @@ mod test {
                 schema_adapter_factory: Arc::new(DefaultSchemaAdapterFactory),
                 enable_row_group_stats_pruning: true,
                 coerce_int96: None,
+                column_key_provider: None,
             }
         };
 
@@ -816,6 +932,7 @@ mod test {
                 schema_adapter_factory: Arc::new(DefaultSchemaAdapterFactory),
                 enable_row_group_stats_pruning: true,
                 coerce_int96: None,
+                column_key_provider: None,
             }
         };
         let make_meta = || FileMeta {
@@ -879,4 +996,157 @@ mod test {
         assert_eq!(num_batches, 0);
         assert_eq!(num_rows, 0);
     }
+
+    #[tokio::test]
+    async fn test_prune_on_row_group_statistics() {
+        let store = Arc::new(InMemory::new()) as Arc<dyn ObjectStore>;
+
+        let batch = record_batch!(("a", Int32, vec![Some(1), Some(2), Some(3), Some(4)]))
+            .unwrap();
+        // two row groups, with a in [1, 2] and [3, 4]
+        let mut out = BytesMut::new().writer();
+        {
+            let props = WriterProperties::builder()
+                .set_max_row_group_size(2)
+                .build();
+            let mut writer =
+                ArrowWriter::try_new(&mut out, batch.schema(), Some(props)).unwrap();
+            writer.write(&batch).unwrap();
+            writer.finish().unwrap();
+        }
+        let data = out.into_inner().freeze();
+        let data_size = data.len() as u64;
+        store
+            .put(&Path::from("test.parquet"), data.into())
+            .await
+            .unwrap();
+
+        let schema = batch.schema();
+        let row_group_statistics = |min: i32, max: i32| {
+            let mut statistics = Statistics::new_unknown(&schema);
+            statistics.column_statistics[0] = ColumnStatistics::new_unknown()
+                .with_min_value(Precision::Exact(ScalarValue::Int32(Some(min))))
+                .with_max_value(Precision::Exact(ScalarValue::Int32(Some(max))));
+            Arc::new(statistics)
+        };
+        let file = PartitionedFile::new("test.parquet", data_size);
+
+        let expr = col("a").eq(lit(1));
+        let predicate = logical2physical(&expr, &schema);
+        let opener = ParquetOpener {
+            partition_index: 0,
+            projection: Arc::new([0]),
+            batch_size: 1024,
+            limit: None,
+            predicate: Some(predicate),
+            logical_file_schema: schema.clone(),
+            metadata_size_hint: None,
+            metrics: ExecutionPlanMetricsSet::new(),
+            parquet_file_reader_factory: Arc::new(DefaultParquetFileReaderFactory::new(
+                Arc::clone(&store),
+            )),
+            partition_fields: vec![],
+            pushdown_filters: false,
+            reorder_filters: false,
+            enable_page_index: false,
+            enable_bloom_filter: false,
+            schema_adapter_factory: Arc::new(DefaultSchemaAdapterFactory),
+            enable_row_group_stats_pruning: true,
+            coerce_int96: None,
+            column_key_provider: None,
+        };
+        let open = |file: PartitionedFile| {
+            let meta = FileMeta::from(file.object_meta.clone());
+            opener.open(meta, file).unwrap()
+        };
+
+        // The statistics of the footer only skip the second row group
+        let stream = open(file.clone()).await.unwrap();
+        let (_, num_rows) = count_batches_and_rows(stream).await;
+        assert_eq!(num_rows, 2);
+
+        // The attached statistics are used instead of the footer, and skip
+        // both row groups
+        let stream = open(file.clone().with_row_group_statistics(vec![
+            row_group_statistics(5, 6),
+            row_group_statistics(3, 4),
+        ]))
+        .await
+        .unwrap();
+        let (_, num_rows) = count_batches_and_rows(stream).await;
+        assert_eq!(num_rows, 0);
+
+        // There must be statistics for each row group
+        let err = open(file.with_row_group_statistics(vec![row_group_statistics(1, 2)]))
+            .await
+            .err()
+            .unwrap();
+        assert!(err
+            .to_string()
+            .contains("Specified 1 row groups, but file has 2"));
+    }
+
+    #[cfg(feature = "parquet_encryption")]
+    #[test]
+    fn test_collect_nested_column_names() {
+        use arrow::datatypes::Fields;
+        use parquet::arrow::ArrowSchemaConverter;
+
+        use crate::opener::collect_column_names;
+
+        let item = Arc::new(Field::new("item", DataType::Int32, true));
+        let point = Fields::from(vec![
+            Field::new("x", DataType::Float64, false),
+            Field::new("y", DataType::Float64, false),
+        ]);
+        let entries = Arc::new(Field::new(
+            "entries",
+            DataType::Struct(Fields::from(vec![
+                Field::new("keys", DataType::Utf8, false),
+                Field::new("values", DataType::Int64, true),
+            ])),
+            false,
+        ));
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("ints", DataType::List(Arc::clone(&item)), true),
+            Field::new("large_ints", DataType::LargeList(Arc::clone(&item)), true),
+            Field::new("fixed_ints", DataType::FixedSizeList(item, 2), true),
+            Field::new(
+                "nested",
+                DataType::List(Arc::new(Field::new(
+                    "item",
+                    DataType::List(Arc::new(Field::new_struct("item", point, true))),
+                    true,
+                ))),
+                true,
+            ),
+            Field::new("map", DataType::Map(entries, false), true),
+        ]);
+
+        let mut column_names = vec![];
+        collect_column_names(schema.fields(), "", &mut column_names);
+
+        // the paths match the leaf columns written to the file
+        let parquet_schema = ArrowSchemaConverter::new().convert(&schema).unwrap();
+        let leaf_paths = parquet_schema
+            .columns()
+            .iter()
+            .map(|column| column.path().string())
+            .collect::<Vec<_>>();
+        assert_eq!(column_names, leaf_paths);
+        assert_eq!(
+            column_names,
+            [
+                "id",
+                "ints.list.item",
+                "large_ints.list.item",
+                "fixed_ints.list.item",
+                "nested.list.item.list.item.x",
+                "nested.list.item.list.item.y",
+                "map.entries.keys",
+                "map.entries.values",
+            ]
+        );
+    }
 }
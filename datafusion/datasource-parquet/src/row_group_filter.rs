@@ -20,9 +20,9 @@ use std::sync::Arc;
 
 use super::{ParquetAccessPlan, ParquetFileMetrics};
 use arrow::array::{ArrayRef, BooleanArray};
-use arrow::datatypes::Schema;
-use datafusion_common::pruning::PruningStatistics;
-use datafusion_common::{Column, Result, ScalarValue};
+use arrow::datatypes::{Schema, SchemaRef};
+use datafusion_common::pruning::{PrunableStatistics, PruningStatistics};
+use datafusion_common::{Column, Result, ScalarValue, Statistics};
 use datafusion_datasource::FileRange;
 use datafusion_physical_optimizer::pruning::PruningPredicate;
 use parquet::arrow::arrow_reader::statistics::StatisticsConverter;
@@ -126,9 +126,53 @@ impl RowGroupAccessPlanFilter {
             row_group_metadatas,
             arrow_schema,
         };
+        self.prune_row_groups(&row_group_indexes, &pruning_stats, predicate, metrics);
+    }
+
+    /// Prune remaining row groups using `statistics` of each row group of the
+    /// file, e.g. the [`PartitionedFile::row_group_statistics`], instead of
+    /// the statistics of the row group metadata.
+    ///
+    /// The `statistics` describe the columns of `file_schema`.
+    ///
+    /// [`PartitionedFile::row_group_statistics`]: datafusion_datasource::PartitionedFile::row_group_statistics
+    ///
+    /// # Panics
+    /// if `statistics.len() != self.len()`
+    pub fn prune_by_row_group_statistics(
+        &mut self,
+        file_schema: &SchemaRef,
+        statistics: &[Arc<Statistics>],
+        predicate: &PruningPredicate,
+        metrics: &ParquetFileMetrics,
+    ) {
+        // scoped timer updates on drop
+        let _timer_guard = metrics.statistics_eval_time.timer();
+
+        assert_eq!(statistics.len(), self.access_plan.len());
+        let row_group_indexes = self.access_plan.row_group_indexes();
+        let pruning_stats = PrunableStatistics::new(
+            row_group_indexes
+                .iter()
+                .map(|&i| Arc::clone(&statistics[i]))
+                .collect(),
+            Arc::clone(file_schema),
+        );
+        self.prune_row_groups(&row_group_indexes, &pruning_stats, predicate, metrics);
+    }
 
+    /// Skips the row groups at `row_group_indexes` for which `predicate` can
+    /// not be true according to `pruning_stats`, which has a container per
+    /// row group index
+    fn prune_row_groups(
+        &mut self,
+        row_group_indexes: &[usize],
+        pruning_stats: &dyn PruningStatistics,
+        predicate: &PruningPredicate,
+        metrics: &ParquetFileMetrics,
+    ) {
         // try to prune the row groups in a single call
-        match predicate.prune(&pruning_stats) {
+        match predicate.prune(pruning_stats) {
             Ok(values) => {
                 // values[i] is false means the predicate could not be true for row group i
                 for (idx, &value) in row_group_indexes.iter().zip(values.iter()) {
@@ -502,6 +502,7 @@ impl FileSource for ParquetSource {
             enable_row_group_stats_pruning: self.table_parquet_options.global.pruning,
             schema_adapter_factory,
             coerce_int96,
+            column_key_provider: base_config.column_key_provider.clone(),
         })
     }
 
@@ -651,6 +652,10 @@ impl FileSource for ParquetSource {
         Ok(FilterPushdownPropagation::with_filters(filters).with_updated_node(source))
     }
 
+    fn filter(&self) -> Option<Arc<dyn PhysicalExpr>> {
+        self.predicate.clone()
+    }
+
     fn with_schema_adapter_factory(
         &self,
         schema_adapter_factory: Arc<dyn SchemaAdapterFactory>,
@@ -70,6 +70,29 @@ pub fn compare_split_groups_by_statistics_algorithms(c: &mut Criterion) {
                 },
             );
 
+            // Benchmark original algorithm with an index over the groups
+            group.bench_with_input(
+                BenchmarkId::new(
+                    "indexed",
+                    format!("files={num_files},overlap={overlap:.1}"),
+                ),
+                &(
+                    file_groups.clone(),
+                    file_schema.clone(),
+                    sort_ordering.clone(),
+                ),
+                |b, (fg, schema, order)| {
+                    let mut result = Vec::new();
+                    b.iter(|| {
+                        result = FileScanConfig::split_groups_by_statistics_indexed(
+                            schema, fg, order,
+                        )
+                        .unwrap();
+                    });
+                    assert!(verify_sort_integrity(&result));
+                },
+            );
+
             // Benchmark new algorithm with different target partitions
             for &tp in &target_partitions {
                 group.bench_with_input(
@@ -99,6 +122,39 @@ pub fn compare_split_groups_by_statistics_algorithms(c: &mut Criterion) {
     }
 
     group.finish();
+
+    // Many files ending up in many groups, where scanning all groups for each
+    // file is slow
+    let mut group = c.benchmark_group("split_groups_many_files");
+    group.sample_size(10);
+
+    for &num_files in &[1_000, 10_000] {
+        // for more than 100 files all files have the same min and max value,
+        // so each file is placed in its own group
+        let file_groups = generate_test_files(num_files, 0.0);
+        let input = (file_groups, file_schema.clone(), sort_ordering.clone());
+        group.bench_with_input(
+            BenchmarkId::new("original", format!("files={num_files}")),
+            &input,
+            |b, (fg, schema, order)| {
+                b.iter(|| {
+                    FileScanConfig::split_groups_by_statistics(schema, fg, order).unwrap()
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("indexed", format!("files={num_files}")),
+            &input,
+            |b, (fg, schema, order)| {
+                b.iter(|| {
+                    FileScanConfig::split_groups_by_statistics_indexed(schema, fg, order)
+                        .unwrap()
+                });
+            },
+        );
+    }
+
+    group.finish();
 }
 
 criterion_group!(benches, compare_split_groups_by_statistics_algorithms);
@@ -0,0 +1,280 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Verification of file contents against expected checksums, see
+//! [`FileScanConfigBuilder::with_checksum_verification`]
+//!
+//! [`FileScanConfigBuilder::with_checksum_verification`]: crate::file_scan_config::FileScanConfigBuilder::with_checksum_verification
+
+use std::fmt;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use crate::file_meta::FileMeta;
+use crate::file_stream::{FileOpenFuture, FileOpener};
+use crate::PartitionedFile;
+
+use datafusion_common::{exec_err, Result};
+use futures::StreamExt;
+use object_store::ObjectStore;
+use sha2::Digest;
+
+/// Algorithm used to compute the checksum of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChecksumAlgorithm {
+    /// CRC-32C (Castagnoli), as a 4 byte big endian value
+    Crc32c,
+    /// 64 bit xxHash with a seed of 0, as an 8 byte big endian value
+    XxHash64,
+    /// SHA-256, as a 32 byte digest
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    /// Computes the checksum of `data`
+    pub fn checksum(&self, data: &[u8]) -> Vec<u8> {
+        let mut state = ChecksumState::new(*self);
+        state.update(data);
+        state.finish()
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Crc32c => write!(f, "crc32c"),
+            Self::XxHash64 => write!(f, "xxhash64"),
+            Self::Sha256 => write!(f, "sha256"),
+        }
+    }
+}
+
+/// Incrementally computes a checksum
+enum ChecksumState {
+    Crc32c(u32),
+    XxHash64(twox_hash::XxHash64),
+    Sha256(sha2::Sha256),
+}
+
+impl ChecksumState {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Crc32c => Self::Crc32c(0),
+            ChecksumAlgorithm::XxHash64 => {
+                Self::XxHash64(twox_hash::XxHash64::with_seed(0))
+            }
+            ChecksumAlgorithm::Sha256 => Self::Sha256(sha2::Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Crc32c(crc) => *crc = crc32c::crc32c_append(*crc, data),
+            Self::XxHash64(hasher) => hasher.write(data),
+            Self::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            Self::Crc32c(crc) => crc.to_be_bytes().to_vec(),
+            Self::XxHash64(hasher) => hasher.finish().to_be_bytes().to_vec(),
+            Self::Sha256(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// A [`FileOpener`] that verifies the checksum of each file before opening it
+/// with the inner opener.
+///
+/// # Cost
+///
+/// Every file is read twice: the whole object is first streamed from the
+/// object store to compute its checksum, and then read again by the inner
+/// opener. The checksum can not be computed from the bytes the inner opener
+/// reads, as formats like Parquet fetch the footer first and then only the
+/// byte ranges of the selected row groups and columns, so these bytes
+/// neither cover the whole file nor arrive in order. Verification therefore
+/// at least doubles the bytes fetched from the object store, and adds a full
+/// download to files the inner opener would mostly skip, e.g. Parquet files
+/// pruned down to a few row groups. The file is hashed as it is streamed, so
+/// it is not buffered in memory.
+///
+/// Files read in byte ranges are rejected, as each range would download and
+/// verify the whole file again.
+///
+/// Opening a file fails if it has no [`PartitionedFile::expected_checksum`],
+/// or if the checksum does not match.
+pub struct ChecksumVerifyingOpener {
+    inner: Arc<dyn FileOpener>,
+    object_store: Arc<dyn ObjectStore>,
+    algorithm: ChecksumAlgorithm,
+}
+
+impl ChecksumVerifyingOpener {
+    /// Create a new opener verifying files with `algorithm` before opening
+    /// them with `inner`
+    pub fn new(
+        inner: Arc<dyn FileOpener>,
+        object_store: Arc<dyn ObjectStore>,
+        algorithm: ChecksumAlgorithm,
+    ) -> Self {
+        Self {
+            inner,
+            object_store,
+            algorithm,
+        }
+    }
+}
+
+impl FileOpener for ChecksumVerifyingOpener {
+    fn open(&self, file_meta: FileMeta, file: PartitionedFile) -> Result<FileOpenFuture> {
+        let Some(expected) = file.expected_checksum.clone() else {
+            return exec_err!("No expected checksum for file {}", file.path());
+        };
+        if file_meta.range.is_some() || !file.ranges.is_empty() {
+            return exec_err!(
+                "Cannot verify the checksum of file {} read in byte ranges",
+                file.path()
+            );
+        }
+
+        let inner = Arc::clone(&self.inner);
+        let object_store = Arc::clone(&self.object_store);
+        let algorithm = self.algorithm;
+        Ok(Box::pin(async move {
+            let mut stream = object_store.get(file_meta.location()).await?.into_stream();
+            let mut state = ChecksumState::new(algorithm);
+            while let Some(chunk) = stream.next().await.transpose()? {
+                state.update(&chunk);
+            }
+
+            let actual = state.finish();
+            if actual != expected {
+                return exec_err!(
+                    "{algorithm} checksum mismatch for file {}: expected {}, got {}",
+                    file.path(),
+                    to_hex(&expected),
+                    to_hex(&actual)
+                );
+            }
+
+            inner.open(file_meta, file)?.await
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use object_store::{memory::InMemory, path::Path};
+
+    #[test]
+    fn test_checksum_algorithms() {
+        let data = b"123456789";
+        assert_eq!(
+            to_hex(&ChecksumAlgorithm::Crc32c.checksum(data)),
+            "e3069283"
+        );
+        assert_eq!(
+            to_hex(&ChecksumAlgorithm::Sha256.checksum(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            to_hex(&ChecksumAlgorithm::XxHash64.checksum(b"")),
+            "ef46db3751d8e999"
+        );
+
+        // the checksum does not depend on how the data is split
+        for algorithm in [
+            ChecksumAlgorithm::Crc32c,
+            ChecksumAlgorithm::XxHash64,
+            ChecksumAlgorithm::Sha256,
+        ] {
+            let mut state = ChecksumState::new(algorithm);
+            state.update(&data[..4]);
+            state.update(&data[4..]);
+            assert_eq!(state.finish(), algorithm.checksum(data));
+        }
+    }
+
+    /// Opener returning an empty stream for every file
+    struct EmptyOpener;
+
+    impl FileOpener for EmptyOpener {
+        fn open(&self, _: FileMeta, _: PartitionedFile) -> Result<FileOpenFuture> {
+            Ok(Box::pin(async { Ok(futures::stream::empty().boxed()) }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checksum_verifying_opener() -> Result<()> {
+        let data = b"a,1\nb,2\n";
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let location = Path::from("data.csv");
+        store.put(&location, data.to_vec().into()).await?;
+        let object_meta = store.head(&location).await?;
+
+        let opener = ChecksumVerifyingOpener::new(
+            Arc::new(EmptyOpener),
+            Arc::clone(&store),
+            ChecksumAlgorithm::Sha256,
+        );
+        let open = |checksum: Option<Vec<u8>>| {
+            let mut file = PartitionedFile::from(object_meta.clone());
+            if let Some(checksum) = checksum {
+                file = file.with_expected_checksum(checksum);
+            }
+            opener.open(object_meta.clone().into(), file)
+        };
+
+        // correct checksum
+        let correct = ChecksumAlgorithm::Sha256.checksum(data);
+        assert!(open(Some(correct.clone()))?.await.is_ok());
+
+        // wrong checksum
+        let err = open(Some(vec![0; 32]))?.await.err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("sha256 checksum mismatch for file data.csv"));
+
+        // missing checksum
+        let err = open(None).err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("No expected checksum for file data.csv"));
+
+        // byte range
+        let file = PartitionedFile::from(object_meta.clone())
+            .with_expected_checksum(correct)
+            .with_range(0, 4);
+        let file_meta = FileMeta {
+            range: file.range.clone(),
+            ..FileMeta::from(object_meta.clone())
+        };
+        let err = opener.open(file_meta, file).err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("Cannot verify the checksum of file data.csv read in byte ranges"));
+        Ok(())
+    }
+}
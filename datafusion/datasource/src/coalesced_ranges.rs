@@ -0,0 +1,259 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reading the [`PartitionedFile::ranges`] of a file with coalesced requests,
+//! see [`FileScanConfigBuilder::with_range_coalescing`]
+//!
+//! [`FileScanConfigBuilder::with_range_coalescing`]: crate::file_scan_config::FileScanConfigBuilder::with_range_coalescing
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::file_meta::FileMeta;
+use crate::file_stream::FileOpenFuture;
+use crate::sub_file::CreateOpenerForStore;
+use crate::PartitionedFile;
+
+use arrow::error::ArrowError;
+use async_trait::async_trait;
+use bytes::Bytes;
+use datafusion_common::Result;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::{
+    coalesce_ranges, GetOptions, GetRange, GetResult, GetResultPayload, ListResult,
+    MultipartUpload, ObjectMeta, ObjectStore, PutMultipartOpts, PutOptions, PutPayload,
+    PutResult,
+};
+
+/// A read-only view of an object store serving reads of byte ranges of an
+/// object that were fetched beforehand from memory.
+///
+/// Reads of other locations, and reads of the object that are not contained
+/// in one of the fetched ranges, are passed to the inner store unchanged.
+#[derive(Debug)]
+pub struct PrefetchedRangesObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    meta: ObjectMeta,
+    /// The fetched ranges of the object and their bytes
+    ranges: Vec<(Range<u64>, Bytes)>,
+}
+
+impl PrefetchedRangesObjectStore {
+    /// Create a view of `inner` serving reads of the `ranges` of the object
+    /// described by `meta` from their fetched bytes
+    pub fn new(
+        inner: Arc<dyn ObjectStore>,
+        meta: ObjectMeta,
+        ranges: Vec<(Range<u64>, Bytes)>,
+    ) -> Self {
+        Self {
+            inner,
+            meta,
+            ranges,
+        }
+    }
+
+    /// Returns the bytes of `range` of the object, if it is contained in one
+    /// of the fetched ranges
+    fn prefetched(&self, range: &Range<u64>) -> Option<Bytes> {
+        self.ranges
+            .iter()
+            .find(|(fetched, _)| fetched.start <= range.start && range.end <= fetched.end)
+            .map(|(fetched, bytes)| {
+                let start = (range.start - fetched.start) as usize;
+                let end = (range.end - fetched.start) as usize;
+                bytes.slice(start..end)
+            })
+    }
+}
+
+impl fmt::Display for PrefetchedRangesObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PrefetchedRangesObjectStore({}, {})",
+            self.inner, self.meta.location
+        )
+    }
+}
+
+#[async_trait]
+impl ObjectStore for PrefetchedRangesObjectStore {
+    async fn put_opts(
+        &self,
+        _location: &Path,
+        _payload: PutPayload,
+        _opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        _location: &Path,
+        _opts: PutMultipartOpts,
+    ) -> object_store::Result<Box<dyn MultipartUpload>> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        let unconditional = !options.head
+            && options.if_match.is_none()
+            && options.if_none_match.is_none()
+            && options.if_modified_since.is_none()
+            && options.if_unmodified_since.is_none()
+            && options.version.is_none();
+        if let (true, Some(GetRange::Bounded(range))) = (
+            location == &self.meta.location && unconditional,
+            &options.range,
+        ) {
+            if let Some(bytes) = self.prefetched(range) {
+                return Ok(GetResult {
+                    payload: GetResultPayload::Stream(
+                        futures::stream::once(async move { Ok(bytes) }).boxed(),
+                    ),
+                    meta: self.meta.clone(),
+                    range: range.clone(),
+                    attributes: Default::default(),
+                });
+            }
+        }
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn delete(&self, _location: &Path) -> object_store::Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    fn list(
+        &self,
+        prefix: Option<&Path>,
+    ) -> BoxStream<'static, object_store::Result<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(
+        &self,
+        prefix: Option<&Path>,
+    ) -> object_store::Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, _from: &Path, _to: &Path) -> object_store::Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn copy_if_not_exists(
+        &self,
+        _from: &Path,
+        _to: &Path,
+    ) -> object_store::Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+}
+
+/// Opens the [`PartitionedFile::ranges`] of `file` one after the other, as
+/// files split into each range, and concatenates their batches.
+///
+/// The ranges are fetched first with [`coalesce_ranges`], merging the ranges
+/// at most `max_gap` bytes apart into a single request, and are read with an
+/// opener created by `create_opener` for a [`PrefetchedRangesObjectStore`]
+/// of `object_store`.
+pub(crate) fn open_coalesced_ranges(
+    object_store: Arc<dyn ObjectStore>,
+    create_opener: Arc<CreateOpenerForStore>,
+    max_gap: u64,
+    file_meta: FileMeta,
+    file: PartitionedFile,
+) -> Result<FileOpenFuture> {
+    Ok(Box::pin(async move {
+        let location = &file.object_meta.location;
+        let ranges = file
+            .ranges
+            .iter()
+            .map(|range| range.start as u64..range.end as u64)
+            .collect::<Vec<_>>();
+        let fetched = coalesce_ranges(
+            &ranges,
+            |range| object_store.get_range(location, range),
+            max_gap,
+        )
+        .await?;
+        let store = PrefetchedRangesObjectStore::new(
+            Arc::clone(&object_store),
+            file.object_meta.clone(),
+            ranges.into_iter().zip(fetched).collect(),
+        );
+        let opener = create_opener(Arc::new(store));
+
+        let opens = file
+            .ranges
+            .iter()
+            .map(|range| {
+                let file_meta = FileMeta {
+                    range: Some(range.clone()),
+                    ..file_meta.clone()
+                };
+                let file = PartitionedFile {
+                    range: Some(range.clone()),
+                    ranges: vec![],
+                    ..file.clone()
+                };
+                opener.open(file_meta, file)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(futures::stream::iter(opens)
+            .then(|open| open)
+            .map_err(ArrowError::from)
+            .try_flatten()
+            .boxed())
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use object_store::memory::InMemory;
+
+    #[tokio::test]
+    async fn test_prefetched_ranges_object_store() -> object_store::Result<()> {
+        let inner: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let location = Path::from("data");
+        inner.put(&location, "aaabbbbcc".into()).await?;
+        let meta = inner.head(&location).await?;
+
+        // the bytes of the fetched range are served from memory
+        let store = PrefetchedRangesObjectStore::new(
+            Arc::clone(&inner),
+            meta,
+            vec![(3..7, Bytes::from("BBBB"))],
+        );
+        assert_eq!(store.get_range(&location, 4..6).await?, "BB");
+        // other reads are passed to the inner store
+        assert_eq!(store.get_range(&location, 2..4).await?, "ab");
+        assert_eq!(store.get(&location).await?.bytes().await?, "aaabbbbcc");
+        Ok(())
+    }
+}
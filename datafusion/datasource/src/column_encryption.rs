@@ -0,0 +1,55 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Keys to decrypt files with encrypted columns, see
+//! [`FileScanConfigBuilder::with_column_key_provider`]
+//!
+//! [`FileScanConfigBuilder::with_column_key_provider`]: crate::file_scan_config::FileScanConfigBuilder::with_column_key_provider
+
+use std::fmt::Debug;
+
+use crate::PartitionedFile;
+
+use datafusion_common::Result;
+
+/// Provides the keys to decrypt files whose columns are encrypted
+/// individually, such as Parquet files with modular encryption.
+///
+/// Columns without a key are read as plaintext, so files may mix encrypted
+/// and plaintext columns. Whether the keys are used depends on the
+/// [`FileSource`], e.g. the Parquet source requires the
+/// `parquet_encryption` feature.
+///
+/// [`FileSource`]: crate::file::FileSource
+pub trait ColumnKeyProvider: Debug + Send + Sync {
+    /// Returns the key to decrypt the footer of `file`, if it is encrypted.
+    ///
+    /// For files with a plaintext footer, the key is used to verify the
+    /// integrity of the footer, which is skipped if no key is returned.
+    fn footer_key(&self, file: &PartitionedFile) -> Result<Option<Vec<u8>>>;
+
+    /// Returns the key to decrypt the column named `column_name` of `file`,
+    /// or `None` if it is not encrypted.
+    ///
+    /// Columns nested in a struct are named by their path, with the names of
+    /// the enclosing fields separated by dots, e.g. `address.zip`.
+    fn column_key(
+        &self,
+        file: &PartitionedFile,
+        column_name: &str,
+    ) -> Result<Option<Vec<u8>>>;
+}
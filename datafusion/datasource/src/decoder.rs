@@ -23,12 +23,14 @@ use ::arrow::array::RecordBatch;
 use arrow::error::ArrowError;
 use bytes::Buf;
 use bytes::Bytes;
-use datafusion_common::Result;
+use bytes::BytesMut;
+use datafusion_common::{exec_err, Result};
 use futures::stream::BoxStream;
 use futures::StreamExt as _;
 use futures::{ready, Stream};
 use std::collections::VecDeque;
 use std::fmt;
+use std::io::Read;
 use std::task::Poll;
 
 /// Possible outputs of a [`BatchDeserializer`].
@@ -189,3 +191,110 @@ pub fn deserialize_stream<'a>(
     })
     .boxed()
 }
+
+/// The UTF-8 byte order mark
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// Returns the length of the UTF-8 byte order mark at the start of `head`, if
+/// any, or an error if `head` starts with a UTF-16 byte order mark, as such
+/// files can not be read as UTF-8.
+fn bom_len(head: &[u8]) -> Result<usize> {
+    if head.starts_with(UTF8_BOM) {
+        Ok(UTF8_BOM.len())
+    } else if head.starts_with(&[0xFF, 0xFE]) || head.starts_with(&[0xFE, 0xFF]) {
+        exec_err!(
+            "File starts with a UTF-16 byte order mark, but only UTF-8 encoded files are supported"
+        )
+    } else {
+        Ok(0)
+    }
+}
+
+/// Skips a leading UTF-8 byte order mark (BOM) of `reader`, so that it does
+/// not become part of the first field or record of a text file.
+///
+/// Returns an error if the input starts with a UTF-16 byte order mark.
+pub fn strip_bom_read(mut reader: Box<dyn Read + Send>) -> Result<Box<dyn Read + Send>> {
+    let mut head = Vec::with_capacity(UTF8_BOM.len());
+    (&mut reader)
+        .take(UTF8_BOM.len() as u64)
+        .read_to_end(&mut head)?;
+    head.drain(..bom_len(&head)?);
+    Ok(Box::new(Read::chain(std::io::Cursor::new(head), reader)))
+}
+
+/// Skips a leading UTF-8 byte order mark (BOM) of a stream of bytes.
+///
+/// See [`strip_bom_read`] for details.
+pub fn strip_bom_stream<'a>(
+    mut input: BoxStream<'a, Result<Bytes>>,
+) -> BoxStream<'a, Result<Bytes>> {
+    futures::stream::once(async move {
+        // The BOM may be split across several chunks
+        let mut head = BytesMut::new();
+        while head.len() < UTF8_BOM.len() {
+            match input.next().await {
+                Some(Ok(chunk)) => head.extend_from_slice(&chunk),
+                Some(Err(e)) => return futures::stream::iter(vec![Err(e)]).boxed(),
+                None => break,
+            }
+        }
+        let head = match bom_len(&head) {
+            Ok(len) => head.freeze().slice(len..),
+            Err(e) => return futures::stream::iter(vec![Err(e)]).boxed(),
+        };
+        let head = (!head.is_empty()).then_some(Ok(head));
+        futures::stream::iter(head).chain(input).boxed()
+    })
+    .flatten()
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::TryStreamExt;
+
+    fn read_all(input: &'static [u8]) -> Result<Vec<u8>> {
+        let mut output = vec![];
+        strip_bom_read(Box::new(input))?.read_to_end(&mut output)?;
+        Ok(output)
+    }
+
+    async fn stream_all(chunks: Vec<&'static [u8]>) -> Result<Vec<u8>> {
+        let input = futures::stream::iter(chunks.into_iter().map(|c| Ok(Bytes::from(c))));
+        let chunks = strip_bom_stream(input.boxed())
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(chunks.concat())
+    }
+
+    #[tokio::test]
+    async fn test_strip_utf8_bom() -> Result<()> {
+        let expected = b"a,b\n1,2\n".to_vec();
+        assert_eq!(read_all(b"\xEF\xBB\xBFa,b\n1,2\n")?, expected);
+        assert_eq!(read_all(b"a,b\n1,2\n")?, expected);
+        assert_eq!(read_all(b"\xEF\xBB\xBF")?, b"");
+        assert_eq!(read_all(b"a")?, b"a");
+
+        // the BOM may be split across chunks
+        let chunks: Vec<&[u8]> = vec![b"\xEF", b"\xBB\xBFa,b", b"\n1,2\n"];
+        assert_eq!(stream_all(chunks).await?, expected);
+        let chunks: Vec<&[u8]> = vec![b"a,b", b"\n1,2\n"];
+        assert_eq!(stream_all(chunks).await?, expected);
+        assert_eq!(stream_all(vec![]).await?, b"");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_utf16_bom_is_rejected() {
+        for input in [&b"\xFF\xFEa\x00"[..], &b"\xFE\xFF\x00a"[..]] {
+            let err = read_all(input).unwrap_err();
+            assert!(err.to_string().contains("UTF-16 byte order mark"));
+
+            let err = stream_all(vec![input]).await.unwrap_err();
+            assert!(err.to_string().contains("UTF-16 byte order mark"));
+        }
+    }
+}
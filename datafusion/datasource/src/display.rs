@@ -18,7 +18,8 @@
 use datafusion_physical_plan::{DisplayAs, DisplayFormatType};
 
 use crate::file_groups::FileGroup;
-use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 
 /// A wrapper to customize partitioned file display
 ///
@@ -26,8 +27,14 @@ use std::fmt::{Debug, Formatter, Result as FmtResult};
 /// ```text
 /// {NUM_GROUPS groups: [[file1, file2,...], [fileN, fileM, ...], ...]}
 /// ```
+///
+/// The second field holds optional labels of the groups, by index. In verbose
+/// mode a labeled group is prefixed with its label, e.g. `recent: [file1]`.
 #[derive(Debug)]
-pub(crate) struct FileGroupsDisplay<'a>(pub(crate) &'a [FileGroup]);
+pub(crate) struct FileGroupsDisplay<'a>(
+    pub(crate) &'a [FileGroup],
+    pub(crate) &'a [Option<String>],
+);
 
 impl DisplayAs for FileGroupsDisplay<'_> {
     fn fmt_as(&self, t: DisplayFormatType, f: &mut Formatter) -> FmtResult {
@@ -42,11 +49,16 @@ impl DisplayAs for FileGroupsDisplay<'_> {
                     FileGroupDisplay(group).fmt_as(t, f)
                 })?;
             }
-            DisplayFormatType::Verbose => {
-                fmt_elements_split_by_commas(self.0.iter(), f, |group, f| {
+            DisplayFormatType::Verbose => fmt_elements_split_by_commas(
+                self.0.iter().enumerate(),
+                f,
+                |(idx, group), f| {
+                    if let Some(Some(label)) = self.1.get(idx) {
+                        write!(f, "{label}: ")?;
+                    }
                     FileGroupDisplay(group).fmt_as(t, f)
-                })?
-            }
+                },
+            )?,
         }
         write!(f, "]}}")
     }
@@ -90,6 +102,58 @@ impl DisplayAs for FileGroupDisplay<'_> {
     }
 }
 
+/// A wrapper displaying how evenly files are spread across file groups
+///
+/// Prints in the format:
+/// ```text
+/// {groups: 2, files_per_group: {1 file: 1 group, 3 files: 1 group}, total_bytes: 400, avg_bytes_per_group: 200, min_bytes_per_group: 100, max_bytes_per_group: 300}
+/// ```
+///
+/// The bytes of a file are the length of its range if it has one, otherwise
+/// its size.
+#[derive(Debug)]
+pub(crate) struct FileGroupsBalanceDisplay<'a>(pub(crate) &'a [FileGroup]);
+
+impl Display for FileGroupsBalanceDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let n_groups = self.0.len();
+        let mut files_per_group = BTreeMap::<usize, usize>::new();
+        let mut group_bytes = Vec::with_capacity(n_groups);
+        for group in self.0 {
+            *files_per_group.entry(group.len()).or_default() += 1;
+            let bytes = group
+                .iter()
+                .map(|pf| match &pf.range {
+                    Some(range) => (range.end - range.start).max(0) as u64,
+                    None => pf.object_meta.size,
+                })
+                .sum::<u64>();
+            group_bytes.push(bytes);
+        }
+
+        write!(f, "{{groups: {n_groups}, files_per_group: {{")?;
+        fmt_elements_split_by_commas(
+            files_per_group.iter(),
+            f,
+            |(n_files, count), f| {
+                let files = if *n_files == 1 { "file" } else { "files" };
+                let groups = if *count == 1 { "group" } else { "groups" };
+                write!(f, "{n_files} {files}: {count} {groups}")
+            },
+        )?;
+
+        let total_bytes = group_bytes.iter().sum::<u64>();
+        let avg_bytes = total_bytes.checked_div(n_groups as u64).unwrap_or(0);
+        let min_bytes = group_bytes.iter().min().copied().unwrap_or(0);
+        let max_bytes = group_bytes.iter().max().copied().unwrap_or(0);
+        write!(
+            f,
+            "}}, total_bytes: {total_bytes}, avg_bytes_per_group: {avg_bytes}, \
+             min_bytes_per_group: {min_bytes}, max_bytes_per_group: {max_bytes}}}"
+        )
+    }
+}
+
 /// helper to format an array of up to N elements
 fn fmt_up_to_n_elements<E, F>(
     elements: &[E],
@@ -143,7 +207,10 @@ mod tests {
     #[test]
     fn file_groups_display_empty() {
         let expected = "{0 groups: []}";
-        assert_eq!(DefaultDisplay(FileGroupsDisplay(&[])).to_string(), expected);
+        assert_eq!(
+            DefaultDisplay(FileGroupsDisplay(&[], &[])).to_string(),
+            expected
+        );
     }
 
     #[test]
@@ -155,7 +222,7 @@ mod tests {
 
         let expected = "{1 group: [[foo, bar]]}";
         assert_eq!(
-            DefaultDisplay(FileGroupsDisplay(&files)).to_string(),
+            DefaultDisplay(FileGroupsDisplay(&files, &[])).to_string(),
             expected
         );
     }
@@ -170,7 +237,7 @@ mod tests {
 
         let expected = "{3 groups: [[foo, bar], [baz], []]}";
         assert_eq!(
-            DefaultDisplay(FileGroupsDisplay(&files)).to_string(),
+            DefaultDisplay(FileGroupsDisplay(&files, &[])).to_string(),
             expected
         );
     }
@@ -185,7 +252,30 @@ mod tests {
 
         let expected = "{3 groups: [[foo, bar], [baz], []]}";
         assert_eq!(
-            VerboseDisplay(FileGroupsDisplay(&files)).to_string(),
+            VerboseDisplay(FileGroupsDisplay(&files, &[])).to_string(),
+            expected
+        );
+    }
+
+    #[test]
+    fn file_groups_display_labels() {
+        let files = [
+            FileGroup::new(vec![partitioned_file("foo"), partitioned_file("bar")]),
+            FileGroup::new(vec![partitioned_file("baz")]),
+            FileGroup::new(vec![partitioned_file("qux")]),
+        ];
+        let labels = [Some("recent".to_string()), None];
+
+        let expected = "{3 groups: [recent: [foo, bar], [baz], [qux]]}";
+        assert_eq!(
+            VerboseDisplay(FileGroupsDisplay(&files, &labels)).to_string(),
+            expected
+        );
+
+        // labels are only shown in verbose mode
+        let expected = "{3 groups: [[foo, bar], [baz], [qux]]}";
+        assert_eq!(
+            DefaultDisplay(FileGroupsDisplay(&files, &labels)).to_string(),
             expected
         );
     }
@@ -204,7 +294,7 @@ mod tests {
 
         let expected = "{7 groups: [[foo, bar], [baz], [qux], [quux], [quuux], ...]}";
         assert_eq!(
-            DefaultDisplay(FileGroupsDisplay(&files)).to_string(),
+            DefaultDisplay(FileGroupsDisplay(&files, &[])).to_string(),
             expected
         );
     }
@@ -224,7 +314,7 @@ mod tests {
         let expected =
             "{7 groups: [[foo, bar], [baz], [qux], [quux], [quuux], [quuuux], []]}";
         assert_eq!(
-            VerboseDisplay(FileGroupsDisplay(&files)).to_string(),
+            VerboseDisplay(FileGroupsDisplay(&files, &[])).to_string(),
             expected
         );
     }
@@ -294,6 +384,13 @@ mod tests {
             statistics: None,
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         }
     }
 }
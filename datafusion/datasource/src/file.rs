@@ -26,6 +26,7 @@ use crate::file_groups::FileGroupPartitioner;
 use crate::file_scan_config::FileScanConfig;
 use crate::file_stream::FileOpener;
 use crate::schema_adapter::SchemaAdapterFactory;
+use crate::PartitionedFile;
 use arrow::datatypes::SchemaRef;
 use datafusion_common::config::ConfigOptions;
 use datafusion_common::{not_impl_err, Result, Statistics};
@@ -34,6 +35,7 @@ use datafusion_physical_plan::filter_pushdown::FilterPushdownPropagation;
 use datafusion_physical_plan::metrics::ExecutionPlanMetricsSet;
 use datafusion_physical_plan::DisplayFormatType;
 
+use futures::future::BoxFuture;
 use object_store::ObjectStore;
 
 /// Helper function to convert any type implementing FileSource to Arc&lt;dyn FileSource&gt;
@@ -123,6 +125,20 @@ pub trait FileSource: Send + Sync {
         Ok(FilterPushdownPropagation::unsupported(filters))
     }
 
+    /// Returns the filter pushed down into this [`FileSource`], if any, which
+    /// the rows it produces are filtered by.
+    ///
+    /// Sources accepting filters in [`Self::try_pushdown_filters`] must
+    /// return them here for scans with
+    /// [`FileScanConfigBuilder::with_statistics_fast_path`] to be correct, as
+    /// the fast path does not open the files. The default implementation
+    /// returns `None`.
+    ///
+    /// [`FileScanConfigBuilder::with_statistics_fast_path`]: crate::file_scan_config::FileScanConfigBuilder::with_statistics_fast_path
+    fn filter(&self) -> Option<Arc<dyn PhysicalExpr>> {
+        None
+    }
+
     /// Set optional schema adapter factory.
     ///
     /// [`SchemaAdapterFactory`] allows user to specify how fields from the
@@ -148,4 +164,23 @@ pub trait FileSource: Send + Sync {
     fn schema_adapter_factory(&self) -> Option<Arc<dyn SchemaAdapterFactory>> {
         None
     }
+
+    /// Returns a future resolving to the names of the columns of `file` in
+    /// the order they are stored, for formats whose columns are read by
+    /// position, such as CSV files with a header. Used to match the columns
+    /// of the files to the file schema by name, see
+    /// [`FileScanConfigBuilder::with_name_based_column_matching`].
+    ///
+    /// The default implementation returns `None`, for formats whose columns
+    /// are already matched by name, or that do not name their columns.
+    ///
+    /// [`FileScanConfigBuilder::with_name_based_column_matching`]: crate::file_scan_config::FileScanConfigBuilder::with_name_based_column_matching
+    fn physical_column_names(
+        &self,
+        _object_store: Arc<dyn ObjectStore>,
+        _file: &PartitionedFile,
+        _config: &FileScanConfig,
+    ) -> Option<BoxFuture<'static, Result<Vec<String>>>> {
+        None
+    }
 }
@@ -180,6 +180,9 @@ impl FileCompressionType {
     }
 
     /// Given a `Stream`, create a `Stream` which data are decompressed with `FileCompressionType`.
+    ///
+    /// Gzip input made of several concatenated members (e.g. `cat a.gz b.gz`)
+    /// is decoded until the end of the stream, not just the first member.
     pub fn convert_stream<'a>(
         &self,
         s: BoxStream<'a, Result<Bytes>>,
@@ -331,4 +334,47 @@ mod tests {
 
         Ok(())
     }
+
+    /// Gzip-compress `data` as a single gzip member
+    #[cfg(feature = "compression")]
+    fn gzip_member(data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut encoder =
+            flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_gzip_concatenated_members() -> Result<(), DataFusionError> {
+        use std::io::Read;
+
+        // equivalent to `cat a.gz b.gz > c.gz`
+        let mut data = gzip_member(b"a,1\nb,2\n");
+        data.extend(gzip_member(b"c,3\nd,4\n"));
+        let expected = "a,1\nb,2\nc,3\nd,4\n";
+
+        // async decoding continues past the end of the first member
+        let stream = futures::stream::iter(vec![Ok::<Bytes, DataFusionError>(
+            Bytes::from(data.clone()),
+        )]);
+        let decoded = FileCompressionType::GZIP
+            .convert_stream(stream.boxed())?
+            .map(|r| r.unwrap())
+            .collect::<Vec<Bytes>>()
+            .await
+            .concat();
+        assert_eq!(String::from_utf8_lossy(&decoded), expected);
+
+        // as does sync decoding
+        let mut decoded = String::new();
+        FileCompressionType::GZIP
+            .convert_read(std::io::Cursor::new(data))?
+            .read_to_string(&mut decoded)?;
+        assert_eq!(decoded, expected);
+
+        Ok(())
+    }
 }
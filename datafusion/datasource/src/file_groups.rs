@@ -193,7 +193,7 @@ impl FileGroupPartitioner {
         let has_ranges = file_groups
             .iter()
             .flat_map(FileGroup::iter)
-            .any(|f| f.range.is_some());
+            .any(|f| f.range.is_some() || !f.ranges.is_empty());
         if has_ranges {
             return None;
         }
@@ -19,52 +19,88 @@
 //! file sources.
 
 use std::{
-    any::Any, borrow::Cow, collections::HashMap, fmt::Debug, fmt::Formatter,
-    fmt::Result as FmtResult, marker::PhantomData, sync::Arc,
+    any::Any,
+    borrow::Cow,
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet},
+    fmt::Debug,
+    fmt::Formatter,
+    fmt::Result as FmtResult,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+    num::NonZeroUsize,
+    sync::Arc,
 };
 
+#[cfg(feature = "checksum")]
+use crate::checksum::ChecksumAlgorithm;
 use crate::file_groups::FileGroup;
+use crate::schema_adapter::ExtraColumnPolicy;
 #[allow(unused_imports)]
 use crate::schema_adapter::SchemaAdapterFactory;
 use crate::{
-    display::FileGroupsDisplay,
+    column_encryption::ColumnKeyProvider,
+    display::{FileGroupsBalanceDisplay, FileGroupsDisplay},
     file::FileSource,
     file_compression_type::FileCompressionType,
-    file_stream::FileStream,
+    file_stream::{ProgressCallback, ScanCursor},
+    memory::MemorySourceConfig,
+    missing_file::MissingFilePolicy,
     source::{DataSource, DataSourceExec},
     statistics::MinMaxStatistics,
-    PartitionedFile,
+    trailing_bytes::Trailer,
+    url_refresh::UrlRefresher,
+    FileRange, PartitionedFile,
 };
+use ahash::RandomState;
 use arrow::datatypes::FieldRef;
+use arrow::row::Row;
 use arrow::{
     array::{
-        ArrayData, ArrayRef, BufferBuilder, DictionaryArray, RecordBatch,
-        RecordBatchOptions,
+        ArrayData, ArrayRef, BufferBuilder, DictionaryArray, PrimitiveArray, RecordBatch,
+        RecordBatchOptions, RunArray, UInt64Array,
     },
     buffer::Buffer,
-    datatypes::{ArrowNativeType, DataType, Field, Schema, SchemaRef, UInt16Type},
+    compute::cast,
+    datatypes::{
+        ArrowNativeType, DataType, Field, Fields, Int16Type, Int32Type, Int64Type,
+        RunEndIndexType, Schema, SchemaRef, TimeUnit, UInt16Type,
+    },
 };
 use datafusion_common::config::ConfigOptions;
+use datafusion_common::parsers::CompressionTypeVariant;
+use datafusion_common::pruning::{
+    CompositePruningStatistics, PartitionPruningStatistics, PrunableStatistics,
+};
+use datafusion_common::stats::Precision;
 use datafusion_common::{
-    exec_err, ColumnStatistics, Constraints, DataFusionError, Result, ScalarValue,
-    Statistics,
+    exec_err, hash_utils::create_hashes, plan_err, ColumnStatistics, Constraints,
+    DataFusionError, Result, ScalarValue, Statistics,
 };
 use datafusion_execution::{
     object_store::ObjectStoreUrl, SendableRecordBatchStream, TaskContext,
 };
 use datafusion_physical_expr::expressions::Column;
-use datafusion_physical_expr::{EquivalenceProperties, Partitioning};
+use datafusion_physical_expr::pruning::PruningPredicate;
+use datafusion_physical_expr::utils::{collect_columns, reassign_predicate_columns};
+use datafusion_physical_expr::{
+    AcrossPartitions, ConstExpr, EquivalenceProperties, Partitioning,
+};
 use datafusion_physical_expr_common::physical_expr::PhysicalExpr;
 use datafusion_physical_expr_common::sort_expr::{LexOrdering, PhysicalSortExpr};
 use datafusion_physical_plan::filter_pushdown::FilterPushdownPropagation;
 use datafusion_physical_plan::{
     display::{display_orderings, ProjectSchemaDisplay},
-    metrics::ExecutionPlanMetricsSet,
+    metrics::{ExecutionPlanMetricsSet, MetricBuilder},
     projection::{all_alias_free_columns, new_projections_for_columns, ProjectionExec},
     DisplayAs, DisplayFormatType, ExecutionPlan,
 };
 
 use log::{debug, warn};
+use object_store::path::Path;
+use object_store::ObjectMeta;
+use twox_hash::XxHash64;
 
 /// The base configurations for a [`DataSourceExec`], the a physical plan for
 /// any given file format.
@@ -186,6 +222,296 @@ pub struct FileScanConfig {
     /// Batch size while creating new batches
     /// Defaults to [`datafusion_common::config::ExecutionOptions`] batch_size.
     pub batch_size: Option<usize>,
+    /// Expressions, keyed by column name, used to fill columns of
+    /// `file_schema` that are missing from a file instead of NULLs.
+    ///
+    /// See [`ColumnDefaultsSchemaAdapterFactory`] for details.
+    ///
+    /// [`ColumnDefaultsSchemaAdapterFactory`]: crate::schema_adapter::ColumnDefaultsSchemaAdapterFactory
+    pub column_default_exprs: HashMap<String, Arc<dyn PhysicalExpr>>,
+    /// Optional function applied to `file_groups` when the config is built,
+    /// and cleared once applied.
+    ///
+    /// See [`FileScanConfigBuilder::with_file_preprocessor`]
+    pub file_preprocessor: Option<FilePreprocessor>,
+    /// Error of the [`Self::file_preprocessor`], reported when the config
+    /// is validated
+    pub(crate) file_preprocessor_error: Option<Arc<DataFusionError>>,
+    /// If true, [`FileScanConfigBuilder::try_build`] fails if any file has an
+    /// unknown (zero) size
+    pub require_known_sizes: bool,
+    /// Object store URLs that override [`Self::object_store_url`] for
+    /// individual partitions (file groups), keyed by partition index.
+    pub partition_store_urls: BTreeMap<usize, ObjectStoreUrl>,
+    /// If set, the checksum of each file is verified before it is read
+    #[cfg(feature = "checksum")]
+    pub checksum_verification: Option<ChecksumAlgorithm>,
+    /// Maximum number of rows to read from each file, see
+    /// [`FileScanConfigBuilder::with_per_file_row_limit`]
+    pub per_file_row_limit: Option<usize>,
+    /// How columns of a file that are not part of [`Self::file_schema`] are
+    /// handled
+    pub extra_column_policy: ExtraColumnPolicy,
+    /// If false, all columns are read from the files and the projection is
+    /// applied afterwards. Only meant for debugging, see
+    /// [`FileScanConfigBuilder::with_projection_pushdown`]
+    pub projection_pushdown: bool,
+    /// How files listed more than once in [`Self::file_groups`] are handled
+    pub duplicate_file_policy: DuplicateFilePolicy,
+    /// If true, the files of each group and the rows of each file are read in
+    /// reverse, see [`FileScanConfigBuilder::with_reverse_scan`]
+    pub reverse_scan: bool,
+    /// Optional labels of [`Self::file_groups`] by index, shown in verbose
+    /// EXPLAIN output, see [`FileScanConfigBuilder::with_group_labels`]
+    pub group_labels: Vec<Option<String>>,
+    /// If true, the schema metadata of the first file read is merged into
+    /// the schema metadata of the output, see
+    /// [`FileScanConfigBuilder::with_merge_file_metadata`]
+    pub merge_file_metadata: bool,
+    /// Maximum estimated memory size of the batches read from the files, see
+    /// [`FileScanConfigBuilder::with_max_batch_bytes`]
+    pub max_batch_bytes: Option<usize>,
+    /// Whether repartitioning splits the files by their statistics to preserve
+    /// the output ordering, see
+    /// [`FileScanConfigBuilder::with_order_preserving_repartition`]
+    pub order_preserving_repartition: bool,
+    /// Function refreshing the URL of files whose access expired while they are
+    /// read, see [`FileScanConfigBuilder::with_url_refresher`]
+    pub url_refresher: Option<UrlRefresher>,
+    /// If true, files without any rows produce a single empty batch, see
+    /// [`FileScanConfigBuilder::with_emit_empty_file_batches`]
+    pub emit_empty_file_batches: bool,
+    /// If true, the files are merged into a single globally sorted partition, see
+    /// [`FileScanConfigBuilder::with_sort_merge_on_read`]
+    pub sort_merge_on_read: bool,
+    /// Function the progress of the scan of each partition is reported to, see
+    /// [`FileScanConfigBuilder::with_progress_callback`]
+    pub progress_callback: Option<ProgressCallback>,
+    /// Keys to decrypt the encrypted columns of the files, see
+    /// [`FileScanConfigBuilder::with_column_key_provider`]
+    pub column_key_provider: Option<Arc<dyn ColumnKeyProvider>>,
+    /// Key the files are declared to be partitioned by, see
+    /// [`FileScanConfigBuilder::with_declared_partitioning`] and
+    /// [`Self::hash_partition_by`]
+    pub declared_partitioning: Option<PartitionKey>,
+    /// Read every column of the file schema as nullable, see
+    /// [`FileScanConfigBuilder::with_force_nullable`]
+    pub force_nullable: bool,
+    /// Whether the scan reserves the memory of its batches in flight, see
+    /// [`FileScanConfigBuilder::with_memory_reservation`]
+    pub memory_reservation: bool,
+    /// New names of output columns by their name in the table schema, see
+    /// [`FileScanConfigBuilder::with_output_rename`]
+    pub output_rename: HashMap<String, String>,
+    /// Whether the batches are verified to be sorted by the output ordering, see
+    /// [`FileScanConfigBuilder::with_verify_ordering`]
+    pub verify_ordering: bool,
+    /// Indexes of the columns of the table schema to compute statistics for, see
+    /// [`FileScanConfigBuilder::with_stats_columns`]
+    pub stats_columns: Option<Vec<usize>>,
+    /// Maximum number of files each partition opens ahead of the file it
+    /// scans, see [`FileScanConfigBuilder::with_max_open_files_per_partition`]
+    pub max_open_files_per_partition: NonZeroUsize,
+    /// Expected ratios of the decompressed to the compressed size of files, see
+    /// [`FileScanConfigBuilder::with_compression_ratio_hint`]
+    pub compression_ratio_hints: HashMap<CompressionTypeVariant, f64>,
+    /// Where the values of the partition columns are read from, see
+    /// [`FileScanConfigBuilder::with_partition_column_source`]
+    pub partition_column_source: PartitionColumnSource,
+    /// Positions to resume the scan of partitions from, keyed by partition
+    /// index, see [`FileScanConfigBuilder::with_resume_cursor`]
+    pub resume_cursors: BTreeMap<usize, ScanCursor>,
+    /// Dialect used to parse CSV files, see
+    /// [`FileScanConfigBuilder::with_csv_dialect`]
+    pub csv_dialect: Option<CsvDialect>,
+    /// Name of the output column holding the index of the partition each row
+    /// was read from, see [`FileScanConfigBuilder::with_partition_index_column`]
+    pub partition_index_column: Option<String>,
+    /// What to do when a file is not found when it is opened, see
+    /// [`FileScanConfigBuilder::with_missing_file_policy`]
+    pub missing_file_policy: MissingFilePolicy,
+    /// Whether the files of each group are read in the order of their path, see
+    /// [`FileScanConfigBuilder::with_sort_files_in_group`]
+    pub sort_files_in_group: bool,
+    /// Function deciding whether each file is read, see
+    /// [`FileScanConfigBuilder::with_file_admission`]
+    pub file_admission: Option<FileAdmissionPolicy>,
+    /// Whether bytes following the end of the data of Parquet and Arrow IPC files
+    /// are ignored, see [`FileScanConfigBuilder::with_tolerate_trailing_bytes`]
+    pub tolerate_trailing_bytes: bool,
+    /// Whether files marked with [`PartitionedFile::with_tombstoned`] are read, see
+    /// [`FileScanConfigBuilder::with_include_tombstoned`]
+    pub include_tombstoned: bool,
+    /// Number of bytes of files each partition opens ahead of the file it scans,
+    /// see [`FileScanConfigBuilder::with_read_ahead_bytes`]
+    pub read_ahead_bytes: Option<usize>,
+    /// Columns with the same value in every row appended to the output, see
+    /// [`FileScanConfigBuilder::with_constant_column`]
+    pub constant_columns: Vec<(String, ScalarValue)>,
+    /// Whether the columns of the files are matched to the file schema by name
+    /// rather than by position, see
+    /// [`FileScanConfigBuilder::with_name_based_column_matching`]
+    pub name_based_column_matching: bool,
+    /// Unit the values of the timestamp partition columns are normalized to, see
+    /// [`FileScanConfigBuilder::with_partition_timestamp_normalization`]
+    pub partition_timestamp_normalization: Option<TimeUnit>,
+    /// The number of bytes of files the scan stops opening files after, see
+    /// [`FileScanConfigBuilder::with_byte_scan_budget`]
+    pub byte_scan_budget: Option<usize>,
+    /// Whether scans of only partition columns are answered from the
+    /// statistics of the files without opening them, see
+    /// [`FileScanConfigBuilder::with_statistics_fast_path`]
+    pub statistics_fast_path: bool,
+    /// Maximum number of bytes between the [`PartitionedFile::ranges`] of a
+    /// file that are fetched with a single request, see
+    /// [`FileScanConfigBuilder::with_range_coalescing`]
+    pub range_coalescing: Option<u64>,
+}
+
+/// Function that transforms the file groups of a [`FileScanConfig`] when it is
+/// built, see [`FileScanConfigBuilder::with_file_preprocessor`]
+pub type FilePreprocessor =
+    Arc<dyn Fn(Vec<FileGroup>) -> Result<Vec<FileGroup>> + Send + Sync>;
+
+/// Whether a file is read by the scan, see [`FileAdmissionPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileAdmission {
+    /// The file is read
+    Admit,
+    /// The file is silently removed from the scan
+    Reject,
+    /// The scan fails with the message
+    RejectWithError(String),
+}
+
+/// Function deciding whether a file is read by the scan from its
+/// [`ObjectMeta`], e.g. to enforce a retention policy, see
+/// [`FileScanConfigBuilder::with_file_admission`]
+pub type FileAdmissionPolicy = Arc<dyn Fn(&ObjectMeta) -> FileAdmission + Send + Sync>;
+
+/// Function computing the value of a partition column of a file from the
+/// values of the preceding partition columns, see
+/// [`FileScanConfigBuilder::with_derived_partition_column`]
+pub type DerivePartitionValue =
+    Arc<dyn Fn(&[ScalarValue]) -> Result<ScalarValue> + Send + Sync>;
+
+/// How files that appear more than once in the file groups of a
+/// [`FileScanConfig`] are handled, see
+/// [`FileScanConfigBuilder::with_duplicate_file_policy`]
+///
+/// Two files are duplicates if they have the same location and their byte
+/// ranges overlap, a file without a range covering the whole file. Different
+/// ranges of the same file that do not overlap are not duplicates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateFilePolicy {
+    /// Duplicate files are read as listed, so their rows are returned more
+    /// than once
+    #[default]
+    Allow,
+    /// Only the first occurrence of a file is kept
+    Dedup,
+    /// Opening the scan fails, naming the duplicate files
+    Error,
+}
+
+/// Key the files of a [`FileScanConfig`] are declared to be partitioned by,
+/// see [`FileScanConfigBuilder::with_declared_partitioning`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartitionKey {
+    /// The partition column with this name
+    Column(String),
+}
+
+/// How unevenly the data of a [`FileScanConfig`] is distributed across its
+/// file groups, see [`FileScanConfig::group_skew`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupSkew {
+    /// Bytes of the largest group divided by the mean bytes per group, 1.0
+    /// if the groups are balanced
+    pub max_over_mean_bytes: f64,
+    /// Rows of the largest group divided by the mean rows per group, `None`
+    /// if the number of rows of any file is unknown
+    pub max_over_mean_rows: Option<f64>,
+    /// Gini coefficient of the bytes per group, from 0.0 if the groups are
+    /// balanced to almost 1.0 if a single group has all the bytes
+    pub gini: f64,
+}
+
+/// How the constant values of partition columns are encoded in the output,
+/// see [`FileScanConfigBuilder::with_partition_encoding`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionEncoding {
+    /// `Dictionary(UInt16, _)` arrays, whose keys are all zero. The keys
+    /// buffer is shared between batches of the same length.
+    Dictionary,
+    /// `RunEndEncoded(Int32, _)` arrays with a single run, so the memory used
+    /// by a partition column does not depend on the number of rows.
+    RunEnd,
+    /// Arrays of the value type repeating the value for every row, for
+    /// operators that perform poorly on encoded inputs
+    Decoded,
+}
+
+impl PartitionEncoding {
+    /// Returns the type of a partition column declared with `data_type` when
+    /// encoded with this encoding
+    pub fn data_type(&self, data_type: &DataType) -> DataType {
+        let value_type = match data_type {
+            DataType::Dictionary(_, value_type) => value_type.as_ref().clone(),
+            DataType::RunEndEncoded(_, values) => values.data_type().clone(),
+            data_type => data_type.clone(),
+        };
+        match self {
+            Self::Dictionary => wrap_partition_type_in_dict(value_type),
+            Self::RunEnd => DataType::RunEndEncoded(
+                Arc::new(Field::new("run_ends", DataType::Int32, false)),
+                Arc::new(Field::new("values", value_type, true)),
+            ),
+            Self::Decoded => value_type,
+        }
+    }
+}
+
+/// Where the values of the partition columns are read from when the files
+/// also contain a column of the same name, see
+/// [`FileScanConfigBuilder::with_partition_column_source`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PartitionColumnSource {
+    /// The [`PartitionedFile::partition_values`], usually parsed from the
+    /// path of the files. The columns of the files are not read.
+    #[default]
+    PathOnly,
+    /// The columns of the files, or the partition values for the rows where
+    /// they are null, e.g. for files without the column
+    FilePreferred,
+    /// The partition values, after checking that the non null values of the
+    /// columns of the files are equal to them. Reading a file fails
+    /// otherwise.
+    Validate,
+}
+
+/// The dialect of CSV files, see [`FileScanConfigBuilder::with_csv_dialect`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    /// The column delimiter
+    pub delimiter: u8,
+    /// The quote character
+    pub quote: u8,
+    /// The escape character, quotes are escaped by doubling them if `None`
+    pub escape: Option<u8>,
+    /// Lines beginning with this byte are ignored
+    pub comment: Option<u8>,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            escape: None,
+            comment: None,
+        }
+    }
 }
 
 /// A builder for [`FileScanConfig`]'s.
@@ -263,6 +589,54 @@ pub struct FileScanConfigBuilder {
     file_compression_type: Option<FileCompressionType>,
     new_lines_in_values: Option<bool>,
     batch_size: Option<usize>,
+    column_default_exprs: HashMap<String, Arc<dyn PhysicalExpr>>,
+    file_preprocessor: Option<FilePreprocessor>,
+    file_preprocessor_error: Option<Arc<DataFusionError>>,
+    require_known_sizes: bool,
+    partition_store_urls: BTreeMap<usize, ObjectStoreUrl>,
+    #[cfg(feature = "checksum")]
+    checksum_verification: Option<ChecksumAlgorithm>,
+    per_file_row_limit: Option<usize>,
+    extra_column_policy: ExtraColumnPolicy,
+    projection_pushdown: bool,
+    duplicate_file_policy: DuplicateFilePolicy,
+    reverse_scan: bool,
+    group_labels: Vec<Option<String>>,
+    merge_file_metadata: bool,
+    max_batch_bytes: Option<usize>,
+    order_preserving_repartition: bool,
+    url_refresher: Option<UrlRefresher>,
+    emit_empty_file_batches: bool,
+    sort_merge_on_read: bool,
+    progress_callback: Option<ProgressCallback>,
+    column_key_provider: Option<Arc<dyn ColumnKeyProvider>>,
+    declared_partitioning: Option<PartitionKey>,
+    force_nullable: bool,
+    memory_reservation: bool,
+    output_rename: HashMap<String, String>,
+    verify_ordering: bool,
+    stats_columns: Option<Vec<usize>>,
+    max_open_files_per_partition: NonZeroUsize,
+    compression_ratio_hints: HashMap<CompressionTypeVariant, f64>,
+    partition_column_source: PartitionColumnSource,
+    resume_cursors: BTreeMap<usize, ScanCursor>,
+    csv_dialect: Option<CsvDialect>,
+    partition_index_column: Option<String>,
+    missing_file_policy: MissingFilePolicy,
+    sort_files_in_group: bool,
+    file_admission: Option<FileAdmissionPolicy>,
+    tolerate_trailing_bytes: bool,
+    include_tombstoned: bool,
+    read_ahead_bytes: Option<usize>,
+    constant_columns: Vec<(String, ScalarValue)>,
+    name_based_column_matching: bool,
+    partition_timestamp_normalization: Option<TimeUnit>,
+    byte_scan_budget: Option<usize>,
+    statistics_fast_path: bool,
+    range_coalescing: Option<u64>,
+    per_file_byte_cap: Option<u64>,
+    partition_encoding: Option<PartitionEncoding>,
+    derived_partition_cols: Vec<(Field, DerivePartitionValue)>,
 }
 
 impl FileScanConfigBuilder {
@@ -291,9 +665,69 @@ impl FileScanConfigBuilder {
             table_partition_cols: vec![],
             constraints: None,
             batch_size: None,
+            column_default_exprs: HashMap::new(),
+            file_preprocessor: None,
+            file_preprocessor_error: None,
+            require_known_sizes: false,
+            partition_store_urls: BTreeMap::new(),
+            #[cfg(feature = "checksum")]
+            checksum_verification: None,
+            per_file_row_limit: None,
+            extra_column_policy: ExtraColumnPolicy::default(),
+            projection_pushdown: true,
+            duplicate_file_policy: DuplicateFilePolicy::default(),
+            reverse_scan: false,
+            group_labels: vec![],
+            merge_file_metadata: false,
+            max_batch_bytes: None,
+            order_preserving_repartition: false,
+            url_refresher: None,
+            emit_empty_file_batches: false,
+            sort_merge_on_read: false,
+            progress_callback: None,
+            column_key_provider: None,
+            declared_partitioning: None,
+            force_nullable: false,
+            memory_reservation: false,
+            output_rename: HashMap::new(),
+            verify_ordering: false,
+            stats_columns: None,
+            max_open_files_per_partition: NonZeroUsize::new(1).unwrap(),
+            compression_ratio_hints: HashMap::new(),
+            partition_column_source: PartitionColumnSource::PathOnly,
+            resume_cursors: BTreeMap::new(),
+            csv_dialect: None,
+            partition_index_column: None,
+            missing_file_policy: MissingFilePolicy::Error,
+            sort_files_in_group: false,
+            file_admission: None,
+            tolerate_trailing_bytes: false,
+            include_tombstoned: false,
+            read_ahead_bytes: None,
+            constant_columns: vec![],
+            name_based_column_matching: false,
+            partition_timestamp_normalization: None,
+            byte_scan_budget: None,
+            statistics_fast_path: false,
+            range_coalescing: None,
+            per_file_byte_cap: None,
+            partition_encoding: None,
+            derived_partition_cols: vec![],
         }
     }
 
+    /// Create a new [`FileScanConfigBuilder`] whose file schema is the schema
+    /// of the sample `batch`, e.g. to scan files written from that batch.
+    ///
+    /// Only the schema is shared with `batch`, its data is not copied.
+    pub fn new_from_batch_schema(
+        object_store_url: ObjectStoreUrl,
+        batch: &RecordBatch,
+        file_source: Arc<dyn FileSource>,
+    ) -> Self {
+        Self::new(object_store_url, batch.schema(), file_source)
+    }
+
     /// Set the maximum number of records to read from this plan. If `None`,
     /// all records after filtering are returned.
     pub fn with_limit(mut self, limit: Option<usize>) -> Self {
@@ -317,6 +751,36 @@ impl FileScanConfigBuilder {
         self
     }
 
+    /// Set the projection to every column of `file_schema` and of the
+    /// partition columns set so far, in order, except the columns named
+    /// `excluded`, e.g. to drop a few columns of a wide table.
+    ///
+    /// Like [`Self::with_projection`], this replaces any previous projection.
+    /// Returns an error if a name is not the name of a column.
+    pub fn with_projection_except(mut self, excluded: &[&str]) -> Result<Self> {
+        let names = self
+            .file_schema
+            .fields()
+            .iter()
+            .chain(&self.table_partition_cols)
+            .map(|field| field.name().as_str())
+            .collect::<Vec<_>>();
+        if let Some(unknown) = excluded.iter().find(|name| !names.contains(name)) {
+            return plan_err!(
+                "Cannot exclude unknown column {unknown} from the projection"
+            );
+        }
+        self.projection = Some(
+            names
+                .iter()
+                .enumerate()
+                .filter(|(_, name)| !excluded.contains(name))
+                .map(|(idx, _)| idx)
+                .collect(),
+        );
+        Ok(self)
+    }
+
     /// Set the partitioning columns
     pub fn with_table_partition_cols(mut self, table_partition_cols: Vec<Field>) -> Self {
         self.table_partition_cols = table_partition_cols
@@ -339,6 +803,30 @@ impl FileScanConfigBuilder {
         self
     }
 
+    /// Set the statistics of the column `name` of `file_schema` only,
+    /// keeping those of the other columns, which are unknown if no
+    /// statistics were set.
+    ///
+    /// Returns an error if `file_schema` has no column `name`.
+    pub fn with_column_statistics(
+        mut self,
+        name: &str,
+        column_statistics: ColumnStatistics,
+    ) -> Result<Self> {
+        let Ok(idx) = self.file_schema.index_of(name) else {
+            return plan_err!("Cannot set the statistics of unknown column {name}");
+        };
+        let statistics = self
+            .statistics
+            .get_or_insert_with(|| Statistics::new_unknown(&self.file_schema));
+        statistics.column_statistics.resize(
+            self.file_schema.fields().len(),
+            ColumnStatistics::new_unknown(),
+        );
+        statistics.column_statistics[idx] = column_statistics;
+        Ok(self)
+    }
+
     /// Set the list of files to be processed, grouped into partitions.
     ///
     /// Each file must have a schema of `file_schema` or a subset. If
@@ -393,1975 +881,8765 @@ impl FileScanConfigBuilder {
         self
     }
 
-    /// Set the batch_size property
+    /// Set the batch size used when reading files, overriding the session's
+    /// `datafusion.execution.batch_size` for this scan only.
+    ///
+    /// If `None` (the default) the session batch size is used. The batch size
+    /// must be greater than 0, otherwise opening the scan returns an error.
     pub fn with_batch_size(mut self, batch_size: Option<usize>) -> Self {
         self.batch_size = batch_size;
         self
     }
 
-    /// Build the final [`FileScanConfig`] with all the configured settings.
+    /// Set expressions used to fill columns that are missing from a file.
     ///
-    /// This method takes ownership of the builder and returns the constructed `FileScanConfig`.
-    /// Any unset optional fields will use their default values.
-    pub fn build(self) -> FileScanConfig {
-        let Self {
-            object_store_url,
-            file_schema,
-            file_source,
-            limit,
-            projection,
-            table_partition_cols,
-            constraints,
-            file_groups,
-            statistics,
-            output_ordering,
-            file_compression_type,
-            new_lines_in_values,
-            batch_size,
-        } = self;
-
-        let constraints = constraints.unwrap_or_default();
-        let statistics =
-            statistics.unwrap_or_else(|| Statistics::new_unknown(&file_schema));
-
-        let file_source = file_source
-            .with_statistics(statistics.clone())
-            .with_schema(Arc::clone(&file_schema));
-        let file_compression_type =
-            file_compression_type.unwrap_or(FileCompressionType::UNCOMPRESSED);
-        let new_lines_in_values = new_lines_in_values.unwrap_or(false);
-
-        FileScanConfig {
-            object_store_url,
-            file_schema,
-            file_source,
-            limit,
-            projection,
-            table_partition_cols,
-            constraints,
-            file_groups,
-            output_ordering,
-            file_compression_type,
-            new_lines_in_values,
-            batch_size,
-        }
+    /// By default, columns of the file schema that a particular file does not
+    /// contain are filled with NULLs. A column with an entry in
+    /// `column_default_exprs` is instead computed by evaluating the expression
+    /// against the other columns read from that file. Expressions are written
+    /// against the file schema, must produce the column's declared type, and
+    /// may only reference projected columns.
+    ///
+    /// The [`FileSource`] must support
+    /// [`FileSource::with_schema_adapter_factory`].
+    pub fn with_column_default_exprs(
+        mut self,
+        column_default_exprs: HashMap<String, Arc<dyn PhysicalExpr>>,
+    ) -> Self {
+        self.column_default_exprs = column_default_exprs;
+        self
     }
-}
 
-impl From<FileScanConfig> for FileScanConfigBuilder {
-    fn from(config: FileScanConfig) -> Self {
-        Self {
-            object_store_url: config.object_store_url,
-            file_schema: config.file_schema,
-            file_source: Arc::<dyn FileSource>::clone(&config.file_source),
-            file_groups: config.file_groups,
-            statistics: config.file_source.statistics().ok(),
-            output_ordering: config.output_ordering,
-            file_compression_type: Some(config.file_compression_type),
-            new_lines_in_values: Some(config.new_lines_in_values),
-            limit: config.limit,
-            projection: config.projection,
-            table_partition_cols: config.table_partition_cols,
-            constraints: Some(config.constraints),
-            batch_size: config.batch_size,
-        }
+    /// Only read the first `bytes` bytes of each file.
+    ///
+    /// When set, [`Self::build`] restricts the [`PartitionedFile::range`] of
+    /// every file to `0..min(size, bytes)`, which is useful to cheaply sample
+    /// the start of each file (e.g. for schema inference or profiling).
+    ///
+    /// Formats that rely on metadata at the end of the file, such as Parquet
+    /// or Arrow IPC files, can not be read this way and a warning is logged.
+    pub fn with_per_file_byte_cap(mut self, bytes: Option<u64>) -> Self {
+        self.per_file_byte_cap = bytes;
+        self
     }
-}
-
-impl DataSource for FileScanConfig {
-    fn open(
-        &self,
-        partition: usize,
-        context: Arc<TaskContext>,
-    ) -> Result<SendableRecordBatchStream> {
-        let object_store = context.runtime_env().object_store(&self.object_store_url)?;
-        let batch_size = self
-            .batch_size
-            .unwrap_or_else(|| context.session_config().batch_size());
 
-        let source = self
-            .file_source
-            .with_batch_size(batch_size)
-            .with_projection(self);
+    /// Add a partition column `field` whose value is computed from the values
+    /// of the preceding partition columns of each file by `derive`, e.g. to
+    /// combine a key encoded across several path segments into one column.
+    ///
+    /// Derived columns are appended to the [`Self::with_table_partition_cols`]
+    /// in the order they are added, and their values to the partition values
+    /// of each file when the config is built, so they can be projected like
+    /// any other partition column. Values that can not be derived are null.
+    pub fn with_derived_partition_column(
+        mut self,
+        field: Field,
+        derive: DerivePartitionValue,
+    ) -> Self {
+        self.derived_partition_cols.push((field, derive));
+        self
+    }
 
-        let opener = source.create_file_opener(object_store, self, partition);
+    /// Normalize the values of the partition columns with a timestamp type
+    /// to timestamps of `unit`, e.g. so that paths written with different
+    /// precisions, such as `ts=2021-01-01` and `ts=2021-01-01T00:00:00`, have
+    /// the same partition value when grouping or pruning files.
+    ///
+    /// When the config is built, the type of these columns becomes a
+    /// timestamp of `unit` with the same time zone, and the partition values
+    /// of each file, whether strings or timestamps of any unit, are cast to
+    /// it, truncating any finer precision. Values that can not be cast are
+    /// null.
+    pub fn with_partition_timestamp_normalization(mut self, unit: TimeUnit) -> Self {
+        self.partition_timestamp_normalization = Some(unit);
+        self
+    }
 
-        let stream = FileStream::new(self, partition, opener, source.metrics())?;
-        Ok(Box::pin(stream))
+    /// Set a function that transforms the file groups when the config is built.
+    ///
+    /// This is an extension point for policies not covered by the built-in
+    /// options, such as reordering files or dropping quarantined files. The
+    /// function is applied once to all file groups, after the files removed
+    /// by the other options, and before the files are repartitioned. It must
+    /// return the same number of groups it was given. If it fails or returns
+    /// another number of groups, the file groups are left unchanged and
+    /// [`Self::try_build`] returns an error, and so does opening the scan.
+    ///
+    /// The statistics of the scan become inexact, as the preprocessor may
+    /// drop files.
+    pub fn with_file_preprocessor(mut self, file_preprocessor: FilePreprocessor) -> Self {
+        self.file_preprocessor = Some(file_preprocessor);
+        self
     }
 
-    fn as_any(&self) -> &dyn Any {
+    /// Require the size of every file to be known.
+    ///
+    /// Size based repartitioning and byte budgets silently degrade when a
+    /// [`PartitionedFile`] has a size of 0, which usually indicates a listing
+    /// bug. When set to `true`, [`Self::try_build`] returns an error naming
+    /// the first such file instead. The sizes are only checked there, once,
+    /// and not by [`Self::build`].
+    pub fn with_require_known_sizes(mut self, require_known_sizes: bool) -> Self {
+        self.require_known_sizes = require_known_sizes;
         self
     }
 
-    fn fmt_as(&self, t: DisplayFormatType, f: &mut Formatter) -> FmtResult {
-        match t {
-            DisplayFormatType::Default | DisplayFormatType::Verbose => {
-                let schema = self.projected_schema();
-                let orderings = get_projected_output_ordering(self, &schema);
+    /// Read the files of `partition` from the object store at `object_store_url`
+    /// rather than the object store URL of the config.
+    ///
+    /// Note that a config with per partition object stores is not
+    /// repartitioned, as that would move files between partitions.
+    pub fn with_partition_store_url(
+        mut self,
+        partition: usize,
+        object_store_url: ObjectStoreUrl,
+    ) -> Self {
+        self.partition_store_urls
+            .insert(partition, object_store_url);
+        self
+    }
 
-                write!(f, "file_groups=")?;
-                FileGroupsDisplay(&self.file_groups).fmt_as(t, f)?;
+    /// Verify the contents of each file against its expected checksum before
+    /// reading it.
+    ///
+    /// The expected checksum of a file is set with
+    /// [`PartitionedFile::with_expected_checksum`]. Files without an expected
+    /// checksum, or whose checksum does not match, fail to open. As files are
+    /// verified as a whole, they are not split into byte ranges when
+    /// repartitioning.
+    ///
+    /// Each file is downloaded in full to verify it before the file format
+    /// reads it, so every file is read twice, including the parts of it the
+    /// file format would skip. See [`ChecksumVerifyingOpener`] for details.
+    ///
+    /// Requires the `checksum` feature.
+    ///
+    /// [`ChecksumVerifyingOpener`]: crate::checksum::ChecksumVerifyingOpener
+    #[cfg(feature = "checksum")]
+    pub fn with_checksum_verification(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        self.checksum_verification = Some(algorithm);
+        self
+    }
 
-                if !schema.fields().is_empty() {
-                    write!(f, ", projection={}", ProjectSchemaDisplay(&schema))?;
-                }
+    /// Set the maximum number of records to read from each file. If `None`,
+    /// all records of each file are read.
+    ///
+    /// Unlike [`Self::with_limit`], which limits the records returned by the
+    /// whole scan, this caps each file independently, e.g. to read an even
+    /// sample of rows across files.
+    pub fn with_per_file_row_limit(mut self, limit: Option<usize>) -> Self {
+        self.per_file_row_limit = limit;
+        self
+    }
 
-                if let Some(limit) = self.limit {
-                    write!(f, ", limit={limit}")?;
-                }
+    /// Set how columns of a file that are not part of the file schema are
+    /// handled, by default they are ignored.
+    ///
+    /// This applies to file sources that map files with a
+    /// [`SchemaAdapter`](crate::schema_adapter::SchemaAdapter), such as
+    /// Parquet. See [`Self::include_extra_columns`] to add extra columns to
+    /// the output.
+    pub fn with_extra_column_policy(mut self, policy: ExtraColumnPolicy) -> Self {
+        self.extra_column_policy = policy;
+        self
+    }
 
-                display_orderings(f, &orderings)?;
+    /// Set whether the projection is pushed down to the [`FileSource`], which
+    /// is the default.
+    ///
+    /// When disabled, all columns of the files are read and the projection is
+    /// applied to the resulting batches. This is a debugging aid, e.g. to tell
+    /// whether a bug is in the projection handling of a custom [`FileSource`],
+    /// and makes scans considerably more expensive.
+    pub fn with_projection_pushdown(mut self, projection_pushdown: bool) -> Self {
+        self.projection_pushdown = projection_pushdown;
+        self
+    }
 
-                if !self.constraints.is_empty() {
-                    write!(f, ", {}", self.constraints)?;
-                }
+    /// Set how files listed more than once in the file groups are handled,
+    /// by default they are read as often as they are listed.
+    ///
+    /// With [`DuplicateFilePolicy::Dedup`] duplicates are removed when the
+    /// config is built, with [`DuplicateFilePolicy::Error`] opening the scan
+    /// fails.
+    pub fn with_duplicate_file_policy(mut self, policy: DuplicateFilePolicy) -> Self {
+        self.duplicate_file_policy = policy;
+        self
+    }
 
-                self.fmt_file_source(t, f)
-            }
-            DisplayFormatType::TreeRender => {
-                writeln!(f, "format={}", self.file_source.file_type())?;
-                self.file_source.fmt_extra(t, f)?;
-                let num_files = self.file_groups.iter().map(|fg| fg.len()).sum::<usize>();
-                writeln!(f, "files={num_files}")?;
-                Ok(())
-            }
-        }
+    /// Set whether the files are read in reverse, by default they are not.
+    ///
+    /// The files of each group are read from last to first, and the rows of
+    /// each file from its last row to its first, so that files sorted in
+    /// ascending order produce a descending output without a sort. The
+    /// [`Self::with_output_ordering`] is reversed accordingly.
+    ///
+    /// All batches of a file are buffered before its first row is emitted,
+    /// with their memory reserved from the memory pool of the task. The scan
+    /// fails if the memory cannot be reserved.
+    pub fn with_reverse_scan(mut self, reverse_scan: bool) -> Self {
+        self.reverse_scan = reverse_scan;
+        self
     }
 
-    /// If supported by the underlying [`FileSource`], redistribute files across partitions according to their size.
-    fn repartitioned(
-        &self,
-        target_partitions: usize,
-        repartition_file_min_size: usize,
-        output_ordering: Option<LexOrdering>,
-    ) -> Result<Option<Arc<dyn DataSource>>> {
-        let source = self.file_source.repartitioned(
-            target_partitions,
-            repartition_file_min_size,
-            output_ordering,
-            self,
-        )?;
+    /// Set human readable labels of the file groups, e.g. the storage tier
+    /// they are read from, by the index of the group.
+    ///
+    /// Groups without a label, or beyond the end of `group_labels`, are not
+    /// labeled. Labels are shown in verbose EXPLAIN output and dropped when
+    /// the files are regrouped, e.g. by repartitioning.
+    pub fn with_group_labels(mut self, group_labels: Vec<Option<String>>) -> Self {
+        self.group_labels = group_labels;
+        self
+    }
 
-        Ok(source.map(|s| Arc::new(s) as _))
+    /// Set whether the schema metadata of the files is added to the schema
+    /// metadata of the output batches, by default it is not.
+    ///
+    /// The metadata of the first file read by each partition is merged into
+    /// the metadata of the file schema, which wins for keys present in both.
+    /// This requires the [`FileOpener`] to produce batches carrying the schema
+    /// metadata of the file, as the opener of Arrow IPC files does. Note that
+    /// the metadata is only known once the first file is read, so it is not
+    /// part of the schema of the plan.
+    ///
+    /// [`FileOpener`]: crate::file_stream::FileOpener
+    pub fn with_merge_file_metadata(mut self, merge_file_metadata: bool) -> Self {
+        self.merge_file_metadata = merge_file_metadata;
+        self
     }
 
-    fn output_partitioning(&self) -> Partitioning {
-        Partitioning::UnknownPartitioning(self.file_groups.len())
+    /// Set the maximum memory size of the batches read from the files, by
+    /// default only their number of rows is limited by the batch size.
+    ///
+    /// Batches larger than `max_batch_bytes` are split into batches of as many
+    /// rows as fit, estimated from the average memory size of a row of the
+    /// batch. This avoids huge batches of very wide rows, e.g. large strings.
+    /// A batch is never split below a single row, and the memory of partition
+    /// columns is not included in the estimate.
+    pub fn with_max_batch_bytes(mut self, max_batch_bytes: Option<usize>) -> Self {
+        self.max_batch_bytes = max_batch_bytes;
+        self
     }
 
-    fn eq_properties(&self) -> EquivalenceProperties {
-        let (schema, constraints, _, orderings) = self.project();
-        EquivalenceProperties::new_with_orderings(schema, orderings)
-            .with_constraints(constraints)
+    /// Set whether repartitioning preserves the output ordering, by default it
+    /// does not.
+    ///
+    /// When set and an [`Self::with_output_ordering`] is declared, the files
+    /// are redistributed with
+    /// [`FileScanConfig::split_groups_by_statistics_with_target_partitions`],
+    /// so that the files of every group are ordered and non-overlapping. If
+    /// the min/max statistics of any file are missing, or the files can not be
+    /// split into at most the target number of partitions, the files are
+    /// redistributed by size instead, which is cheaper but may lose the
+    /// ordering.
+    pub fn with_order_preserving_repartition(
+        mut self,
+        order_preserving_repartition: bool,
+    ) -> Self {
+        self.order_preserving_repartition = order_preserving_repartition;
+        self
     }
 
-    fn statistics(&self) -> Result<Statistics> {
-        Ok(self.projected_stats())
+    /// Set a function returning a fresh object store URL for a file whose
+    /// access expired while it was read, e.g. a pre-signed URL.
+    ///
+    /// When opening or reading a file fails with an
+    /// [`object_store::Error::Unauthenticated`], the file is opened again from
+    /// the object store of the refreshed URL, which must be registered in the
+    /// runtime environment, skipping the rows already read. See
+    /// [`UrlRefreshingOpener`] for details.
+    ///
+    /// [`UrlRefreshingOpener`]: crate::url_refresh::UrlRefreshingOpener
+    pub fn with_url_refresher(mut self, url_refresher: UrlRefresher) -> Self {
+        self.url_refresher = Some(url_refresher);
+        self
     }
 
-    fn with_fetch(&self, limit: Option<usize>) -> Option<Arc<dyn DataSource>> {
-        let source = FileScanConfigBuilder::from(self.clone())
-            .with_limit(limit)
-            .build();
-        Some(Arc::new(source))
+    /// Set whether a file producing no batches, e.g. an empty file, produces
+    /// a single batch without rows instead, by default it does not.
+    ///
+    /// This accounts for every file in the output, e.g. for provenance or
+    /// audit logic relying on each file producing at least one batch, along
+    /// with its partition values.
+    pub fn with_emit_empty_file_batches(mut self, emit_empty_file_batches: bool) -> Self {
+        self.emit_empty_file_batches = emit_empty_file_batches;
+        self
     }
 
-    fn fetch(&self) -> Option<usize> {
-        self.limit
+    /// Set whether the files are merged into a single partition sorted by
+    /// the first [`Self::with_output_ordering`], by default they are not.
+    ///
+    /// Every file must be sorted by the output ordering, but the files may
+    /// overlap, within or across file groups. The scan then opens all the
+    /// files at once and merges them with a k-way merge, which keeps at least
+    /// one batch of every file in memory, tracked by the memory pool of the
+    /// runtime environment, so this is best suited to a moderate number of
+    /// files.
+    ///
+    /// The scan claims the output ordering without checking the statistics
+    /// of the files, produces a single output partition, and is not
+    /// repartitioned. If the ordering is projected away, the files are read
+    /// as usual.
+    pub fn with_sort_merge_on_read(mut self, sort_merge_on_read: bool) -> Self {
+        self.sort_merge_on_read = sort_merge_on_read;
+        self
     }
 
-    fn metrics(&self) -> ExecutionPlanMetricsSet {
-        self.file_source.metrics().clone()
+    /// Set a function the progress of the scan of each partition is reported
+    /// to, e.g. to display it to the user.
+    ///
+    /// The [`ScanProgress`] of a partition is reported with cumulative counts
+    /// whenever one of its files is read to completion, and every few batches
+    /// in between. The function is called from the stream of the partition,
+    /// so it should return quickly and never block, e.g. by sending the
+    /// progress to a channel.
+    ///
+    /// [`ScanProgress`]: crate::file_stream::ScanProgress
+    pub fn with_progress_callback(mut self, progress_callback: ProgressCallback) -> Self {
+        self.progress_callback = Some(progress_callback);
+        self
     }
 
-    fn try_swapping_with_projection(
-        &self,
-        projection: &ProjectionExec,
-    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
-        // This process can be moved into CsvExec, but it would be an overlap of their responsibility.
-
-        // Must be all column references, with no table partition columns (which can not be projected)
-        let partitioned_columns_in_proj = projection.expr().iter().any(|(expr, _)| {
-            expr.as_any()
-                .downcast_ref::<Column>()
-                .map(|expr| expr.index() >= self.file_schema.fields().len())
-                .unwrap_or(false)
-        });
-
-        // If there is any non-column or alias-carrier expression, Projection should not be removed.
-        let no_aliases = all_alias_free_columns(projection.expr());
-
-        Ok((no_aliases && !partitioned_columns_in_proj).then(|| {
-            let file_scan = self.clone();
-            let source = Arc::clone(&file_scan.file_source);
-            let new_projections = new_projections_for_columns(
-                projection,
-                &file_scan
-                    .projection
-                    .clone()
-                    .unwrap_or_else(|| (0..self.file_schema.fields().len()).collect()),
-            );
-            DataSourceExec::from_data_source(
-                FileScanConfigBuilder::from(file_scan)
-                    // Assign projected statistics to source
-                    .with_projection(Some(new_projections))
-                    .with_source(source)
-                    .build(),
-            ) as _
-        }))
+    /// Set the provider of the keys to decrypt files with encrypted columns,
+    /// such as Parquet files with modular encryption.
+    ///
+    /// Columns without a key are read as plaintext. See
+    /// [`ColumnKeyProvider`] for details.
+    pub fn with_column_key_provider(
+        mut self,
+        column_key_provider: Arc<dyn ColumnKeyProvider>,
+    ) -> Self {
+        self.column_key_provider = Some(column_key_provider);
+        self
     }
 
-    fn try_pushdown_filters(
-        &self,
-        filters: Vec<Arc<dyn PhysicalExpr>>,
-        config: &ConfigOptions,
-    ) -> Result<FilterPushdownPropagation<Arc<dyn DataSource>>> {
-        let result = self.file_source.try_pushdown_filters(filters, config)?;
-        match result.updated_node {
-            Some(new_file_source) => {
-                let file_scan_config = FileScanConfigBuilder::from(self.clone())
-                    .with_source(new_file_source)
-                    .build();
-                Ok(FilterPushdownPropagation {
-                    filters: result.filters,
-                    updated_node: Some(Arc::new(file_scan_config) as _),
-                })
-            }
-            None => {
-                // If the file source does not support filter pushdown, return the original config
-                Ok(FilterPushdownPropagation {
-                    filters: result.filters,
-                    updated_node: None,
-                })
-            }
-        }
+    /// Declare that the file groups are partitioned by `key`, so that the
+    /// output partitioning of the scan is [`Partitioning::Hash`] on it, e.g.
+    /// to join with another input partitioned the same way without a
+    /// shuffle.
+    ///
+    /// The partitioning is only reported while the partition column is
+    /// projected, and each file is in the group a [`RepartitionExec`] hash
+    /// partitioning on the column into as many partitions would send its
+    /// value to, as arranged by [`FileScanConfig::hash_partition_by`].
+    /// Otherwise, e.g. if the files are regrouped by a
+    /// [`Self::with_file_preprocessor`], the partitioning is unknown.
+    /// Unlike [`FileScanConfig::hash_partition_by`], the files are not
+    /// reassigned, so it is up to the caller to ensure they are grouped
+    /// consistently with the other inputs. The file groups are not
+    /// repartitioned.
+    ///
+    /// [`RepartitionExec`]: datafusion_physical_plan::repartition::RepartitionExec
+    pub fn with_declared_partitioning(mut self, key: PartitionKey) -> Self {
+        self.declared_partitioning = Some(key);
+        self
     }
-}
 
-impl FileScanConfig {
-    /// Create a new [`FileScanConfig`] with default settings for scanning files.
+    /// Read every column of the file schema as nullable, even if the schema
+    /// declares it non-nullable.
     ///
-    /// See example on [`FileScanConfig`]
+    /// Readers fail on nulls in columns declared non-nullable, which some
+    /// producers write anyway. With this set, the file schema, and therefore
+    /// the output schema of the scan, is relaxed so that such files can still
+    /// be read. Only the nullability of the top level columns is changed.
+    pub fn with_force_nullable(mut self, force_nullable: bool) -> Self {
+        self.force_nullable = force_nullable;
+        self
+    }
+
+    /// Set whether the scan reserves the memory of its batches in flight
+    /// from the memory pool of the [`TaskContext`], so that it is accounted
+    /// for by the memory management of the execution.
     ///
-    /// No file groups are added by default. See [`Self::with_file`], [`Self::with_file_group`] and
-    /// [`Self::with_file_groups`].
+    /// Each partition initially reserves the estimated size of a batch of
+    /// the batch size rows for the scanned file and each of the
+    /// [`Self::with_max_open_files_per_partition`] files opened ahead of it,
+    /// and fails to open if the memory cannot be reserved. The reservation
+    /// then follows the files in flight as batches are read, and is released
+    /// as they drain, see [`FileStream::with_memory_reservation`]. Values of
+    /// variable width types are estimated to be [`VARIABLE_WIDTH_VALUE_BYTES`]
+    /// bytes.
     ///
-    /// # Parameters:
-    /// * `object_store_url`: See [`Self::object_store_url`]
-    /// * `file_schema`: See [`Self::file_schema`]
-    #[allow(deprecated)] // `new` will be removed same time as `with_source`
-    pub fn new(
-        object_store_url: ObjectStoreUrl,
-        file_schema: SchemaRef,
-        file_source: Arc<dyn FileSource>,
-    ) -> Self {
-        let statistics = Statistics::new_unknown(&file_schema);
-        let file_source = file_source
-            .with_statistics(statistics.clone())
-            .with_schema(Arc::clone(&file_schema));
-        Self {
-            object_store_url,
-            file_schema,
-            file_groups: vec![],
-            constraints: Constraints::default(),
-            projection: None,
-            limit: None,
-            table_partition_cols: vec![],
-            output_ordering: vec![],
-            file_compression_type: FileCompressionType::UNCOMPRESSED,
-            new_lines_in_values: false,
-            file_source: Arc::clone(&file_source),
-            batch_size: None,
-        }
+    /// [`FileStream::with_memory_reservation`]: crate::file_stream::FileStream::with_memory_reservation
+    pub fn with_memory_reservation(mut self, memory_reservation: bool) -> Self {
+        self.memory_reservation = memory_reservation;
+        self
     }
 
-    /// Set the file source
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_source(mut self, file_source: Arc<dyn FileSource>) -> Self {
-        self.file_source =
-            file_source.with_statistics(Statistics::new_unknown(&self.file_schema));
+    /// Rename output columns, mapping the name of a column in the table
+    /// schema to its name in the output schema, e.g. to present `c1` as
+    /// `customer_id`.
+    ///
+    /// Only the [`FileScanConfig::projected_schema`] is renamed, after the
+    /// projection: the files are read with the columns of the file schema,
+    /// and the projection, constraints and output ordering are still
+    /// expressed against the table schema. Filters are not pushed down into
+    /// the file source of a scan with renamed columns. Opening the scan fails
+    /// if two output columns end up with the same name.
+    pub fn with_output_rename(mut self, output_rename: HashMap<String, String>) -> Self {
+        self.output_rename = output_rename;
         self
     }
 
-    /// Set the table constraints of the files
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
-        self.constraints = constraints;
+    /// Set whether to verify that the batches of each partition are actually
+    /// sorted by the output orderings of the scan, failing the scan with an
+    /// error otherwise, e.g. to catch files declared with an ordering they
+    /// do not have in tests.
+    ///
+    /// The rows are compared within and across batches, which is expensive,
+    /// so the verification only happens in builds with debug assertions and
+    /// is skipped in release builds.
+    pub fn with_verify_ordering(mut self, verify_ordering: bool) -> Self {
+        self.verify_ordering = verify_ordering;
         self
     }
 
-    /// Set the statistics of the files
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_statistics(mut self, statistics: Statistics) -> Self {
-        self.file_source = self.file_source.with_statistics(statistics);
+    /// Set the indexes of the columns of the table schema, i.e. the file
+    /// schema followed by the partition columns, whose statistics are
+    /// computed and used, e.g. the few columns the optimizer needs from a
+    /// wide table.
+    ///
+    /// The statistics of the other columns are [`Precision::Absent`], both in
+    /// [`FileScanConfig::projected_stats`] and when pruning files with
+    /// [`FileScanConfig::prune_files_by_filter`]. Defaults to `None`, which
+    /// uses the statistics of every column.
+    pub fn with_stats_columns(mut self, stats_columns: Option<Vec<usize>>) -> Self {
+        self.stats_columns = stats_columns;
         self
     }
 
-    fn projection_indices(&self) -> Vec<usize> {
-        match &self.projection {
-            Some(proj) => proj.clone(),
-            None => (0..self.file_schema.fields().len()
-                + self.table_partition_cols.len())
-                .collect(),
-        }
+    /// Set the maximum number of files each partition opens ahead of the file
+    /// it scans, so that at most `max_open_files_per_partition + 1` files of
+    /// the partition are open at the same time.
+    ///
+    /// While a file is scanned, the following files of the partition are
+    /// opened ahead of time to overlap the IO of opening them with decoding.
+    /// Raising it opens more files ahead, at the cost of more file handles,
+    /// e.g. on local file systems with huge file groups. Defaults to 1,
+    /// which only opens the next file.
+    pub fn with_max_open_files_per_partition(
+        mut self,
+        max_open_files_per_partition: NonZeroUsize,
+    ) -> Self {
+        self.max_open_files_per_partition = max_open_files_per_partition;
+        self
     }
 
-    pub fn projected_stats(&self) -> Statistics {
-        let statistics = self.file_source.statistics().unwrap();
-
-        let table_cols_stats = self
-            .projection_indices()
-            .into_iter()
-            .map(|idx| {
-                if idx < self.file_schema.fields().len() {
-                    statistics.column_statistics[idx].clone()
-                } else {
-                    // TODO provide accurate stat for partition column (#1186)
-                    ColumnStatistics::new_unknown()
-                }
-            })
-            .collect();
-
-        Statistics {
-            num_rows: statistics.num_rows,
-            // TODO correct byte size: https://github.com/apache/datafusion/issues/14936
-            total_byte_size: statistics.total_byte_size,
-            column_statistics: table_cols_stats,
-        }
+    /// Set the number of bytes of files each partition opens ahead of the
+    /// file it scans, for stores with a high overhead per request.
+    ///
+    /// While a file is scanned, the following files of the partition are
+    /// opened until the bytes of the files opened and not scanned yet reach
+    /// `read_ahead_bytes`, however many files that is, so the window may be
+    /// exceeded by the last file opened. At least the next file is always
+    /// opened. The bytes of a file are those of its range if it has one.
+    /// The files open are still limited by
+    /// [`Self::with_max_open_files_per_partition`], which must be raised for
+    /// more than the next file to be opened.
+    pub fn with_read_ahead_bytes(mut self, read_ahead_bytes: usize) -> Self {
+        self.read_ahead_bytes = Some(read_ahead_bytes);
+        self
     }
 
-    pub fn projected_schema(&self) -> Arc<Schema> {
-        let table_fields: Vec<_> = self
-            .projection_indices()
-            .into_iter()
-            .map(|idx| {
-                if idx < self.file_schema.fields().len() {
-                    self.file_schema.field(idx).clone()
-                } else {
-                    let partition_idx = idx - self.file_schema.fields().len();
-                    Arc::unwrap_or_clone(Arc::clone(
-                        &self.table_partition_cols[partition_idx],
-                    ))
-                }
-            })
-            .collect();
-
-        Arc::new(Schema::new_with_metadata(
-            table_fields,
-            self.file_schema.metadata().clone(),
-        ))
+    /// Stop opening files once the files opened by the scan, across all its
+    /// partitions, add up to `bytes`, e.g. as a guardrail against runaway
+    /// queries. The scan then returns the rows read so far, so its result
+    /// is incomplete.
+    ///
+    /// The files are counted in the order they are opened if the partitions
+    /// are scanned at the same pace: the first file of every group, then the
+    /// second one, and so on. The file reaching the budget is read entirely,
+    /// and the bytes of a file are those of its range if it has one. The
+    /// files that are not read are counted in the
+    /// `files_skipped_by_byte_budget` metric of each partition.
+    ///
+    /// The budget only limits the files opened, however many of them are
+    /// opened ahead of time, see [`Self::with_read_ahead_bytes`].
+    pub fn with_byte_scan_budget(mut self, bytes: usize) -> Self {
+        self.byte_scan_budget = Some(bytes);
+        self
     }
 
-    pub fn projected_constraints(&self) -> Constraints {
-        let indexes = self.projection_indices();
-        self.constraints.project(&indexes).unwrap_or_default()
+    /// Answer scans whose projection only references partition columns, or
+    /// no column at all as for `COUNT(*)`, from the partition values and the
+    /// exact row counts in the statistics of the files, without opening them.
+    /// This also enables [`FileScanConfig::exact_row_count`]. Defaults to
+    /// `false`.
+    ///
+    /// The statistics must describe the rows the scan reads from each file:
+    /// files read partially, e.g. because of a [`PartitionedFile::range`] or
+    /// an access plan in their [`PartitionedFile::extensions`], are still
+    /// opened, but the rows dropped by a filter the [`FileSource`] applies
+    /// are only accounted for if the source reports it in
+    /// [`FileSource::filter`].
+    pub fn with_statistics_fast_path(mut self, statistics_fast_path: bool) -> Self {
+        self.statistics_fast_path = statistics_fast_path;
+        self
     }
 
-    /// Set the projection of the files
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_projection(mut self, projection: Option<Vec<usize>>) -> Self {
-        self.projection = projection;
+    /// Fetch the [`PartitionedFile::ranges`] of each file that are at most
+    /// `max_gap` bytes apart with a single request to the object store, rather
+    /// than a request per range.
+    ///
+    /// The fetched bytes are kept in memory while the ranges of the file are
+    /// read, and still split into the records of each range by the format.
+    /// Reads outside of the ranges, e.g. to find the record boundaries of a
+    /// CSV file, are still sent to the object store. Files whose columns are
+    /// matched by name, with a schema override, or with trailing bytes are
+    /// read a range at a time.
+    pub fn with_range_coalescing(mut self, max_gap: u64) -> Self {
+        self.range_coalescing = Some(max_gap);
         self
     }
 
-    /// Set the limit of the files
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
-        self.limit = limit;
+    /// Set the expected ratio of the decompressed to the compressed size of
+    /// the files of each compression type, used by
+    /// [`FileScanConfig::estimated_decompressed_bytes`], e.g. from the ratios
+    /// observed on previous scans of the table.
+    ///
+    /// Compression types without a hint use a default ratio, see
+    /// [`FileScanConfig::compression_ratio`].
+    pub fn with_compression_ratio_hint(
+        mut self,
+        compression_ratio_hints: HashMap<CompressionTypeVariant, f64>,
+    ) -> Self {
+        self.compression_ratio_hints = compression_ratio_hints;
         self
     }
 
-    /// Add a file as a single group
+    /// Set where the values of the partition columns are read from, for
+    /// tables whose files also contain the partition columns, e.g. to catch
+    /// files placed in the wrong partition with
+    /// [`PartitionColumnSource::Validate`].
     ///
-    /// See [Self::file_groups] for more information.
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    #[allow(deprecated)]
-    pub fn with_file(self, file: PartitionedFile) -> Self {
-        self.with_file_group(FileGroup::new(vec![file]))
+    /// Unless [`PartitionColumnSource::PathOnly`], the default, the columns
+    /// of the files with the names of the partition columns are read with
+    /// the file columns, so the scan can not be answered from the partition
+    /// values only.
+    pub fn with_partition_column_source(
+        mut self,
+        partition_column_source: PartitionColumnSource,
+    ) -> Self {
+        self.partition_column_source = partition_column_source;
+        self
     }
 
-    /// Add the file groups
+    /// Resume the scan of `partition` from `cursor`, e.g. the position a
+    /// checkpointed pipeline stopped at, skipping the files and rows already
+    /// processed.
     ///
-    /// See [Self::file_groups] for more information.
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_file_groups(mut self, mut file_groups: Vec<FileGroup>) -> Self {
-        self.file_groups.append(&mut file_groups);
+    /// The cursor refers to the files of the partition in scan order, so it
+    /// is only valid for plans of the same config. A config with resume
+    /// cursors is therefore not repartitioned, and can not be sort merged on
+    /// read.
+    pub fn with_resume_cursor(mut self, partition: usize, cursor: ScanCursor) -> Self {
+        self.resume_cursors.insert(partition, cursor);
         self
     }
 
-    /// Add a new file group
+    /// Set the dialect CSV files are parsed with, overriding the delimiter,
+    /// quote, escape and comment characters of the CSV file source.
     ///
-    /// See [Self::file_groups] for more information
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_file_group(mut self, file_group: FileGroup) -> Self {
-        self.file_groups.push(file_group);
+    /// The quote and escape characters are also used to find the line
+    /// boundaries when files are split into byte ranges, see
+    /// [`Self::with_newlines_in_values`]. Other file sources ignore the
+    /// dialect.
+    pub fn with_csv_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.csv_dialect = Some(dialect);
         self
     }
 
-    /// Set the partitioning columns of the files
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_table_partition_cols(mut self, table_partition_cols: Vec<Field>) -> Self {
-        self.table_partition_cols = table_partition_cols
-            .into_iter()
-            .map(|f| Arc::new(f) as FieldRef)
-            .collect();
+    /// Set whether the columns of each file are matched to the file schema
+    /// by name rather than by position, for formats that read their columns
+    /// by position, such as CSV files with a header.
+    ///
+    /// The names of the columns of each file are read when it is opened, see
+    /// [`FileSource::physical_column_names`], and a file whose columns are in
+    /// another order than the file schema is read as if it had a
+    /// [`PartitionedFile::file_schema_override`] with its columns. Formats
+    /// that already match columns by name, such as Parquet, are not affected.
+    pub fn with_name_based_column_matching(
+        mut self,
+        name_based_column_matching: bool,
+    ) -> Self {
+        self.name_based_column_matching = name_based_column_matching;
         self
     }
 
-    /// Set the output ordering of the files
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_output_ordering(mut self, output_ordering: Vec<LexOrdering>) -> Self {
-        self.output_ordering = output_ordering;
+    /// Append a column `name` to the output of the scan, holding the index of
+    /// the partition each row was read from, e.g. to check how rows are
+    /// routed through a plan.
+    ///
+    /// The column is a non null `UInt64`, and is not a column of the table,
+    /// so projections and filters are not pushed into a scan with it.
+    pub fn with_partition_index_column(mut self, name: impl Into<String>) -> Self {
+        self.partition_index_column = Some(name.into());
         self
     }
 
-    /// Set the file compression type
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_file_compression_type(
+    /// Append a column `name` holding `value` in every row to the output of
+    /// the scan, e.g. to tag the rows with the id of the dataset they were
+    /// read from.
+    ///
+    /// Unlike a partition column, the value does not depend on the file. The
+    /// constant columns follow the columns of the table and the
+    /// [`Self::with_partition_index_column`], in the order they were added.
+    /// They are not columns of the table, so projections and filters are not
+    /// pushed into a scan with them.
+    pub fn with_constant_column(
         mut self,
-        file_compression_type: FileCompressionType,
+        name: impl Into<String>,
+        value: ScalarValue,
     ) -> Self {
-        self.file_compression_type = file_compression_type;
+        self.constant_columns.push((name.into(), value));
         self
     }
 
-    /// Set the new_lines_in_values property
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_newlines_in_values(mut self, new_lines_in_values: bool) -> Self {
-        self.new_lines_in_values = new_lines_in_values;
+    /// Set what to do when a file is not found when it is opened, e.g.
+    /// because it was deleted from an eventually consistent store after the
+    /// plan was created. By default the scan fails.
+    ///
+    /// See [`MissingFileOpener`] for details.
+    ///
+    /// [`MissingFileOpener`]: crate::missing_file::MissingFileOpener
+    pub fn with_missing_file_policy(mut self, policy: MissingFilePolicy) -> Self {
+        self.missing_file_policy = policy;
         self
     }
 
-    /// Set the batch_size property
-    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
-    pub fn with_batch_size(mut self, batch_size: Option<usize>) -> Self {
-        self.batch_size = batch_size;
+    /// Set whether the files of each group are read in the order of their
+    /// path, rather than the order they were added in, e.g. for reproducible
+    /// output in tests. Ranges of the same file are read in the order of
+    /// their start.
+    ///
+    /// The files are sorted right before they are read, after the
+    /// [`Self::with_file_preprocessor`] and any repartitioning, so a declared
+    /// [`Self::with_output_ordering`] must also hold for this order.
+    pub fn with_sort_files_in_group(mut self, sort_files_in_group: bool) -> Self {
+        self.sort_files_in_group = sort_files_in_group;
         self
     }
 
-    /// Specifies whether newlines in (quoted) values are supported.
+    /// Set a function deciding whether each file is read from its
+    /// [`ObjectMeta`], e.g. to skip files older than a retention period or
+    /// outside an allowlist.
     ///
-    /// Parsing newlines in quoted values may be affected by execution behaviour such as
-    /// parallel file scanning. Setting this to `true` ensures that newlines in values are
-    /// parsed successfully, which may reduce performance.
+    /// Files the function rejects with [`FileAdmission::Reject`] are removed
+    /// from the file groups when the config is built, and counted in the
+    /// `files_rejected` metric. Files rejected with
+    /// [`FileAdmission::RejectWithError`] fail [`FileScanConfig::validate`],
+    /// and therefore the execution of the scan, with the given message.
+    pub fn with_file_admission(mut self, file_admission: FileAdmissionPolicy) -> Self {
+        self.file_admission = Some(file_admission);
+        self
+    }
+
+    /// Set whether the bytes following the end of the data of each file are
+    /// ignored, for files produced by writers appending padding or garbage.
     ///
-    /// The default behaviour depends on the `datafusion.catalog.newlines_in_values` setting.
-    pub fn newlines_in_values(&self) -> bool {
-        self.new_lines_in_values
+    /// Only supported for Parquet and Arrow IPC files, whose data ends with a
+    /// footer, its length and a magic number: the end of the data is the last
+    /// such magic number in the last 64 KiB of the file.
+    pub fn with_tolerate_trailing_bytes(mut self, tolerate_trailing_bytes: bool) -> Self {
+        self.tolerate_trailing_bytes = tolerate_trailing_bytes;
+        self
     }
 
-    /// Project the schema, constraints, and the statistics on the given column indices
-    pub fn project(&self) -> (SchemaRef, Constraints, Statistics, Vec<LexOrdering>) {
-        if self.projection.is_none() && self.table_partition_cols.is_empty() {
-            return (
-                Arc::clone(&self.file_schema),
-                self.constraints.clone(),
-                self.file_source.statistics().unwrap().clone(),
-                self.output_ordering.clone(),
-            );
-        }
+    /// Set whether the files marked with [`PartitionedFile::with_tombstoned`]
+    /// are read, e.g. for a time travel query reading a snapshot of the table
+    /// in which they were not deleted yet.
+    ///
+    /// By default, tombstoned files are removed from the file groups when the
+    /// config is built.
+    pub fn with_include_tombstoned(mut self, include_tombstoned: bool) -> Self {
+        self.include_tombstoned = include_tombstoned;
+        self
+    }
 
-        let schema = self.projected_schema();
-        let constraints = self.projected_constraints();
-        let stats = self.projected_stats();
-
-        let output_ordering = get_projected_output_ordering(self, &schema);
-
-        (schema, constraints, stats, output_ordering)
+    /// Set how the values of partition columns are encoded, by default they
+    /// are produced with the type declared in
+    /// [`Self::with_table_partition_cols`].
+    ///
+    /// The types of the partition columns are changed accordingly when the
+    /// config is built, see [`PartitionEncoding::data_type`]. As the types are
+    /// part of the output schema, they must match the types other parts of
+    /// the plan expect.
+    pub fn with_partition_encoding(mut self, encoding: PartitionEncoding) -> Self {
+        self.partition_encoding = Some(encoding);
+        self
     }
 
-    pub fn projected_file_column_names(&self) -> Option<Vec<String>> {
-        self.projection.as_ref().map(|p| {
-            p.iter()
-                .filter(|col_idx| **col_idx < self.file_schema.fields().len())
-                .map(|col_idx| self.file_schema.field(*col_idx).name())
-                .cloned()
-                .collect()
-        })
+    /// Set whether the values of partition columns are produced as decoded
+    /// arrays of their value type, even if they are declared as dictionaries.
+    ///
+    /// Dictionary encoded partition columns use little memory, as their
+    /// values array has a single element, but some operators perform poorly
+    /// on dictionary inputs. This is a shorthand for
+    /// [`PartitionEncoding::Decoded`], and `false` produces the declared types
+    /// as without a partition encoding, unless another encoding was set with
+    /// [`Self::with_partition_encoding`], which is kept.
+    pub fn with_decode_partition_columns(mut self, decode: bool) -> Self {
+        if decode {
+            self.partition_encoding = Some(PartitionEncoding::Decoded);
+        } else if self.partition_encoding == Some(PartitionEncoding::Decoded) {
+            self.partition_encoding = None;
+        }
+        self
     }
 
-    /// Projects only file schema, ignoring partition columns
-    pub fn projected_file_schema(&self) -> SchemaRef {
-        let fields = self.file_column_projection_indices().map(|indices| {
-            indices
+    /// Append the columns of the files that are not part of the file schema
+    /// to it, and set the [`ExtraColumnPolicy::Include`] policy.
+    ///
+    /// `file_schemas` are the schemas of the files to scan, which must all
+    /// have the same extra columns. Their statistics are unknown.
+    pub fn include_extra_columns(mut self, file_schemas: &[SchemaRef]) -> Result<Self> {
+        let mut extras: Option<Vec<FieldRef>> = None;
+        for file_schema in file_schemas {
+            let file_extras = file_schema
+                .fields()
                 .iter()
-                .map(|col_idx| self.file_schema.field(*col_idx))
+                .filter(|field| self.file_schema.field_with_name(field.name()).is_err())
                 .cloned()
-                .collect::<Vec<_>>()
-        });
-
-        fields.map_or_else(
-            || Arc::clone(&self.file_schema),
-            |f| {
-                Arc::new(Schema::new_with_metadata(
-                    f,
-                    self.file_schema.metadata.clone(),
-                ))
-            },
-        )
-    }
-
-    pub fn file_column_projection_indices(&self) -> Option<Vec<usize>> {
-        self.projection.as_ref().map(|p| {
-            p.iter()
-                .filter(|col_idx| **col_idx < self.file_schema.fields().len())
-                .copied()
-                .collect()
-        })
-    }
+                .collect::<Vec<_>>();
+            match &extras {
+                Some(extras) if extras != &file_extras => {
+                    let names = |fields: &[FieldRef]| {
+                        fields
+                            .iter()
+                            .map(|f| f.name().as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    return plan_err!(
+                        "Cannot include extra columns, files have different extra \
+                         columns: [{}] and [{}]",
+                        names(extras),
+                        names(&file_extras)
+                    );
+                }
+                Some(_) => {}
+                None => extras = Some(file_extras),
+            }
+        }
 
-    /// Splits file groups into new groups based on statistics to enable efficient parallel processing.
-    ///
-    /// The method distributes files across a target number of partitions while ensuring
-    /// files within each partition maintain sort order based on their min/max statistics.
-    ///
-    /// The algorithm works by:
-    /// 1. Takes files sorted by minimum values
-    /// 2. For each file:
-    ///   - Finds eligible groups (empty or where file's min > group's last max)
-    ///   - Selects the smallest eligible group
-    ///   - Creates a new group if needed
-    ///
-    /// # Parameters
-    /// * `table_schema`: Schema containing information about the columns
-    /// * `file_groups`: The original file groups to split
-    /// * `sort_order`: The lexicographical ordering to maintain within each group
-    /// * `target_partitions`: The desired number of output partitions
-    ///
-    /// # Returns
-    /// A new set of file groups, where files within each group are non-overlapping with respect to
-    /// their min/max statistics and maintain the specified sort order.
-    pub fn split_groups_by_statistics_with_target_partitions(
-        table_schema: &SchemaRef,
-        file_groups: &[FileGroup],
-        sort_order: &LexOrdering,
-        target_partitions: usize,
-    ) -> Result<Vec<FileGroup>> {
-        if target_partitions == 0 {
-            return Err(DataFusionError::Internal(
-                "target_partitions must be greater than 0".to_string(),
-            ));
+        self.extra_column_policy = ExtraColumnPolicy::Include;
+        let extras = extras.unwrap_or_default();
+        if extras.is_empty() {
+            return Ok(self);
         }
 
-        let flattened_files = file_groups
+        let num_file_columns = self.file_schema.fields().len();
+        let fields = self
+            .file_schema
+            .fields()
             .iter()
-            .flat_map(FileGroup::iter)
+            .chain(&extras)
+            .cloned()
             .collect::<Vec<_>>();
+        self.file_schema = Arc::new(Schema::new_with_metadata(
+            fields,
+            self.file_schema.metadata().clone(),
+        ));
 
-        if flattened_files.is_empty() {
-            return Ok(vec![]);
-        }
-
-        let statistics = MinMaxStatistics::new_from_files(
-            sort_order,
-            table_schema,
-            None,
-            flattened_files.iter().copied(),
-        )?;
-
-        let indices_sorted_by_min = statistics.min_values_sorted();
-
-        // Initialize with target_partitions empty groups
-        let mut file_groups_indices: Vec<Vec<usize>> = vec![vec![]; target_partitions];
-
-        for (idx, min) in indices_sorted_by_min {
-            if let Some((_, group)) = file_groups_indices
+        // partition columns come after the file columns
+        if let Some(projection) = &mut self.projection {
+            for idx in projection
                 .iter_mut()
-                .enumerate()
-                .filter(|(_, group)| {
-                    group.is_empty()
-                        || min
-                            > statistics
-                                .max(*group.last().expect("groups should not be empty"))
-                })
-                .min_by_key(|(_, group)| group.len())
+                .filter(|idx| **idx >= num_file_columns)
             {
-                group.push(idx);
-            } else {
-                // Create a new group if no existing group fits
-                file_groups_indices.push(vec![idx]);
+                *idx += extras.len();
             }
         }
-
-        // Remove any empty groups
-        file_groups_indices.retain(|group| !group.is_empty());
-
-        // Assemble indices back into groups of PartitionedFiles
-        Ok(file_groups_indices
-            .into_iter()
-            .map(|file_group_indices| {
-                FileGroup::new(
-                    file_group_indices
-                        .into_iter()
-                        .map(|idx| flattened_files[idx].clone())
-                        .collect(),
-                )
-            })
-            .collect())
-    }
-
-    /// Attempts to do a bin-packing on files into file groups, such that any two files
-    /// in a file group are ordered and non-overlapping with respect to their statistics.
-    /// It will produce the smallest number of file groups possible.
-    pub fn split_groups_by_statistics(
-        table_schema: &SchemaRef,
-        file_groups: &[FileGroup],
-        sort_order: &LexOrdering,
-    ) -> Result<Vec<FileGroup>> {
-        let flattened_files = file_groups
-            .iter()
-            .flat_map(FileGroup::iter)
-            .collect::<Vec<_>>();
-        // First Fit:
-        // * Choose the first file group that a file can be placed into.
-        // * If it fits into no existing file groups, create a new one.
-        //
-        // By sorting files by min values and then applying first-fit bin packing,
-        // we can produce the smallest number of file groups such that
-        // files within a group are in order and non-overlapping.
-        //
-        // Source: Applied Combinatorics (Keller and Trotter), Chapter 6.8
-        // https://www.appliedcombinatorics.org/book/s_posets_dilworth-intord.html
-
-        if flattened_files.is_empty() {
-            return Ok(vec![]);
+        if let Some(statistics) = &mut self.statistics {
+            statistics
+                .column_statistics
+                .extend(extras.iter().map(|_| ColumnStatistics::new_unknown()));
         }
-
-        let statistics = MinMaxStatistics::new_from_files(
-            sort_order,
-            table_schema,
-            None,
-            flattened_files.iter().copied(),
-        )
-        .map_err(|e| {
-            e.context("construct min/max statistics for split_groups_by_statistics")
-        })?;
-
-        let indices_sorted_by_min = statistics.min_values_sorted();
-        let mut file_groups_indices: Vec<Vec<usize>> = vec![];
-
-        for (idx, min) in indices_sorted_by_min {
-            let file_group_to_insert = file_groups_indices.iter_mut().find(|group| {
-                // If our file is non-overlapping and comes _after_ the last file,
-                // it fits in this file group.
-                min > statistics.max(
-                    *group
-                        .last()
-                        .expect("groups should be nonempty at construction"),
-                )
-            });
-            match file_group_to_insert {
-                Some(group) => group.push(idx),
-                None => file_groups_indices.push(vec![idx]),
-            }
+        if !self.output_ordering.is_empty() && !self.table_partition_cols.is_empty() {
+            let table_schema = Arc::new(Schema::new(
+                self.file_schema
+                    .fields()
+                    .iter()
+                    .chain(&self.table_partition_cols)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            ));
+            self.output_ordering = self
+                .output_ordering
+                .into_iter()
+                .map(|ordering| {
+                    let sort_exprs = ordering
+                        .iter()
+                        .map(|sort_expr| {
+                            let expr = reassign_predicate_columns(
+                                Arc::clone(&sort_expr.expr),
+                                &table_schema,
+                                false,
+                            )?;
+                            Ok(PhysicalSortExpr::new(expr, sort_expr.options))
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    Ok(LexOrdering::new(sort_exprs).unwrap_or(ordering))
+                })
+                .collect::<Result<_>>()?;
         }
-
-        // Assemble indices back into groups of PartitionedFiles
-        Ok(file_groups_indices
-            .into_iter()
-            .map(|file_group_indices| {
-                file_group_indices
-                    .into_iter()
-                    .map(|idx| flattened_files[idx].clone())
-                    .collect()
-            })
-            .collect())
+        Ok(self)
     }
 
-    /// Returns a new [`DataSourceExec`] to scan the files specified by this config
-    #[deprecated(since = "47.0.0", note = "use DataSourceExec::new instead")]
-    pub fn build(self) -> Arc<DataSourceExec> {
-        DataSourceExec::from_data_source(self)
+    /// Build a [`FileScanConfig`] like [`Self::build`], returning an error if
+    /// the [`Self::with_file_preprocessor`] failed or did not return as many
+    /// file groups as it was given, or an error naming the first file with an
+    /// unknown (zero) size if [`Self::with_require_known_sizes`] is set.
+    pub fn try_build(self) -> Result<FileScanConfig> {
+        let config = self.build();
+        config.validate_file_preprocessor()?;
+        config.validate_file_sizes()?;
+        Ok(config)
     }
 
-    /// Write the data_type based on file_source
-    fn fmt_file_source(&self, t: DisplayFormatType, f: &mut Formatter) -> FmtResult {
-        write!(f, ", file_type={}", self.file_source.file_type())?;
-        self.file_source.fmt_extra(t, f)
-    }
+    /// Build the final [`FileScanConfig`] with all the configured settings.
+    ///
+    /// This method takes ownership of the builder and returns the constructed `FileScanConfig`.
+    /// Any unset optional fields will use their default values.
+    pub fn build(self) -> FileScanConfig {
+        let Self {
+            object_store_url,
+            file_schema,
+            file_source,
+            limit,
+            projection,
+            table_partition_cols,
+            constraints,
+            file_groups,
+            statistics,
+            output_ordering,
+            file_compression_type,
+            new_lines_in_values,
+            batch_size,
+            column_default_exprs,
+            file_preprocessor,
+            file_preprocessor_error,
+            require_known_sizes,
+            partition_store_urls,
+            #[cfg(feature = "checksum")]
+            checksum_verification,
+            per_file_row_limit,
+            extra_column_policy,
+            projection_pushdown,
+            duplicate_file_policy,
+            reverse_scan,
+            group_labels,
+            merge_file_metadata,
+            max_batch_bytes,
+            order_preserving_repartition,
+            url_refresher,
+            emit_empty_file_batches,
+            sort_merge_on_read,
+            progress_callback,
+            column_key_provider,
+            declared_partitioning,
+            force_nullable,
+            memory_reservation,
+            output_rename,
+            verify_ordering,
+            stats_columns,
+            max_open_files_per_partition,
+            compression_ratio_hints,
+            partition_column_source,
+            resume_cursors,
+            csv_dialect,
+            partition_index_column,
+            missing_file_policy,
+            sort_files_in_group,
+            file_admission,
+            tolerate_trailing_bytes,
+            include_tombstoned,
+            read_ahead_bytes,
+            constant_columns,
+            name_based_column_matching,
+            partition_timestamp_normalization,
+            byte_scan_budget,
+            statistics_fast_path,
+            range_coalescing,
+            per_file_byte_cap,
+            partition_encoding,
+            derived_partition_cols,
+        } = self;
 
-    /// Returns the file_source
-    pub fn file_source(&self) -> &Arc<dyn FileSource> {
-        &self.file_source
-    }
-}
+        let file_schema = if force_nullable {
+            nullable_schema(&file_schema)
+        } else {
+            file_schema
+        };
+        let constraints = constraints.unwrap_or_default();
+        let statistics = retain_column_statistics(
+            statistics.unwrap_or_else(|| Statistics::new_unknown(&file_schema)),
+            stats_columns.as_deref(),
+        );
 
-impl Debug for FileScanConfig {
-    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(f, "FileScanConfig {{")?;
-        write!(f, "object_store_url={:?}, ", self.object_store_url)?;
+        let file_source = file_source
+            .with_statistics(match file_preprocessor {
+                Some(_) => statistics.clone().to_inexact(),
+                None => statistics.clone(),
+            })
+            .with_schema(Arc::clone(&file_schema));
+        let file_compression_type =
+            file_compression_type.unwrap_or(FileCompressionType::UNCOMPRESSED);
+        let new_lines_in_values = new_lines_in_values.unwrap_or(false);
 
-        write!(
-            f,
-            "statistics={:?}, ",
-            self.file_source.statistics().unwrap()
-        )?;
+        let mut table_partition_cols = table_partition_cols;
+        let file_groups = match partition_timestamp_normalization {
+            Some(unit) => normalize_partition_timestamps(
+                file_groups,
+                &mut table_partition_cols,
+                unit,
+            ),
+            None => file_groups,
+        };
+        let file_groups = if derived_partition_cols.is_empty() {
+            file_groups
+        } else {
+            table_partition_cols.extend(
+                derived_partition_cols
+                    .iter()
+                    .map(|(field, _)| Arc::new(field.clone())),
+            );
+            derive_partition_values(file_groups, &derived_partition_cols)
+        };
+        let table_partition_cols = match partition_encoding {
+            Some(encoding) => table_partition_cols
+                .into_iter()
+                .map(|field| {
+                    let data_type = encoding.data_type(field.data_type());
+                    Arc::new(field.as_ref().clone().with_data_type(data_type))
+                })
+                .collect(),
+            None => table_partition_cols,
+        };
+        let file_groups = match duplicate_file_policy {
+            DuplicateFilePolicy::Dedup => remove_duplicate_files(file_groups),
+            DuplicateFilePolicy::Allow | DuplicateFilePolicy::Error => file_groups,
+        };
+        let file_groups = match &file_admission {
+            Some(file_admission) => {
+                remove_rejected_files(file_groups, file_admission, file_source.metrics())
+            }
+            None => file_groups,
+        };
+        let file_groups = if include_tombstoned {
+            file_groups
+        } else {
+            remove_tombstoned_files(file_groups)
+        };
+        let file_groups = match per_file_byte_cap {
+            Some(cap) => {
+                if matches!(file_source.file_type(), "parquet" | "arrow") {
+                    warn!(
+                        "per_file_byte_cap is set but {} files can not be read from their first bytes only",
+                        file_source.file_type()
+                    );
+                }
+                cap_file_ranges(file_groups, cap)
+            }
+            None => file_groups,
+        };
+        let mut file_preprocessor_error = file_preprocessor_error;
+        let file_groups = match file_preprocessor {
+            Some(file_preprocessor) => {
+                match preprocess_file_groups(&file_preprocessor, file_groups.clone()) {
+                    Ok(file_groups) => file_groups,
+                    Err(e) => {
+                        file_preprocessor_error = Some(Arc::new(e));
+                        file_groups
+                    }
+                }
+            }
+            None => file_groups,
+        };
 
-        DisplayAs::fmt_as(self, DisplayFormatType::Verbose, f)?;
-        write!(f, "}}")
+        FileScanConfig {
+            object_store_url,
+            file_schema,
+            file_source,
+            limit,
+            projection,
+            table_partition_cols,
+            constraints,
+            file_groups,
+            output_ordering,
+            file_compression_type,
+            new_lines_in_values,
+            batch_size,
+            column_default_exprs,
+            file_preprocessor: None,
+            file_preprocessor_error,
+            require_known_sizes,
+            partition_store_urls,
+            #[cfg(feature = "checksum")]
+            checksum_verification,
+            per_file_row_limit,
+            extra_column_policy,
+            projection_pushdown,
+            duplicate_file_policy,
+            reverse_scan,
+            group_labels,
+            merge_file_metadata,
+            max_batch_bytes,
+            order_preserving_repartition,
+            url_refresher,
+            emit_empty_file_batches,
+            sort_merge_on_read,
+            progress_callback,
+            column_key_provider,
+            declared_partitioning,
+            force_nullable,
+            memory_reservation,
+            output_rename,
+            verify_ordering,
+            stats_columns,
+            max_open_files_per_partition,
+            compression_ratio_hints,
+            partition_column_source,
+            resume_cursors,
+            csv_dialect,
+            partition_index_column,
+            missing_file_policy,
+            sort_files_in_group,
+            file_admission,
+            tolerate_trailing_bytes,
+            include_tombstoned,
+            read_ahead_bytes,
+            constant_columns,
+            name_based_column_matching,
+            partition_timestamp_normalization,
+            byte_scan_budget,
+            statistics_fast_path,
+            range_coalescing,
+        }
     }
 }
 
-impl DisplayAs for FileScanConfig {
-    fn fmt_as(&self, t: DisplayFormatType, f: &mut Formatter) -> FmtResult {
-        let schema = self.projected_schema();
-        let orderings = get_projected_output_ordering(self, &schema);
+impl From<FileScanConfig> for FileScanConfigBuilder {
+    fn from(config: FileScanConfig) -> Self {
+        Self {
+            object_store_url: config.object_store_url,
+            file_schema: config.file_schema,
+            file_source: Arc::<dyn FileSource>::clone(&config.file_source),
+            file_groups: config.file_groups,
+            statistics: config.file_source.statistics().ok(),
+            output_ordering: config.output_ordering,
+            file_compression_type: Some(config.file_compression_type),
+            new_lines_in_values: Some(config.new_lines_in_values),
+            limit: config.limit,
+            projection: config.projection,
+            table_partition_cols: config.table_partition_cols,
+            constraints: Some(config.constraints),
+            batch_size: config.batch_size,
+            column_default_exprs: config.column_default_exprs,
+            file_preprocessor: config.file_preprocessor,
+            file_preprocessor_error: config.file_preprocessor_error,
+            require_known_sizes: config.require_known_sizes,
+            partition_store_urls: config.partition_store_urls,
+            #[cfg(feature = "checksum")]
+            checksum_verification: config.checksum_verification,
+            per_file_row_limit: config.per_file_row_limit,
+            extra_column_policy: config.extra_column_policy,
+            projection_pushdown: config.projection_pushdown,
+            duplicate_file_policy: config.duplicate_file_policy,
+            reverse_scan: config.reverse_scan,
+            group_labels: config.group_labels,
+            merge_file_metadata: config.merge_file_metadata,
+            max_batch_bytes: config.max_batch_bytes,
+            order_preserving_repartition: config.order_preserving_repartition,
+            url_refresher: config.url_refresher,
+            emit_empty_file_batches: config.emit_empty_file_batches,
+            sort_merge_on_read: config.sort_merge_on_read,
+            progress_callback: config.progress_callback,
+            column_key_provider: config.column_key_provider,
+            declared_partitioning: config.declared_partitioning,
+            force_nullable: config.force_nullable,
+            memory_reservation: config.memory_reservation,
+            output_rename: config.output_rename,
+            verify_ordering: config.verify_ordering,
+            stats_columns: config.stats_columns,
+            max_open_files_per_partition: config.max_open_files_per_partition,
+            compression_ratio_hints: config.compression_ratio_hints,
+            partition_column_source: config.partition_column_source,
+            resume_cursors: config.resume_cursors,
+            csv_dialect: config.csv_dialect,
+            partition_index_column: config.partition_index_column,
+            missing_file_policy: config.missing_file_policy,
+            sort_files_in_group: config.sort_files_in_group,
+            file_admission: config.file_admission,
+            tolerate_trailing_bytes: config.tolerate_trailing_bytes,
+            include_tombstoned: config.include_tombstoned,
+            read_ahead_bytes: config.read_ahead_bytes,
+            constant_columns: config.constant_columns,
+            name_based_column_matching: config.name_based_column_matching,
+            partition_timestamp_normalization: config.partition_timestamp_normalization,
+            byte_scan_budget: config.byte_scan_budget,
+            statistics_fast_path: config.statistics_fast_path,
+            range_coalescing: config.range_coalescing,
+            per_file_byte_cap: None,
+            partition_encoding: None,
+            derived_partition_cols: vec![],
+        }
+    }
+}
 
-        write!(f, "file_groups=")?;
-        FileGroupsDisplay(&self.file_groups).fmt_as(t, f)?;
+/// Builds a [`FileScanConfig`] from files added one at a time, e.g. while
+/// streaming them from a catalog, accumulating their statistics along the way.
+///
+/// The [`PartitionedFile::statistics`] of the files are merged as they are
+/// added, so the statistics of the scan do not need to be computed from all
+/// files afterwards. A file without statistics makes the statistics of the
+/// scan unknown. The distinct values of each partition column are collected
+/// as well.
+///
+/// Created with [`FileScanConfig::builder`], or from a [`FileScanConfigBuilder`]
+/// to set the other options of the scan.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use arrow::datatypes::{DataType, Field, Schema};
+/// # use datafusion_common::Result;
+/// # use datafusion_datasource::file::FileSource;
+/// # use datafusion_datasource::file_scan_config::FileScanConfig;
+/// # use datafusion_datasource::PartitionedFile;
+/// # use datafusion_execution::object_store::ObjectStoreUrl;
+/// # fn with_source(file_source: Arc<dyn FileSource>) -> Result<()> {
+/// let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+/// let mut builder =
+///     FileScanConfig::builder(ObjectStoreUrl::local_filesystem(), schema, file_source);
+/// for file in ["data/1.parquet", "data/2.parquet"] {
+///     builder.add_file(PartitionedFile::new(file, 1024))?;
+/// }
+/// let config = builder.finish();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FileScanConfigAccumulator {
+    builder: FileScanConfigBuilder,
+    files: Vec<PartitionedFile>,
+    statistics: Option<Statistics>,
+    partition_values: Vec<HashSet<ScalarValue>>,
+}
 
-        if !schema.fields().is_empty() {
-            write!(f, ", projection={}", ProjectSchemaDisplay(&schema))?;
+impl FileScanConfigAccumulator {
+    /// Create an accumulator adding files to the config of `builder`
+    pub fn new(builder: FileScanConfigBuilder) -> Self {
+        Self {
+            builder,
+            files: vec![],
+            statistics: None,
+            partition_values: vec![],
         }
+    }
 
-        if let Some(limit) = self.limit {
-            write!(f, ", limit={limit}")?;
+    /// Add `file` to the scan, merging its statistics into the statistics
+    /// of the scan.
+    ///
+    /// Returns an error if the statistics of the file do not have a column
+    /// statistics per column of the file schema.
+    pub fn add_file(&mut self, file: PartitionedFile) -> Result<()> {
+        let unknown;
+        let file_statistics = match &file.statistics {
+            Some(statistics) => statistics.as_ref(),
+            None => {
+                unknown = Statistics::new_unknown(&self.builder.file_schema);
+                &unknown
+            }
+        };
+        let num_columns = self.builder.file_schema.fields().len();
+        if file_statistics.column_statistics.len() != num_columns {
+            return plan_err!(
+                "File {} has statistics for {} columns, but the file schema has {num_columns}",
+                file.path(),
+                file_statistics.column_statistics.len()
+            );
         }
+        self.statistics = Some(match self.statistics.take() {
+            Some(statistics) => statistics.try_merge(file_statistics)?,
+            None => file_statistics.clone(),
+        });
 
-        display_orderings(f, &orderings)?;
-
-        if !self.constraints.is_empty() {
-            write!(f, ", {}", self.constraints)?;
+        if self.partition_values.len() < file.partition_values.len() {
+            self.partition_values
+                .resize_with(file.partition_values.len(), HashSet::new);
+        }
+        for (values, value) in
+            self.partition_values.iter_mut().zip(&file.partition_values)
+        {
+            if !values.contains(value) {
+                values.insert(value.clone());
+            }
         }
 
+        self.files.push(file);
         Ok(())
     }
-}
 
-/// A helper that projects partition columns into the file record batches.
-///
-/// One interesting trick is the usage of a cache for the key buffers of the partition column
-/// dictionaries. Indeed, the partition columns are constant, so the dictionaries that represent them
-/// have all their keys equal to 0. This enables us to re-use the same "all-zero" buffer across batches,
-/// which makes the space consumption of the partition columns O(batch_size) instead of O(record_count).
-pub struct PartitionColumnProjector {
-    /// An Arrow buffer initialized to zeros that represents the key array of all partition
-    /// columns (partition columns are materialized by dictionary arrays with only one
-    /// value in the dictionary, thus all the keys are equal to zero).
-    key_buffer_cache: ZeroBufferGenerators,
-    /// Mapping between the indexes in the list of partition columns and the target
-    /// schema. Sorted by index in the target schema so that we can iterate on it to
-    /// insert the partition columns in the target record batch.
-    projected_partition_indexes: Vec<(usize, usize)>,
-    /// The schema of the table once the projection was applied.
-    projected_schema: SchemaRef,
-}
+    /// Returns the number of files added so far
+    pub fn num_files(&self) -> usize {
+        self.files.len()
+    }
 
-impl PartitionColumnProjector {
-    // Create a projector to insert the partitioning columns into batches read from files
-    // - `projected_schema`: the target schema with both file and partitioning columns
-    // - `table_partition_cols`: all the partitioning column names
-    pub fn new(projected_schema: SchemaRef, table_partition_cols: &[String]) -> Self {
-        let mut idx_map = HashMap::new();
-        for (partition_idx, partition_name) in table_partition_cols.iter().enumerate() {
-            if let Ok(schema_idx) = projected_schema.index_of(partition_name) {
-                idx_map.insert(partition_idx, schema_idx);
-            }
-        }
+    /// Returns the merged statistics of the files added so far, which are
+    /// unknown if no file was added
+    pub fn statistics(&self) -> Statistics {
+        self.statistics
+            .clone()
+            .unwrap_or_else(|| Statistics::new_unknown(&self.builder.file_schema))
+    }
 
-        let mut projected_partition_indexes: Vec<_> = idx_map.into_iter().collect();
-        projected_partition_indexes.sort_by(|(_, a), (_, b)| a.cmp(b));
+    /// Returns the distinct values of each partition column of the files
+    /// added so far, in the order of the partition values of the files
+    pub fn partition_values(&self) -> &[HashSet<ScalarValue>] {
+        &self.partition_values
+    }
 
-        Self {
-            projected_partition_indexes,
-            key_buffer_cache: Default::default(),
-            projected_schema,
-        }
+    /// Builds the config, with the files in a single file group and the
+    /// merged statistics, which replace any statistics set on the builder
+    pub fn finish(self) -> FileScanConfig {
+        let statistics = self.statistics();
+        let file_group =
+            FileGroup::new(self.files).with_statistics(Arc::new(statistics.clone()));
+        self.builder
+            .with_file_group(file_group)
+            .with_statistics(statistics)
+            .build()
     }
+}
 
-    // Transform the batch read from the file by inserting the partitioning columns
-    // to the right positions as deduced from `projected_schema`
-    // - `file_batch`: batch read from the file, with internal projection applied
-    // - `partition_values`: the list of partition values, one for each partition column
-    pub fn project(
-        &mut self,
-        file_batch: RecordBatch,
-        partition_values: &[ScalarValue],
-    ) -> Result<RecordBatch> {
-        let expected_cols =
-            self.projected_schema.fields().len() - self.projected_partition_indexes.len();
+impl From<FileScanConfigBuilder> for FileScanConfigAccumulator {
+    fn from(builder: FileScanConfigBuilder) -> Self {
+        Self::new(builder)
+    }
+}
 
-        if file_batch.columns().len() != expected_cols {
-            return exec_err!(
-                "Unexpected batch schema from file, expected {} cols but got {}",
-                expected_cols,
-                file_batch.columns().len()
-            );
-        }
+impl DataSource for FileScanConfig {
+    fn open(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        self.open_partition(partition, context)
+    }
 
-        let mut cols = file_batch.columns().to_vec();
-        for &(pidx, sidx) in &self.projected_partition_indexes {
-            let p_value =
-                partition_values
-                    .get(pidx)
-                    .ok_or(DataFusionError::Execution(
-                        "Invalid partitioning found on disk".to_string(),
-                    ))?;
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
 
-            let mut partition_value = Cow::Borrowed(p_value);
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut Formatter) -> FmtResult {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                let schema = self.projected_schema();
+                let orderings = get_projected_output_ordering(self, &schema);
 
-            // check if user forgot to dict-encode the partition value
-            let field = self.projected_schema.field(sidx);
-            let expected_data_type = field.data_type();
-            let actual_data_type = partition_value.data_type();
-            if let DataType::Dictionary(key_type, _) = expected_data_type {
-                if !matches!(actual_data_type, DataType::Dictionary(_, _)) {
-                    warn!("Partition value for column {} was not dictionary-encoded, applied auto-fix.", field.name());
-                    partition_value = Cow::Owned(ScalarValue::Dictionary(
-                        key_type.clone(),
-                        Box::new(partition_value.as_ref().clone()),
-                    ));
-                }
-            }
+                write!(f, "file_groups=")?;
+                FileGroupsDisplay(&self.file_groups, &self.group_labels).fmt_as(t, f)?;
 
-            cols.insert(
-                sidx,
-                create_output_array(
-                    &mut self.key_buffer_cache,
-                    partition_value.as_ref(),
-                    file_batch.num_rows(),
-                )?,
-            )
-        }
+                if !schema.fields().is_empty() {
+                    write!(f, ", projection={}", ProjectSchemaDisplay(&schema))?;
+                }
 
-        RecordBatch::try_new_with_options(
-            Arc::clone(&self.projected_schema),
-            cols,
-            &RecordBatchOptions::new().with_row_count(Some(file_batch.num_rows())),
-        )
-        .map_err(Into::into)
-    }
-}
+                if let Some(limit) = self.limit {
+                    write!(f, ", limit={limit}")?;
+                }
 
-#[derive(Debug, Default)]
-struct ZeroBufferGenerators {
-    gen_i8: ZeroBufferGenerator<i8>,
-    gen_i16: ZeroBufferGenerator<i16>,
-    gen_i32: ZeroBufferGenerator<i32>,
-    gen_i64: ZeroBufferGenerator<i64>,
-    gen_u8: ZeroBufferGenerator<u8>,
-    gen_u16: ZeroBufferGenerator<u16>,
-    gen_u32: ZeroBufferGenerator<u32>,
-    gen_u64: ZeroBufferGenerator<u64>,
-}
+                display_orderings(f, &orderings)?;
 
-/// Generate a arrow [`Buffer`] that contains zero values.
-#[derive(Debug, Default)]
-struct ZeroBufferGenerator<T>
-where
-    T: ArrowNativeType,
-{
-    cache: Option<Buffer>,
-    _t: PhantomData<T>,
-}
+                if !self.constraints.is_empty() {
+                    write!(f, ", {}", self.constraints)?;
+                }
 
-impl<T> ZeroBufferGenerator<T>
-where
-    T: ArrowNativeType,
-{
-    const SIZE: usize = size_of::<T>();
+                self.fmt_partition_store_urls(f)?;
+                self.fmt_compression_histogram(f)?;
+                self.fmt_file_group_balance(t, f)?;
 
-    fn get_buffer(&mut self, n_vals: usize) -> Buffer {
-        match &mut self.cache {
-            Some(buf) if buf.len() >= n_vals * Self::SIZE => {
-                buf.slice_with_length(0, n_vals * Self::SIZE)
+                self.fmt_file_source(t, f)
             }
-            _ => {
-                let mut key_buffer_builder = BufferBuilder::<T>::new(n_vals);
-                key_buffer_builder.advance(n_vals); // keys are all 0
-                self.cache.insert(key_buffer_builder.finish()).clone()
+            DisplayFormatType::TreeRender => {
+                writeln!(f, "format={}", self.file_source.file_type())?;
+                self.file_source.fmt_extra(t, f)?;
+                let num_files = self.file_groups.iter().map(|fg| fg.len()).sum::<usize>();
+                writeln!(f, "files={num_files}")?;
+                Ok(())
             }
         }
     }
-}
 
-fn create_dict_array<T>(
-    buffer_gen: &mut ZeroBufferGenerator<T>,
-    dict_val: &ScalarValue,
-    len: usize,
-    data_type: DataType,
-) -> Result<ArrayRef>
-where
-    T: ArrowNativeType,
-{
-    let dict_vals = dict_val.to_array()?;
-
-    let sliced_key_buffer = buffer_gen.get_buffer(len);
-
-    // assemble pieces together
-    let mut builder = ArrayData::builder(data_type)
-        .len(len)
-        .add_buffer(sliced_key_buffer);
-    builder = builder.add_child_data(dict_vals.to_data());
-    Ok(Arc::new(DictionaryArray::<UInt16Type>::from(
-        builder.build().unwrap(),
-    )))
-}
+    /// If supported by the underlying [`FileSource`], redistribute files across partitions according to their size.
+    fn repartitioned(
+        &self,
+        target_partitions: usize,
+        repartition_file_min_size: usize,
+        output_ordering: Option<LexOrdering>,
+    ) -> Result<Option<Arc<dyn DataSource>>> {
+        // Repartitioning would move files away from their object store, or
+        // break the hash partitioning of the files
+        if self.has_pinned_file_groups() {
+            return Ok(None);
+        }
 
-fn create_output_array(
-    key_buffer_cache: &mut ZeroBufferGenerators,
-    val: &ScalarValue,
-    len: usize,
-) -> Result<ArrayRef> {
-    if let ScalarValue::Dictionary(key_type, dict_val) = &val {
-        match key_type.as_ref() {
-            DataType::Int8 => {
-                return create_dict_array(
-                    &mut key_buffer_cache.gen_i8,
-                    dict_val,
-                    len,
-                    val.data_type(),
-                );
-            }
-            DataType::Int16 => {
-                return create_dict_array(
-                    &mut key_buffer_cache.gen_i16,
-                    dict_val,
-                    len,
-                    val.data_type(),
-                );
-            }
-            DataType::Int32 => {
-                return create_dict_array(
-                    &mut key_buffer_cache.gen_i32,
-                    dict_val,
-                    len,
-                    val.data_type(),
-                );
-            }
-            DataType::Int64 => {
-                return create_dict_array(
-                    &mut key_buffer_cache.gen_i64,
-                    dict_val,
-                    len,
-                    val.data_type(),
-                );
-            }
-            DataType::UInt8 => {
-                return create_dict_array(
-                    &mut key_buffer_cache.gen_u8,
-                    dict_val,
-                    len,
-                    val.data_type(),
-                );
-            }
-            DataType::UInt16 => {
-                return create_dict_array(
-                    &mut key_buffer_cache.gen_u16,
-                    dict_val,
-                    len,
-                    val.data_type(),
-                );
-            }
-            DataType::UInt32 => {
-                return create_dict_array(
-                    &mut key_buffer_cache.gen_u32,
-                    dict_val,
-                    len,
-                    val.data_type(),
-                );
-            }
-            DataType::UInt64 => {
-                return create_dict_array(
-                    &mut key_buffer_cache.gen_u64,
-                    dict_val,
-                    len,
-                    val.data_type(),
-                );
-            }
-            _ => {}
+        // The files are merged into a single partition
+        if self.sort_merge_ordering().is_some() {
+            return Ok(None);
         }
-    }
 
-    val.to_array_of_size(len)
-}
+        // The checksums are computed over whole files, which must not be
+        // split into byte ranges
+        if self.verifies_checksums() {
+            return Ok(None);
+        }
 
-/// The various listing tables does not attempt to read all files
-/// concurrently, instead they will read files in sequence within a
-/// partition.  This is an important property as it allows plans to
-/// run against 1000s of files and not try to open them all
-/// concurrently.
-///
-/// However, it means if we assign more than one file to a partition
-/// the output sort order will not be preserved as illustrated in the
-/// following diagrams:
-///
-/// When only 1 file is assigned to each partition, each partition is
-/// correctly sorted on `(A, B, C)`
-///
-/// ```text
-///┏ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ┓
-///  ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─ ┐ ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─  ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─  ┌ ─ ─ ─ ─ ─ ─ ─ ─ ┐
-///┃   ┌───────────────┐     ┌──────────────┐ │   ┌──────────────┐ │   ┌─────────────┐   ┃
-///  │ │   1.parquet   │ │ │ │  2.parquet   │   │ │  3.parquet   │   │ │  4.parquet  │ │
-///┃   │ Sort: A, B, C │     │Sort: A, B, C │ │   │Sort: A, B, C │ │   │Sort: A, B, C│   ┃
-///  │ └───────────────┘ │ │ └──────────────┘   │ └──────────────┘   │ └─────────────┘ │
-///┃                                          │                    │                     ┃
-///  │                   │ │                    │                    │                 │
-///┃                                          │                    │                     ┃
-///  │                   │ │                    │                    │                 │
-///┃                                          │                    │                     ┃
-///  │                   │ │                    │                    │                 │
-///┃  ─ ─ ─ ─ ─ ─ ─ ─ ─ ─   ─ ─ ─ ─ ─ ─ ─ ─ ─ ┘  ─ ─ ─ ─ ─ ─ ─ ─ ─ ┘  ─ ─ ─ ─ ─ ─ ─ ─ ─  ┃
-///     DataFusion           DataFusion           DataFusion           DataFusion
-///┃    Partition 1          Partition 2          Partition 3          Partition 4       ┃
-/// ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━
-///
-///                                      DataSourceExec
-///```
-///
-/// However, when more than 1 file is assigned to each partition, each
-/// partition is NOT correctly sorted on `(A, B, C)`. Once the second
-/// file is scanned, the same values for A, B and C can be repeated in
-/// the same sorted stream
-///
-///```text
-///┏ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━
-///  ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─ ┐ ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─  ┃
-///┃   ┌───────────────┐     ┌──────────────┐ │
-///  │ │   1.parquet   │ │ │ │  2.parquet   │   ┃
-///┃   │ Sort: A, B, C │     │Sort: A, B, C │ │
-///  │ └───────────────┘ │ │ └──────────────┘   ┃
-///┃   ┌───────────────┐     ┌──────────────┐ │
-///  │ │   3.parquet   │ │ │ │  4.parquet   │   ┃
-///┃   │ Sort: A, B, C │     │Sort: A, B, C │ │
-///  │ └───────────────┘ │ │ └──────────────┘   ┃
-///┃                                          │
-///  │                   │ │                    ┃
-///┃  ─ ─ ─ ─ ─ ─ ─ ─ ─ ─   ─ ─ ─ ─ ─ ─ ─ ─ ─ ┘
-///     DataFusion           DataFusion         ┃
-///┃    Partition 1          Partition 2
-/// ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ┛
-///
-///              DataSourceExec
-///```
-fn get_projected_output_ordering(
-    base_config: &FileScanConfig,
-    projected_schema: &SchemaRef,
-) -> Vec<LexOrdering> {
-    let mut all_orderings = vec![];
-    for output_ordering in &base_config.output_ordering {
-        let mut new_ordering = vec![];
-        for PhysicalSortExpr { expr, options } in output_ordering.iter() {
-            if let Some(col) = expr.as_any().downcast_ref::<Column>() {
-                let name = col.name();
-                if let Some((idx, _)) = projected_schema.column_with_name(name) {
-                    // Compute the new sort expression (with correct index) after projection:
-                    new_ordering.push(PhysicalSortExpr::new(
-                        Arc::new(Column::new(name, idx)),
-                        *options,
-                    ));
-                    continue;
-                }
+        if self.order_preserving_repartition {
+            if let Some(file_groups) =
+                self.split_groups_preserving_order(target_partitions)
+            {
+                let mut source = self.clone();
+                source.file_groups = file_groups;
+                source.group_labels.clear();
+                return Ok(Some(Arc::new(source)));
             }
-            // Cannot find expression in the projected_schema, stop iterating
-            // since rest of the orderings are violated
-            break;
         }
 
-        let Some(new_ordering) = LexOrdering::new(new_ordering) else {
-            continue;
-        };
+        let source = self.file_source.repartitioned(
+            target_partitions,
+            repartition_file_min_size,
+            output_ordering,
+            self,
+        )?;
 
-        // Check if any file groups are not sorted
-        if base_config.file_groups.iter().any(|group| {
-            if group.len() <= 1 {
-                // File groups with <= 1 files are always sorted
-                return false;
-            }
-
-            let statistics = match MinMaxStatistics::new_from_files(
-                &new_ordering,
-                projected_schema,
-                base_config.projection.as_deref(),
-                group.iter(),
-            ) {
-                Ok(statistics) => statistics,
-                Err(e) => {
-                    log::trace!("Error fetching statistics for file group: {e}");
-                    // we can't prove that it's ordered, so we have to reject it
-                    return true;
-                }
-            };
+        Ok(source.map(|mut source| {
+            // the labels no longer describe the regrouped files
+            source.group_labels.clear();
+            Arc::new(source) as _
+        }))
+    }
 
-            !statistics.is_sorted()
-        }) {
-            debug!(
-                "Skipping specified output ordering {:?}. \
-                Some file groups couldn't be determined to be sorted: {:?}",
-                base_config.output_ordering[0], base_config.file_groups
-            );
-            continue;
+    fn output_partitioning(&self) -> Partitioning {
+        if self.sort_merge_ordering().is_some() {
+            return Partitioning::UnknownPartitioning(1);
         }
-
-        all_orderings.push(new_ordering);
+        if let Some(partitioning) = self.declared_output_partitioning() {
+            return partitioning;
+        }
+        Partitioning::UnknownPartitioning(self.file_groups.len())
     }
-    all_orderings
-}
-
-/// Convert type to a type suitable for use as a `ListingTable`
-/// partition column. Returns `Dictionary(UInt16, val_type)`, which is
-/// a reasonable trade off between a reasonable number of partition
-/// values and space efficiency.
-///
-/// This use this to specify types for partition columns. However
-/// you MAY also choose not to dictionary-encode the data or to use a
-/// different dictionary type.
-///
-/// Use [`wrap_partition_value_in_dict`] to wrap a [`ScalarValue`] in the same say.
-pub fn wrap_partition_type_in_dict(val_type: DataType) -> DataType {
-    DataType::Dictionary(Box::new(DataType::UInt16), Box::new(val_type))
-}
 
-/// Convert a [`ScalarValue`] of partition columns to a type, as
-/// described in the documentation of [`wrap_partition_type_in_dict`],
-/// which can wrap the types.
-pub fn wrap_partition_value_in_dict(val: ScalarValue) -> ScalarValue {
-    ScalarValue::Dictionary(Box::new(DataType::UInt16), Box::new(val))
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        generate_test_files, test_util::MockSource, tests::aggr_test_schema,
-        verify_sort_integrity,
-    };
+    fn eq_properties(&self) -> EquivalenceProperties {
+        let (schema, constraints, _, orderings) = self.project();
+        EquivalenceProperties::new_with_orderings(schema, orderings)
+            .with_constraints(constraints)
+    }
 
-    use arrow::array::{Int32Array, RecordBatch};
-    use datafusion_common::stats::Precision;
-    use datafusion_common::{assert_batches_eq, internal_err};
-    use datafusion_expr::SortExpr;
-    use datafusion_physical_expr::create_physical_sort_expr;
+    fn statistics(&self) -> Result<Statistics> {
+        Ok(self.projected_stats())
+    }
 
-    /// Returns the column names on the schema
-    pub fn columns(schema: &Schema) -> Vec<String> {
-        schema.fields().iter().map(|f| f.name().clone()).collect()
+    fn with_fetch(&self, limit: Option<usize>) -> Option<Arc<dyn DataSource>> {
+        let source = FileScanConfigBuilder::from(self.clone())
+            .with_limit(limit)
+            .build();
+        Some(Arc::new(source))
     }
 
-    #[test]
-    fn physical_plan_config_no_projection() {
-        let file_schema = aggr_test_schema();
-        let conf = config_for_projection(
-            Arc::clone(&file_schema),
-            None,
-            Statistics::new_unknown(&file_schema),
-            to_partition_cols(vec![(
-                "date".to_owned(),
-                wrap_partition_type_in_dict(DataType::Utf8),
-            )]),
-        );
-
-        let (proj_schema, _, proj_statistics, _) = conf.project();
-        assert_eq!(proj_schema.fields().len(), file_schema.fields().len() + 1);
-        assert_eq!(
-            proj_schema.field(file_schema.fields().len()).name(),
-            "date",
-            "partition columns are the last columns"
-        );
-        assert_eq!(
-            proj_statistics.column_statistics.len(),
-            file_schema.fields().len() + 1
-        );
-        // TODO implement tests for partition column statistics once implemented
-
-        let col_names = conf.projected_file_column_names();
-        assert_eq!(col_names, None);
+    fn fetch(&self) -> Option<usize> {
+        self.limit
+    }
 
-        let col_indices = conf.file_column_projection_indices();
-        assert_eq!(col_indices, None);
+    fn metrics(&self) -> ExecutionPlanMetricsSet {
+        self.file_source.metrics().clone()
     }
 
-    #[test]
-    fn physical_plan_config_no_projection_tab_cols_as_field() {
-        let file_schema = aggr_test_schema();
+    fn try_swapping_with_projection(
+        &self,
+        projection: &ProjectionExec,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        // This process can be moved into CsvExec, but it would be an overlap of their responsibility.
 
-        // make a table_partition_col as a field
-        let table_partition_col =
-            Field::new("date", wrap_partition_type_in_dict(DataType::Utf8), true)
-                .with_metadata(HashMap::from_iter(vec![(
-                    "key_whatever".to_owned(),
-                    "value_whatever".to_owned(),
-                )]));
+        // The partition index and constant columns are not columns of the
+        // table
+        if self.partition_index_column.is_some() || !self.constant_columns.is_empty() {
+            return Ok(None);
+        }
 
-        let conf = config_for_projection(
-            Arc::clone(&file_schema),
-            None,
-            Statistics::new_unknown(&file_schema),
-            vec![table_partition_col.clone()],
-        );
+        // Must be all column references, with no table partition columns (which can not be projected)
+        let partitioned_columns_in_proj = projection.expr().iter().any(|(expr, _)| {
+            expr.as_any()
+                .downcast_ref::<Column>()
+                .map(|expr| expr.index() >= self.file_schema.fields().len())
+                .unwrap_or(false)
+        });
 
-        // verify the proj_schema includes the last column and exactly the same the field it is defined
-        let proj_schema = conf.projected_schema();
-        assert_eq!(proj_schema.fields().len(), file_schema.fields().len() + 1);
-        assert_eq!(
-            *proj_schema.field(file_schema.fields().len()),
-            table_partition_col,
-            "partition columns are the last columns and ust have all values defined in created field"
-        );
+        // If there is any non-column or alias-carrier expression, Projection should not be removed.
+        let no_aliases = all_alias_free_columns(projection.expr());
+
+        Ok((no_aliases && !partitioned_columns_in_proj).then(|| {
+            let file_scan = self.clone();
+            let source = Arc::clone(&file_scan.file_source);
+            let new_projections = new_projections_for_columns(
+                projection,
+                &file_scan.effective_projection(),
+            );
+            DataSourceExec::from_data_source(
+                FileScanConfigBuilder::from(file_scan)
+                    // Assign projected statistics to source
+                    .with_projection(Some(new_projections))
+                    .with_source(source)
+                    .build(),
+            ) as _
+        }))
     }
 
-    #[test]
-    fn physical_plan_config_with_projection() {
-        let file_schema = aggr_test_schema();
-        let conf = config_for_projection(
-            Arc::clone(&file_schema),
-            Some(vec![file_schema.fields().len(), 0]),
-            Statistics {
-                num_rows: Precision::Inexact(10),
-                // assign the column index to distinct_count to help assert
-                // the source statistic after the projection
-                column_statistics: (0..file_schema.fields().len())
-                    .map(|i| ColumnStatistics {
-                        distinct_count: Precision::Inexact(i),
-                        ..Default::default()
-                    })
-                    .collect(),
-                total_byte_size: Precision::Absent,
-            },
-            to_partition_cols(vec![(
-                "date".to_owned(),
-                wrap_partition_type_in_dict(DataType::Utf8),
-            )]),
-        );
+    fn try_pushdown_filters(
+        &self,
+        filters: Vec<Arc<dyn PhysicalExpr>>,
+        config: &ConfigOptions,
+    ) -> Result<FilterPushdownPropagation<Arc<dyn DataSource>>> {
+        // The filters refer to the renamed output columns, or the partition
+        // index and constant columns, which the file source does not know about
+        if !self.output_rename.is_empty()
+            || self.partition_index_column.is_some()
+            || !self.constant_columns.is_empty()
+        {
+            return Ok(FilterPushdownPropagation::unsupported(filters));
+        }
+        let result = self.file_source.try_pushdown_filters(filters, config)?;
+        match result.updated_node {
+            Some(new_file_source) => {
+                let file_scan_config = FileScanConfigBuilder::from(self.clone())
+                    .with_source(new_file_source)
+                    .build();
+                Ok(FilterPushdownPropagation {
+                    filters: result.filters,
+                    updated_node: Some(Arc::new(file_scan_config) as _),
+                })
+            }
+            None => {
+                // If the file source does not support filter pushdown, return the original config
+                Ok(FilterPushdownPropagation {
+                    filters: result.filters,
+                    updated_node: None,
+                })
+            }
+        }
+    }
+}
 
-        let (proj_schema, _, proj_statistics, _) = conf.project();
-        assert_eq!(
-            columns(&proj_schema),
-            vec!["date".to_owned(), "c1".to_owned()]
-        );
-        let proj_stat_cols = proj_statistics.column_statistics;
-        assert_eq!(proj_stat_cols.len(), 2);
-        // TODO implement tests for proj_stat_cols[0] once partition column
-        // statistics are implemented
-        assert_eq!(proj_stat_cols[1].distinct_count, Precision::Inexact(0));
+impl FileScanConfig {
+    /// Create a new [`FileScanConfig`] with default settings for scanning files.
+    ///
+    /// See example on [`FileScanConfig`]
+    ///
+    /// No file groups are added by default. See [`Self::with_file`], [`Self::with_file_group`] and
+    /// [`Self::with_file_groups`].
+    ///
+    /// # Parameters:
+    /// * `object_store_url`: See [`Self::object_store_url`]
+    /// * `file_schema`: See [`Self::file_schema`]
+    #[allow(deprecated)] // `new` will be removed same time as `with_source`
+    pub fn new(
+        object_store_url: ObjectStoreUrl,
+        file_schema: SchemaRef,
+        file_source: Arc<dyn FileSource>,
+    ) -> Self {
+        let statistics = Statistics::new_unknown(&file_schema);
+        let file_source = file_source
+            .with_statistics(statistics.clone())
+            .with_schema(Arc::clone(&file_schema));
+        Self {
+            object_store_url,
+            file_schema,
+            file_groups: vec![],
+            constraints: Constraints::default(),
+            projection: None,
+            limit: None,
+            table_partition_cols: vec![],
+            output_ordering: vec![],
+            file_compression_type: FileCompressionType::UNCOMPRESSED,
+            new_lines_in_values: false,
+            file_source: Arc::clone(&file_source),
+            batch_size: None,
+            column_default_exprs: HashMap::new(),
+            file_preprocessor: None,
+            file_preprocessor_error: None,
+            require_known_sizes: false,
+            partition_store_urls: BTreeMap::new(),
+            #[cfg(feature = "checksum")]
+            checksum_verification: None,
+            per_file_row_limit: None,
+            extra_column_policy: ExtraColumnPolicy::default(),
+            projection_pushdown: true,
+            duplicate_file_policy: DuplicateFilePolicy::default(),
+            reverse_scan: false,
+            group_labels: vec![],
+            merge_file_metadata: false,
+            max_batch_bytes: None,
+            order_preserving_repartition: false,
+            url_refresher: None,
+            emit_empty_file_batches: false,
+            sort_merge_on_read: false,
+            progress_callback: None,
+            column_key_provider: None,
+            declared_partitioning: None,
+            force_nullable: false,
+            memory_reservation: false,
+            output_rename: HashMap::new(),
+            verify_ordering: false,
+            stats_columns: None,
+            max_open_files_per_partition: NonZeroUsize::new(1).unwrap(),
+            compression_ratio_hints: HashMap::new(),
+            partition_column_source: PartitionColumnSource::PathOnly,
+            resume_cursors: BTreeMap::new(),
+            csv_dialect: None,
+            partition_index_column: None,
+            missing_file_policy: MissingFilePolicy::Error,
+            sort_files_in_group: false,
+            file_admission: None,
+            tolerate_trailing_bytes: false,
+            include_tombstoned: false,
+            read_ahead_bytes: None,
+            constant_columns: vec![],
+            name_based_column_matching: false,
+            partition_timestamp_normalization: None,
+            byte_scan_budget: None,
+            statistics_fast_path: false,
+            range_coalescing: None,
+        }
+    }
 
-        let col_names = conf.projected_file_column_names();
-        assert_eq!(col_names, Some(vec!["c1".to_owned()]));
+    /// Returns a [`FileScanConfigAccumulator`] building a config from files
+    /// added one at a time, accumulating their statistics
+    pub fn builder(
+        object_store_url: ObjectStoreUrl,
+        file_schema: SchemaRef,
+        file_source: Arc<dyn FileSource>,
+    ) -> FileScanConfigAccumulator {
+        FileScanConfigAccumulator::new(FileScanConfigBuilder::new(
+            object_store_url,
+            file_schema,
+            file_source,
+        ))
+    }
 
-        let col_indices = conf.file_column_projection_indices();
-        assert_eq!(col_indices, Some(vec![0]));
+    /// Set the file source
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_source(mut self, file_source: Arc<dyn FileSource>) -> Self {
+        self.file_source =
+            file_source.with_statistics(Statistics::new_unknown(&self.file_schema));
+        self
     }
 
-    #[test]
-    fn partition_column_projector() {
-        let file_batch = build_table_i32(
-            ("a", &vec![0, 1, 2]),
-            ("b", &vec![-2, -1, 0]),
-            ("c", &vec![10, 11, 12]),
-        );
-        let partition_cols = vec![
-            (
-                "year".to_owned(),
-                wrap_partition_type_in_dict(DataType::Utf8),
-            ),
-            (
-                "month".to_owned(),
-                wrap_partition_type_in_dict(DataType::Utf8),
-            ),
-            (
-                "day".to_owned(),
-                wrap_partition_type_in_dict(DataType::Utf8),
-            ),
-        ];
-        // create a projected schema
-        let statistics = Statistics {
-            num_rows: Precision::Inexact(3),
-            total_byte_size: Precision::Absent,
-            column_statistics: Statistics::unknown_column(&file_batch.schema()),
-        };
+    /// Set the table constraints of the files
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
+        self.constraints = constraints;
+        self
+    }
 
-        let conf = config_for_projection(
-            file_batch.schema(),
-            // keep all cols from file and 2 from partitioning
-            Some(vec![
-                0,
-                1,
-                2,
-                file_batch.schema().fields().len(),
-                file_batch.schema().fields().len() + 2,
-            ]),
-            statistics.clone(),
-            to_partition_cols(partition_cols.clone()),
-        );
+    /// Set the statistics of the files
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_statistics(mut self, statistics: Statistics) -> Self {
+        self.file_source = self.file_source.with_statistics(statistics);
+        self
+    }
 
-        let source_statistics = conf.file_source.statistics().unwrap();
-        let conf_stats = conf.statistics().unwrap();
+    /// Whether the partition columns are also read from the files, see
+    /// [`PartitionColumnSource`]
+    pub(crate) fn reads_partition_columns(&self) -> bool {
+        self.partition_column_source != PartitionColumnSource::PathOnly
+            && !self.table_partition_cols.is_empty()
+    }
 
-        // projection should be reflected in the file source statistics
-        assert_eq!(conf_stats.num_rows, Precision::Inexact(3));
+    /// Returns the file groups in the order their files are read, sorted by
+    /// path if [`FileScanConfig::sort_files_in_group`] is set.
+    pub fn file_groups_in_read_order(&self) -> Cow<'_, [FileGroup]> {
+        if self.sort_files_in_group {
+            return Cow::Owned(sort_files_by_path(self.file_groups.clone()));
+        }
+        Cow::Borrowed(&self.file_groups)
+    }
 
-        // 3 original statistics + 2 partition statistics
-        assert_eq!(conf_stats.column_statistics.len(), 5);
+    /// Returns the number of files of each of `file_groups` read within the
+    /// [`FileScanConfig::byte_scan_budget`], if any, in the order they are scanned
+    pub(crate) fn files_within_byte_scan_budget(
+        &self,
+        file_groups: &[FileGroup],
+    ) -> Option<Vec<usize>> {
+        let budget = self.byte_scan_budget? as u64;
+        let mut num_files = vec![0; file_groups.len()];
+        let mut bytes = 0;
+        let max_files = file_groups.iter().map(FileGroup::len).max().unwrap_or(0);
+        for idx in 0..max_files {
+            for (group, num_read) in file_groups.iter().zip(&mut num_files) {
+                if bytes >= budget {
+                    return Some(num_files);
+                }
+                let idx = if self.reverse_scan {
+                    group.len().checked_sub(idx + 1)
+                } else {
+                    Some(idx)
+                };
+                if let Some(file) = idx.and_then(|idx| group.files().get(idx)) {
+                    bytes += file_read_size(file);
+                    *num_read += 1;
+                }
+            }
+        }
+        Some(num_files)
+    }
 
-        // file statics should not be modified
-        assert_eq!(source_statistics, statistics);
-        assert_eq!(source_statistics.column_statistics.len(), 3);
+    /// Returns true if the checksum of each file is verified before it is
+    /// read, see [`FileScanConfigBuilder::with_checksum_verification`]
+    pub(crate) fn verifies_checksums(&self) -> bool {
+        #[cfg(feature = "checksum")]
+        {
+            self.checksum_verification.is_some()
+        }
+        #[cfg(not(feature = "checksum"))]
+        {
+            false
+        }
+    }
 
-        let proj_schema = conf.projected_schema();
-        // created a projector for that projected schema
-        let mut proj = PartitionColumnProjector::new(
-            proj_schema,
-            &partition_cols
-                .iter()
-                .map(|x| x.0.clone())
-                .collect::<Vec<_>>(),
-        );
+    /// Returns the ordering the files are merged by on read, if
+    /// [`FileScanConfigBuilder::with_sort_merge_on_read`] is set and the
+    /// output ordering is not projected away
+    pub(crate) fn sort_merge_ordering(&self) -> Option<LexOrdering> {
+        if !self.sort_merge_on_read {
+            return None;
+        }
+        let (_, _, _, orderings) = self.project();
+        orderings.into_iter().next()
+    }
 
-        // project first batch
-        let projected_batch = proj
-            .project(
-                // file_batch is ok here because we kept all the file cols in the projection
-                file_batch,
-                &[
-                    wrap_partition_value_in_dict(ScalarValue::from("2021")),
-                    wrap_partition_value_in_dict(ScalarValue::from("10")),
-                    wrap_partition_value_in_dict(ScalarValue::from("26")),
-                ],
-            )
-            .expect("Projection of partition columns into record batch failed");
-        let expected = [
-            "+---+----+----+------+-----+",
-            "| a | b  | c  | year | day |",
-            "+---+----+----+------+-----+",
-            "| 0 | -2 | 10 | 2021 | 26  |",
-            "| 1 | -1 | 11 | 2021 | 26  |",
-            "| 2 | 0  | 12 | 2021 | 26  |",
-            "+---+----+----+------+-----+",
-        ];
-        assert_batches_eq!(expected, &[projected_batch]);
+    /// Returns the URL of the object store the files of `partition` are read
+    /// from: the URL set with
+    /// [`FileScanConfigBuilder::with_partition_store_url`] if any, otherwise
+    /// [`Self::object_store_url`].
+    pub fn partition_store_url(&self, partition: usize) -> &ObjectStoreUrl {
+        self.partition_store_urls
+            .get(&partition)
+            .unwrap_or(&self.object_store_url)
+    }
 
-        // project another batch that is larger than the previous one
-        let file_batch = build_table_i32(
-            ("a", &vec![5, 6, 7, 8, 9]),
-            ("b", &vec![-10, -9, -8, -7, -6]),
-            ("c", &vec![12, 13, 14, 15, 16]),
-        );
-        let projected_batch = proj
-            .project(
-                // file_batch is ok here because we kept all the file cols in the projection
-                file_batch,
-                &[
-                    wrap_partition_value_in_dict(ScalarValue::from("2021")),
-                    wrap_partition_value_in_dict(ScalarValue::from("10")),
-                    wrap_partition_value_in_dict(ScalarValue::from("27")),
-                ],
-            )
-            .expect("Projection of partition columns into record batch failed");
-        let expected = [
-            "+---+-----+----+------+-----+",
-            "| a | b   | c  | year | day |",
-            "+---+-----+----+------+-----+",
-            "| 5 | -10 | 12 | 2021 | 27  |",
-            "| 6 | -9  | 13 | 2021 | 27  |",
-            "| 7 | -8  | 14 | 2021 | 27  |",
-            "| 8 | -7  | 15 | 2021 | 27  |",
-            "| 9 | -6  | 16 | 2021 | 27  |",
-            "+---+-----+----+------+-----+",
-        ];
-        assert_batches_eq!(expected, &[projected_batch]);
+    /// Returns the files of the output partition `partition`, or `None` if
+    /// there is no such partition.
+    ///
+    /// These are the files of the [`Self::file_groups`], before
+    /// [`FileScanConfig::sort_files_in_group`] is applied, see
+    /// [`Self::file_groups_in_read_order`].
+    pub fn files_for_partition(&self, partition: usize) -> Option<&[PartitionedFile]> {
+        self.file_groups.get(partition).map(FileGroup::files)
+    }
 
-        // project another batch that is smaller than the previous one
-        let file_batch = build_table_i32(
-            ("a", &vec![0, 1, 3]),
-            ("b", &vec![2, 3, 4]),
-            ("c", &vec![4, 5, 6]),
+    /// Returns the URLs of all the object stores the scan may read from, e.g.
+    /// to check that they are registered before executing it: the
+    /// [`Self::object_store_url`], the URLs set with
+    /// [`FileScanConfigBuilder::with_partition_store_url`] and the
+    /// [`PartitionedFile::object_store_url`] of each file.
+    pub fn object_store_urls(&self) -> HashSet<ObjectStoreUrl> {
+        let mut urls = HashSet::from([self.object_store_url.clone()]);
+        urls.extend(self.partition_store_urls.values().cloned());
+        urls.extend(
+            self.file_groups
+                .iter()
+                .flat_map(FileGroup::iter)
+                .filter_map(|file| file.object_store_url.clone()),
         );
-        let projected_batch = proj
-            .project(
-                // file_batch is ok here because we kept all the file cols in the projection
-                file_batch,
-                &[
-                    wrap_partition_value_in_dict(ScalarValue::from("2021")),
-                    wrap_partition_value_in_dict(ScalarValue::from("10")),
-                    wrap_partition_value_in_dict(ScalarValue::from("28")),
-                ],
-            )
-            .expect("Projection of partition columns into record batch failed");
-        let expected = [
-            "+---+---+---+------+-----+",
-            "| a | b | c | year | day |",
-            "+---+---+---+------+-----+",
-            "| 0 | 2 | 4 | 2021 | 28  |",
-            "| 1 | 3 | 5 | 2021 | 28  |",
-            "| 3 | 4 | 6 | 2021 | 28  |",
-            "+---+---+---+------+-----+",
-        ];
-        assert_batches_eq!(expected, &[projected_batch]);
+        urls
+    }
 
-        // forgot to dictionary-wrap the scalar value
-        let file_batch = build_table_i32(
-            ("a", &vec![0, 1, 2]),
-            ("b", &vec![-2, -1, 0]),
-            ("c", &vec![10, 11, 12]),
-        );
-        let projected_batch = proj
-            .project(
-                // file_batch is ok here because we kept all the file cols in the projection
-                file_batch,
-                &[
-                    ScalarValue::from("2021"),
-                    ScalarValue::from("10"),
-                    ScalarValue::from("26"),
-                ],
-            )
-            .expect("Projection of partition columns into record batch failed");
-        let expected = [
-            "+---+----+----+------+-----+",
-            "| a | b  | c  | year | day |",
-            "+---+----+----+------+-----+",
-            "| 0 | -2 | 10 | 2021 | 26  |",
-            "| 1 | -1 | 11 | 2021 | 26  |",
-            "| 2 | 0  | 12 | 2021 | 26  |",
-            "+---+----+----+------+-----+",
-        ];
-        assert_batches_eq!(expected, &[projected_batch]);
+    /// Returns the compression type `file` is read with.
+    ///
+    /// Every file is currently read with [`Self::file_compression_type`].
+    pub fn effective_file_compression_type(
+        &self,
+        _file: &PartitionedFile,
+    ) -> FileCompressionType {
+        self.file_compression_type
     }
 
-    #[test]
-    fn test_projected_file_schema_with_partition_col() {
-        let schema = aggr_test_schema();
-        let partition_cols = vec![
-            (
-                "part1".to_owned(),
-                wrap_partition_type_in_dict(DataType::Utf8),
-            ),
-            (
-                "part2".to_owned(),
-                wrap_partition_type_in_dict(DataType::Utf8),
-            ),
-        ];
+    /// Returns the number of files of each compression type, e.g.
+    /// `[("gzip", 10), ("none", 3)]`, by the name of the compression type, to
+    /// spot tables with inconsistently compressed files.
+    ///
+    /// The compression type of each file is the one returned by
+    /// [`Self::effective_file_compression_type`]. Uncompressed files are
+    /// counted as `none`.
+    pub fn compression_histogram(&self) -> Vec<(&'static str, usize)> {
+        let mut histogram: BTreeMap<&'static str, usize> = BTreeMap::new();
+        for file in self.file_groups.iter().flat_map(FileGroup::iter) {
+            let name = match self.effective_file_compression_type(file).get_variant() {
+                CompressionTypeVariant::GZIP => "gzip",
+                CompressionTypeVariant::BZIP2 => "bzip2",
+                CompressionTypeVariant::XZ => "xz",
+                CompressionTypeVariant::ZSTD => "zstd",
+                CompressionTypeVariant::UNCOMPRESSED => "none",
+            };
+            *histogram.entry(name).or_default() += 1;
+        }
+        histogram.into_iter().collect()
+    }
 
-        // Projected file schema for config with projection including partition column
-        let projection = config_for_projection(
-            schema.clone(),
-            Some(vec![0, 3, 5, schema.fields().len()]),
-            Statistics::new_unknown(&schema),
-            to_partition_cols(partition_cols),
-        )
-        .projected_file_schema();
+    /// Returns the expected ratio of the decompressed to the compressed size
+    /// of files compressed with `compression`: the hint set with
+    /// [`FileScanConfigBuilder::with_compression_ratio_hint`] if any,
+    /// otherwise 3 for gzip and zstd, 4 for bzip2 and xz, and 1 for
+    /// uncompressed files.
+    pub fn compression_ratio(&self, compression: CompressionTypeVariant) -> f64 {
+        if let Some(ratio) = self.compression_ratio_hints.get(&compression) {
+            return *ratio;
+        }
+        match compression {
+            CompressionTypeVariant::GZIP | CompressionTypeVariant::ZSTD => 3.0,
+            CompressionTypeVariant::BZIP2 | CompressionTypeVariant::XZ => 4.0,
+            CompressionTypeVariant::UNCOMPRESSED => 1.0,
+        }
+    }
 
-        // Assert partition column filtered out in projected file schema
-        let expected_columns = vec!["c1", "c4", "c6"];
-        let actual_columns = projection
-            .fields()
+    /// Returns an estimate of the number of bytes the files decompress to,
+    /// e.g. to budget the memory of text scans, whose compressed size
+    /// understates it.
+    ///
+    /// The bytes read from each file, its range if any, are multiplied by the
+    /// [`Self::compression_ratio`] of its
+    /// [`Self::effective_file_compression_type`], so the total is always
+    /// [`Precision::Inexact`].
+    pub fn estimated_decompressed_bytes(&self) -> Precision<u64> {
+        let total = self
+            .file_groups
             .iter()
-            .map(|f| f.name().clone())
-            .collect::<Vec<_>>();
-        assert_eq!(expected_columns, actual_columns);
+            .flat_map(FileGroup::iter)
+            .map(|file| {
+                let bytes = file_read_size(file);
+                let compression = self.effective_file_compression_type(file);
+                bytes as f64 * self.compression_ratio(*compression.get_variant())
+            })
+            .sum::<f64>();
+        Precision::Inexact(total.round() as u64)
     }
 
-    #[test]
-    fn test_projected_file_schema_without_projection() {
-        let schema = aggr_test_schema();
-        let partition_cols = vec![
-            (
-                "part1".to_owned(),
-                wrap_partition_type_in_dict(DataType::Utf8),
-            ),
-            (
-                "part2".to_owned(),
-                wrap_partition_type_in_dict(DataType::Utf8),
-            ),
-        ];
-
-        // Projected file schema for config without projection
-        let projection = config_for_projection(
-            schema.clone(),
-            None,
-            Statistics::new_unknown(&schema),
-            to_partition_cols(partition_cols),
-        )
-        .projected_file_schema();
+    /// Checks that the files can be read with this config, which is done
+    /// when the files are opened.
+    ///
+    /// Returns an error naming the offending file(s) if:
+    /// * the [`FileScanConfig::file_preprocessor`] failed when the config was
+    ///   built
+    /// * a file is listed more than once and [`FileScanConfig::duplicate_file_policy`]
+    ///   is [`DuplicateFilePolicy::Error`]
+    /// * the number of partition values of a file does not match the number
+    ///   of [`Self::table_partition_cols`]
+    /// * the [`FileScanConfig::file_admission`] rejects a file with an error
+    /// * [`FileScanConfig::tolerate_trailing_bytes`] is set for a file format without a
+    ///   trailer
+    /// * the [`FileScanConfig::partition_index_column`] or a [`FileScanConfig::constant_columns`]
+    ///   has the name of another output column
+    pub fn validate(&self) -> Result<()> {
+        self.validate_file_preprocessor()?;
+        self.validate_no_duplicate_files()?;
+        self.validate_partition_values()?;
+        self.validate_output_rename()?;
+        self.validate_file_admission()?;
+        self.validate_tolerate_trailing_bytes()?;
+        self.validate_appended_columns()
+    }
 
-        // Assert projected file schema is equal to file schema
-        assert_eq!(projection.fields(), schema.fields());
+    /// Returns an error if the [`FileScanConfig::partition_index_column`] or a
+    /// [`FileScanConfig::constant_columns`] has the same name as another output column
+    pub(crate) fn validate_appended_columns(&self) -> Result<()> {
+        let schema = self.projected_schema();
+        let fields = schema.fields();
+        // the columns preceding the column at `idx` in the output
+        let conflicts = |idx: usize, name: &str| {
+            fields[..idx].iter().any(|field| field.name() == name)
+        };
+        if let Some(name) = &self.partition_index_column {
+            if conflicts(self.effective_projection().len(), name) {
+                return plan_err!(
+                    "Partition index column {name} conflicts with a column of the table"
+                );
+            }
+        }
+        let first_constant_idx = fields.len() - self.constant_columns.len();
+        for (idx, (name, _)) in self.constant_columns.iter().enumerate() {
+            if conflicts(first_constant_idx + idx, name) {
+                return plan_err!(
+                    "Constant column {name} conflicts with another column of the output"
+                );
+            }
+        }
+        Ok(())
     }
 
-    #[test]
-    fn test_split_groups_by_statistics() -> Result<()> {
-        use chrono::TimeZone;
-        use datafusion_common::DFSchema;
-        use datafusion_expr::execution_props::ExecutionProps;
-        use object_store::{path::Path, ObjectMeta};
+    /// Returns the name of the column `name` of the table schema in the
+    /// output schema, see [`FileScanConfig::output_rename`]
+    pub(crate) fn output_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.output_rename
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or(name)
+    }
 
-        struct File {
-            name: &'static str,
-            date: &'static str,
-            statistics: Vec<Option<(f64, f64)>>,
+    /// Returns an error if two columns of the [`Self::projected_schema`] have
+    /// the same name after applying [`FileScanConfig::output_rename`]
+    fn validate_output_rename(&self) -> Result<()> {
+        if self.output_rename.is_empty() {
+            return Ok(());
         }
-        impl File {
-            fn new(
-                name: &'static str,
-                date: &'static str,
-                statistics: Vec<Option<(f64, f64)>>,
-            ) -> Self {
-                Self {
-                    name,
-                    date,
-                    statistics,
-                }
+        let schema = self.projected_schema();
+        let mut names = HashSet::new();
+        for field in schema.fields() {
+            if !names.insert(field.name()) {
+                return plan_err!(
+                    "Renaming the output columns results in duplicate column {}",
+                    field.name()
+                );
             }
         }
+        Ok(())
+    }
 
-        struct TestCase {
-            name: &'static str,
-            file_schema: Schema,
-            files: Vec<File>,
-            sort: Vec<SortExpr>,
-            expected_result: Result<Vec<Vec<&'static str>>, &'static str>,
+    /// Returns the error of the [`FileScanConfig::file_preprocessor`] if it
+    /// failed when the config was built
+    fn validate_file_preprocessor(&self) -> Result<()> {
+        match &self.file_preprocessor_error {
+            Some(e) => Err(DataFusionError::Shared(Arc::clone(e))),
+            None => Ok(()),
         }
+    }
 
-        use datafusion_expr::col;
-        let cases = vec![
-            TestCase {
-                name: "test sort",
-                file_schema: Schema::new(vec![Field::new(
-                    "value".to_string(),
-                    DataType::Float64,
-                    false,
-                )]),
-                files: vec![
-                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
-                    File::new("1", "2023-01-01", vec![Some((0.50, 1.00))]),
-                    File::new("2", "2023-01-02", vec![Some((0.00, 1.00))]),
-                ],
-                sort: vec![col("value").sort(true, false)],
-                expected_result: Ok(vec![vec!["0", "1"], vec!["2"]]),
-            },
-            // same input but file '2' is in the middle
-            // test that we still order correctly
-            TestCase {
-                name: "test sort with files ordered differently",
-                file_schema: Schema::new(vec![Field::new(
-                    "value".to_string(),
-                    DataType::Float64,
-                    false,
-                )]),
-                files: vec![
-                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
-                    File::new("2", "2023-01-02", vec![Some((0.00, 1.00))]),
-                    File::new("1", "2023-01-01", vec![Some((0.50, 1.00))]),
-                ],
-                sort: vec![col("value").sort(true, false)],
-                expected_result: Ok(vec![vec!["0", "1"], vec!["2"]]),
-            },
-            TestCase {
-                name: "reverse sort",
-                file_schema: Schema::new(vec![Field::new(
-                    "value".to_string(),
-                    DataType::Float64,
-                    false,
-                )]),
-                files: vec![
-                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
-                    File::new("1", "2023-01-01", vec![Some((0.50, 1.00))]),
-                    File::new("2", "2023-01-02", vec![Some((0.00, 1.00))]),
-                ],
-                sort: vec![col("value").sort(false, true)],
-                expected_result: Ok(vec![vec!["1", "0"], vec!["2"]]),
-            },
-            // reject nullable sort columns
-            TestCase {
-                name: "no nullable sort columns",
-                file_schema: Schema::new(vec![Field::new(
-                    "value".to_string(),
-                    DataType::Float64,
-                    true, // should fail because nullable
-                )]),
-                files: vec![
-                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
-                    File::new("1", "2023-01-01", vec![Some((0.50, 1.00))]),
-                    File::new("2", "2023-01-02", vec![Some((0.00, 1.00))]),
-                ],
-                sort: vec![col("value").sort(true, false)],
-                expected_result: Err("construct min/max statistics for split_groups_by_statistics\ncaused by\nbuild min rows\ncaused by\ncreate sorting columns\ncaused by\nError during planning: cannot sort by nullable column")
-            },
-            TestCase {
-                name: "all three non-overlapping",
-                file_schema: Schema::new(vec![Field::new(
-                    "value".to_string(),
-                    DataType::Float64,
-                    false,
-                )]),
-                files: vec![
-                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
-                    File::new("1", "2023-01-01", vec![Some((0.50, 0.99))]),
-                    File::new("2", "2023-01-02", vec![Some((1.00, 1.49))]),
-                ],
-                sort: vec![col("value").sort(true, false)],
-                expected_result: Ok(vec![vec!["0", "1", "2"]]),
-            },
-            TestCase {
-                name: "all three overlapping",
-                file_schema: Schema::new(vec![Field::new(
-                    "value".to_string(),
-                    DataType::Float64,
-                    false,
-                )]),
-                files: vec![
-                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
-                    File::new("1", "2023-01-01", vec![Some((0.00, 0.49))]),
-                    File::new("2", "2023-01-02", vec![Some((0.00, 0.49))]),
-                ],
-                sort: vec![col("value").sort(true, false)],
-                expected_result: Ok(vec![vec!["0"], vec!["1"], vec!["2"]]),
+    /// Returns an error naming the first file with an unknown (zero) size if
+    /// [`FileScanConfig::require_known_sizes`] is set, see
+    /// [`FileScanConfigBuilder::try_build`]
+    fn validate_file_sizes(&self) -> Result<()> {
+        if !self.require_known_sizes {
+            return Ok(());
+        }
+        match self
+            .file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .find(|file| file.object_meta.size == 0)
+        {
+            Some(file) => exec_err!(
+                "Size of file {} is unknown, but FileScanConfig requires known file sizes",
+                file.path()
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns an error naming the duplicate files if
+    /// [`FileScanConfig::duplicate_file_policy`] is [`DuplicateFilePolicy::Error`].
+    fn validate_no_duplicate_files(&self) -> Result<()> {
+        if self.duplicate_file_policy != DuplicateFilePolicy::Error {
+            return Ok(());
+        }
+        let mut duplicates = duplicate_files(&self.file_groups)
+            .into_iter()
+            .map(|(group, file)| self.file_groups[group][file].path().to_string())
+            .collect::<Vec<_>>();
+        if duplicates.is_empty() {
+            return Ok(());
+        }
+        duplicates.dedup();
+        exec_err!(
+            "Files are listed more than once in the file groups: {}",
+            duplicates.join(", ")
+        )
+    }
+
+    /// Returns an error if [`FileScanConfig::tolerate_trailing_bytes`] is set for a
+    /// file format whose end of data cannot be found
+    fn validate_tolerate_trailing_bytes(&self) -> Result<()> {
+        let file_type = self.file_source.file_type();
+        if self.tolerate_trailing_bytes && Trailer::for_file_type(file_type).is_none() {
+            return plan_err!("Cannot tolerate trailing bytes of {file_type} files");
+        }
+        Ok(())
+    }
+
+    /// Returns the error of the first file the [`FileScanConfig::file_admission`]
+    /// rejects with [`FileAdmission::RejectWithError`]
+    fn validate_file_admission(&self) -> Result<()> {
+        let Some(file_admission) = &self.file_admission else {
+            return Ok(());
+        };
+        for file in self.file_groups.iter().flat_map(FileGroup::iter) {
+            if let FileAdmission::RejectWithError(message) =
+                file_admission(&file.object_meta)
+            {
+                return plan_err!("File {} is not admitted: {message}", file.path());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an error naming the first file whose number of partition values
+    /// does not match the number of [`Self::table_partition_cols`].
+    ///
+    /// Partition values may be lists, e.g. for multi-value partition keys, in
+    /// which case the partition column must be declared with the same list
+    /// type.
+    fn validate_partition_values(&self) -> Result<()> {
+        let expected = self.table_partition_cols.len();
+        for file in self.file_groups.iter().flat_map(FileGroup::iter) {
+            if file.partition_values.len() != expected {
+                return exec_err!(
+                    "File {} has {} partition values, but {expected} table partition columns are declared: [{}]",
+                    file.path(),
+                    file.partition_values.len(),
+                    self.table_partition_cols
+                        .iter()
+                        .map(|field| field.name().as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            for (value, field) in
+                file.partition_values.iter().zip(&self.table_partition_cols)
+            {
+                let value_type = value.data_type();
+                let is_list = matches!(
+                    value_type,
+                    DataType::List(_)
+                        | DataType::LargeList(_)
+                        | DataType::FixedSizeList(_, _)
+                );
+                if is_list && &value_type != field.data_type() {
+                    return exec_err!(
+                        "Partition value of column {} of file {} is a {value_type}, but the column is declared as {}",
+                        field.name(),
+                        file.path(),
+                        field.data_type()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the output of this scan already satisfies the
+    /// `required` ordering, in which case a sort on top of it can be elided.
+    ///
+    /// This checks the [`DataSource::eq_properties`] of the scan, so equivalent
+    /// orderings are taken into account, as well as partition columns that
+    /// have a single value within every file group and are therefore constant
+    /// in each output partition.
+    pub fn satisfies_ordering(&self, required: &LexOrdering) -> Result<bool> {
+        let mut eq_properties = self.eq_properties();
+        eq_properties.add_constants(self.constant_partition_columns())?;
+        eq_properties.ordering_satisfy(required.clone())
+    }
+
+    /// Returns the sorted distinct values of each partition column across all
+    /// files, keyed by column name.
+    ///
+    /// Dictionary encoded values are returned as their value type.
+    pub fn distinct_partition_values(&self) -> HashMap<String, Vec<ScalarValue>> {
+        self.table_partition_cols
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let mut values = self
+                    .file_groups
+                    .iter()
+                    .flat_map(FileGroup::iter)
+                    .filter_map(|file| file.partition_values.get(idx))
+                    .map(|value| match value {
+                        ScalarValue::Dictionary(_, value) => value.as_ref().clone(),
+                        value => value.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                values.dedup();
+                (field.name().clone(), values)
+            })
+            .collect()
+    }
+
+    /// Assigns each file to one of `num_partitions` file groups based on a
+    /// hash of its value for the partition column `column`, and reports the
+    /// output partitioning of the scan as [`Partitioning::Hash`] on `column`.
+    ///
+    /// Values are hashed the same way as by [`RepartitionExec`], so the scan
+    /// can be joined on `column` with another input hash partitioned into the
+    /// same number of partitions without a shuffle. This relies on each file
+    /// having a single value for `column`, as in a Hive style layout. The
+    /// partitioning is reported as if declared with
+    /// [`FileScanConfigBuilder::with_declared_partitioning`].
+    ///
+    /// [`RepartitionExec`]: datafusion_physical_plan::repartition::RepartitionExec
+    pub fn hash_partition_by(
+        mut self,
+        column: &str,
+        num_partitions: usize,
+    ) -> Result<Self> {
+        if num_partitions == 0 {
+            return plan_err!("Cannot hash partition files into 0 partitions");
+        }
+        let Some(partition_idx) = self
+            .table_partition_cols
+            .iter()
+            .position(|field| field.name() == column)
+        else {
+            return plan_err!(
+                "Cannot hash partition files by {column}, which is not a partition column"
+            );
+        };
+
+        let mut file_groups = vec![FileGroup::default(); num_partitions];
+        for file in mem::take(&mut self.file_groups)
+            .into_iter()
+            .flat_map(FileGroup::into_inner)
+        {
+            let Some(value) = file.partition_values.get(partition_idx) else {
+                return plan_err!(
+                    "File {} has no value for partition column {column}",
+                    file.path()
+                );
+            };
+            file_groups[hash_partition_index(value, num_partitions)?].push(file);
+        }
+
+        self.file_groups = file_groups;
+        self.group_labels.clear();
+        self.declared_partitioning = Some(PartitionKey::Column(column.to_string()));
+        Ok(self)
+    }
+
+    /// Redistributes the files left after pruning into at most
+    /// `target_partitions` groups, dropping the empty groups.
+    ///
+    /// Without an output ordering, the files are assigned from the largest to
+    /// the smallest to the group with the fewest bytes so far. With an output
+    /// ordering, the files are split by their statistics as
+    /// [`Self::split_groups_by_statistics_with_target_partitions`] does, so
+    /// every group stays ordered; if their statistics do not allow it, the
+    /// groups are kept as they are and only the empty groups are dropped.
+    ///
+    /// The files are not regrouped if the partitions are tied to object
+    /// stores, resume cursors or a hash partitioning.
+    pub fn rebalance_after_pruning(mut self, target_partitions: usize) -> Self {
+        if self.has_pinned_file_groups() {
+            return self;
+        }
+        self.group_labels.clear();
+
+        let target_partitions = target_partitions.max(1);
+        if !self.output_ordering.is_empty() {
+            self.file_groups.retain(|group| !group.is_empty());
+            if let Some(file_groups) =
+                self.split_groups_preserving_order(target_partitions)
+            {
+                self.file_groups = file_groups;
+            }
+            return self;
+        }
+
+        let mut files = mem::take(&mut self.file_groups)
+            .into_iter()
+            .flat_map(FileGroup::into_inner)
+            .collect::<Vec<_>>();
+        // stable, so that files of the same size keep their order
+        files.sort_by_key(|file| Reverse(file_read_size(file)));
+        let num_groups = target_partitions.min(files.len());
+        let mut groups = (0..num_groups)
+            .map(|idx| Reverse((0, idx)))
+            .collect::<BinaryHeap<_>>();
+        let mut file_groups = vec![FileGroup::default(); num_groups];
+        for file in files {
+            let Reverse((size, idx)) = groups.pop().unwrap();
+            groups.push(Reverse((size + file_read_size(&file), idx)));
+            file_groups[idx].push(file);
+        }
+        self.file_groups = file_groups;
+        self
+    }
+
+    /// Returns whether the files can not be moved to another file group, as
+    /// the groups are tied to object stores, resume cursors or a hash
+    /// partitioning
+    fn has_pinned_file_groups(&self) -> bool {
+        !self.partition_store_urls.is_empty()
+            || !self.resume_cursors.is_empty()
+            || self.declared_partitioning.is_some()
+    }
+
+    /// Returns the output partitioning declared with
+    /// [`FileScanConfigBuilder::with_declared_partitioning`], if the file
+    /// groups are consistent with it
+    fn declared_output_partitioning(&self) -> Option<Partitioning> {
+        let PartitionKey::Column(column) = self.declared_partitioning.as_ref()?;
+        let name = self.output_name(column);
+        let proj_idx = self.projected_schema().index_of(name).ok()?;
+        let partition_idx = self
+            .table_partition_cols
+            .iter()
+            .position(|field| field.name() == column)?;
+
+        let num_groups = self.file_groups.len();
+        for (idx, group) in self.file_groups.iter().enumerate() {
+            // each value must be in the group `RepartitionExec` would send
+            // it to, which also ensures it is in no other group
+            for file in group.iter() {
+                let value = file.partition_values.get(partition_idx)?;
+                if hash_partition_index(value, num_groups).ok()? != idx {
+                    debug!("Ignoring the declared partitioning on {column}, which does not match the file groups");
+                    return None;
+                }
+            }
+        }
+
+        Some(Partitioning::Hash(
+            vec![Arc::new(Column::new(name, proj_idx))],
+            num_groups,
+        ))
+    }
+
+    /// Returns the projected partition columns which have the same value for
+    /// all files of each file group
+    fn constant_partition_columns(&self) -> Vec<ConstExpr> {
+        let schema = self.projected_schema();
+        let num_file_cols = self.file_schema.fields().len();
+        self.effective_projection()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, idx)| *idx >= num_file_cols)
+            .filter_map(|(proj_idx, idx)| {
+                let partition_idx = idx - num_file_cols;
+                let mut distinct_values = HashSet::new();
+                for group in self.file_groups.iter().filter(|g| !g.is_empty()) {
+                    let mut values = group
+                        .iter()
+                        .map(|file| file.partition_values.get(partition_idx));
+                    let first = values.next().flatten()?;
+                    if !values.all(|value| value == Some(first)) {
+                        return None;
+                    }
+                    distinct_values.insert(first);
+                }
+
+                let across_partitions = if distinct_values.len() == 1 {
+                    AcrossPartitions::Uniform(None)
+                } else {
+                    AcrossPartitions::Heterogeneous
+                };
+                let column = Column::new(schema.field(proj_idx).name(), proj_idx);
+                Some(ConstExpr::new(Arc::new(column), across_partitions))
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the table columns (file columns followed by
+    /// partition columns) in the output of this scan: [`Self::projection`]
+    /// if set, otherwise all columns.
+    ///
+    /// These are the indices [`Self::project`] uses.
+    pub fn effective_projection(&self) -> Vec<usize> {
+        match &self.projection {
+            Some(proj) => proj.clone(),
+            None => (0..self.file_schema.fields().len()
+                + self.table_partition_cols.len())
+                .collect(),
+        }
+    }
+
+    /// Returns a copy of this config whose projection also includes the
+    /// columns referenced by `predicate`, so that a filter evaluating it on
+    /// the output of the scan finds all of them.
+    ///
+    /// The columns of `predicate` are resolved by name against the table
+    /// columns, and the missing ones are appended to [`Self::projection`] in
+    /// table order. The config is returned unchanged if there is no
+    /// projection, as all columns are read.
+    pub fn widen_projection_for_filter(
+        &self,
+        predicate: &Arc<dyn PhysicalExpr>,
+    ) -> Result<Self> {
+        let Some(projection) = &self.projection else {
+            return Ok(self.clone());
+        };
+
+        let table_schema = self.table_schema();
+        let mut filter_indices = collect_columns(predicate)
+            .iter()
+            .map(|column| table_schema.index_of(column.name()))
+            .collect::<Result<Vec<_>, _>>()?;
+        filter_indices.sort_unstable();
+
+        let mut projection = projection.clone();
+        for idx in filter_indices {
+            if !projection.contains(&idx) {
+                projection.push(idx);
+            }
+        }
+
+        let mut config = self.clone();
+        config.projection = Some(projection);
+        Ok(config)
+    }
+
+    pub fn projected_stats(&self) -> Statistics {
+        let statistics = self.file_source.statistics().unwrap();
+
+        let mut table_cols_stats = self
+            .effective_projection()
+            .into_iter()
+            .map(|idx| {
+                if !self.has_column_statistics(idx) {
+                    ColumnStatistics::new_unknown()
+                } else if idx < self.file_schema.fields().len() {
+                    statistics.column_statistics[idx].clone()
+                } else {
+                    self.partition_column_statistics(
+                        idx - self.file_schema.fields().len(),
+                    )
+                }
+            })
+            .collect::<Vec<_>>();
+        if self.partition_index_column.is_some() {
+            table_cols_stats.push(self.partition_index_column_statistics());
+        }
+        table_cols_stats.extend(
+            self.constant_columns
+                .iter()
+                .map(|(_, value)| constant_column_statistics(value, statistics.num_rows)),
+        );
+
+        Statistics {
+            num_rows: statistics.num_rows,
+            // TODO correct byte size: https://github.com/apache/datafusion/issues/14936
+            total_byte_size: statistics.total_byte_size,
+            column_statistics: table_cols_stats,
+        }
+    }
+
+    /// Returns a lower and an upper bound of the number of rows of the scan,
+    /// folded from the `num_rows` of the [`PartitionedFile::statistics`] of
+    /// each file.
+    ///
+    /// A bound is [`Precision::Exact`] if it is guaranteed, and
+    /// [`Precision::Inexact`] if it is estimated from an inexact number of
+    /// rows. A file with an unknown number of rows, or only read in part, has
+    /// at least 0 rows and no known maximum, which makes the upper bound
+    /// [`Precision::Absent`]. Both bounds are capped by [`Self::limit`] and the
+    /// number of rows of each file by [`FileScanConfig::per_file_row_limit`], which
+    /// bounds even unknown numbers of rows.
+    pub fn output_row_bounds(&self) -> (Precision<usize>, Precision<usize>) {
+        let cap = |rows: Precision<usize>, cap: Option<usize>| match (rows, cap) {
+            (Precision::Absent, Some(cap)) => Precision::Exact(cap),
+            (rows, Some(cap)) => rows.map(|rows| rows.min(cap)),
+            (rows, None) => rows,
+        };
+
+        let (min, max) = self.file_groups.iter().flat_map(FileGroup::iter).fold(
+            (Precision::Exact(0), Precision::Exact(0)),
+            |(min, max), file| {
+                let num_rows = match (&file.statistics, &file.range) {
+                    (Some(statistics), None) if file.ranges.is_empty() => {
+                        statistics.num_rows
+                    }
+                    _ => Precision::Absent,
+                };
+                let file_min = match num_rows {
+                    Precision::Absent => Precision::Exact(0),
+                    num_rows => cap(num_rows, self.per_file_row_limit),
+                };
+                let file_max = cap(num_rows, self.per_file_row_limit);
+                (min.add(&file_min), max.add(&file_max))
             },
-            TestCase {
-                name: "empty input",
-                file_schema: Schema::new(vec![Field::new(
-                    "value".to_string(),
-                    DataType::Float64,
+        );
+        (cap(min, self.limit), cap(max, self.limit))
+    }
+
+    /// Whether the statistics of the column at `idx` of the table schema are
+    /// used, see [`FileScanConfigBuilder::with_stats_columns`]
+    fn has_column_statistics(&self, idx: usize) -> bool {
+        self.stats_columns
+            .as_ref()
+            .is_none_or(|stats_columns| stats_columns.contains(&idx))
+    }
+
+    /// Statistics for the [`FileScanConfig::partition_index_column`], whose values are
+    /// the indexes of the file groups
+    fn partition_index_column_statistics(&self) -> ColumnStatistics {
+        let Some(max) = self.file_groups.len().checked_sub(1) else {
+            return ColumnStatistics::new_unknown();
+        };
+        ColumnStatistics::new_unknown()
+            .with_null_count(Precision::Exact(0))
+            .with_min_value(Precision::Exact(ScalarValue::UInt64(Some(0))))
+            .with_max_value(Precision::Exact(ScalarValue::UInt64(Some(max as u64))))
+            .with_distinct_count(Precision::Exact(self.file_groups.len()))
+    }
+
+    /// Statistics for the partition column at `partition_idx` in
+    /// [`Self::table_partition_cols`].
+    ///
+    /// The partition values of every file are known at planning time, so the
+    /// number of distinct (non null) values is exact as long as every file
+    /// carries a value for the column.
+    fn partition_column_statistics(&self, partition_idx: usize) -> ColumnStatistics {
+        let mut distinct_values = HashSet::new();
+        for file in self.file_groups.iter().flat_map(FileGroup::iter) {
+            let Some(value) = file.partition_values.get(partition_idx) else {
+                return ColumnStatistics::new_unknown();
+            };
+            if !value.is_null() {
+                distinct_values.insert(value);
+            }
+        }
+
+        if distinct_values.is_empty() {
+            return ColumnStatistics::new_unknown();
+        }
+
+        ColumnStatistics::new_unknown()
+            .with_distinct_count(Precision::Exact(distinct_values.len()))
+    }
+
+    pub fn projected_schema(&self) -> Arc<Schema> {
+        let table_fields: Vec<_> = self
+            .effective_projection()
+            .into_iter()
+            .map(|idx| {
+                let field = if idx < self.file_schema.fields().len() {
+                    self.file_schema.field(idx).clone()
+                } else {
+                    let partition_idx = idx - self.file_schema.fields().len();
+                    Arc::unwrap_or_clone(Arc::clone(
+                        &self.table_partition_cols[partition_idx],
+                    ))
+                };
+                match self.output_rename.get(field.name()) {
+                    Some(name) => field.with_name(name),
+                    None => field,
+                }
+            })
+            .chain(
+                self.partition_index_column
+                    .as_ref()
+                    .map(|name| Field::new(name, DataType::UInt64, false)),
+            )
+            .chain(self.constant_columns.iter().map(|(name, value)| {
+                Field::new(name, value.data_type(), value.is_null())
+            }))
+            .collect();
+
+        Arc::new(Schema::new_with_metadata(
+            table_fields,
+            self.file_schema.metadata().clone(),
+        ))
+    }
+
+    /// Returns a description of where each output column of the scan comes
+    /// from, with a line per column of the [`Self::projected_schema`].
+    ///
+    /// Each line shows the index, name and type of the output column and the
+    /// `file_schema[i]` or `table_partition_cols[j]` it is read from, e.g.
+    /// `1: date (Utf8) <- table_partition_cols[0]`. Indices of the projection
+    /// beyond the table columns are reported as invalid. This is a debugging
+    /// aid for configs built by hand.
+    pub fn explain_projection(&self) -> String {
+        let num_file_columns = self.file_schema.fields().len();
+        self.effective_projection()
+            .into_iter()
+            .enumerate()
+            .map(|(output_idx, idx)| {
+                let (field, source) = if idx < num_file_columns {
+                    (self.file_schema.field(idx), format!("file_schema[{idx}]"))
+                } else if let Some(field) =
+                    self.table_partition_cols.get(idx - num_file_columns)
+                {
+                    let partition_idx = idx - num_file_columns;
+                    (
+                        field.as_ref(),
+                        format!("table_partition_cols[{partition_idx}]"),
+                    )
+                } else {
+                    return format!("{output_idx}: invalid table column index {idx}");
+                };
+                format!(
+                    "{output_idx}: {} ({}) <- {source}",
+                    field.name(),
+                    field.data_type()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn projected_constraints(&self) -> Constraints {
+        self.project_constraints(&self.effective_projection())
+    }
+
+    /// Returns the [`Self::constraints`] that still hold after projecting the
+    /// table columns to `projection`, without computing the projected schema,
+    /// statistics and orderings as [`Self::project`] does.
+    ///
+    /// A constraint is kept only if all its columns are in `projection`, and
+    /// its column indexes refer to the projected columns.
+    pub fn project_constraints(&self, projection: &[usize]) -> Constraints {
+        self.constraints.project(projection).unwrap_or_default()
+    }
+
+    /// Set the projection of the files
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_projection(mut self, projection: Option<Vec<usize>>) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Set the limit of the files
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Add a file as a single group
+    ///
+    /// See [Self::file_groups] for more information.
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    #[allow(deprecated)]
+    pub fn with_file(self, file: PartitionedFile) -> Self {
+        self.with_file_group(FileGroup::new(vec![file]))
+    }
+
+    /// Add the file groups
+    ///
+    /// See [Self::file_groups] for more information.
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_file_groups(mut self, mut file_groups: Vec<FileGroup>) -> Self {
+        self.file_groups.append(&mut file_groups);
+        self
+    }
+
+    /// Add a new file group
+    ///
+    /// See [Self::file_groups] for more information
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_file_group(mut self, file_group: FileGroup) -> Self {
+        self.file_groups.push(file_group);
+        self
+    }
+
+    /// Set the partitioning columns of the files
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_table_partition_cols(mut self, table_partition_cols: Vec<Field>) -> Self {
+        self.table_partition_cols = table_partition_cols
+            .into_iter()
+            .map(|f| Arc::new(f) as FieldRef)
+            .collect();
+        self
+    }
+
+    /// Set the output ordering of the files
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_output_ordering(mut self, output_ordering: Vec<LexOrdering>) -> Self {
+        self.output_ordering = output_ordering;
+        self
+    }
+
+    /// Set the file compression type
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_file_compression_type(
+        mut self,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        self.file_compression_type = file_compression_type;
+        self
+    }
+
+    /// Set the new_lines_in_values property
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_newlines_in_values(mut self, new_lines_in_values: bool) -> Self {
+        self.new_lines_in_values = new_lines_in_values;
+        self
+    }
+
+    /// Set the batch_size property
+    #[deprecated(since = "47.0.0", note = "use FileScanConfigBuilder instead")]
+    pub fn with_batch_size(mut self, batch_size: Option<usize>) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Specifies whether newlines in (quoted) values are supported.
+    ///
+    /// Parsing newlines in quoted values may be affected by execution behaviour such as
+    /// parallel file scanning. Setting this to `true` ensures that newlines in values are
+    /// parsed successfully, which may reduce performance.
+    ///
+    /// The default behaviour depends on the `datafusion.catalog.newlines_in_values` setting.
+    pub fn newlines_in_values(&self) -> bool {
+        self.new_lines_in_values
+    }
+
+    /// Project the schema, constraints, and the statistics on the given column indices
+    pub fn project(&self) -> (SchemaRef, Constraints, Statistics, Vec<LexOrdering>) {
+        if self.outputs_file_schema() {
+            return (
+                Arc::clone(&self.file_schema),
+                self.constraints.clone(),
+                self.file_source.statistics().unwrap().clone(),
+                self.output_ordering
+                    .iter()
+                    .map(|ordering| {
+                        if self.reverse_scan {
+                            reverse_ordering(ordering)
+                        } else {
+                            ordering.clone()
+                        }
+                    })
+                    .collect(),
+            );
+        }
+
+        let schema = self.projected_schema();
+        let constraints = self.projected_constraints();
+        let stats = self.projected_stats();
+
+        let output_ordering = get_projected_output_ordering(self, &schema);
+
+        (schema, constraints, stats, output_ordering)
+    }
+
+    /// Returns the statistics of the output of the scan, as returned by
+    /// [`Self::project`], without computing the projected schema,
+    /// constraints and orderings, e.g. for cost models.
+    pub fn projected_statistics(&self) -> Statistics {
+        if self.outputs_file_schema() {
+            self.file_source.statistics().unwrap().clone()
+        } else {
+            self.projected_stats()
+        }
+    }
+
+    /// Returns a plan outputting the [`Self::projected_statistics`] of the
+    /// scan as a single row instead of its data, e.g. to profile a table
+    /// without reading it.
+    ///
+    /// The row has a `num_rows` and a `total_bytes` column, followed by a
+    /// `{column}_min` and a `{column}_max` column per output column of the
+    /// scan, with the type of the column. Unknown statistics are null, exact
+    /// and inexact statistics are not told apart.
+    pub fn statistics_plan(&self) -> Result<Arc<DataSourceExec>> {
+        let statistics = self.projected_statistics();
+        let schema = self.projected_schema();
+
+        let count = |count: &Precision<usize>| -> ArrayRef {
+            Arc::new(UInt64Array::from(vec![count
+                .get_value()
+                .map(|n| *n as u64)]))
+        };
+        let mut fields = vec![
+            Field::new("num_rows", DataType::UInt64, true),
+            Field::new("total_bytes", DataType::UInt64, true),
+        ];
+        let mut columns = vec![
+            count(&statistics.num_rows),
+            count(&statistics.total_byte_size),
+        ];
+        for (field, column_statistics) in
+            schema.fields().iter().zip(&statistics.column_statistics)
+        {
+            for (suffix, value) in [
+                ("min", &column_statistics.min_value),
+                ("max", &column_statistics.max_value),
+            ] {
+                let data_type = field.data_type();
+                let value = match value.get_value() {
+                    Some(value) => value.cast_to(data_type)?,
+                    None => ScalarValue::try_from(data_type)?,
+                };
+                fields.push(Field::new(
+                    format!("{}_{suffix}", field.name()),
+                    data_type.clone(),
+                    true,
+                ));
+                columns.push(value.to_array()?);
+            }
+        }
+
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(Arc::clone(&schema), columns)?;
+        MemorySourceConfig::try_new_exec(&[vec![batch]], schema, None)
+    }
+
+    /// Returns true if the output of the scan is the file schema, unchanged
+    fn outputs_file_schema(&self) -> bool {
+        self.projection.is_none()
+            && self.table_partition_cols.is_empty()
+            && self.output_rename.is_empty()
+            && self.partition_index_column.is_none()
+            && self.constant_columns.is_empty()
+    }
+
+    pub fn projected_file_column_names(&self) -> Option<Vec<String>> {
+        self.projection.as_ref().map(|p| {
+            p.iter()
+                .filter(|col_idx| **col_idx < self.file_schema.fields().len())
+                .map(|col_idx| self.file_schema.field(*col_idx).name())
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// Returns a copy of this config without the files that can not contain
+    /// rows matching `predicate`, along with the number of files pruned.
+    ///
+    /// The `predicate` refers to the columns of the table, i.e. the file
+    /// schema followed by the partition columns, and is evaluated against the
+    /// min/max [`PartitionedFile::statistics`] of each file and its partition
+    /// values. Files without statistics are kept. The number of file groups is
+    /// unchanged, even if some of them become empty, and the statistics of the
+    /// config become inexact if any file is pruned.
+    pub fn prune_files_by_filter(
+        &self,
+        predicate: &Arc<dyn PhysicalExpr>,
+    ) -> Result<(FileScanConfig, usize)> {
+        let pruning_predicate =
+            PruningPredicate::try_new(Arc::clone(predicate), self.table_schema())?;
+        if pruning_predicate.always_true() {
+            return Ok((self.clone(), 0));
+        }
+
+        let files = self
+            .file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .collect::<Vec<_>>();
+        let file_statistics = PrunableStatistics::new(
+            files
+                .iter()
+                .map(|file| match &file.statistics {
+                    Some(statistics) if self.stats_columns.is_some() => {
+                        Arc::new(retain_column_statistics(
+                            statistics.as_ref().clone(),
+                            self.stats_columns.as_deref(),
+                        ))
+                    }
+                    Some(statistics) => Arc::clone(statistics),
+                    None => Arc::new(Statistics::new_unknown(&self.file_schema)),
+                })
+                .collect(),
+            Arc::clone(&self.file_schema),
+        );
+        let num_file_columns = self.file_schema.fields().len();
+        let (partition_idxs, partition_cols): (Vec<_>, Vec<_>) = self
+            .table_partition_cols
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| self.has_column_statistics(num_file_columns + idx))
+            .map(|(idx, field)| (idx, Arc::clone(field)))
+            .unzip();
+        let partition_statistics = PartitionPruningStatistics::try_new(
+            files
+                .iter()
+                .map(|file| {
+                    partition_idxs
+                        .iter()
+                        .filter_map(|idx| file.partition_values.get(*idx).cloned())
+                        .collect()
+                })
+                .collect(),
+            partition_cols,
+        )?;
+        let statistics = CompositePruningStatistics::new(vec![
+            Box::new(file_statistics),
+            Box::new(partition_statistics),
+        ]);
+        let mut keep = pruning_predicate.prune(&statistics)?.into_iter();
+
+        let mut num_pruned = 0;
+        let file_groups = self
+            .file_groups
+            .iter()
+            .map(|group| {
+                let files = group
+                    .iter()
+                    .filter(|_| {
+                        let keep = keep.next().unwrap_or(true);
+                        num_pruned += usize::from(!keep);
+                        keep
+                    })
+                    .cloned()
+                    .collect();
+                FileGroup::new(files)
+            })
+            .collect();
+
+        let mut config = self.clone();
+        config.file_groups = file_groups;
+        if num_pruned > 0 {
+            let statistics = self.file_source.statistics()?.to_inexact();
+            config.file_source = self.file_source.with_statistics(statistics);
+        }
+        Ok((config, num_pruned))
+    }
+
+    /// Returns a copy of this config with only the files modified after
+    /// `watermark`, along with the number of files retained.
+    ///
+    /// Files whose `object_meta.last_modified` is at or before `watermark` are
+    /// dropped, e.g. to only read the files changed since the last refresh of
+    /// an incrementally maintained result. The number of file groups is
+    /// unchanged, and the statistics of the config become inexact if any file
+    /// is dropped.
+    pub fn filter_files_modified_after(
+        &self,
+        watermark: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(FileScanConfig, usize)> {
+        let mut num_retained = 0;
+        let mut num_dropped = 0;
+        let file_groups = self
+            .file_groups
+            .iter()
+            .map(|group| {
+                let files = group
+                    .iter()
+                    .filter(|file| {
+                        let keep = file.object_meta.last_modified > watermark;
+                        num_retained += usize::from(keep);
+                        num_dropped += usize::from(!keep);
+                        keep
+                    })
+                    .cloned()
+                    .collect();
+                FileGroup::new(files)
+            })
+            .collect();
+
+        let mut config = self.clone();
+        config.file_groups = file_groups;
+        if num_dropped > 0 {
+            let statistics = self.file_source.statistics()?.to_inexact();
+            config.file_source = self.file_source.with_statistics(statistics);
+        }
+        Ok((config, num_retained))
+    }
+
+    /// Returns a copy of this config with only the files whose
+    /// [`PartitionedFile::partition_values`] are equal to one of `partitions`,
+    /// e.g. to look up specific partitions without a predicate.
+    ///
+    /// Each tuple of `partitions` has a value for every partition column, in
+    /// the order of [`Self::table_partition_cols`]. Dictionary encoded values
+    /// are compared by their value. The number of file groups is unchanged,
+    /// and the statistics of the config become inexact if any file is
+    /// dropped.
+    pub fn retain_partitions(&self, partitions: &[Vec<ScalarValue>]) -> Result<Self> {
+        let num_cols = self.table_partition_cols.len();
+        if let Some(tuple) = partitions.iter().find(|tuple| tuple.len() != num_cols) {
+            return plan_err!(
+                "Expected {num_cols} partition values to retain, got {}",
+                tuple.len()
+            );
+        }
+
+        fn unpack(value: &ScalarValue) -> &ScalarValue {
+            match value {
+                ScalarValue::Dictionary(_, value) => value.as_ref(),
+                value => value,
+            }
+        }
+        let matches = |file: &PartitionedFile| {
+            partitions.iter().any(|tuple| {
+                file.partition_values.len() == num_cols
+                    && tuple
+                        .iter()
+                        .zip(&file.partition_values)
+                        .all(|(expected, actual)| unpack(expected) == unpack(actual))
+            })
+        };
+
+        let mut num_dropped = 0;
+        let file_groups = self
+            .file_groups
+            .iter()
+            .map(|group| {
+                let files = group
+                    .iter()
+                    .filter(|file| {
+                        let keep = matches(file);
+                        num_dropped += usize::from(!keep);
+                        keep
+                    })
+                    .cloned()
+                    .collect();
+                FileGroup::new(files)
+            })
+            .collect();
+
+        let mut config = self.clone();
+        config.file_groups = file_groups;
+        if num_dropped > 0 {
+            let statistics = self.file_source.statistics()?.to_inexact();
+            config.file_source = self.file_source.with_statistics(statistics);
+        }
+        Ok(config)
+    }
+
+    /// Returns a copy of this config with only the files whose value of the
+    /// partition column `partition_col` falls in the time range
+    /// `[start, end)`, e.g. the days of a table partitioned by date.
+    ///
+    /// `start` and `end` must be of the same date or timestamp type. The
+    /// partition values are converted to that type first, so that string
+    /// values such as `2024-01-31` are parsed as dates or timestamps. Files
+    /// with a null value, or without a value for the column, are dropped. The
+    /// number of file groups is unchanged, and the statistics of the config
+    /// become inexact if any file is dropped.
+    ///
+    /// Returns an error if `partition_col` is not a partition column, if
+    /// `start` and `end` are not of the same temporal type, or if a partition
+    /// value can not be converted to it.
+    pub fn for_time_range(
+        &self,
+        start: &ScalarValue,
+        end: &ScalarValue,
+        partition_col: &str,
+    ) -> Result<Self> {
+        let data_type = start.data_type();
+        if !data_type.is_temporal() || end.data_type() != data_type {
+            return plan_err!(
+                "Time range bounds must be of the same date or timestamp type, got {data_type} and {}",
+                end.data_type()
+            );
+        }
+        let Some(idx) = self
+            .table_partition_cols
+            .iter()
+            .position(|field| field.name() == partition_col)
+        else {
+            return plan_err!("{partition_col} is not a partition column");
+        };
+
+        let mut num_dropped = 0;
+        let file_groups = self
+            .file_groups
+            .iter()
+            .map(|group| {
+                let mut files = vec![];
+                for file in group.iter() {
+                    let value = match file.partition_values.get(idx) {
+                        Some(value) if !value.is_null() => value.cast_to(&data_type)?,
+                        _ => ScalarValue::Null,
+                    };
+                    let keep = !value.is_null() && &value >= start && &value < end;
+                    num_dropped += usize::from(!keep);
+                    if keep {
+                        files.push(file.clone());
+                    }
+                }
+                Ok(FileGroup::new(files))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut config = self.clone();
+        config.file_groups = file_groups;
+        if num_dropped > 0 {
+            let statistics = self.file_source.statistics()?.to_inexact();
+            config.file_source = self.file_source.with_statistics(statistics);
+        }
+        Ok(config)
+    }
+
+    /// Returns a copy of this config with the files regrouped so that every
+    /// group holds the files of a single tuple of
+    /// [`PartitionedFile::partition_values`], e.g. so that the partition
+    /// columns are constant within each output partition.
+    ///
+    /// The groups are ordered by the first file of each tuple, and keep the
+    /// order of their files. Files without a value for every partition
+    /// column are placed in a group of their own.
+    ///
+    /// The files are not regrouped if the groups are tied to object stores,
+    /// resume cursors or a hash partitioning.
+    pub fn group_files_by_partition_values(&self) -> Self {
+        if self.has_pinned_file_groups() {
+            return self.clone();
+        }
+        let num_cols = self.table_partition_cols.len();
+        let mut group_of_values: HashMap<&[ScalarValue], usize> = HashMap::new();
+        let mut file_groups: Vec<Vec<PartitionedFile>> = vec![];
+        for file in self.file_groups.iter().flat_map(FileGroup::iter) {
+            let values = file.partition_values.as_slice();
+            if values.len() == num_cols {
+                if let Some(&idx) = group_of_values.get(values) {
+                    file_groups[idx].push(file.clone());
+                    continue;
+                }
+                group_of_values.insert(values, file_groups.len());
+            }
+            file_groups.push(vec![file.clone()]);
+        }
+
+        let mut config = self.clone();
+        config.file_groups = file_groups.into_iter().map(FileGroup::new).collect();
+        config.group_labels.clear();
+        config
+    }
+
+    /// Splits this config into one config per object store, e.g. to schedule
+    /// the scans of files spread over several stores separately.
+    ///
+    /// The files are grouped by the store they are read from: their
+    /// [`PartitionedFile::object_store_url`] if set, otherwise the
+    /// [`Self::partition_store_url`] of their group. Each config reads its
+    /// files from [`Self::object_store_url`], keeps the non empty subsets of
+    /// the original groups and the projection, ordering and file source of
+    /// this config. The configs are ordered by the first file of each store,
+    /// and their statistics become inexact if there is more than one.
+    ///
+    /// As the groups are split, the configs are no longer hash partitioned,
+    /// even if this config is, see [`Self::hash_partition_by`]. Returns an
+    /// error if some partitions are resumed from a cursor, which refers to
+    /// the files of the original groups.
+    pub fn split_by_store(self) -> Result<Vec<FileScanConfig>> {
+        if !self.resume_cursors.is_empty() {
+            return plan_err!(
+                "Cannot split a file scan resumed from a cursor by object store"
+            );
+        }
+
+        let mut store_idxs: HashMap<ObjectStoreUrl, usize> = HashMap::new();
+        let mut stores: Vec<(ObjectStoreUrl, Vec<FileGroup>)> = vec![];
+        for (partition, group) in self.file_groups.iter().enumerate() {
+            let mut group_files: Vec<Vec<PartitionedFile>> = vec![vec![]; stores.len()];
+            for file in group.iter() {
+                let url = file
+                    .object_store_url
+                    .as_ref()
+                    .unwrap_or_else(|| self.partition_store_url(partition));
+                let idx = *store_idxs.entry(url.clone()).or_insert_with(|| {
+                    stores.push((url.clone(), vec![]));
+                    group_files.push(vec![]);
+                    stores.len() - 1
+                });
+                group_files[idx].push(PartitionedFile {
+                    object_store_url: None,
+                    ..file.clone()
+                });
+            }
+            for (idx, files) in group_files.into_iter().enumerate() {
+                if !files.is_empty() {
+                    stores[idx].1.push(FileGroup::new(files));
+                }
+            }
+        }
+
+        let statistics = match self.file_source.statistics() {
+            Ok(statistics) if stores.len() > 1 => Some(statistics.to_inexact()),
+            _ => None,
+        };
+        Ok(stores
+            .into_iter()
+            .map(|(object_store_url, file_groups)| {
+                let mut config = self.clone();
+                config.object_store_url = object_store_url;
+                config.file_groups = file_groups;
+                config.partition_store_urls.clear();
+                config.group_labels.clear();
+                config.declared_partitioning = None;
+                if let Some(statistics) = &statistics {
+                    config.file_source =
+                        self.file_source.with_statistics(statistics.clone());
+                }
+                config
+            })
+            .collect())
+    }
+
+    /// Projects only file schema, ignoring partition columns
+    pub fn projected_file_schema(&self) -> SchemaRef {
+        let fields = self.file_column_projection_indices().map(|indices| {
+            indices
+                .iter()
+                .map(|col_idx| self.file_schema.field(*col_idx))
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+
+        fields.map_or_else(
+            || Arc::clone(&self.file_schema),
+            |f| {
+                Arc::new(Schema::new_with_metadata(
+                    f,
+                    self.file_schema.metadata.clone(),
+                ))
+            },
+        )
+    }
+
+    pub fn file_column_projection_indices(&self) -> Option<Vec<usize>> {
+        self.projection
+            .as_ref()
+            .map(|p| self.table_projection_to_file_projection(p))
+    }
+
+    /// Translates `projection`, a list of indices into the table schema (the
+    /// file columns followed by the partition columns), into the indices of
+    /// the projected file columns in the file schema, in projection order.
+    ///
+    /// Partition columns are dropped, as they are not read from the files, so
+    /// e.g. with 3 file columns and 2 partition columns, `[4, 1, 3, 0]`
+    /// becomes `[1, 0]`.
+    pub fn table_projection_to_file_projection(
+        &self,
+        projection: &[usize],
+    ) -> Vec<usize> {
+        projection
+            .iter()
+            .filter(|col_idx| **col_idx < self.file_schema.fields().len())
+            .copied()
+            .collect()
+    }
+
+    /// Splits file groups into new groups based on statistics to enable efficient parallel processing.
+    ///
+    /// The method distributes files across a target number of partitions while ensuring
+    /// files within each partition maintain sort order based on their min/max statistics.
+    ///
+    /// The algorithm works by:
+    /// 1. Takes files sorted by minimum values
+    /// 2. For each file:
+    ///   - Finds eligible groups (empty or where file's min > group's last max)
+    ///   - Selects the smallest eligible group
+    ///   - Creates a new group if needed
+    ///
+    /// # Parameters
+    /// * `table_schema`: Schema containing information about the columns
+    /// * `file_groups`: The original file groups to split
+    /// * `sort_order`: The lexicographical ordering to maintain within each group
+    /// * `target_partitions`: The desired number of output partitions
+    ///
+    /// # Returns
+    /// A new set of file groups, where files within each group are non-overlapping with respect to
+    /// their min/max statistics and maintain the specified sort order.
+    pub fn split_groups_by_statistics_with_target_partitions(
+        table_schema: &SchemaRef,
+        file_groups: &[FileGroup],
+        sort_order: &LexOrdering,
+        target_partitions: usize,
+    ) -> Result<Vec<FileGroup>> {
+        if target_partitions == 0 {
+            return Err(DataFusionError::Internal(
+                "target_partitions must be greater than 0".to_string(),
+            ));
+        }
+
+        let flattened_files = file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .collect::<Vec<_>>();
+
+        if flattened_files.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let statistics = MinMaxStatistics::new_from_files(
+            sort_order,
+            table_schema,
+            None,
+            flattened_files.iter().copied(),
+        )?;
+
+        let indices_sorted_by_min = statistics.min_values_sorted();
+
+        // Initialize with target_partitions empty groups
+        let mut file_groups_indices: Vec<Vec<usize>> = vec![vec![]; target_partitions];
+
+        for (idx, min) in indices_sorted_by_min {
+            if let Some((_, group)) = file_groups_indices
+                .iter_mut()
+                .enumerate()
+                .filter(|(_, group)| {
+                    group.is_empty()
+                        || min
+                            > statistics
+                                .max(*group.last().expect("groups should not be empty"))
+                })
+                .min_by_key(|(_, group)| group.len())
+            {
+                group.push(idx);
+            } else {
+                // Create a new group if no existing group fits
+                file_groups_indices.push(vec![idx]);
+            }
+        }
+
+        // Remove any empty groups
+        file_groups_indices.retain(|group| !group.is_empty());
+
+        // Assemble indices back into groups of PartitionedFiles
+        Ok(file_groups_indices
+            .into_iter()
+            .map(|file_group_indices| {
+                FileGroup::new(
+                    file_group_indices
+                        .into_iter()
+                        .map(|idx| flattened_files[idx].clone())
+                        .collect(),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the schema of the file columns followed by the partition columns
+    fn table_schema(&self) -> SchemaRef {
+        Arc::new(Schema::new(
+            self.file_schema
+                .fields()
+                .iter()
+                .chain(&self.table_partition_cols)
+                .cloned()
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Returns a hash of what the scan reads, e.g. to key a cache of its
+    /// results.
+    ///
+    /// The hash covers the [`Self::object_store_url`], [`Self::file_schema`],
+    /// [`Self::table_partition_cols`], [`Self::projection`], [`Self::limit`]
+    /// and the location, size, e-tag, ranges, object store URL and partition
+    /// values of each file, so that replacing a file changes it. It is
+    /// computed with xxHash64 and a fixed seed, and is therefore the same in
+    /// every process running the same build.
+    pub fn content_signature(&self) -> u64 {
+        let mut hasher = XxHash64::with_seed(0);
+        self.object_store_url.hash(&mut hasher);
+        self.file_schema.hash(&mut hasher);
+        self.table_partition_cols.hash(&mut hasher);
+        self.projection.hash(&mut hasher);
+        self.limit.hash(&mut hasher);
+        for file in self.file_groups.iter().flat_map(FileGroup::iter) {
+            let ObjectMeta {
+                location,
+                size,
+                e_tag,
+                ..
+            } = &file.object_meta;
+            (location, size, e_tag, &file.range, &file.partition_values)
+                .hash(&mut hasher);
+            (&file.object_store_url, &file.sub_files, &file.ranges).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Returns true if [`DataSource::repartitioned`] would change the grouping
+    /// of the files for `target_partitions` and `repartition_file_min_size`,
+    /// e.g. to avoid replacing a plan with an equivalent one in optimizer
+    /// loops.
+    ///
+    /// The files are regrouped with the output ordering of the scan, as
+    /// [`DataSourceExec`] does. Returns false if repartitioning fails.
+    ///
+    /// [`DataSourceExec`]: crate::source::DataSourceExec
+    pub fn would_repartition_change(
+        &self,
+        target_partitions: usize,
+        repartition_file_min_size: usize,
+    ) -> bool {
+        let output_ordering = self.eq_properties().output_ordering();
+        let Ok(Some(source)) = self.repartitioned(
+            target_partitions,
+            repartition_file_min_size,
+            output_ordering,
+        ) else {
+            return false;
+        };
+        let Some(config) = source.as_any().downcast_ref::<FileScanConfig>() else {
+            return true;
+        };
+
+        // a range covering the whole file reads the same rows as no range
+        let grouping = |file_groups: &[FileGroup]| {
+            file_groups
+                .iter()
+                .map(|group| {
+                    group
+                        .iter()
+                        .map(|file| {
+                            let range = file.range.clone().filter(|range| {
+                                range.start > 0
+                                    || (range.end as u64) < file.object_meta.size
+                            });
+                            (file.path().clone(), range)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        grouping(&config.file_groups) != grouping(&self.file_groups)
+    }
+
+    /// Splits the files into at most `target_partitions` groups ordered by the
+    /// output ordering, see
+    /// [`FileScanConfigBuilder::with_order_preserving_repartition`].
+    ///
+    /// Returns `None` if there is no output ordering, or if the statistics of
+    /// the files do not allow it.
+    fn split_groups_preserving_order(
+        &self,
+        target_partitions: usize,
+    ) -> Option<Vec<FileGroup>> {
+        let sort_order = self.output_ordering.first()?;
+        match Self::split_groups_by_statistics_with_target_partitions(
+            &self.table_schema(),
+            &self.file_groups,
+            sort_order,
+            target_partitions,
+        ) {
+            Ok(file_groups) if file_groups.len() <= target_partitions => {
+                Some(file_groups)
+            }
+            Ok(_) => {
+                debug!("more file groups than target partitions needed to preserve the ordering, repartitioning by size");
+                None
+            }
+            Err(e) => {
+                debug!("failed to split file groups by statistics, repartitioning by size: {e}");
+                None
+            }
+        }
+    }
+
+    /// Attempts to do a bin-packing on files into file groups, such that any two files
+    /// in a file group are ordered and non-overlapping with respect to their statistics.
+    /// It will produce the smallest number of file groups possible.
+    pub fn split_groups_by_statistics(
+        table_schema: &SchemaRef,
+        file_groups: &[FileGroup],
+        sort_order: &LexOrdering,
+    ) -> Result<Vec<FileGroup>> {
+        let flattened_files = file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .collect::<Vec<_>>();
+        // First Fit:
+        // * Choose the first file group that a file can be placed into.
+        // * If it fits into no existing file groups, create a new one.
+        //
+        // By sorting files by min values and then applying first-fit bin packing,
+        // we can produce the smallest number of file groups such that
+        // files within a group are in order and non-overlapping.
+        //
+        // Source: Applied Combinatorics (Keller and Trotter), Chapter 6.8
+        // https://www.appliedcombinatorics.org/book/s_posets_dilworth-intord.html
+
+        if flattened_files.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let statistics = MinMaxStatistics::new_from_files(
+            sort_order,
+            table_schema,
+            None,
+            flattened_files.iter().copied(),
+        )
+        .map_err(|e| {
+            e.context("construct min/max statistics for split_groups_by_statistics")
+        })?;
+
+        let indices_sorted_by_min = statistics.min_values_sorted();
+        let mut file_groups_indices: Vec<Vec<usize>> = vec![];
+
+        for (idx, min) in indices_sorted_by_min {
+            let file_group_to_insert = file_groups_indices.iter_mut().find(|group| {
+                // If our file is non-overlapping and comes _after_ the last file,
+                // it fits in this file group.
+                min > statistics.max(
+                    *group
+                        .last()
+                        .expect("groups should be nonempty at construction"),
+                )
+            });
+            match file_group_to_insert {
+                Some(group) => group.push(idx),
+                None => file_groups_indices.push(vec![idx]),
+            }
+        }
+
+        // Assemble indices back into groups of PartitionedFiles
+        Ok(file_groups_indices
+            .into_iter()
+            .map(|file_group_indices| {
+                file_group_indices
+                    .into_iter()
+                    .map(|idx| flattened_files[idx].clone())
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Produces the same file groups as [`Self::split_groups_by_statistics`],
+    /// but finds the first group a file fits into in O(log groups) rather than
+    /// by scanning all groups, which is much faster for many files that end up
+    /// in many groups.
+    pub fn split_groups_by_statistics_indexed(
+        table_schema: &SchemaRef,
+        file_groups: &[FileGroup],
+        sort_order: &LexOrdering,
+    ) -> Result<Vec<FileGroup>> {
+        let flattened_files = file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .collect::<Vec<_>>();
+
+        if flattened_files.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let statistics = MinMaxStatistics::new_from_files(
+            sort_order,
+            table_schema,
+            None,
+            flattened_files.iter().copied(),
+        )
+        .map_err(|e| {
+            e.context(
+                "construct min/max statistics for split_groups_by_statistics_indexed",
+            )
+        })?;
+
+        // there are at most as many groups as files
+        let mut index = FirstFitIndex::new(&statistics, flattened_files.len());
+        let mut file_groups_indices: Vec<Vec<usize>> = vec![];
+
+        for (idx, min) in statistics.min_values_sorted() {
+            let group = match index.first_fit(&min) {
+                Some(group) => {
+                    file_groups_indices[group].push(idx);
+                    group
+                }
+                None => {
+                    file_groups_indices.push(vec![idx]);
+                    file_groups_indices.len() - 1
+                }
+            };
+            index.set_last_file(group, idx);
+        }
+
+        Ok(file_groups_indices
+            .into_iter()
+            .map(|file_group_indices| {
+                file_group_indices
+                    .into_iter()
+                    .map(|idx| flattened_files[idx].clone())
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Like [`Self::split_groups_by_statistics`], but only returns the groups
+    /// needed to produce the first `limit` rows in `sort_order`.
+    ///
+    /// Taking files in order of their min values, once the files taken so far
+    /// contain at least `limit` rows, the first `limit` rows in sort order are
+    /// all at most the largest max value of those files. Files whose min
+    /// value is beyond that can not contribute to the result and are skipped.
+    ///
+    /// If the exact row count of a file needed to reach `limit` is unknown,
+    /// no files are skipped.
+    pub fn split_groups_by_statistics_with_limit(
+        table_schema: &SchemaRef,
+        file_groups: &[FileGroup],
+        sort_order: &LexOrdering,
+        limit: usize,
+    ) -> Result<Vec<FileGroup>> {
+        let flattened_files = file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .collect::<Vec<_>>();
+
+        if flattened_files.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let statistics = MinMaxStatistics::new_from_files(
+            sort_order,
+            table_schema,
+            None,
+            flattened_files.iter().copied(),
+        )
+        .map_err(|e| {
+            e.context(
+                "construct min/max statistics for split_groups_by_statistics_with_limit",
+            )
+        })?;
+
+        let indices_sorted_by_min = statistics.min_values_sorted();
+
+        // Find the largest max value of the leading files covering `limit` rows
+        let mut num_rows = 0;
+        let mut covering_max = None;
+        for (idx, _) in &indices_sorted_by_min {
+            let Some(Precision::Exact(file_rows)) = flattened_files[*idx]
+                .statistics
+                .as_ref()
+                .map(|stats| stats.num_rows)
+            else {
+                break;
+            };
+            let max = statistics.max(*idx);
+            covering_max = match covering_max {
+                Some(covering_max) if covering_max >= max => Some(covering_max),
+                _ => Some(max),
+            };
+            num_rows += file_rows;
+            if num_rows >= limit {
+                break;
+            }
+        }
+
+        let needed_files = match covering_max {
+            Some(covering_max) if num_rows >= limit => indices_sorted_by_min
+                .iter()
+                .filter(|(_, min)| *min <= covering_max)
+                .map(|(idx, _)| flattened_files[*idx].clone())
+                .collect(),
+            _ => flattened_files.into_iter().cloned().collect(),
+        };
+
+        Self::split_groups_by_statistics(
+            table_schema,
+            &[FileGroup::new(needed_files)],
+            sort_order,
+        )
+    }
+
+    /// Splits files into at most `target_partitions` groups, such that the total
+    /// number of rows of every group is roughly the same.
+    ///
+    /// Files are assigned greedily, largest first, to the group with the
+    /// fewest rows so far, using the `num_rows` of each file's statistics. If
+    /// the row count of any file is unknown, file sizes are balanced instead.
+    ///
+    /// Balancing rows rather than bytes gives more even partitions for CPU
+    /// bound scans, as the size of a row may vary considerably between files.
+    pub fn split_groups_by_row_count(
+        file_groups: &[FileGroup],
+        target_partitions: usize,
+    ) -> Result<Vec<FileGroup>> {
+        if target_partitions == 0 {
+            return Err(DataFusionError::Internal(
+                "target_partitions must be greater than 0".to_string(),
+            ));
+        }
+
+        let flattened_files = file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .collect::<Vec<_>>();
+
+        let row_counts = flattened_files
+            .iter()
+            .map(|file| {
+                file.statistics
+                    .as_ref()
+                    .and_then(|stats| stats.num_rows.get_value().copied())
+                    .map(|num_rows| num_rows as u64)
+            })
+            .collect::<Option<Vec<_>>>();
+        let weights = row_counts.unwrap_or_else(|| {
+            flattened_files
+                .iter()
+                .map(|file| file.object_meta.size)
+                .collect()
+        });
+
+        // Largest files first, ties broken by path for deterministic plans
+        let mut indices = (0..flattened_files.len()).collect::<Vec<_>>();
+        indices.sort_by(|&a, &b| {
+            weights[b]
+                .cmp(&weights[a])
+                .then_with(|| flattened_files[a].path().cmp(flattened_files[b].path()))
+        });
+
+        let mut groups: Vec<(u64, Vec<PartitionedFile>)> =
+            vec![(0, vec![]); target_partitions.min(flattened_files.len())];
+        for idx in indices {
+            let (total, files) = groups
+                .iter_mut()
+                .min_by_key(|(total, _)| *total)
+                .expect("at least one group when there are files");
+            *total += weights[idx];
+            files.push(flattened_files[idx].clone());
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|(_, files)| FileGroup::new(files))
+            .collect())
+    }
+
+    /// Returns a copy of this config where adjacent file groups are merged
+    /// as long as their combined size is at most `min_group_bytes`, e.g. to
+    /// avoid scheduling many partitions of a single tiny file each after
+    /// splitting the files.
+    ///
+    /// If an output ordering is declared, two groups are only merged if the
+    /// min/max statistics of their files show that the merged group is still
+    /// ordered by the first output ordering. The order of the files is
+    /// preserved and the labels of the groups are cleared.
+    ///
+    /// The groups are not merged if they are tied to object stores, resume
+    /// cursors or a hash partitioning.
+    pub fn merge_small_groups(&self, min_group_bytes: u64) -> Self {
+        if self.has_pinned_file_groups() {
+            return self.clone();
+        }
+        let table_schema = self.table_schema();
+        let group_size = |files: &[PartitionedFile]| {
+            files.iter().map(|file| file.object_meta.size).sum::<u64>()
+        };
+        let is_ordered = |files: &[PartitionedFile]| match self.output_ordering.first() {
+            Some(ordering) => MinMaxStatistics::new_from_files(
+                ordering,
+                &table_schema,
+                None,
+                files.iter(),
+            )
+            .is_ok_and(|statistics| statistics.is_sorted()),
+            None => true,
+        };
+
+        let mut file_groups: Vec<Vec<PartitionedFile>> = vec![];
+        for group in &self.file_groups {
+            if let Some(last) = file_groups.last_mut() {
+                let mut merged = last.clone();
+                merged.extend(group.iter().cloned());
+                if group_size(&merged) <= min_group_bytes && is_ordered(&merged) {
+                    *last = merged;
+                    continue;
+                }
+            }
+            file_groups.push(group.files().to_vec());
+        }
+
+        let mut config = self.clone();
+        config.file_groups = file_groups.into_iter().map(FileGroup::new).collect();
+        config.group_labels.clear();
+        config
+    }
+
+    /// Returns how unevenly the bytes and rows to read are distributed across
+    /// the file groups, e.g. to decide whether to repartition the files.
+    ///
+    /// The bytes of a file are the size of its range if any, otherwise of
+    /// the whole file. The rows are the `num_rows` of the statistics of the
+    /// files, which are unknown if any file has a range. Without any bytes
+    /// or rows, the groups are considered balanced.
+    pub fn group_skew(&self) -> GroupSkew {
+        let group_bytes = self
+            .file_groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|file| match &file.range {
+                        Some(range) => (range.end - range.start).max(0) as f64,
+                        None => file.object_meta.size as f64,
+                    })
+                    .sum::<f64>()
+            })
+            .collect::<Vec<_>>();
+        let group_rows = self
+            .file_groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|file| {
+                        if file.range.is_some() {
+                            return None;
+                        }
+                        let statistics = file.statistics.as_ref()?;
+                        statistics.num_rows.get_value().map(|rows| *rows as f64)
+                    })
+                    .sum::<Option<f64>>()
+            })
+            .collect::<Option<Vec<_>>>();
+
+        GroupSkew {
+            max_over_mean_bytes: max_over_mean(&group_bytes),
+            max_over_mean_rows: group_rows.as_deref().map(max_over_mean),
+            gini: gini_coefficient(group_bytes),
+        }
+    }
+
+    /// Returns true if any file group contains the file at `location`.
+    ///
+    /// This scans all files, see [`Self::build_path_index`] to check many
+    /// locations.
+    pub fn contains_file(&self, location: &Path) -> bool {
+        self.file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .any(|file| file.path() == location)
+    }
+
+    /// Returns the locations of all files of the file groups, e.g. to check
+    /// whether the scan reads any of many changed files in constant time per
+    /// file, unlike [`Self::contains_file`].
+    pub fn build_path_index(&self) -> HashSet<Path> {
+        self.file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .map(|file| file.path().clone())
+            .collect()
+    }
+
+    /// Splits files into groups of roughly the same total size, each with at
+    /// most `max_files_per_group` files.
+    ///
+    /// Files are first assigned greedily, largest first, to at most
+    /// `target_partitions` groups with the smallest total size so far. Every
+    /// group with more than `max_files_per_group` files is then split into
+    /// as few groups as needed, dealing its files out largest first so the
+    /// new groups have similar sizes. The result may therefore have more than
+    /// `target_partitions` groups, e.g. for tables with many small files.
+    pub fn repartition_balanced(
+        file_groups: &[FileGroup],
+        target_partitions: usize,
+        max_files_per_group: usize,
+    ) -> Result<Vec<FileGroup>> {
+        if target_partitions == 0 {
+            return Err(DataFusionError::Internal(
+                "target_partitions must be greater than 0".to_string(),
+            ));
+        }
+        if max_files_per_group == 0 {
+            return Err(DataFusionError::Internal(
+                "max_files_per_group must be greater than 0".to_string(),
+            ));
+        }
+
+        // Largest files first, ties broken by path for deterministic plans
+        let mut files = file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| {
+            b.object_meta
+                .size
+                .cmp(&a.object_meta.size)
+                .then_with(|| a.path().cmp(b.path()))
+        });
+
+        let mut groups: Vec<(u64, Vec<&PartitionedFile>)> =
+            vec![(0, vec![]); target_partitions.min(files.len())];
+        for file in files {
+            let (total, files) = groups
+                .iter_mut()
+                .min_by_key(|(total, _)| *total)
+                .expect("at least one group when there are files");
+            *total += file.object_meta.size;
+            files.push(file);
+        }
+
+        Ok(groups
+            .into_iter()
+            .flat_map(|(_, files)| {
+                // the files of each group are still sorted by size
+                let num_groups = files.len().div_ceil(max_files_per_group);
+                let mut split = vec![vec![]; num_groups];
+                for (idx, file) in files.into_iter().enumerate() {
+                    split[idx % num_groups].push(file.clone());
+                }
+                split.into_iter().map(FileGroup::new)
+            })
+            .collect())
+    }
+
+    /// Returns a new [`DataSourceExec`] to scan the files specified by this config
+    #[deprecated(since = "47.0.0", note = "use DataSourceExec::new instead")]
+    pub fn build(self) -> Arc<DataSourceExec> {
+        DataSourceExec::from_data_source(self)
+    }
+
+    /// Write the object store URLs overriding the default for some partitions
+    fn fmt_partition_store_urls(&self, f: &mut Formatter) -> FmtResult {
+        if self.partition_store_urls.is_empty() {
+            return Ok(());
+        }
+        let urls = self
+            .partition_store_urls
+            .iter()
+            .map(|(partition, url)| format!("{partition}: {url}"))
+            .collect::<Vec<_>>();
+        write!(f, ", partition_store_urls=[{}]", urls.join(", "))
+    }
+
+    /// Write the number of files of each compression type, see
+    /// [`Self::compression_histogram`]. Only shown if any file is compressed.
+    fn fmt_compression_histogram(&self, f: &mut Formatter) -> FmtResult {
+        let histogram = self.compression_histogram();
+        if histogram.iter().all(|(name, _)| *name == "none") {
+            return Ok(());
+        }
+        let counts = histogram
+            .iter()
+            .map(|(name, count)| format!("{name}: {count}"))
+            .collect::<Vec<_>>();
+        write!(f, ", compression={{{}}}", counts.join(", "))
+    }
+
+    /// Write a summary of how evenly files are spread across the file groups,
+    /// which is hard to see from long lists of files. Only shown in verbose
+    /// mode and when there is more than one group.
+    fn fmt_file_group_balance(
+        &self,
+        t: DisplayFormatType,
+        f: &mut Formatter,
+    ) -> FmtResult {
+        if !matches!(t, DisplayFormatType::Verbose) || self.file_groups.len() < 2 {
+            return Ok(());
+        }
+        write!(
+            f,
+            ", file_group_balance={}",
+            FileGroupsBalanceDisplay(&self.file_groups)
+        )
+    }
+
+    /// Write the data_type based on file_source
+    fn fmt_file_source(&self, t: DisplayFormatType, f: &mut Formatter) -> FmtResult {
+        write!(f, ", file_type={}", self.file_source.file_type())?;
+        self.file_source.fmt_extra(t, f)
+    }
+
+    /// Returns the file_source
+    pub fn file_source(&self) -> &Arc<dyn FileSource> {
+        &self.file_source
+    }
+}
+
+impl Debug for FileScanConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "FileScanConfig {{")?;
+        write!(f, "object_store_url={:?}, ", self.object_store_url)?;
+
+        write!(
+            f,
+            "statistics={:?}, ",
+            self.file_source.statistics().unwrap()
+        )?;
+
+        DisplayAs::fmt_as(self, DisplayFormatType::Verbose, f)?;
+        write!(f, "}}")
+    }
+}
+
+impl DisplayAs for FileScanConfig {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut Formatter) -> FmtResult {
+        let schema = self.projected_schema();
+        let orderings = get_projected_output_ordering(self, &schema);
+
+        write!(f, "file_groups=")?;
+        FileGroupsDisplay(&self.file_groups, &self.group_labels).fmt_as(t, f)?;
+
+        if !schema.fields().is_empty() {
+            write!(f, ", projection={}", ProjectSchemaDisplay(&schema))?;
+        }
+
+        if let Some(limit) = self.limit {
+            write!(f, ", limit={limit}")?;
+        }
+
+        display_orderings(f, &orderings)?;
+
+        if !self.constraints.is_empty() {
+            write!(f, ", {}", self.constraints)?;
+        }
+
+        self.fmt_partition_store_urls(f)?;
+        self.fmt_compression_histogram(f)?;
+        self.fmt_file_group_balance(t, f)
+    }
+}
+
+/// A helper that projects partition columns into the file record batches.
+///
+/// One interesting trick is the usage of a cache for the key buffers of the partition column
+/// dictionaries. Indeed, the partition columns are constant, so the dictionaries that represent them
+/// have all their keys equal to 0. This enables us to re-use the same "all-zero" buffer across batches,
+/// which makes the space consumption of the partition columns O(batch_size) instead of O(record_count).
+pub struct PartitionColumnProjector {
+    /// An Arrow buffer initialized to zeros that represents the key array of all partition
+    /// columns (partition columns are materialized by dictionary arrays with only one
+    /// value in the dictionary, thus all the keys are equal to zero).
+    key_buffer_cache: ZeroBufferGenerators,
+    /// Mapping between the indexes in the list of partition columns and the target
+    /// schema. Sorted by index in the target schema so that we can iterate on it to
+    /// insert the partition columns in the target record batch.
+    projected_partition_indexes: Vec<(usize, usize)>,
+    /// The schema of the table once the projection was applied.
+    projected_schema: SchemaRef,
+}
+
+impl PartitionColumnProjector {
+    // Create a projector to insert the partitioning columns into batches read from files
+    // - `projected_schema`: the target schema with both file and partitioning columns
+    // - `table_partition_cols`: all the partitioning column names
+    pub fn new(projected_schema: SchemaRef, table_partition_cols: &[String]) -> Self {
+        let mut idx_map = HashMap::new();
+        for (partition_idx, partition_name) in table_partition_cols.iter().enumerate() {
+            if let Ok(schema_idx) = projected_schema.index_of(partition_name) {
+                idx_map.insert(partition_idx, schema_idx);
+            }
+        }
+
+        let mut projected_partition_indexes: Vec<_> = idx_map.into_iter().collect();
+        projected_partition_indexes.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+        Self {
+            projected_partition_indexes,
+            key_buffer_cache: Default::default(),
+            projected_schema,
+        }
+    }
+
+    // Transform the batch read from the file by inserting the partitioning columns
+    // to the right positions as deduced from `projected_schema`
+    // - `file_batch`: batch read from the file, with internal projection applied
+    // - `partition_values`: the list of partition values, one for each partition column
+    pub fn project(
+        &mut self,
+        file_batch: RecordBatch,
+        partition_values: &[ScalarValue],
+    ) -> Result<RecordBatch> {
+        let expected_cols =
+            self.projected_schema.fields().len() - self.projected_partition_indexes.len();
+
+        if file_batch.columns().len() != expected_cols {
+            return exec_err!(
+                "Unexpected batch schema from file, expected {} cols but got {}",
+                expected_cols,
+                file_batch.columns().len()
+            );
+        }
+
+        let mut cols = file_batch.columns().to_vec();
+        for &(pidx, sidx) in &self.projected_partition_indexes {
+            let p_value =
+                partition_values
+                    .get(pidx)
+                    .ok_or(DataFusionError::Execution(
+                        "Invalid partitioning found on disk".to_string(),
+                    ))?;
+
+            let mut partition_value = Cow::Borrowed(p_value);
+
+            // check if user forgot to dict-encode the partition value
+            let field = self.projected_schema.field(sidx);
+            let expected_data_type = field.data_type();
+            let actual_data_type = partition_value.data_type();
+            if let DataType::Dictionary(key_type, _) = expected_data_type {
+                if !matches!(actual_data_type, DataType::Dictionary(_, _)) {
+                    warn!("Partition value for column {} was not dictionary-encoded, applied auto-fix.", field.name());
+                    partition_value = Cow::Owned(ScalarValue::Dictionary(
+                        key_type.clone(),
+                        Box::new(partition_value.as_ref().clone()),
+                    ));
+                }
+            } else if let ScalarValue::Dictionary(_, value) = partition_value.as_ref() {
+                // decode the values of dictionary partition columns, see
+                // `PartitionEncoding::Decoded`
+                partition_value = Cow::Owned(value.as_ref().clone());
+            }
+
+            let array = match expected_data_type {
+                DataType::RunEndEncoded(run_ends, values) => create_run_end_array(
+                    partition_value.as_ref(),
+                    run_ends.data_type(),
+                    values.data_type(),
+                    file_batch.num_rows(),
+                )?,
+                _ => create_output_array(
+                    &mut self.key_buffer_cache,
+                    partition_value.as_ref(),
+                    file_batch.num_rows(),
+                )?,
+            };
+            cols.insert(sidx, array)
+        }
+
+        RecordBatch::try_new_with_options(
+            Arc::clone(&self.projected_schema),
+            cols,
+            &RecordBatchOptions::new().with_row_count(Some(file_batch.num_rows())),
+        )
+        .map_err(Into::into)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ZeroBufferGenerators {
+    gen_i8: ZeroBufferGenerator<i8>,
+    gen_i16: ZeroBufferGenerator<i16>,
+    gen_i32: ZeroBufferGenerator<i32>,
+    gen_i64: ZeroBufferGenerator<i64>,
+    gen_u8: ZeroBufferGenerator<u8>,
+    gen_u16: ZeroBufferGenerator<u16>,
+    gen_u32: ZeroBufferGenerator<u32>,
+    gen_u64: ZeroBufferGenerator<u64>,
+}
+
+/// Generate a arrow [`Buffer`] that contains zero values.
+#[derive(Debug, Default)]
+struct ZeroBufferGenerator<T>
+where
+    T: ArrowNativeType,
+{
+    cache: Option<Buffer>,
+    _t: PhantomData<T>,
+}
+
+impl<T> ZeroBufferGenerator<T>
+where
+    T: ArrowNativeType,
+{
+    const SIZE: usize = size_of::<T>();
+
+    fn get_buffer(&mut self, n_vals: usize) -> Buffer {
+        match &mut self.cache {
+            Some(buf) if buf.len() >= n_vals * Self::SIZE => {
+                buf.slice_with_length(0, n_vals * Self::SIZE)
+            }
+            _ => {
+                let mut key_buffer_builder = BufferBuilder::<T>::new(n_vals);
+                key_buffer_builder.advance(n_vals); // keys are all 0
+                self.cache.insert(key_buffer_builder.finish()).clone()
+            }
+        }
+    }
+}
+
+fn create_dict_array<T>(
+    buffer_gen: &mut ZeroBufferGenerator<T>,
+    dict_val: &ScalarValue,
+    len: usize,
+    data_type: DataType,
+) -> Result<ArrayRef>
+where
+    T: ArrowNativeType,
+{
+    let dict_vals = dict_val.to_array()?;
+
+    let sliced_key_buffer = buffer_gen.get_buffer(len);
+
+    // assemble pieces together
+    let mut builder = ArrayData::builder(data_type)
+        .len(len)
+        .add_buffer(sliced_key_buffer);
+    builder = builder.add_child_data(dict_vals.to_data());
+    Ok(Arc::new(DictionaryArray::<UInt16Type>::from(
+        builder.build().unwrap(),
+    )))
+}
+
+fn create_output_array(
+    key_buffer_cache: &mut ZeroBufferGenerators,
+    val: &ScalarValue,
+    len: usize,
+) -> Result<ArrayRef> {
+    if let ScalarValue::Dictionary(key_type, dict_val) = &val {
+        match key_type.as_ref() {
+            DataType::Int8 => {
+                return create_dict_array(
+                    &mut key_buffer_cache.gen_i8,
+                    dict_val,
+                    len,
+                    val.data_type(),
+                );
+            }
+            DataType::Int16 => {
+                return create_dict_array(
+                    &mut key_buffer_cache.gen_i16,
+                    dict_val,
+                    len,
+                    val.data_type(),
+                );
+            }
+            DataType::Int32 => {
+                return create_dict_array(
+                    &mut key_buffer_cache.gen_i32,
+                    dict_val,
+                    len,
+                    val.data_type(),
+                );
+            }
+            DataType::Int64 => {
+                return create_dict_array(
+                    &mut key_buffer_cache.gen_i64,
+                    dict_val,
+                    len,
+                    val.data_type(),
+                );
+            }
+            DataType::UInt8 => {
+                return create_dict_array(
+                    &mut key_buffer_cache.gen_u8,
+                    dict_val,
+                    len,
+                    val.data_type(),
+                );
+            }
+            DataType::UInt16 => {
+                return create_dict_array(
+                    &mut key_buffer_cache.gen_u16,
+                    dict_val,
+                    len,
+                    val.data_type(),
+                );
+            }
+            DataType::UInt32 => {
+                return create_dict_array(
+                    &mut key_buffer_cache.gen_u32,
+                    dict_val,
+                    len,
+                    val.data_type(),
+                );
+            }
+            DataType::UInt64 => {
+                return create_dict_array(
+                    &mut key_buffer_cache.gen_u64,
+                    dict_val,
+                    len,
+                    val.data_type(),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    val.to_array_of_size(len)
+}
+
+/// Creates a run-end encoded array with a single run of `len` times `val`
+fn create_run_end_array(
+    val: &ScalarValue,
+    run_ends_type: &DataType,
+    values_type: &DataType,
+    len: usize,
+) -> Result<ArrayRef> {
+    let val = match val {
+        ScalarValue::Dictionary(_, val) => val.as_ref(),
+        val => val,
+    };
+    let values = val.to_array_of_size(usize::from(len > 0))?;
+    let values = if values.data_type() == values_type {
+        values
+    } else {
+        cast(&values, values_type)?
+    };
+    match run_ends_type {
+        DataType::Int16 => create_run_array::<Int16Type>(len, &values),
+        DataType::Int32 => create_run_array::<Int32Type>(len, &values),
+        DataType::Int64 => create_run_array::<Int64Type>(len, &values),
+        _ => exec_err!("Invalid run ends type {run_ends_type} for a partition column"),
+    }
+}
+
+fn create_run_array<R: RunEndIndexType>(
+    len: usize,
+    values: &ArrayRef,
+) -> Result<ArrayRef> {
+    let Some(run_end) = R::Native::from_usize(len) else {
+        return exec_err!(
+            "Cannot encode {len} rows with run ends of type {}",
+            R::DATA_TYPE
+        );
+    };
+    let run_ends = PrimitiveArray::<R>::from_iter_values((len > 0).then_some(run_end));
+    Ok(Arc::new(RunArray::<R>::try_new(
+        &run_ends,
+        values.as_ref(),
+    )?))
+}
+
+/// Segment tree over the file groups built by
+/// [`FileScanConfig::split_groups_by_statistics_indexed`], finding the first
+/// group whose last file ends before a given value in O(log groups).
+///
+/// Each node holds the index of the file with the smallest max value among
+/// the last files of the groups below it, so the first group a file fits into
+/// is found by descending into the leftmost subtree with a smaller max value.
+struct FirstFitIndex<'a> {
+    statistics: &'a MinMaxStatistics,
+    /// Number of leaves, i.e. the maximum number of groups
+    size: usize,
+    nodes: Vec<Option<usize>>,
+}
+
+impl<'a> FirstFitIndex<'a> {
+    fn new(statistics: &'a MinMaxStatistics, max_groups: usize) -> Self {
+        let size = max_groups.next_power_of_two();
+        Self {
+            statistics,
+            size,
+            nodes: vec![None; 2 * size],
+        }
+    }
+
+    /// Returns whether a file with the `min` value fits after the file `node`
+    fn fits(&self, node: Option<usize>, min: &Row) -> bool {
+        node.is_some_and(|file| *min > self.statistics.max(file))
+    }
+
+    /// Returns the first group whose last file ends before `min`
+    fn first_fit(&self, min: &Row) -> Option<usize> {
+        if !self.fits(self.nodes[1], min) {
+            return None;
+        }
+        let mut node = 1;
+        while node < self.size {
+            node = if self.fits(self.nodes[2 * node], min) {
+                2 * node
+            } else {
+                2 * node + 1
+            };
+        }
+        Some(node - self.size)
+    }
+
+    /// Sets the last file of `group` to `file`
+    fn set_last_file(&mut self, group: usize, file: usize) {
+        let mut node = self.size + group;
+        self.nodes[node] = Some(file);
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = match (self.nodes[2 * node], self.nodes[2 * node + 1]) {
+                (Some(left), Some(right)) => {
+                    if self.statistics.max(right) < self.statistics.max(left) {
+                        Some(right)
+                    } else {
+                        Some(left)
+                    }
+                }
+                (left, right) => left.or(right),
+            };
+        }
+    }
+}
+
+/// Appends the values of the `derived` partition columns to the partition
+/// values of each file, or nulls if they can not be derived
+fn derive_partition_values(
+    file_groups: Vec<FileGroup>,
+    derived: &[(Field, DerivePartitionValue)],
+) -> Vec<FileGroup> {
+    file_groups
+        .into_iter()
+        .map(|group| {
+            let statistics = group.file_statistics(None).cloned();
+            let files = group
+                .into_inner()
+                .into_iter()
+                .map(|mut file| {
+                    for (field, derive) in derived {
+                        let value = derive(&file.partition_values)
+                            .and_then(|value| value.cast_to(field.data_type()))
+                            .unwrap_or_else(|e| {
+                                warn!(
+                                    "Failed to derive partition column {} of file {}: {e}",
+                                    field.name(),
+                                    file.path()
+                                );
+                                ScalarValue::try_from(field.data_type())
+                                    .unwrap_or(ScalarValue::Null)
+                            });
+                        file.partition_values.push(value);
+                    }
+                    file
+                })
+                .collect();
+            let group = FileGroup::new(files);
+            match statistics {
+                Some(statistics) => group.with_statistics(Arc::new(statistics)),
+                None => group,
+            }
+        })
+        .collect()
+}
+
+/// Casts the partition values of the timestamp columns of
+/// `table_partition_cols` to timestamps of `unit`, changing the type of the
+/// columns accordingly, or to nulls if they can not be cast
+fn normalize_partition_timestamps(
+    file_groups: Vec<FileGroup>,
+    table_partition_cols: &mut [FieldRef],
+    unit: TimeUnit,
+) -> Vec<FileGroup> {
+    let normalized = table_partition_cols
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(idx, field)| {
+            let DataType::Timestamp(_, tz) = field.data_type() else {
+                return None;
+            };
+            let data_type = DataType::Timestamp(unit, tz.clone());
+            *field = Arc::new(field.as_ref().clone().with_data_type(data_type));
+            Some((idx, Arc::clone(field)))
+        })
+        .collect::<Vec<_>>();
+    if normalized.is_empty() {
+        return file_groups;
+    }
+
+    file_groups
+        .into_iter()
+        .map(|group| {
+            let statistics = group.file_statistics(None).cloned();
+            let files = group
+                .into_inner()
+                .into_iter()
+                .map(|mut file| {
+                    for (idx, field) in &normalized {
+                        let Some(value) = file.partition_values.get(*idx) else {
+                            continue;
+                        };
+                        let value =
+                            value.cast_to(field.data_type()).unwrap_or_else(|e| {
+                                warn!(
+                                    "Failed to normalize partition column {} of file {}: {e}",
+                                    field.name(),
+                                    file.path()
+                                );
+                                ScalarValue::try_from(field.data_type())
+                                    .unwrap_or(ScalarValue::Null)
+                            });
+                        file.partition_values[*idx] = value;
+                    }
+                    file
+                })
+                .collect();
+            let group = FileGroup::new(files);
+            match statistics {
+                Some(statistics) => group.with_statistics(Arc::new(statistics)),
+                None => group,
+            }
+        })
+        .collect()
+}
+
+/// Restricts the range of every file in `file_groups` to its first `cap` bytes
+///
+/// Group statistics no longer describe the capped ranges, so they are dropped.
+fn cap_file_ranges(file_groups: Vec<FileGroup>, cap: u64) -> Vec<FileGroup> {
+    file_groups
+        .into_iter()
+        .map(|group| {
+            let files = group
+                .into_inner()
+                .into_iter()
+                .map(|mut file| {
+                    let cap = cap.min(file.object_meta.size) as i64;
+                    file.range = Some(match file.range.take() {
+                        Some(FileRange { start, end }) => FileRange {
+                            start: start.min(cap),
+                            end: end.min(cap),
+                        },
+                        None => FileRange { start: 0, end: cap },
+                    });
+                    file
+                })
+                .collect();
+            FileGroup::new(files)
+        })
+        .collect()
+}
+
+/// Returns the `(group index, file index)` of every file in `file_groups`
+/// that duplicates an earlier file, see [`DuplicateFilePolicy`]
+fn duplicate_files(file_groups: &[FileGroup]) -> Vec<(usize, usize)> {
+    let mut seen: HashMap<&Path, Vec<(i64, i64)>> = HashMap::new();
+    let mut duplicates = vec![];
+    for (group_idx, group) in file_groups.iter().enumerate() {
+        for (file_idx, file) in group.iter().enumerate() {
+            let file_ranges = file_byte_ranges(file);
+            let ranges = seen.entry(&file.object_meta.location).or_default();
+            let overlaps = file_ranges.iter().any(|range| {
+                ranges
+                    .iter()
+                    .any(|(start, end)| *start < range.1 && range.0 < *end)
+            });
+            if overlaps {
+                duplicates.push((group_idx, file_idx));
+            } else {
+                ranges.extend(file_ranges);
+            }
+        }
+    }
+    duplicates
+}
+
+/// Returns the byte ranges of `file` that are read, from its
+/// [`PartitionedFile::ranges`] or [`PartitionedFile::range`], or a
+/// single range covering the whole file
+fn file_byte_ranges(file: &PartitionedFile) -> Vec<(i64, i64)> {
+    if !file.ranges.is_empty() {
+        return file
+            .ranges
+            .iter()
+            .map(|range| (range.start, range.end))
+            .collect();
+    }
+    vec![file
+        .range
+        .as_ref()
+        .map_or((0, i64::MAX), |range| (range.start, range.end))]
+}
+
+/// Removes the files of `file_groups` that duplicate an earlier file, keeping
+/// the first occurrence of every file
+///
+/// Statistics of groups that lose files are dropped.
+fn remove_duplicate_files(file_groups: Vec<FileGroup>) -> Vec<FileGroup> {
+    let duplicates = duplicate_files(&file_groups);
+    if duplicates.is_empty() {
+        return file_groups;
+    }
+    file_groups
+        .into_iter()
+        .enumerate()
+        .map(|(group_idx, group)| {
+            if !duplicates.iter().any(|(idx, _)| *idx == group_idx) {
+                return group;
+            }
+            let files = group
+                .into_inner()
+                .into_iter()
+                .enumerate()
+                .filter(|(file_idx, _)| !duplicates.contains(&(group_idx, *file_idx)))
+                .map(|(_, file)| file)
+                .collect();
+            FileGroup::new(files)
+        })
+        .collect()
+}
+
+/// Removes the files `file_admission` rejects with [`FileAdmission::Reject`]
+/// from `file_groups`, counting them in the `files_rejected` metric of
+/// `metrics`
+fn remove_rejected_files(
+    file_groups: Vec<FileGroup>,
+    file_admission: &FileAdmissionPolicy,
+    metrics: &ExecutionPlanMetricsSet,
+) -> Vec<FileGroup> {
+    let files_rejected = MetricBuilder::new(metrics).global_counter("files_rejected");
+    file_groups
+        .into_iter()
+        .map(|group| {
+            let files = group
+                .into_inner()
+                .into_iter()
+                .filter(|file| {
+                    let rejected =
+                        file_admission(&file.object_meta) == FileAdmission::Reject;
+                    if rejected {
+                        files_rejected.add(1);
+                    }
+                    !rejected
+                })
+                .collect();
+            FileGroup::new(files)
+        })
+        .collect()
+}
+
+/// Statistics for a column holding `value` in each of `num_rows` rows, see
+/// [`FileScanConfig::constant_columns`]
+fn constant_column_statistics(
+    value: &ScalarValue,
+    num_rows: Precision<usize>,
+) -> ColumnStatistics {
+    if value.is_null() {
+        return ColumnStatistics::new_unknown()
+            .with_null_count(num_rows)
+            .with_distinct_count(Precision::Exact(0));
+    }
+    ColumnStatistics::new_unknown()
+        .with_null_count(Precision::Exact(0))
+        .with_min_value(Precision::Exact(value.clone()))
+        .with_max_value(Precision::Exact(value.clone()))
+        .with_distinct_count(Precision::Exact(1))
+}
+
+/// Returns the partition out of `num_partitions` that `value` is sent to by
+/// a [`RepartitionExec`] hash partitioning on it
+///
+/// [`RepartitionExec`]: datafusion_physical_plan::repartition::RepartitionExec
+fn hash_partition_index(value: &ScalarValue, num_partitions: usize) -> Result<usize> {
+    // Same fixed seeds as `RepartitionExec`
+    let random_state = RandomState::with_seeds(0, 0, 0, 0);
+    let mut hashes = vec![0; 1];
+    create_hashes(&[value.to_array()?], &random_state, &mut hashes)?;
+    Ok((hashes[0] % num_partitions as u64) as usize)
+}
+
+/// Returns the number of bytes of `file` that are read, those of its
+/// [`PartitionedFile::ranges`] or [`PartitionedFile::range`] if it
+/// has any
+pub(crate) fn file_read_size(file: &PartitionedFile) -> u64 {
+    if !file.ranges.is_empty() {
+        return file
+            .ranges
+            .iter()
+            .map(|range| (range.end - range.start) as u64)
+            .sum();
+    }
+    match &file.range {
+        Some(range) => (range.end - range.start) as u64,
+        None => file.object_meta.size,
+    }
+}
+
+/// Removes the files marked with [`PartitionedFile::with_tombstoned`] from
+/// `file_groups`
+fn remove_tombstoned_files(file_groups: Vec<FileGroup>) -> Vec<FileGroup> {
+    file_groups
+        .into_iter()
+        .map(|group| {
+            if !group.iter().any(|file| file.tombstoned) {
+                return group;
+            }
+            let files = group
+                .into_inner()
+                .into_iter()
+                .filter(|file| !file.tombstoned)
+                .collect();
+            FileGroup::new(files)
+        })
+        .collect()
+}
+
+/// Applies `file_preprocessor` to `file_groups`, checking that it returns as
+/// many groups as it was given
+fn preprocess_file_groups(
+    file_preprocessor: &FilePreprocessor,
+    file_groups: Vec<FileGroup>,
+) -> Result<Vec<FileGroup>> {
+    let num_groups = file_groups.len();
+    let file_groups = file_preprocessor(file_groups)?;
+    if file_groups.len() != num_groups {
+        return exec_err!(
+            "File preprocessor returned {} file groups, expected {num_groups}",
+            file_groups.len()
+        );
+    }
+    Ok(file_groups)
+}
+
+/// Sorts the files of each group by path, and the ranges of the same file by
+/// their start
+fn sort_files_by_path(file_groups: Vec<FileGroup>) -> Vec<FileGroup> {
+    file_groups
+        .into_iter()
+        .map(|group| {
+            let statistics = group.file_statistics(None).cloned();
+            let mut files = group.into_inner();
+            files.sort_by(|a, b| {
+                let start = |file: &PartitionedFile| file.range.as_ref().map(|r| r.start);
+                a.path().cmp(b.path()).then_with(|| start(a).cmp(&start(b)))
+            });
+            let group = FileGroup::new(files);
+            match statistics {
+                Some(statistics) => group.with_statistics(Arc::new(statistics)),
+                None => group,
+            }
+        })
+        .collect()
+}
+
+/// The various listing tables does not attempt to read all files
+/// concurrently, instead they will read files in sequence within a
+/// partition.  This is an important property as it allows plans to
+/// run against 1000s of files and not try to open them all
+/// concurrently.
+///
+/// However, it means if we assign more than one file to a partition
+/// the output sort order will not be preserved as illustrated in the
+/// following diagrams:
+///
+/// When only 1 file is assigned to each partition, each partition is
+/// correctly sorted on `(A, B, C)`
+///
+/// ```text
+///┏ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ┓
+///  ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─ ┐ ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─  ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─  ┌ ─ ─ ─ ─ ─ ─ ─ ─ ┐
+///┃   ┌───────────────┐     ┌──────────────┐ │   ┌──────────────┐ │   ┌─────────────┐   ┃
+///  │ │   1.parquet   │ │ │ │  2.parquet   │   │ │  3.parquet   │   │ │  4.parquet  │ │
+///┃   │ Sort: A, B, C │     │Sort: A, B, C │ │   │Sort: A, B, C │ │   │Sort: A, B, C│   ┃
+///  │ └───────────────┘ │ │ └──────────────┘   │ └──────────────┘   │ └─────────────┘ │
+///┃                                          │                    │                     ┃
+///  │                   │ │                    │                    │                 │
+///┃                                          │                    │                     ┃
+///  │                   │ │                    │                    │                 │
+///┃                                          │                    │                     ┃
+///  │                   │ │                    │                    │                 │
+///┃  ─ ─ ─ ─ ─ ─ ─ ─ ─ ─   ─ ─ ─ ─ ─ ─ ─ ─ ─ ┘  ─ ─ ─ ─ ─ ─ ─ ─ ─ ┘  ─ ─ ─ ─ ─ ─ ─ ─ ─  ┃
+///     DataFusion           DataFusion           DataFusion           DataFusion
+///┃    Partition 1          Partition 2          Partition 3          Partition 4       ┃
+/// ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━
+///
+///                                      DataSourceExec
+///```
+///
+/// However, when more than 1 file is assigned to each partition, each
+/// partition is NOT correctly sorted on `(A, B, C)`. Once the second
+/// file is scanned, the same values for A, B and C can be repeated in
+/// the same sorted stream
+///
+///```text
+///┏ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━
+///  ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─ ┐ ┌ ─ ─ ─ ─ ─ ─ ─ ─ ─  ┃
+///┃   ┌───────────────┐     ┌──────────────┐ │
+///  │ │   1.parquet   │ │ │ │  2.parquet   │   ┃
+///┃   │ Sort: A, B, C │     │Sort: A, B, C │ │
+///  │ └───────────────┘ │ │ └──────────────┘   ┃
+///┃   ┌───────────────┐     ┌──────────────┐ │
+///  │ │   3.parquet   │ │ │ │  4.parquet   │   ┃
+///┃   │ Sort: A, B, C │     │Sort: A, B, C │ │
+///  │ └───────────────┘ │ │ └──────────────┘   ┃
+///┃                                          │
+///  │                   │ │                    ┃
+///┃  ─ ─ ─ ─ ─ ─ ─ ─ ─ ─   ─ ─ ─ ─ ─ ─ ─ ─ ─ ┘
+///     DataFusion           DataFusion         ┃
+///┃    Partition 1          Partition 2
+/// ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ┛
+///
+///              DataSourceExec
+///```
+pub(crate) fn get_projected_output_ordering(
+    base_config: &FileScanConfig,
+    projected_schema: &SchemaRef,
+) -> Vec<LexOrdering> {
+    let mut all_orderings = vec![];
+    for output_ordering in &base_config.output_ordering {
+        let mut new_ordering = vec![];
+        for PhysicalSortExpr { expr, options } in output_ordering.iter() {
+            if let Some(col) = expr.as_any().downcast_ref::<Column>() {
+                let name = base_config.output_name(col.name());
+                if let Some((idx, _)) = projected_schema.column_with_name(name) {
+                    // Compute the new sort expression (with correct index) after projection:
+                    new_ordering.push(PhysicalSortExpr::new(
+                        Arc::new(Column::new(name, idx)),
+                        *options,
+                    ));
+                    continue;
+                }
+            }
+            // Cannot find expression in the projected_schema, stop iterating
+            // since rest of the orderings are violated
+            break;
+        }
+
+        let Some(new_ordering) = LexOrdering::new(new_ordering) else {
+            continue;
+        };
+
+        // Check if any file groups are not sorted, unless the files are
+        // merged on read
+        if !base_config.sort_merge_on_read
+            && base_config.file_groups.iter().any(|group| {
+                if group.len() <= 1 {
+                    // File groups with <= 1 files are always sorted
+                    return false;
+                }
+
+                let statistics = match MinMaxStatistics::new_from_files(
+                    &new_ordering,
+                    projected_schema,
+                    base_config.projection.as_deref(),
+                    group.iter(),
+                ) {
+                    Ok(statistics) => statistics,
+                    Err(e) => {
+                        log::trace!("Error fetching statistics for file group: {e}");
+                        // we can't prove that it's ordered, so we have to reject it
+                        return true;
+                    }
+                };
+
+                !statistics.is_sorted()
+            })
+        {
+            debug!(
+                "Skipping specified output ordering {:?}. \
+                Some file groups couldn't be determined to be sorted: {:?}",
+                base_config.output_ordering[0], base_config.file_groups
+            );
+            continue;
+        }
+
+        if base_config.reverse_scan {
+            all_orderings.push(reverse_ordering(&new_ordering));
+        } else {
+            all_orderings.push(new_ordering);
+        }
+    }
+    all_orderings
+}
+
+/// Returns the largest of `values` divided by their mean, or 1.0 if their
+/// sum is zero
+fn max_over_mean(values: &[f64]) -> f64 {
+    let total = values.iter().sum::<f64>();
+    if total <= 0.0 {
+        return 1.0;
+    }
+    let max = values.iter().copied().fold(0.0, f64::max);
+    max / (total / values.len() as f64)
+}
+
+/// Returns the Gini coefficient of `values`, or 0.0 if their sum is zero
+fn gini_coefficient(mut values: Vec<f64>) -> f64 {
+    let total = values.iter().sum::<f64>();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let n = values.len() as f64;
+    let weighted = values
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| (idx + 1) as f64 * value)
+        .sum::<f64>();
+    2.0 * weighted / (n * total) - (n + 1.0) / n
+}
+
+/// Estimated memory size of a value of a variable width type, such as a
+/// string, see [`FileScanConfigBuilder::with_memory_reservation`]
+pub const VARIABLE_WIDTH_VALUE_BYTES: usize = 32;
+
+/// Returns `schema` with every top level field nullable
+fn nullable_schema(schema: &SchemaRef) -> SchemaRef {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| Arc::new(field.as_ref().clone().with_nullable(true)) as FieldRef)
+        .collect::<Fields>();
+    Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+/// Returns `statistics` with the statistics of the columns not in
+/// `stats_columns` unknown, or unchanged if `stats_columns` is `None`
+fn retain_column_statistics(
+    mut statistics: Statistics,
+    stats_columns: Option<&[usize]>,
+) -> Statistics {
+    if let Some(stats_columns) = stats_columns {
+        for (idx, column_statistics) in
+            statistics.column_statistics.iter_mut().enumerate()
+        {
+            if !stats_columns.contains(&idx) {
+                *column_statistics = ColumnStatistics::new_unknown();
+            }
+        }
+    }
+    statistics
+}
+
+/// Reverses every sort expression of `ordering`, e.g. `[a ASC, b DESC]` turns
+/// into `[a DESC, b ASC]`
+fn reverse_ordering(ordering: &LexOrdering) -> LexOrdering {
+    LexOrdering::new(ordering.iter().map(PhysicalSortExpr::reverse))
+        .expect("reversed ordering is not empty")
+}
+
+/// Convert type to a type suitable for use as a `ListingTable`
+/// partition column. Returns `Dictionary(UInt16, val_type)`, which is
+/// a reasonable trade off between a reasonable number of partition
+/// values and space efficiency.
+///
+/// This use this to specify types for partition columns. However
+/// you MAY also choose not to dictionary-encode the data or to use a
+/// different dictionary type.
+///
+/// Use [`wrap_partition_value_in_dict`] to wrap a [`ScalarValue`] in the same say.
+pub fn wrap_partition_type_in_dict(val_type: DataType) -> DataType {
+    DataType::Dictionary(Box::new(DataType::UInt16), Box::new(val_type))
+}
+
+/// Convert a [`ScalarValue`] of partition columns to a type, as
+/// described in the documentation of [`wrap_partition_type_in_dict`],
+/// which can wrap the types.
+pub fn wrap_partition_value_in_dict(val: ScalarValue) -> ScalarValue {
+    ScalarValue::Dictionary(Box::new(DataType::UInt16), Box::new(val))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generate_test_files, test_util::MockSource, tests::aggr_test_schema,
+        verify_sort_integrity, SplitGroupsError,
+    };
+
+    use arrow::array::{Int32Array, RecordBatch};
+    use datafusion_common::{
+        assert_batches_eq, assert_contains, internal_err, Constraint,
+    };
+    use datafusion_expr::SortExpr;
+    use datafusion_physical_expr::create_physical_sort_expr;
+    use datafusion_physical_plan::displayable;
+
+    /// Returns a builder over [`aggr_test_schema`] reading from `test:///`
+    fn test_builder() -> FileScanConfigBuilder {
+        FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            aggr_test_schema(),
+            Arc::new(MockSource::default()),
+        )
+    }
+
+    /// Returns the paths of the files in each group of `config`
+    fn paths(config: &FileScanConfig) -> Vec<Vec<String>> {
+        config
+            .file_groups
+            .iter()
+            .map(|group| group.iter().map(|f| f.path().to_string()).collect())
+            .collect()
+    }
+
+    /// Returns the column names on the schema
+    pub fn columns(schema: &Schema) -> Vec<String> {
+        schema.fields().iter().map(|f| f.name().clone()).collect()
+    }
+
+    #[test]
+    fn physical_plan_config_no_projection() {
+        let file_schema = aggr_test_schema();
+        let conf = config_for_projection(
+            Arc::clone(&file_schema),
+            None,
+            Statistics::new_unknown(&file_schema),
+            to_partition_cols(vec![(
+                "date".to_owned(),
+                wrap_partition_type_in_dict(DataType::Utf8),
+            )]),
+        );
+
+        let (proj_schema, _, proj_statistics, _) = conf.project();
+        assert_eq!(proj_schema.fields().len(), file_schema.fields().len() + 1);
+        assert_eq!(
+            proj_schema.field(file_schema.fields().len()).name(),
+            "date",
+            "partition columns are the last columns"
+        );
+        assert_eq!(
+            proj_statistics.column_statistics.len(),
+            file_schema.fields().len() + 1
+        );
+        // TODO implement tests for partition column statistics once implemented
+
+        let col_names = conf.projected_file_column_names();
+        assert_eq!(col_names, None);
+
+        let col_indices = conf.file_column_projection_indices();
+        assert_eq!(col_indices, None);
+    }
+
+    #[test]
+    fn physical_plan_config_no_projection_tab_cols_as_field() {
+        let file_schema = aggr_test_schema();
+
+        // make a table_partition_col as a field
+        let table_partition_col =
+            Field::new("date", wrap_partition_type_in_dict(DataType::Utf8), true)
+                .with_metadata(HashMap::from_iter(vec![(
+                    "key_whatever".to_owned(),
+                    "value_whatever".to_owned(),
+                )]));
+
+        let conf = config_for_projection(
+            Arc::clone(&file_schema),
+            None,
+            Statistics::new_unknown(&file_schema),
+            vec![table_partition_col.clone()],
+        );
+
+        // verify the proj_schema includes the last column and exactly the same the field it is defined
+        let proj_schema = conf.projected_schema();
+        assert_eq!(proj_schema.fields().len(), file_schema.fields().len() + 1);
+        assert_eq!(
+            *proj_schema.field(file_schema.fields().len()),
+            table_partition_col,
+            "partition columns are the last columns and ust have all values defined in created field"
+        );
+    }
+
+    #[test]
+    fn physical_plan_config_with_projection() {
+        let file_schema = aggr_test_schema();
+        let conf = config_for_projection(
+            Arc::clone(&file_schema),
+            Some(vec![file_schema.fields().len(), 0]),
+            Statistics {
+                num_rows: Precision::Inexact(10),
+                // assign the column index to distinct_count to help assert
+                // the source statistic after the projection
+                column_statistics: (0..file_schema.fields().len())
+                    .map(|i| ColumnStatistics {
+                        distinct_count: Precision::Inexact(i),
+                        ..Default::default()
+                    })
+                    .collect(),
+                total_byte_size: Precision::Absent,
+            },
+            to_partition_cols(vec![(
+                "date".to_owned(),
+                wrap_partition_type_in_dict(DataType::Utf8),
+            )]),
+        );
+
+        let (proj_schema, _, proj_statistics, _) = conf.project();
+        assert_eq!(
+            columns(&proj_schema),
+            vec!["date".to_owned(), "c1".to_owned()]
+        );
+        let proj_stat_cols = proj_statistics.column_statistics;
+        assert_eq!(proj_stat_cols.len(), 2);
+        // no files, so nothing is known about the partition column
+        assert_eq!(proj_stat_cols[0].distinct_count, Precision::Absent);
+        assert_eq!(proj_stat_cols[1].distinct_count, Precision::Inexact(0));
+
+        let col_names = conf.projected_file_column_names();
+        assert_eq!(col_names, Some(vec!["c1".to_owned()]));
+
+        let col_indices = conf.file_column_projection_indices();
+        assert_eq!(col_indices, Some(vec![0]));
+    }
+
+    #[test]
+    fn physical_plan_config_partition_column_distinct_count() {
+        let file_schema = aggr_test_schema();
+        let partitioned_file = |name: &str, date: &str| {
+            let mut file = PartitionedFile::new(name, 10);
+            file.partition_values =
+                vec![wrap_partition_value_in_dict(ScalarValue::from(date))];
+            file
+        };
+        let partition_col_stats = |file_groups: Vec<FileGroup>| {
+            let conf = FileScanConfigBuilder::from(config_for_projection(
+                Arc::clone(&file_schema),
+                Some(vec![file_schema.fields().len()]),
+                Statistics::new_unknown(&file_schema),
+                to_partition_cols(vec![(
+                    "date".to_owned(),
+                    wrap_partition_type_in_dict(DataType::Utf8),
+                )]),
+            ))
+            .with_file_groups(file_groups)
+            .build();
+            conf.projected_stats().column_statistics[0].clone()
+        };
+
+        // a single partition value across all files
+        let stats = partition_col_stats(vec![
+            FileGroup::new(vec![partitioned_file("a", "2021-10-26")]),
+            FileGroup::new(vec![partitioned_file("b", "2021-10-26")]),
+        ]);
+        assert_eq!(stats.distinct_count, Precision::Exact(1));
+
+        // three partition values spread across the files
+        let stats = partition_col_stats(vec![
+            FileGroup::new(vec![
+                partitioned_file("a", "2021-10-26"),
+                partitioned_file("b", "2021-10-27"),
+            ]),
+            FileGroup::new(vec![
+                partitioned_file("c", "2021-10-27"),
+                partitioned_file("d", "2021-10-28"),
+            ]),
+        ]);
+        assert_eq!(stats.distinct_count, Precision::Exact(3));
+
+        // a file without partition values makes the count unknown
+        let stats = partition_col_stats(vec![FileGroup::new(vec![
+            partitioned_file("a", "2021-10-26"),
+            PartitionedFile::new("b", 10),
+        ])]);
+        assert_eq!(stats.distinct_count, Precision::Absent);
+    }
+
+    #[test]
+    fn partition_column_projector() {
+        let file_batch = build_table_i32(
+            ("a", &vec![0, 1, 2]),
+            ("b", &vec![-2, -1, 0]),
+            ("c", &vec![10, 11, 12]),
+        );
+        let partition_cols = vec![
+            (
+                "year".to_owned(),
+                wrap_partition_type_in_dict(DataType::Utf8),
+            ),
+            (
+                "month".to_owned(),
+                wrap_partition_type_in_dict(DataType::Utf8),
+            ),
+            (
+                "day".to_owned(),
+                wrap_partition_type_in_dict(DataType::Utf8),
+            ),
+        ];
+        // create a projected schema
+        let statistics = Statistics {
+            num_rows: Precision::Inexact(3),
+            total_byte_size: Precision::Absent,
+            column_statistics: Statistics::unknown_column(&file_batch.schema()),
+        };
+
+        let conf = config_for_projection(
+            file_batch.schema(),
+            // keep all cols from file and 2 from partitioning
+            Some(vec![
+                0,
+                1,
+                2,
+                file_batch.schema().fields().len(),
+                file_batch.schema().fields().len() + 2,
+            ]),
+            statistics.clone(),
+            to_partition_cols(partition_cols.clone()),
+        );
+
+        let source_statistics = conf.file_source.statistics().unwrap();
+        let conf_stats = conf.statistics().unwrap();
+
+        // projection should be reflected in the file source statistics
+        assert_eq!(conf_stats.num_rows, Precision::Inexact(3));
+
+        // 3 original statistics + 2 partition statistics
+        assert_eq!(conf_stats.column_statistics.len(), 5);
+
+        // file statics should not be modified
+        assert_eq!(source_statistics, statistics);
+        assert_eq!(source_statistics.column_statistics.len(), 3);
+
+        let proj_schema = conf.projected_schema();
+        // created a projector for that projected schema
+        let mut proj = PartitionColumnProjector::new(
+            proj_schema,
+            &partition_cols
+                .iter()
+                .map(|x| x.0.clone())
+                .collect::<Vec<_>>(),
+        );
+
+        // project first batch
+        let projected_batch = proj
+            .project(
+                // file_batch is ok here because we kept all the file cols in the projection
+                file_batch,
+                &[
+                    wrap_partition_value_in_dict(ScalarValue::from("2021")),
+                    wrap_partition_value_in_dict(ScalarValue::from("10")),
+                    wrap_partition_value_in_dict(ScalarValue::from("26")),
+                ],
+            )
+            .expect("Projection of partition columns into record batch failed");
+        let expected = [
+            "+---+----+----+------+-----+",
+            "| a | b  | c  | year | day |",
+            "+---+----+----+------+-----+",
+            "| 0 | -2 | 10 | 2021 | 26  |",
+            "| 1 | -1 | 11 | 2021 | 26  |",
+            "| 2 | 0  | 12 | 2021 | 26  |",
+            "+---+----+----+------+-----+",
+        ];
+        assert_batches_eq!(expected, &[projected_batch]);
+
+        // project another batch that is larger than the previous one
+        let file_batch = build_table_i32(
+            ("a", &vec![5, 6, 7, 8, 9]),
+            ("b", &vec![-10, -9, -8, -7, -6]),
+            ("c", &vec![12, 13, 14, 15, 16]),
+        );
+        let projected_batch = proj
+            .project(
+                // file_batch is ok here because we kept all the file cols in the projection
+                file_batch,
+                &[
+                    wrap_partition_value_in_dict(ScalarValue::from("2021")),
+                    wrap_partition_value_in_dict(ScalarValue::from("10")),
+                    wrap_partition_value_in_dict(ScalarValue::from("27")),
+                ],
+            )
+            .expect("Projection of partition columns into record batch failed");
+        let expected = [
+            "+---+-----+----+------+-----+",
+            "| a | b   | c  | year | day |",
+            "+---+-----+----+------+-----+",
+            "| 5 | -10 | 12 | 2021 | 27  |",
+            "| 6 | -9  | 13 | 2021 | 27  |",
+            "| 7 | -8  | 14 | 2021 | 27  |",
+            "| 8 | -7  | 15 | 2021 | 27  |",
+            "| 9 | -6  | 16 | 2021 | 27  |",
+            "+---+-----+----+------+-----+",
+        ];
+        assert_batches_eq!(expected, &[projected_batch]);
+
+        // project another batch that is smaller than the previous one
+        let file_batch = build_table_i32(
+            ("a", &vec![0, 1, 3]),
+            ("b", &vec![2, 3, 4]),
+            ("c", &vec![4, 5, 6]),
+        );
+        let projected_batch = proj
+            .project(
+                // file_batch is ok here because we kept all the file cols in the projection
+                file_batch,
+                &[
+                    wrap_partition_value_in_dict(ScalarValue::from("2021")),
+                    wrap_partition_value_in_dict(ScalarValue::from("10")),
+                    wrap_partition_value_in_dict(ScalarValue::from("28")),
+                ],
+            )
+            .expect("Projection of partition columns into record batch failed");
+        let expected = [
+            "+---+---+---+------+-----+",
+            "| a | b | c | year | day |",
+            "+---+---+---+------+-----+",
+            "| 0 | 2 | 4 | 2021 | 28  |",
+            "| 1 | 3 | 5 | 2021 | 28  |",
+            "| 3 | 4 | 6 | 2021 | 28  |",
+            "+---+---+---+------+-----+",
+        ];
+        assert_batches_eq!(expected, &[projected_batch]);
+
+        // forgot to dictionary-wrap the scalar value
+        let file_batch = build_table_i32(
+            ("a", &vec![0, 1, 2]),
+            ("b", &vec![-2, -1, 0]),
+            ("c", &vec![10, 11, 12]),
+        );
+        let projected_batch = proj
+            .project(
+                // file_batch is ok here because we kept all the file cols in the projection
+                file_batch,
+                &[
+                    ScalarValue::from("2021"),
+                    ScalarValue::from("10"),
+                    ScalarValue::from("26"),
+                ],
+            )
+            .expect("Projection of partition columns into record batch failed");
+        let expected = [
+            "+---+----+----+------+-----+",
+            "| a | b  | c  | year | day |",
+            "+---+----+----+------+-----+",
+            "| 0 | -2 | 10 | 2021 | 26  |",
+            "| 1 | -1 | 11 | 2021 | 26  |",
+            "| 2 | 0  | 12 | 2021 | 26  |",
+            "+---+----+----+------+-----+",
+        ];
+        assert_batches_eq!(expected, &[projected_batch]);
+    }
+
+    #[test]
+    fn test_projected_file_schema_with_partition_col() {
+        let schema = aggr_test_schema();
+        let partition_cols = vec![
+            (
+                "part1".to_owned(),
+                wrap_partition_type_in_dict(DataType::Utf8),
+            ),
+            (
+                "part2".to_owned(),
+                wrap_partition_type_in_dict(DataType::Utf8),
+            ),
+        ];
+
+        // Projected file schema for config with projection including partition column
+        let projection = config_for_projection(
+            schema.clone(),
+            Some(vec![0, 3, 5, schema.fields().len()]),
+            Statistics::new_unknown(&schema),
+            to_partition_cols(partition_cols),
+        )
+        .projected_file_schema();
+
+        // Assert partition column filtered out in projected file schema
+        let expected_columns = vec!["c1", "c4", "c6"];
+        let actual_columns = projection
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(expected_columns, actual_columns);
+    }
+
+    #[test]
+    fn test_projected_file_schema_without_projection() {
+        let schema = aggr_test_schema();
+        let partition_cols = vec![
+            (
+                "part1".to_owned(),
+                wrap_partition_type_in_dict(DataType::Utf8),
+            ),
+            (
+                "part2".to_owned(),
+                wrap_partition_type_in_dict(DataType::Utf8),
+            ),
+        ];
+
+        // Projected file schema for config without projection
+        let projection = config_for_projection(
+            schema.clone(),
+            None,
+            Statistics::new_unknown(&schema),
+            to_partition_cols(partition_cols),
+        )
+        .projected_file_schema();
+
+        // Assert projected file schema is equal to file schema
+        assert_eq!(projection.fields(), schema.fields());
+    }
+
+    #[test]
+    fn test_table_projection_to_file_projection() {
+        let schema = aggr_test_schema();
+        let n_file_cols = schema.fields().len();
+        let config = config_for_projection(
+            Arc::clone(&schema),
+            None,
+            Statistics::new_unknown(&schema),
+            to_partition_cols(vec![
+                ("part1".to_owned(), DataType::Utf8),
+                ("part2".to_owned(), DataType::Utf8),
+            ]),
+        );
+
+        // partition columns interleaved with file columns are dropped, the
+        // file columns keep the projection order
+        assert_eq!(
+            config.table_projection_to_file_projection(&[
+                n_file_cols + 1,
+                4,
+                n_file_cols,
+                0,
+                7
+            ]),
+            vec![4, 0, 7]
+        );
+        assert_eq!(
+            config.table_projection_to_file_projection(&[2, n_file_cols, 1]),
+            vec![2, 1]
+        );
+        // only partition columns
+        assert!(config
+            .table_projection_to_file_projection(&[n_file_cols + 1, n_file_cols])
+            .is_empty());
+        assert!(config.table_projection_to_file_projection(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_split_groups_by_statistics() -> Result<()> {
+        use chrono::TimeZone;
+        use datafusion_common::DFSchema;
+        use datafusion_expr::execution_props::ExecutionProps;
+        use object_store::{path::Path, ObjectMeta};
+
+        struct File {
+            name: &'static str,
+            date: &'static str,
+            statistics: Vec<Option<(f64, f64)>>,
+        }
+        impl File {
+            fn new(
+                name: &'static str,
+                date: &'static str,
+                statistics: Vec<Option<(f64, f64)>>,
+            ) -> Self {
+                Self {
+                    name,
+                    date,
+                    statistics,
+                }
+            }
+        }
+
+        struct TestCase {
+            name: &'static str,
+            file_schema: Schema,
+            files: Vec<File>,
+            sort: Vec<SortExpr>,
+            expected_result: Result<Vec<Vec<&'static str>>, &'static str>,
+        }
+
+        use datafusion_expr::col;
+        let cases = vec![
+            TestCase {
+                name: "test sort",
+                file_schema: Schema::new(vec![Field::new(
+                    "value".to_string(),
+                    DataType::Float64,
+                    false,
+                )]),
+                files: vec![
+                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
+                    File::new("1", "2023-01-01", vec![Some((0.50, 1.00))]),
+                    File::new("2", "2023-01-02", vec![Some((0.00, 1.00))]),
+                ],
+                sort: vec![col("value").sort(true, false)],
+                expected_result: Ok(vec![vec!["0", "1"], vec!["2"]]),
+            },
+            // same input but file '2' is in the middle
+            // test that we still order correctly
+            TestCase {
+                name: "test sort with files ordered differently",
+                file_schema: Schema::new(vec![Field::new(
+                    "value".to_string(),
+                    DataType::Float64,
+                    false,
+                )]),
+                files: vec![
+                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
+                    File::new("2", "2023-01-02", vec![Some((0.00, 1.00))]),
+                    File::new("1", "2023-01-01", vec![Some((0.50, 1.00))]),
+                ],
+                sort: vec![col("value").sort(true, false)],
+                expected_result: Ok(vec![vec!["0", "1"], vec!["2"]]),
+            },
+            TestCase {
+                name: "reverse sort",
+                file_schema: Schema::new(vec![Field::new(
+                    "value".to_string(),
+                    DataType::Float64,
+                    false,
+                )]),
+                files: vec![
+                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
+                    File::new("1", "2023-01-01", vec![Some((0.50, 1.00))]),
+                    File::new("2", "2023-01-02", vec![Some((0.00, 1.00))]),
+                ],
+                sort: vec![col("value").sort(false, true)],
+                expected_result: Ok(vec![vec!["1", "0"], vec!["2"]]),
+            },
+            // reject nullable sort columns
+            TestCase {
+                name: "no nullable sort columns",
+                file_schema: Schema::new(vec![Field::new(
+                    "value".to_string(),
+                    DataType::Float64,
+                    true, // should fail because nullable
+                )]),
+                files: vec![
+                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
+                    File::new("1", "2023-01-01", vec![Some((0.50, 1.00))]),
+                    File::new("2", "2023-01-02", vec![Some((0.00, 1.00))]),
+                ],
+                sort: vec![col("value").sort(true, false)],
+                expected_result: Err("construct min/max statistics for split_groups_by_statistics\ncaused by\nbuild min rows\ncaused by\ncreate sorting columns\ncaused by\nError during planning: cannot sort by nullable column")
+            },
+            TestCase {
+                name: "all three non-overlapping",
+                file_schema: Schema::new(vec![Field::new(
+                    "value".to_string(),
+                    DataType::Float64,
+                    false,
+                )]),
+                files: vec![
+                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
+                    File::new("1", "2023-01-01", vec![Some((0.50, 0.99))]),
+                    File::new("2", "2023-01-02", vec![Some((1.00, 1.49))]),
+                ],
+                sort: vec![col("value").sort(true, false)],
+                expected_result: Ok(vec![vec!["0", "1", "2"]]),
+            },
+            TestCase {
+                name: "all three overlapping",
+                file_schema: Schema::new(vec![Field::new(
+                    "value".to_string(),
+                    DataType::Float64,
+                    false,
+                )]),
+                files: vec![
+                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
+                    File::new("1", "2023-01-01", vec![Some((0.00, 0.49))]),
+                    File::new("2", "2023-01-02", vec![Some((0.00, 0.49))]),
+                ],
+                sort: vec![col("value").sort(true, false)],
+                expected_result: Ok(vec![vec!["0"], vec!["1"], vec!["2"]]),
+            },
+            TestCase {
+                name: "empty input",
+                file_schema: Schema::new(vec![Field::new(
+                    "value".to_string(),
+                    DataType::Float64,
+                    false,
+                )]),
+                files: vec![],
+                sort: vec![col("value").sort(true, false)],
+                expected_result: Ok(vec![]),
+            },
+            TestCase {
+                name: "one file missing statistics",
+                file_schema: Schema::new(vec![Field::new(
+                    "value".to_string(),
+                    DataType::Float64,
+                    false,
+                )]),
+                files: vec![
+                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
+                    File::new("1", "2023-01-01", vec![Some((0.00, 0.49))]),
+                    File::new("2", "2023-01-02", vec![None]),
+                ],
+                sort: vec![col("value").sort(true, false)],
+                expected_result: Err("construct min/max statistics for split_groups_by_statistics\ncaused by\ncollect min/max values\ncaused by\nget min/max for column: 'value'\ncaused by\nError during planning: statistics not found"),
+            },
+        ];
+
+        for case in cases {
+            let table_schema = Arc::new(Schema::new(
+                case.file_schema
+                    .fields()
+                    .clone()
+                    .into_iter()
+                    .cloned()
+                    .chain(Some(Arc::new(Field::new(
+                        "date".to_string(),
+                        DataType::Utf8,
+                        false,
+                    ))))
+                    .collect::<Vec<_>>(),
+            ));
+            let Some(sort_order) = LexOrdering::new(
+                case.sort
+                    .into_iter()
+                    .map(|expr| {
+                        create_physical_sort_expr(
+                            &expr,
+                            &DFSchema::try_from(table_schema.as_ref().clone())?,
+                            &ExecutionProps::default(),
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            ) else {
+                return internal_err!("This test should always use an ordering");
+            };
+
+            let partitioned_files = FileGroup::new(
+                case.files.into_iter().map(From::from).collect::<Vec<_>>(),
+            );
+            let result = FileScanConfig::split_groups_by_statistics(
+                &table_schema,
+                &[partitioned_files.clone()],
+                &sort_order,
+            );
+            let results_by_name = result
+                .as_ref()
+                .map(|file_groups| {
+                    file_groups
+                        .iter()
+                        .map(|file_group| {
+                            file_group
+                                .iter()
+                                .map(|file| {
+                                    partitioned_files
+                                        .iter()
+                                        .find_map(|f| {
+                                            if f.object_meta == file.object_meta {
+                                                Some(
+                                                    f.object_meta
+                                                        .location
+                                                        .as_ref()
+                                                        .rsplit('/')
+                                                        .next()
+                                                        .unwrap()
+                                                        .trim_end_matches(".parquet"),
+                                                )
+                                            } else {
+                                                None
+                                            }
+                                        })
+                                        .unwrap()
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .map_err(|e| e.strip_backtrace().leak() as &'static str);
+
+            assert_eq!(results_by_name, case.expected_result, "{}", case.name);
+        }
+
+        return Ok(());
+
+        impl From<File> for PartitionedFile {
+            fn from(file: File) -> Self {
+                PartitionedFile {
+                    object_meta: ObjectMeta {
+                        location: Path::from(format!(
+                            "data/date={}/{}.parquet",
+                            file.date, file.name
+                        )),
+                        last_modified: chrono::Utc.timestamp_nanos(0),
+                        size: 0,
+                        e_tag: None,
+                        version: None,
+                    },
+                    partition_values: vec![ScalarValue::from(file.date)],
+                    range: None,
+                    statistics: Some(Arc::new(Statistics {
+                        num_rows: Precision::Absent,
+                        total_byte_size: Precision::Absent,
+                        column_statistics: file
+                            .statistics
+                            .into_iter()
+                            .map(|stats| {
+                                stats
+                                    .map(|(min, max)| ColumnStatistics {
+                                        min_value: Precision::Exact(ScalarValue::from(
+                                            min,
+                                        )),
+                                        max_value: Precision::Exact(ScalarValue::from(
+                                            max,
+                                        )),
+                                        ..Default::default()
+                                    })
+                                    .unwrap_or_default()
+                            })
+                            .collect::<Vec<_>>(),
+                    })),
+                    extensions: None,
+                    metadata_size_hint: None,
+                    object_store_url: None,
+                    sub_files: vec![],
+                    file_schema_override: None,
+                    row_group_statistics: None,
+                    ranges: vec![],
+                    tombstoned: false,
+                    expected_checksum: None,
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_groups_by_statistics_error_variants() -> Result<()> {
+        use datafusion_physical_expr::expressions::Column;
+        use datafusion_physical_expr_common::sort_expr::PhysicalSortExpr;
+
+        let file = |name: &str, stats: Option<(i32, i32)>| {
+            let column_statistics = match stats {
+                Some((min, max)) => ColumnStatistics::new_unknown()
+                    .with_min_value(Precision::Exact(ScalarValue::from(min)))
+                    .with_max_value(Precision::Exact(ScalarValue::from(max))),
+                None => ColumnStatistics::new_unknown(),
+            };
+            PartitionedFile::new(name, 10).with_statistics(Arc::new(Statistics {
+                num_rows: Precision::Absent,
+                total_byte_size: Precision::Absent,
+                column_statistics: vec![column_statistics],
+            }))
+        };
+        let sort_order: LexOrdering =
+            [PhysicalSortExpr::new_default(Arc::new(Column::new("a", 0)))].into();
+        let split = |nullable: bool, files: Vec<PartitionedFile>| {
+            let schema = Schema::new(vec![Field::new("a", DataType::Int32, nullable)]);
+            FileScanConfig::split_groups_by_statistics(
+                &Arc::new(schema),
+                &[FileGroup::new(files)],
+                &sort_order,
+            )
+            .unwrap_err()
+        };
+
+        let err = split(true, vec![file("0", Some((0, 1)))]);
+        assert!(matches!(err.find_root(), DataFusionError::Plan(_)));
+        assert_eq!(
+            SplitGroupsError::find(&err),
+            Some(&SplitGroupsError::NullableSortColumn {
+                column: "a".to_string()
+            })
+        );
+
+        let err = split(false, vec![file("0", Some((0, 1))), file("1", None)]);
+        assert_eq!(
+            SplitGroupsError::find(&err),
+            Some(&SplitGroupsError::MissingStatistics {
+                column: "a".to_string(),
+                file: "1".to_string(),
+            })
+        );
+
+        let err = split(false, vec![PartitionedFile::new("2", 10)]);
+        assert_contains!(
+            err.strip_backtrace(),
+            "caused by\nError during planning: Parquet file missing statistics"
+        );
+        assert_eq!(
+            SplitGroupsError::find(&err),
+            Some(&SplitGroupsError::MissingFileStatistics {
+                file: "2".to_string(),
+            })
+        );
+
+        // errors not caused by the statistics are not matched
+        assert_eq!(
+            SplitGroupsError::find(&DataFusionError::Plan("other".to_string())),
+            None
+        );
+        Ok(())
+    }
+
+    // sets default for configs that play no role in projections
+    fn config_for_projection(
+        file_schema: SchemaRef,
+        projection: Option<Vec<usize>>,
+        statistics: Statistics,
+        table_partition_cols: Vec<Field>,
+    ) -> FileScanConfig {
+        FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_projection(projection)
+        .with_statistics(statistics)
+        .with_table_partition_cols(table_partition_cols)
+        .build()
+    }
+
+    /// Convert partition columns from Vec<String DataType> to Vec<Field>
+    fn to_partition_cols(table_partition_cols: Vec<(String, DataType)>) -> Vec<Field> {
+        table_partition_cols
+            .iter()
+            .map(|(name, dtype)| Field::new(name, dtype.clone(), false))
+            .collect::<Vec<_>>()
+    }
+
+    /// returns record batch with 3 columns of i32 in memory
+    pub fn build_table_i32(
+        a: (&str, &Vec<i32>),
+        b: (&str, &Vec<i32>),
+        c: (&str, &Vec<i32>),
+    ) -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new(a.0, DataType::Int32, false),
+            Field::new(b.0, DataType::Int32, false),
+            Field::new(c.0, DataType::Int32, false),
+        ]);
+
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(a.1.clone())),
+                Arc::new(Int32Array::from(b.1.clone())),
+                Arc::new(Int32Array::from(c.1.clone())),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_file_scan_config_builder() {
+        let file_schema = aggr_test_schema();
+        let object_store_url = ObjectStoreUrl::parse("test:///").unwrap();
+        let file_source: Arc<dyn FileSource> = Arc::new(MockSource::default());
+
+        // Create a builder with required parameters
+        let builder = FileScanConfigBuilder::new(
+            object_store_url.clone(),
+            Arc::clone(&file_schema),
+            Arc::clone(&file_source),
+        );
+
+        // Build with various configurations
+        let config = builder
+            .with_limit(Some(1000))
+            .with_projection(Some(vec![0, 1]))
+            .with_table_partition_cols(vec![Field::new(
+                "date",
+                wrap_partition_type_in_dict(DataType::Utf8),
+                false,
+            )])
+            .with_statistics(Statistics::new_unknown(&file_schema))
+            .with_file_groups(vec![FileGroup::new(vec![PartitionedFile::new(
+                "test.parquet".to_string(),
+                1024,
+            )])])
+            .with_output_ordering(vec![[PhysicalSortExpr::new_default(Arc::new(
+                Column::new("date", 0),
+            ))]
+            .into()])
+            .with_file_compression_type(FileCompressionType::UNCOMPRESSED)
+            .with_newlines_in_values(true)
+            .build();
+
+        // Verify the built config has all the expected values
+        assert_eq!(config.object_store_url, object_store_url);
+        assert_eq!(config.file_schema, file_schema);
+        assert_eq!(config.limit, Some(1000));
+        assert_eq!(config.projection, Some(vec![0, 1]));
+        assert_eq!(config.table_partition_cols.len(), 1);
+        assert_eq!(config.table_partition_cols[0].name(), "date");
+        assert_eq!(config.file_groups.len(), 1);
+        assert_eq!(config.file_groups[0].len(), 1);
+        assert_eq!(
+            config.file_groups[0][0].object_meta.location.as_ref(),
+            "test.parquet"
+        );
+        assert_eq!(
+            config.file_compression_type,
+            FileCompressionType::UNCOMPRESSED
+        );
+        assert!(config.new_lines_in_values);
+        assert_eq!(config.output_ordering.len(), 1);
+    }
+
+    #[test]
+    fn test_file_scan_config_builder_defaults() {
+        let file_schema = aggr_test_schema();
+        let object_store_url = ObjectStoreUrl::parse("test:///").unwrap();
+        let file_source: Arc<dyn FileSource> = Arc::new(MockSource::default());
+
+        // Create a builder with only required parameters and build without any additional configurations
+        let config = FileScanConfigBuilder::new(
+            object_store_url.clone(),
+            Arc::clone(&file_schema),
+            Arc::clone(&file_source),
+        )
+        .build();
+
+        // Verify default values
+        assert_eq!(config.object_store_url, object_store_url);
+        assert_eq!(config.file_schema, file_schema);
+        assert_eq!(config.limit, None);
+        assert_eq!(config.projection, None);
+        assert!(config.table_partition_cols.is_empty());
+        assert!(config.file_groups.is_empty());
+        assert_eq!(
+            config.file_compression_type,
+            FileCompressionType::UNCOMPRESSED
+        );
+        assert!(!config.new_lines_in_values);
+        assert!(config.output_ordering.is_empty());
+        assert!(config.constraints.is_empty());
+
+        // Verify statistics are set to unknown
+        assert_eq!(
+            config.file_source.statistics().unwrap().num_rows,
+            Precision::Absent
+        );
+        assert_eq!(
+            config.file_source.statistics().unwrap().total_byte_size,
+            Precision::Absent
+        );
+        assert_eq!(
+            config
+                .file_source
+                .statistics()
+                .unwrap()
+                .column_statistics
+                .len(),
+            file_schema.fields().len()
+        );
+        for stat in config.file_source.statistics().unwrap().column_statistics {
+            assert_eq!(stat.distinct_count, Precision::Absent);
+            assert_eq!(stat.min_value, Precision::Absent);
+            assert_eq!(stat.max_value, Precision::Absent);
+            assert_eq!(stat.null_count, Precision::Absent);
+        }
+    }
+
+    #[test]
+    fn test_file_scan_config_builder_new_from() {
+        let schema = aggr_test_schema();
+        let object_store_url = ObjectStoreUrl::parse("test:///").unwrap();
+        let file_source: Arc<dyn FileSource> = Arc::new(MockSource::default());
+        let partition_cols = vec![Field::new(
+            "date",
+            wrap_partition_type_in_dict(DataType::Utf8),
+            false,
+        )];
+        let file = PartitionedFile::new("test_file.parquet", 100);
+
+        // Create a config with non-default values
+        let original_config = FileScanConfigBuilder::new(
+            object_store_url.clone(),
+            Arc::clone(&schema),
+            Arc::clone(&file_source),
+        )
+        .with_projection(Some(vec![0, 2]))
+        .with_limit(Some(10))
+        .with_table_partition_cols(partition_cols.clone())
+        .with_file(file.clone())
+        .with_constraints(Constraints::default())
+        .with_newlines_in_values(true)
+        .build();
+
+        // Create a new builder from the config
+        let new_builder = FileScanConfigBuilder::from(original_config);
+
+        // Build a new config from this builder
+        let new_config = new_builder.build();
+
+        // Verify properties match
+        let partition_cols = partition_cols.into_iter().map(Arc::new).collect::<Vec<_>>();
+        assert_eq!(new_config.object_store_url, object_store_url);
+        assert_eq!(new_config.file_schema, schema);
+        assert_eq!(new_config.projection, Some(vec![0, 2]));
+        assert_eq!(new_config.limit, Some(10));
+        assert_eq!(new_config.table_partition_cols, partition_cols);
+        assert_eq!(new_config.file_groups.len(), 1);
+        assert_eq!(new_config.file_groups[0].len(), 1);
+        assert_eq!(
+            new_config.file_groups[0][0].object_meta.location.as_ref(),
+            "test_file.parquet"
+        );
+        assert_eq!(new_config.constraints, Constraints::default());
+        assert!(new_config.new_lines_in_values);
+    }
+
+    #[test]
+    fn test_file_scan_config_file_preprocessor() {
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+        let file_groups = vec![
+            FileGroup::new(vec![
+                PartitionedFile::new("a.csv", 10),
+                PartitionedFile::new("b.quarantined.csv", 10),
+            ]),
+            FileGroup::new(vec![PartitionedFile::new("c.quarantined.csv", 10)]),
+        ];
+        let builder = test_builder().with_file_groups(file_groups);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let drop_quarantined: FilePreprocessor = {
+            let calls = Arc::clone(&calls);
+            Arc::new(move |groups: Vec<FileGroup>| {
+                calls.fetch_add(1, AtomicOrdering::SeqCst);
+                Ok(groups
+                    .into_iter()
+                    .map(|group| {
+                        FileGroup::new(
+                            group
+                                .into_inner()
+                                .into_iter()
+                                .filter(|f| !f.path().as_ref().contains(".quarantined."))
+                                .collect(),
+                        )
+                    })
+                    .collect())
+            })
+        };
+        let config = builder
+            .clone()
+            .with_file_preprocessor(drop_quarantined)
+            .build();
+        // the file groups are preprocessed when the config is built
+        let paths = config
+            .file_groups
+            .iter()
+            .map(|g| g.iter().map(|f| f.path().to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec![vec!["a.csv".to_string()], vec![]]);
+
+        // and only once
+        config.validate().unwrap();
+        let config = FileScanConfigBuilder::from(config).build();
+        config.repartitioned(4, 0, None).unwrap();
+        assert_eq!(calls.load(AtomicOrdering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_file_scan_config_file_preprocessor_group_count() {
+        let file_groups = vec![
+            FileGroup::new(vec![PartitionedFile::new("a.csv", 10)]),
+            FileGroup::new(vec![PartitionedFile::new("b.csv", 10)]),
+        ];
+        let builder = test_builder().with_file_groups(file_groups);
+
+        // a preprocessor returning as many groups is accepted
+        let reverse: FilePreprocessor = Arc::new(|mut groups: Vec<FileGroup>| {
+            groups.reverse();
+            Ok(groups)
+        });
+        let config = builder
+            .clone()
+            .with_file_preprocessor(reverse)
+            .try_build()
+            .unwrap();
+        assert_eq!(config.file_groups[0].files()[0].path().as_ref(), "b.csv");
+
+        // fewer groups
+        let merge: FilePreprocessor = Arc::new(|groups: Vec<FileGroup>| {
+            Ok(vec![FileGroup::new(
+                groups.into_iter().flat_map(|g| g.into_inner()).collect(),
+            )])
+        });
+        // more groups
+        let split: FilePreprocessor = Arc::new(|groups: Vec<FileGroup>| {
+            let mut groups = groups;
+            groups.push(FileGroup::default());
+            Ok(groups)
+        });
+        for (file_preprocessor, expected) in [
+            (merge, "returned 1 file groups, expected 2"),
+            (split, "returned 3 file groups, expected 2"),
+        ] {
+            let builder = builder.clone().with_file_preprocessor(file_preprocessor);
+            let err = builder.clone().try_build().unwrap_err();
+            assert_contains!(err.to_string(), expected);
+
+            // the file groups are left unchanged, and the scan fails when it
+            // is opened
+            let config = builder.build();
+            assert_eq!(config.file_groups.len(), 2);
+            let err = config.validate().unwrap_err();
+            assert_contains!(err.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_file_scan_config_sort_files_in_group() {
+        let files = vec![
+            PartitionedFile::new("c.csv", 10),
+            PartitionedFile::new("a.csv", 20).with_range(10, 20),
+            PartitionedFile::new("b/1.csv", 10),
+            PartitionedFile::new("a.csv", 20).with_range(0, 10),
+        ];
+        let read_order = |files: Vec<PartitionedFile>, sort_files_in_group: bool| {
+            let config = test_builder()
+                .with_file_group(FileGroup::new(files))
+                .with_sort_files_in_group(sort_files_in_group)
+                .build();
+            config.file_groups_in_read_order()[0]
+                .iter()
+                .map(|f| {
+                    let start = f.range.as_ref().map(|r| r.start).unwrap_or_default();
+                    format!("{}@{start}", f.path())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // the files are read in the order they were added by default
+        assert_eq!(
+            read_order(files.clone(), false),
+            ["c.csv@0", "a.csv@10", "b/1.csv@0", "a.csv@0"]
+        );
+
+        // the order does not depend on the order the files were added in
+        let expected = ["a.csv@0", "a.csv@10", "b/1.csv@0", "c.csv@0"];
+        assert_eq!(read_order(files.clone(), true), expected);
+        let mut reversed = files;
+        reversed.reverse();
+        assert_eq!(read_order(reversed, true), expected);
+    }
+
+    #[test]
+    fn test_file_scan_config_file_admission() {
+        let file_admission: FileAdmissionPolicy =
+            Arc::new(|meta: &ObjectMeta| match meta.location.as_ref() {
+                "expired.csv" => FileAdmission::Reject,
+                "forbidden.csv" => {
+                    FileAdmission::RejectWithError("access denied".to_string())
+                }
+                _ => FileAdmission::Admit,
+            });
+        let build = |files: Vec<PartitionedFile>| {
+            test_builder()
+                .with_file_groups(vec![FileGroup::new(files), FileGroup::default()])
+                .with_file_admission(Arc::clone(&file_admission))
+                .build()
+        };
+        let files_rejected = |config: &FileScanConfig| {
+            config
+                .file_source
+                .metrics()
+                .clone_inner()
+                .sum_by_name("files_rejected")
+                .map(|rejected| rejected.as_usize())
+        };
+
+        // admitted files are kept
+        let config = build(vec![
+            PartitionedFile::new("a.csv", 10),
+            PartitionedFile::new("b.csv", 10),
+        ]);
+        assert_eq!(paths(&config), [vec!["a.csv", "b.csv"], vec![]]);
+        assert_eq!(files_rejected(&config), Some(0));
+        config.validate().unwrap();
+
+        // rejected files are dropped and counted
+        let config = build(vec![
+            PartitionedFile::new("a.csv", 10),
+            PartitionedFile::new("expired.csv", 10),
+        ]);
+        assert_eq!(paths(&config), [vec!["a.csv"], vec![]]);
+        assert_eq!(files_rejected(&config), Some(1));
+        config.validate().unwrap();
+
+        // files rejected with an error fail the plan
+        let config = build(vec![
+            PartitionedFile::new("a.csv", 10),
+            PartitionedFile::new("forbidden.csv", 10),
+        ]);
+        assert_eq!(paths(&config), [vec!["a.csv", "forbidden.csv"], vec![]]);
+        let err = config.validate().unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "File forbidden.csv is not admitted: access denied"
+        );
+    }
+
+    #[test]
+    fn test_file_scan_config_satisfies_ordering() {
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let file = |path: &str, date: &str| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = vec![ScalarValue::from(date)];
+            file
+        };
+        let sort = |names: &[&str]| {
+            LexOrdering::new(names.iter().map(|name| {
+                let idx = ["a", "b", "date"].iter().position(|n| n == name).unwrap();
+                PhysicalSortExpr::new_default(Arc::new(Column::new(name, idx)))
+            }))
+            .unwrap()
+        };
+
+        // every file group holds a single date, so "date" is constant in
+        // each output partition
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+        .with_file_groups(vec![
+            FileGroup::new(vec![file("1.parquet", "2024-01-01")]),
+            FileGroup::new(vec![file("2.parquet", "2024-01-02")]),
+        ])
+        .with_output_ordering(vec![sort(&["a"])])
+        .build();
+
+        // satisfied
+        assert!(config.satisfies_ordering(&sort(&["a"])).unwrap());
+        assert!(config.satisfies_ordering(&sort(&["date", "a"])).unwrap());
+        // only a prefix is satisfied
+        assert!(!config.satisfies_ordering(&sort(&["a", "b"])).unwrap());
+        // not satisfied
+        assert!(!config.satisfies_ordering(&sort(&["b"])).unwrap());
+    }
+
+    #[test]
+    fn test_file_scan_config_require_known_sizes() {
+        let builder = test_builder().with_file_groups(vec![
+            FileGroup::new(vec![PartitionedFile::new("sized.csv", 10)]),
+            FileGroup::new(vec![
+                PartitionedFile::new("unknown_1.csv", 0),
+                PartitionedFile::new("unknown_2.csv", 0),
+            ]),
+        ]);
+
+        // not checked by default
+        builder.clone().try_build().unwrap();
+
+        let builder = builder.with_require_known_sizes(true);
+        let err = builder.clone().try_build().unwrap_err();
+        assert_contains!(err.to_string(), "Size of file unknown_1.csv is unknown");
+        // only checked when the config is built
+        builder.build().validate().unwrap();
+    }
+
+    #[test]
+    fn test_split_groups_by_statistics_with_partition_column() -> Result<()> {
+        let date_type =
+            DataType::Dictionary(Box::new(DataType::UInt16), Box::new(DataType::Utf8));
+        let table_schema = Arc::new(Schema::new(vec![
+            Field::new("ts", DataType::Int64, false),
+            Field::new("date", date_type, false),
+        ]));
+        let sort_order = LexOrdering::new(vec![
+            PhysicalSortExpr::new_default(Arc::new(Column::new("date", 1))),
+            PhysicalSortExpr::new_default(Arc::new(Column::new("ts", 0))),
+        ])
+        .unwrap();
+        // only the file column has statistics
+        let file = |name: &str, date: &str, min: i64, max: i64| {
+            let mut file =
+                PartitionedFile::new(name, 100).with_statistics(Arc::new(Statistics {
+                    num_rows: Precision::Absent,
+                    total_byte_size: Precision::Absent,
+                    column_statistics: vec![ColumnStatistics {
+                        min_value: Precision::Exact(ScalarValue::from(min)),
+                        max_value: Precision::Exact(ScalarValue::from(max)),
+                        ..ColumnStatistics::new_unknown()
+                    }],
+                }));
+            file.partition_values = vec![ScalarValue::from(date)];
+            file
+        };
+
+        // "ts" ranges overlap across dates, but not within a date
+        let files = FileGroup::new(vec![
+            file("a", "2024-01-01", 0, 10),
+            file("b", "2024-01-02", 0, 10),
+            file("c", "2024-01-01", 20, 30),
+        ]);
+        let groups = FileScanConfig::split_groups_by_statistics(
+            &table_schema,
+            &[files],
+            &sort_order,
+        )?;
+        let names = groups
+            .iter()
+            .map(|g| g.iter().map(|f| f.path().to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![vec!["a", "c", "b"]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_groups_by_statistics_indexed() -> Result<()> {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "value",
+            DataType::Float64,
+            false,
+        )]));
+        let sort_order = LexOrdering::new(vec![PhysicalSortExpr::new_default(Arc::new(
+            Column::new("value", 0),
+        ))])
+        .unwrap();
+        let file = |idx: usize, min: f64, max: f64| {
+            PartitionedFile::new(format!("file_{idx}.parquet"), 10).with_statistics(
+                Arc::new(Statistics {
+                    num_rows: Precision::Absent,
+                    total_byte_size: Precision::Absent,
+                    column_statistics: vec![ColumnStatistics {
+                        min_value: Precision::Exact(ScalarValue::from(min)),
+                        max_value: Precision::Exact(ScalarValue::from(max)),
+                        ..Default::default()
+                    }],
+                }),
+            )
+        };
+        let paths = |groups: Vec<FileGroup>| {
+            groups
+                .iter()
+                .map(|group| group.iter().map(|f| f.path().to_string()).collect())
+                .collect::<Vec<Vec<_>>>()
+        };
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for num_files in [0, 1, 2, 10, 100, 1000] {
+            for max_len in [1.0, 10.0, 100.0] {
+                let files = (0..num_files)
+                    .map(|idx| {
+                        let min = rng.random_range(0.0..100.0);
+                        file(idx, min, min + rng.random_range(0.0..max_len))
+                    })
+                    .collect();
+                let file_groups = [FileGroup::new(files)];
+
+                let expected = FileScanConfig::split_groups_by_statistics(
+                    &schema,
+                    &file_groups,
+                    &sort_order,
+                )?;
+                let actual = FileScanConfig::split_groups_by_statistics_indexed(
+                    &schema,
+                    &file_groups,
+                    &sort_order,
+                )?;
+                assert!(verify_sort_integrity(&actual));
+                assert_eq!(paths(actual), paths(expected));
+            }
+        }
+
+        // disjoint files all fit into a single group
+        let files = (0..100).map(|idx| file(idx, idx as f64, idx as f64 + 0.5));
+        let groups = FileScanConfig::split_groups_by_statistics_indexed(
+            &schema,
+            &[FileGroup::new(files.collect())],
+            &sort_order,
+        )?;
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 100);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_groups_by_statistics_with_limit() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "value",
+            DataType::Float64,
+            false,
+        )]));
+        let sort_order = LexOrdering::new(vec![PhysicalSortExpr::new_default(Arc::new(
+            Column::new("value", 0),
+        ))])
+        .unwrap();
+        let file = |name: &str, min: f64, max: f64, num_rows: Option<usize>| {
+            let num_rows = num_rows.map_or(Precision::Absent, Precision::Exact);
+            PartitionedFile::new(name, 100).with_statistics(Arc::new(Statistics {
+                num_rows,
+                total_byte_size: Precision::Absent,
+                column_statistics: vec![ColumnStatistics {
+                    min_value: Precision::Exact(ScalarValue::from(min)),
+                    max_value: Precision::Exact(ScalarValue::from(max)),
+                    ..ColumnStatistics::new_unknown()
+                }],
+            }))
+        };
+        let names = |groups: Vec<FileGroup>| {
+            groups
+                .iter()
+                .map(|g| g.iter().map(|f| f.path().to_string()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        let files = FileGroup::new(vec![
+            file("a", 0.0, 9.0, Some(100)),
+            file("b", 20.0, 29.0, Some(100)),
+            file("c", 25.0, 34.0, Some(100)),
+        ]);
+        let all = FileScanConfig::split_groups_by_statistics(
+            &schema,
+            &[files.clone()],
+            &sort_order,
+        )?;
+        assert_eq!(names(all), vec![vec!["a", "b"], vec!["c"]]);
+
+        // the first 50 rows are all in "a", so only the first group is needed
+        let limited = FileScanConfig::split_groups_by_statistics_with_limit(
+            &schema,
+            &[files.clone()],
+            &sort_order,
+            50,
+        )?;
+        assert_eq!(names(limited), vec![vec!["a"]]);
+
+        // "b" and "c" overlap, so both are needed for more rows
+        let limited = FileScanConfig::split_groups_by_statistics_with_limit(
+            &schema,
+            &[files],
+            &sort_order,
+            150,
+        )?;
+        assert_eq!(names(limited), vec![vec!["a", "b"], vec!["c"]]);
+
+        // without exact row counts no files can be skipped
+        let files = FileGroup::new(vec![
+            file("a", 0.0, 9.0, None),
+            file("b", 20.0, 29.0, Some(100)),
+        ]);
+        let limited = FileScanConfig::split_groups_by_statistics_with_limit(
+            &schema,
+            &[files],
+            &sort_order,
+            1,
+        )?;
+        assert_eq!(names(limited), vec![vec!["a", "b"]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_groups_by_row_count() -> Result<()> {
+        let file = |path: &str, size: u64, num_rows: Option<usize>| {
+            let file = PartitionedFile::new(path, size);
+            match num_rows {
+                Some(num_rows) => file.with_statistics(Arc::new(
+                    Statistics::new_unknown(&Schema::empty())
+                        .with_num_rows(Precision::Exact(num_rows)),
+                )),
+                None => file,
+            }
+        };
+        let group_totals = |groups: &[FileGroup], weight: fn(&PartitionedFile) -> u64| {
+            groups
+                .iter()
+                .map(|group| group.iter().map(weight).sum::<u64>())
+                .collect::<Vec<_>>()
+        };
+        let rows = |file: &PartitionedFile| -> u64 {
+            let stats = file.statistics.as_ref().unwrap();
+            *stats.num_rows.get_value().unwrap() as u64
+        };
+
+        // row counts are balanced, even though sizes are not
+        let files = FileGroup::new(vec![
+            file("a", 1, Some(100)),
+            file("b", 1000, Some(10)),
+            file("c", 1000, Some(10)),
+            file("d", 1000, Some(40)),
+            file("e", 1000, Some(50)),
+            file("f", 1000, Some(10)),
+        ]);
+        let groups = FileScanConfig::split_groups_by_row_count(&[files], 2)?;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(group_totals(&groups, rows), vec![110, 110]);
+
+        // falls back to file sizes if any row count is unknown
+        let files = FileGroup::new(vec![
+            file("a", 300, Some(1)),
+            file("b", 100, None),
+            file("c", 200, None),
+        ]);
+        let groups = FileScanConfig::split_groups_by_row_count(&[files], 2)?;
+        assert_eq!(
+            group_totals(&groups, |file| file.object_meta.size),
+            vec![300, 300]
+        );
+
+        // never more groups than files
+        let files = FileGroup::new(vec![file("a", 1, Some(1))]);
+        let groups = FileScanConfig::split_groups_by_row_count(&[files], 4)?;
+        assert_eq!(groups.len(), 1);
+
+        assert!(FileScanConfig::split_groups_by_row_count(&[], 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_repartition_balanced() -> Result<()> {
+        let sizes = |groups: &[FileGroup]| {
+            groups
+                .iter()
+                .map(|group| group.iter().map(|f| f.object_meta.size).sum::<u64>())
+                .collect::<Vec<_>>()
+        };
+
+        // one large file and many small ones
+        let mut files = vec![PartitionedFile::new("large", 1000)];
+        files.extend((0..18).map(|i| PartitionedFile::new(format!("small_{i:02}"), 10)));
+        let files = FileGroup::new(files);
+
+        // sizes alone are balanced by a single group of small files
+        let groups = FileScanConfig::repartition_balanced(&[files.clone()], 2, 18)?;
+        assert_eq!(sizes(&groups), vec![1000, 180]);
+
+        // the small files are split further to respect the file count cap
+        let groups = FileScanConfig::repartition_balanced(&[files], 2, 4)?;
+        assert_eq!(groups.len(), 6);
+        assert!(groups.iter().all(|group| group.len() <= 4));
+        assert_eq!(sizes(&groups), vec![1000, 40, 40, 40, 30, 30]);
+        assert_eq!(groups.iter().map(FileGroup::len).sum::<usize>(), 19);
+
+        assert!(FileScanConfig::repartition_balanced(&[], 0, 1).is_err());
+        assert!(FileScanConfig::repartition_balanced(&[], 1, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_group_skew() {
+        let file = |path: &str, size: u64, num_rows: usize| {
+            PartitionedFile::new(path, size).with_statistics(Arc::new(
+                Statistics::new_unknown(&aggr_test_schema())
+                    .with_num_rows(Precision::Exact(num_rows)),
+            ))
+        };
+        let config = |file_groups: Vec<FileGroup>| {
+            test_builder().with_file_groups(file_groups).build()
+        };
+
+        let balanced = config(vec![
+            FileGroup::new(vec![file("a", 100, 10)]),
+            FileGroup::new(vec![file("b", 50, 5), file("c", 50, 5)]),
+        ])
+        .group_skew();
+        assert_eq!(balanced.max_over_mean_bytes, 1.0);
+        assert_eq!(balanced.max_over_mean_rows, Some(1.0));
+        assert_eq!(balanced.gini, 0.0);
+
+        let skewed = config(vec![
+            FileGroup::new(vec![file("a", 1000, 1000)]),
+            FileGroup::new(vec![file("b", 10, 1)]),
+            FileGroup::new(vec![file("c", 10, 1)]),
+            FileGroup::new(vec![file("d", 10, 1)]),
+        ])
+        .group_skew();
+        assert!(skewed.max_over_mean_bytes > 3.5);
+        assert!(skewed.max_over_mean_rows.unwrap() > 3.5);
+        assert!(skewed.gini > 0.7);
+
+        // rows are unknown without statistics
+        let unknown_rows =
+            config(vec![FileGroup::new(vec![PartitionedFile::new("a", 10)])])
+                .group_skew();
+        assert_eq!(unknown_rows.max_over_mean_rows, None);
+    }
+
+    #[test]
+    fn test_file_scan_config_contains_file() {
+        let config = test_builder()
+            .with_file_groups(vec![
+                FileGroup::new(vec![
+                    PartitionedFile::new("a/1.parquet", 10),
+                    PartitionedFile::new("a/2.parquet", 10),
+                ]),
+                FileGroup::new(vec![PartitionedFile::new("b/1.parquet", 10)]),
+            ])
+            .build();
+
+        let index = config.build_path_index();
+        assert_eq!(index.len(), 3);
+        for present in ["a/1.parquet", "a/2.parquet", "b/1.parquet"] {
+            assert!(config.contains_file(&Path::from(present)));
+            assert!(index.contains(&Path::from(present)));
+        }
+        for absent in ["a/3.parquet", "b/2.parquet", "a", "1.parquet"] {
+            assert!(!config.contains_file(&Path::from(absent)));
+            assert!(!index.contains(&Path::from(absent)));
+        }
+    }
+
+    #[test]
+    fn test_file_scan_config_output_rename() -> Result<()> {
+        let schema = aggr_test_schema();
+        let mut statistics = Statistics::new_unknown(&schema);
+        for (idx, col_stats) in statistics.column_statistics.iter_mut().enumerate() {
+            col_stats.distinct_count = Precision::Exact(idx);
+        }
+        let ordering = LexOrdering::new(vec![PhysicalSortExpr::new_default(Arc::new(
+            Column::new("c4", 3),
+        ))])
+        .unwrap();
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            Arc::clone(&schema),
+            Arc::new(MockSource::default()),
+        )
+        .with_projection(Some(vec![4, 0, 3]))
+        .with_statistics(statistics)
+        .with_output_ordering(vec![ordering]);
+
+        let config = builder
+            .clone()
+            .with_output_rename(HashMap::from([
+                ("c1".to_string(), "customer_id".to_string()),
+                ("c4".to_string(), "amount".to_string()),
+            ]))
+            .build();
+        config.validate()?;
+        let (schema, _, stats, orderings) = config.project();
+        assert_eq!(columns(&schema), vec!["c5", "customer_id", "amount"]);
+        assert_eq!(
+            stats
+                .column_statistics
+                .iter()
+                .map(|col_stats| col_stats.distinct_count)
+                .collect::<Vec<_>>(),
+            vec![
+                Precision::Exact(4),
+                Precision::Exact(0),
+                Precision::Exact(3)
+            ]
+        );
+        // the ordering on `c4` is reported on the renamed column
+        assert_eq!(
+            orderings,
+            vec![
+                LexOrdering::new(vec![PhysicalSortExpr::new_default(Arc::new(
+                    Column::new("amount", 2)
+                ))])
+                .unwrap()
+            ]
+        );
+
+        // renaming a column to the name of another output column
+        let conflicting = builder
+            .with_output_rename(HashMap::from([("c1".to_string(), "c4".to_string())]))
+            .build();
+        let err = conflicting.validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Renaming the output columns results in duplicate column c4"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_partition_store_url() {
+        let local = ObjectStoreUrl::local_filesystem();
+        let remote = ObjectStoreUrl::parse("s3://bucket").unwrap();
+        let builder = FileScanConfigBuilder::new(
+            local.clone(),
+            aggr_test_schema(),
+            Arc::new(MockSource::default()),
+        )
+        .with_file_groups(vec![
+            FileGroup::new(vec![PartitionedFile::new("hot.csv", 10)]),
+            FileGroup::new(vec![PartitionedFile::new("cold.csv", 10)]),
+        ]);
+
+        // a single store is used for all partitions by default
+        let config = builder.clone().build();
+        assert_eq!(config.partition_store_url(0), &local);
+        assert_eq!(config.partition_store_url(1), &local);
+
+        let config = builder.with_partition_store_url(1, remote.clone()).build();
+        let config = FileScanConfigBuilder::from(config).build();
+        assert_eq!(config.partition_store_url(0), &local);
+        assert_eq!(config.partition_store_url(1), &remote);
+
+        assert_contains!(
+            format!("{config:?}"),
+            "partition_store_urls=[1: s3://bucket/]"
+        );
+
+        // files are not moved between partitions
+        assert!(config.repartitioned(4, 0, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_file_scan_config_files_for_partition() {
+        let config = test_builder()
+            .with_file_groups(vec![
+                FileGroup::new(vec![
+                    PartitionedFile::new("a.csv", 10),
+                    PartitionedFile::new("b.csv", 10),
+                ]),
+                FileGroup::default(),
+            ])
+            .build();
+        let paths = |partition| {
+            config.files_for_partition(partition).map(|files| {
+                files
+                    .iter()
+                    .map(|f| f.path().to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            paths(0),
+            Some(vec!["a.csv".to_string(), "b.csv".to_string()])
+        );
+        assert_eq!(paths(1), Some(vec![]));
+        assert_eq!(paths(2), None);
+    }
+
+    #[test]
+    fn test_file_scan_config_object_store_urls() {
+        let local = ObjectStoreUrl::local_filesystem();
+        let s3 = ObjectStoreUrl::parse("s3://bucket").unwrap();
+        let gcs = ObjectStoreUrl::parse("gs://bucket").unwrap();
+        let builder = FileScanConfigBuilder::new(
+            local.clone(),
+            aggr_test_schema(),
+            Arc::new(MockSource::default()),
+        );
+
+        let config = builder
+            .clone()
+            .with_file(PartitionedFile::new("a.csv", 10))
+            .build();
+        assert_eq!(config.object_store_urls(), HashSet::from([local.clone()]));
+
+        let config = builder
+            .with_file_groups(vec![
+                FileGroup::new(vec![
+                    PartitionedFile::new("a.csv", 10),
+                    PartitionedFile::new("b.csv", 10).with_object_store_url(gcs.clone()),
+                ]),
+                FileGroup::new(vec![PartitionedFile::new("c.csv", 10)]),
+            ])
+            .with_partition_store_url(1, s3.clone())
+            .build();
+        assert_eq!(config.object_store_urls(), HashSet::from([local, s3, gcs]));
+    }
+
+    #[test]
+    fn test_file_scan_config_verbose_file_group_balance() {
+        let builder = test_builder();
+
+        // unbalanced: one group reads a single small file, the other three
+        // large files, one of them partially
+        let config = builder
+            .clone()
+            .with_file_groups(vec![
+                FileGroup::new(vec![PartitionedFile::new("a.csv", 100)]),
+                FileGroup::new(vec![
+                    PartitionedFile::new("b.csv", 1000),
+                    PartitionedFile::new("c.csv", 1000),
+                    PartitionedFile::new_with_range("d.csv".to_string(), 5000, 0, 500),
+                ]),
+            ])
+            .build();
+
+        let summary = "file_group_balance={groups: 2, \
+            files_per_group: {1 file: 1 group, 3 files: 1 group}, \
+            total_bytes: 2600, avg_bytes_per_group: 1300, \
+            min_bytes_per_group: 100, max_bytes_per_group: 2500}";
+        // Debug uses the verbose format
+        assert_contains!(format!("{config:?}"), summary);
+
+        let exec = DataSourceExec::from_data_source(config);
+        let verbose = displayable(exec.as_ref()).indent(true).to_string();
+        assert_contains!(verbose, summary);
+        let default = displayable(exec.as_ref()).indent(false).to_string();
+        assert!(!default.contains("file_group_balance"), "{default}");
+
+        // not shown for a single group
+        let config = builder
+            .with_file_groups(vec![FileGroup::new(vec![PartitionedFile::new(
+                "a.csv", 100,
+            )])])
+            .build();
+        let exec = DataSourceExec::from_data_source(config);
+        let verbose = displayable(exec.as_ref()).indent(true).to_string();
+        assert!(!verbose.contains("file_group_balance"), "{verbose}");
+    }
+
+    #[test]
+    fn test_file_scan_config_compression_histogram() {
+        let builder = test_builder().with_file_groups(vec![
+            FileGroup::new(vec![
+                PartitionedFile::new("a.csv.gz", 100),
+                PartitionedFile::new("b.csv.gz", 100),
+            ]),
+            FileGroup::new(vec![PartitionedFile::new("c.csv.gz", 100)]),
+        ]);
+
+        let config = builder
+            .clone()
+            .with_file_compression_type(FileCompressionType::GZIP)
+            .build();
+        assert_eq!(config.compression_histogram(), vec![("gzip", 3)]);
+        let exec = DataSourceExec::from_data_source(config);
+        let default = displayable(exec.as_ref()).indent(false).to_string();
+        assert_contains!(default, ", compression={gzip: 3}");
+
+        // not shown if no file is compressed
+        let config = builder.build();
+        assert_eq!(config.compression_histogram(), vec![("none", 3)]);
+        let exec = DataSourceExec::from_data_source(config);
+        let verbose = displayable(exec.as_ref()).indent(true).to_string();
+        assert!(!verbose.contains("compression="), "{verbose}");
+    }
+
+    #[test]
+    fn test_file_scan_config_estimated_decompressed_bytes() {
+        let builder = test_builder().with_file_groups(vec![
+            FileGroup::new(vec![PartitionedFile::new("a.csv", 100)]),
+            FileGroup::new(vec![PartitionedFile::new_with_range(
+                "b.csv".to_string(),
+                1000,
+                0,
+                300,
+            )]),
+        ]);
+
+        // only the range of a file is read
+        let config = builder.clone().build();
+        assert_eq!(
+            config.estimated_decompressed_bytes(),
+            Precision::Inexact(400)
+        );
+
+        // gzip files expand 3x by default
+        let gzip = builder.with_file_compression_type(FileCompressionType::GZIP);
+        let config = gzip.clone().build();
+        assert_eq!(
+            config.estimated_decompressed_bytes(),
+            Precision::Inexact(1200)
+        );
+
+        // the estimate scales with the configured ratio
+        let config = gzip
+            .with_compression_ratio_hint(HashMap::from([(
+                CompressionTypeVariant::GZIP,
+                7.5,
+            )]))
+            .build();
+        assert_eq!(config.compression_ratio(CompressionTypeVariant::GZIP), 7.5);
+        assert_eq!(
+            config.estimated_decompressed_bytes(),
+            Precision::Inexact(3000)
+        );
+    }
+
+    #[test]
+    fn test_file_scan_config_group_labels() -> Result<()> {
+        let config = test_builder()
+            .with_file_groups(vec![
+                FileGroup::new(vec![PartitionedFile::new("a.csv", 100)]),
+                FileGroup::new(vec![PartitionedFile::new("b.csv", 100)]),
+            ])
+            .with_group_labels(vec![None, Some("archive".to_string())])
+            .build();
+        let config = FileScanConfigBuilder::from(config).build();
+
+        assert_contains!(
+            format!("{config:?}"),
+            "file_groups={2 groups: [[a.csv], archive: [b.csv]]}"
+        );
+        let exec = DataSourceExec::from_data_source(config.clone());
+        let verbose = displayable(exec.as_ref()).indent(true).to_string();
+        assert_contains!(
+            verbose,
+            "file_groups={2 groups: [[a.csv], archive: [b.csv]]}"
+        );
+        let default = displayable(exec.as_ref()).indent(false).to_string();
+        assert!(!default.contains("archive"), "{default}");
+
+        // regrouping the files drops the labels
+        let repartitioned = config.repartitioned(4, 10, None)?.unwrap();
+        let repartitioned = repartitioned
+            .as_any()
+            .downcast_ref::<FileScanConfig>()
+            .unwrap();
+        assert!(repartitioned.group_labels.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_rebalance_after_pruning() {
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let file = |path: &str, size: u64, min: i32| {
+            PartitionedFile::new(path, size).with_statistics(Arc::new(Statistics {
+                num_rows: Precision::Absent,
+                total_byte_size: Precision::Absent,
+                column_statistics: vec![ColumnStatistics::new_unknown()
+                    .with_min_value(Precision::Exact(ScalarValue::from(min)))
+                    .with_max_value(Precision::Exact(ScalarValue::from(min + 9)))],
+            }))
+        };
+        // most files of the last groups were pruned
+        let file_groups = vec![
+            FileGroup::new(vec![
+                file("a", 40, 0),
+                file("b", 30, 10),
+                file("c", 20, 20),
+                file("d", 10, 30),
+            ]),
+            FileGroup::default(),
+            FileGroup::new(vec![file("e", 30, 40)]),
+            FileGroup::default(),
+        ];
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_file_groups(file_groups);
+        let sizes = |config: &FileScanConfig| {
+            config
+                .file_groups
+                .iter()
+                .map(|group| group.iter().map(|f| f.object_meta.size).sum())
+                .collect::<Vec<u64>>()
+        };
+
+        // the files are spread by size over the target partitions
+        let config = builder.clone().build().rebalance_after_pruning(3);
+        assert_eq!(paths(&config), [vec!["a"], vec!["b", "c"], vec!["e", "d"]]);
+        assert_eq!(sizes(&config), [40, 50, 40]);
+
+        // fewer files than target partitions
+        let config = builder.clone().build().rebalance_after_pruning(8);
+        assert_eq!(config.file_groups.len(), 5);
+
+        // with an output ordering, every group stays ordered
+        let ordering: LexOrdering =
+            [PhysicalSortExpr::new_default(Arc::new(Column::new("a", 0)))].into();
+        let config = builder
+            .with_output_ordering(vec![ordering])
+            .build()
+            .rebalance_after_pruning(2);
+        assert_eq!(paths(&config), [vec!["a", "c", "e"], vec!["b", "d"]]);
+    }
+
+    #[test]
+    fn test_file_scan_config_include_tombstoned() {
+        let files = vec![
+            PartitionedFile::new("a.csv", 10),
+            PartitionedFile::new("deleted.csv", 10).with_tombstoned(true),
+            PartitionedFile::new("b.csv", 10),
+        ];
+        let paths = |include_tombstoned: Option<bool>| {
+            let builder = test_builder().with_file_group(FileGroup::new(files.clone()));
+            let builder = match include_tombstoned {
+                Some(include_tombstoned) => {
+                    builder.with_include_tombstoned(include_tombstoned)
+                }
+                None => builder,
+            };
+            builder.build().file_groups[0]
+                .iter()
+                .map(|f| f.path().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // tombstoned files are skipped by default
+        assert_eq!(paths(None), ["a.csv", "b.csv"]);
+        assert_eq!(paths(Some(false)), ["a.csv", "b.csv"]);
+        assert_eq!(paths(Some(true)), ["a.csv", "deleted.csv", "b.csv"]);
+    }
+
+    #[test]
+    fn test_file_scan_config_projected_statistics() {
+        let file_schema = aggr_test_schema();
+        let statistics = Statistics {
+            num_rows: Precision::Exact(10),
+            total_byte_size: Precision::Inexact(100),
+            column_statistics: file_schema
+                .fields()
+                .iter()
+                .enumerate()
+                .map(|(i, _)| ColumnStatistics {
+                    null_count: Precision::Exact(i),
+                    ..ColumnStatistics::new_unknown()
+                })
+                .collect(),
+        };
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            Arc::clone(&file_schema),
+            Arc::new(MockSource::default()),
+        )
+        .with_statistics(statistics.clone());
+
+        let config = builder.clone().build();
+        assert_eq!(config.projected_statistics(), config.project().2);
+        assert_eq!(config.projected_statistics(), statistics);
+
+        let config = builder
+            .with_projection(Some(vec![3, 1]))
+            .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+            .build();
+        let projected = config.projected_statistics();
+        assert_eq!(projected, config.project().2);
+        assert_eq!(projected.column_statistics.len(), 2);
+        assert_eq!(
+            projected.column_statistics[0].null_count,
+            Precision::Exact(3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_scan_config_statistics_plan() -> Result<()> {
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let file = |path: &str, num_rows: usize, min: i32, max: i32| {
+            PartitionedFile::new(path, 10).with_statistics(Arc::new(
+                Statistics::default()
+                    .with_num_rows(Precision::Exact(num_rows))
+                    .with_total_byte_size(Precision::Inexact(num_rows * 4))
+                    .add_column_statistics(
+                        ColumnStatistics::new_unknown()
+                            .with_min_value(Precision::Exact(ScalarValue::from(min)))
+                            .with_max_value(Precision::Exact(ScalarValue::from(max))),
+                    )
+                    .add_column_statistics(ColumnStatistics::new_unknown()),
+            ))
+        };
+        let mut accumulator = FileScanConfigAccumulator::new(FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        ));
+        accumulator.add_file(file("a.parquet", 10, 5, 20))?;
+        accumulator.add_file(file("b.parquet", 25, 1, 8))?;
+        let config = accumulator.finish();
+        let statistics = config.projected_statistics();
+
+        let plan = config.statistics_plan()?;
+        let batches =
+            datafusion_physical_plan::collect(plan, Arc::new(TaskContext::default()))
+                .await?;
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 1);
+        let names = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "num_rows",
+                "total_bytes",
+                "a_min",
+                "a_max",
+                "b_min",
+                "b_max"
+            ]
+        );
+
+        let value = |name: &str| {
+            let column = batch.column_by_name(name).unwrap();
+            ScalarValue::try_from_array(column, 0).unwrap()
+        };
+        assert_eq!(value("num_rows"), ScalarValue::UInt64(Some(35)));
+        assert_eq!(statistics.num_rows, Precision::Exact(35));
+        assert_eq!(value("total_bytes"), ScalarValue::UInt64(Some(140)));
+        assert_eq!(statistics.total_byte_size, Precision::Inexact(140));
+        let a = &statistics.column_statistics[0];
+        assert_eq!(Precision::Exact(value("a_min")), a.min_value);
+        assert_eq!(Precision::Exact(value("a_max")), a.max_value);
+        assert_eq!(value("a_min"), ScalarValue::from(1));
+        assert_eq!(value("a_max"), ScalarValue::from(20));
+        // the statistics of b are unknown
+        assert_eq!(value("b_min"), ScalarValue::Utf8(None));
+        assert_eq!(value("b_max"), ScalarValue::Utf8(None));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_project_constraints() {
+        let config = test_builder()
+            .with_constraints(Constraints::new_unverified(vec![Constraint::Unique(
+                vec![1],
+            )]))
+            .build();
+
+        // the key is kept, at its index in the projection
+        assert_eq!(
+            config.project_constraints(&[3, 1]),
+            Constraints::new_unverified(vec![Constraint::Unique(vec![1])])
+        );
+        // the key is projected out
+        assert!(config.project_constraints(&[0, 2]).is_empty());
+    }
+
+    #[test]
+    fn test_file_scan_config_content_signature() {
+        let file = |e_tag: &str| {
+            let mut file = PartitionedFile::new("a.csv", 10);
+            file.object_meta.e_tag = Some(e_tag.to_string());
+            file
+        };
+        let config = |file: PartitionedFile| {
+            test_builder()
+                .with_file(file)
+                .with_projection(Some(vec![0, 1]))
+                .with_limit(Some(5))
+                .build()
+        };
+
+        let signature = config(file("v1")).content_signature();
+        // the signature does not depend on the config instance
+        assert_eq!(config(file("v1")).content_signature(), signature);
+
+        // the file was replaced
+        assert_ne!(config(file("v2")).content_signature(), signature);
+        assert_ne!(
+            config(file("v1").with_range(0, 5)).content_signature(),
+            signature
+        );
+        let projected = FileScanConfigBuilder::from(config(file("v1")))
+            .with_projection(Some(vec![0]))
+            .build();
+        assert_ne!(projected.content_signature(), signature);
+
+        // the file is read from elsewhere, or only in parts
+        let store_url = ObjectStoreUrl::parse("s3://other").unwrap();
+        assert_ne!(
+            config(file("v1").with_object_store_url(store_url)).content_signature(),
+            signature
+        );
+        let sub_files = vec![FileRange { start: 0, end: 5 }];
+        assert_ne!(
+            config(file("v1").with_sub_files(sub_files.clone())).content_signature(),
+            signature
+        );
+        assert_ne!(
+            config(file("v1").with_ranges(sub_files)).content_signature(),
+            signature
+        );
+
+        // the partition columns are part of the table schema
+        let partitioned = FileScanConfigBuilder::from(config(file("v1")))
+            .with_table_partition_cols(vec![Field::new("p", DataType::Utf8, true)])
+            .build();
+        assert_ne!(partitioned.content_signature(), signature);
+    }
+
+    #[test]
+    fn test_file_scan_config_would_repartition_change() {
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let config = |files: Vec<PartitionedFile>| {
+            FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("test:///").unwrap(),
+                Arc::clone(&file_schema),
+                Arc::new(MockSource::default()),
+            )
+            .with_file_group(FileGroup::new(files))
+            .build()
+        };
+
+        // a large file is split into ranges read by different partitions
+        let large = config(vec![PartitionedFile::new("a.csv", 1000)]);
+        assert!(large.would_repartition_change(4, 10));
+        // but not if it is too small
+        assert!(!large.would_repartition_change(4, 10_000));
+        // or if there is a single partition
+        assert!(!large.would_repartition_change(1, 10));
+
+        // the files are already split
+        let repartitioned = large.repartitioned(4, 10, None).unwrap().unwrap();
+        let repartitioned = repartitioned
+            .as_any()
+            .downcast_ref::<FileScanConfig>()
+            .unwrap();
+        assert_eq!(repartitioned.file_groups.len(), 4);
+        assert!(!repartitioned.would_repartition_change(4, 10));
+
+        // compressed files can not be split
+        let compressed = FileScanConfigBuilder::from(config(vec![PartitionedFile::new(
+            "a.csv", 1000,
+        )]))
+        .with_file_compression_type(FileCompressionType::GZIP)
+        .build();
+        assert!(!compressed.would_repartition_change(4, 10));
+    }
+
+    #[test]
+    fn test_file_scan_config_accumulator() -> Result<()> {
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let file = |path: &str, date: &str, num_rows: usize, min: i32, max: i32| {
+            let mut file = PartitionedFile::new(path, 10).with_statistics(Arc::new(
+                Statistics::default()
+                    .with_num_rows(Precision::Exact(num_rows))
+                    .add_column_statistics(
+                        ColumnStatistics::new_unknown()
+                            .with_min_value(Precision::Exact(ScalarValue::from(min)))
+                            .with_max_value(Precision::Exact(ScalarValue::from(max))),
+                    ),
+            ));
+            file.partition_values = vec![ScalarValue::from(date)];
+            file
+        };
+        let builder = || {
+            FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("test:///").unwrap(),
+                Arc::clone(&file_schema),
+                Arc::new(MockSource::default()),
+            )
+            .with_table_partition_cols(vec![Field::new(
+                "date",
+                DataType::Utf8,
+                false,
+            )])
+        };
+
+        let files = [
+            file("a.parquet", "2024-01-01", 10, 5, 20),
+            file("b.parquet", "2024-01-01", 20, 1, 8),
+            file("c.parquet", "2024-01-02", 5, 10, 30),
+        ];
+        let mut accumulator = FileScanConfigAccumulator::from(builder());
+        for file in files {
+            accumulator.add_file(file)?;
+        }
+        assert_eq!(accumulator.num_files(), 3);
+        assert_eq!(
+            accumulator.partition_values(),
+            [HashSet::from([
+                ScalarValue::from("2024-01-01"),
+                ScalarValue::from("2024-01-02")
+            ])]
+        );
+
+        let config = accumulator.finish();
+        assert_eq!(config.file_groups.len(), 1);
+        assert_eq!(config.file_groups[0].len(), 3);
+        let statistics = config.file_source.statistics()?;
+        assert_eq!(statistics.num_rows, Precision::Exact(35));
+        assert_eq!(
+            statistics.column_statistics[0].min_value,
+            Precision::Exact(ScalarValue::from(1))
+        );
+        assert_eq!(
+            statistics.column_statistics[0].max_value,
+            Precision::Exact(ScalarValue::from(30))
+        );
+        assert_eq!(
+            config.file_groups[0].file_statistics(None),
+            Some(&statistics)
+        );
+
+        // a file without statistics makes the statistics unknown
+        let mut accumulator = FileScanConfigAccumulator::from(builder());
+        accumulator.add_file(file("a.parquet", "2024-01-01", 10, 5, 20))?;
+        accumulator.add_file(PartitionedFile::new("b.parquet", 10))?;
+        assert_eq!(accumulator.statistics().num_rows, Precision::Absent);
+
+        let mut accumulator = FileScanConfigAccumulator::from(builder());
+        let err = accumulator
+            .add_file(
+                PartitionedFile::new("a.parquet", 10)
+                    .with_statistics(Arc::new(Statistics::default())),
+            )
+            .unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "File a.parquet has statistics for 0 columns, but the file schema has 1"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_partition_index_column() {
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_file_groups(vec![
+            FileGroup::new(vec![PartitionedFile::new("a.csv", 10)]),
+            FileGroup::new(vec![PartitionedFile::new("b.csv", 10)]),
+            FileGroup::new(vec![PartitionedFile::new("c.csv", 10)]),
+        ]);
+
+        let config = builder
+            .clone()
+            .with_partition_index_column("_partition_index")
+            .build();
+        let schema = config.projected_schema();
+        assert_eq!(schema.fields().len(), 2);
+        assert_eq!(
+            schema.field(1),
+            &Field::new("_partition_index", DataType::UInt64, false)
+        );
+        let statistics = config.projected_stats();
+        let index_statistics = &statistics.column_statistics[1];
+        assert_eq!(
+            index_statistics.max_value,
+            Precision::Exact(ScalarValue::UInt64(Some(2)))
+        );
+        assert_eq!(index_statistics.distinct_count, Precision::Exact(3));
+        config.validate().unwrap();
+
+        let config = builder.with_partition_index_column("a").build();
+        assert_contains!(
+            config.validate().unwrap_err().to_string(),
+            "Partition index column a conflicts with a column of the table"
+        );
+    }
+
+    #[test]
+    fn test_file_scan_config_constant_column() {
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_file(PartitionedFile::new("a.csv", 10))
+        .with_statistics(Statistics {
+            num_rows: Precision::Exact(5),
+            total_byte_size: Precision::Absent,
+            column_statistics: vec![ColumnStatistics::new_unknown()],
+        });
+
+        let config = builder
+            .clone()
+            .with_partition_index_column("_partition_index")
+            .with_constant_column("dataset", ScalarValue::from("sales"))
+            .with_constant_column("version", ScalarValue::Int64(None))
+            .build();
+        let schema = config.projected_schema();
+        let names = schema.fields().iter().map(|f| f.name()).collect::<Vec<_>>();
+        assert_eq!(names, ["a", "_partition_index", "dataset", "version"]);
+        assert_eq!(
+            schema.field(2),
+            &Field::new("dataset", DataType::Utf8, false)
+        );
+        assert_eq!(
+            schema.field(3),
+            &Field::new("version", DataType::Int64, true)
+        );
+
+        let statistics = config.projected_statistics();
+        let dataset_statistics = &statistics.column_statistics[2];
+        assert_eq!(
+            dataset_statistics.min_value,
+            Precision::Exact(ScalarValue::from("sales"))
+        );
+        assert_eq!(
+            dataset_statistics.max_value,
+            Precision::Exact(ScalarValue::from("sales"))
+        );
+        assert_eq!(dataset_statistics.distinct_count, Precision::Exact(1));
+        assert_eq!(dataset_statistics.null_count, Precision::Exact(0));
+        assert_eq!(
+            statistics.column_statistics[3].null_count,
+            Precision::Exact(5)
+        );
+        config.validate().unwrap();
+
+        let config = builder
+            .with_partition_index_column("_partition_index")
+            .with_constant_column("_partition_index", ScalarValue::from(1))
+            .build();
+        assert_contains!(
+            config.validate().unwrap_err().to_string(),
+            "Constant column _partition_index conflicts with another column of the output"
+        );
+    }
+
+    #[test]
+    fn test_file_scan_config_hash_partition_by() -> Result<()> {
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let file = |path: &str, date: &str| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = vec![ScalarValue::from(date)];
+            file
+        };
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+        .with_file_groups(vec![
+            FileGroup::new(vec![
+                file("1.parquet", "2024-01-01"),
+                file("2.parquet", "2024-01-02"),
+                file("3.parquet", "2024-01-03"),
+            ]),
+            FileGroup::new(vec![
+                file("4.parquet", "2024-01-01"),
+                file("5.parquet", "2024-01-02"),
+                file("6.parquet", "2024-01-04"),
+            ]),
+        ]);
+
+        let config = builder.clone().build().hash_partition_by("date", 4)?;
+        assert_eq!(config.file_groups.len(), 4);
+        assert_eq!(config.file_groups.iter().map(|g| g.len()).sum::<usize>(), 6);
+        // files with the same date land in the same partition
+        let partition_of = |path: &str| {
+            config
+                .file_groups
+                .iter()
+                .position(|g| g.iter().any(|f| f.path().as_ref() == path))
+                .unwrap()
+        };
+        assert_eq!(partition_of("1.parquet"), partition_of("4.parquet"));
+        assert_eq!(partition_of("2.parquet"), partition_of("5.parquet"));
+
+        match config.output_partitioning() {
+            Partitioning::Hash(exprs, 4) => {
+                let column = exprs[0].as_any().downcast_ref::<Column>().unwrap();
+                assert_eq!(column, &Column::new("date", 2));
+            }
+            other => panic!("expected hash partitioning, got {other:?}"),
+        }
+        // the hash partitioning is kept when converting back to a builder
+        let config = FileScanConfigBuilder::from(config).build();
+        assert!(matches!(
+            config.output_partitioning(),
+            Partitioning::Hash(_, 4)
+        ));
+        assert!(config.repartitioned(8, 0, None)?.is_none());
+
+        // unknown once the partition column is projected away
+        let projected = FileScanConfigBuilder::from(config)
+            .with_projection(Some(vec![0, 1]))
+            .build();
+        assert!(matches!(
+            projected.output_partitioning(),
+            Partitioning::UnknownPartitioning(4)
+        ));
+
+        let err = builder.build().hash_partition_by("a", 4).unwrap_err();
+        assert_contains!(err.to_string(), "a, which is not a partition column");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_declared_partitioning() -> Result<()> {
+        let file = |path: &str, region: &str| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = vec![ScalarValue::from(region)];
+            file
+        };
+        let config = |file_groups: Vec<FileGroup>| {
+            test_builder()
+                .with_table_partition_cols(vec![Field::new(
+                    "region",
+                    DataType::Utf8,
                     false,
-                )]),
-                files: vec![],
-                sort: vec![col("value").sort(true, false)],
-                expected_result: Ok(vec![]),
-            },
-            TestCase {
-                name: "one file missing statistics",
-                file_schema: Schema::new(vec![Field::new(
-                    "value".to_string(),
-                    DataType::Float64,
+                )])
+                .with_projection(Some(vec![0, 13]))
+                .with_file_groups(file_groups)
+                .with_declared_partitioning(PartitionKey::Column("region".to_string()))
+                .build()
+        };
+
+        // "asia" and "us" are hash partitioned into the first and second of two
+        // partitions
+        assert_eq!(hash_partition_index(&ScalarValue::from("asia"), 2)?, 0);
+        assert_eq!(hash_partition_index(&ScalarValue::from("us"), 2)?, 1);
+        let partitioned = config(vec![
+            FileGroup::new(vec![file("1", "asia")]),
+            FileGroup::new(vec![file("2", "us"), file("3", "us")]),
+        ]);
+        match partitioned.output_partitioning() {
+            Partitioning::Hash(exprs, 2) => {
+                let column = exprs[0].as_any().downcast_ref::<Column>().unwrap();
+                assert_eq!(column, &Column::new("region", 1));
+            }
+            other => panic!("expected hash partitioning, got {other:?}"),
+        }
+        assert!(partitioned.repartitioned(8, 0, None)?.is_none());
+
+        // the files regrouped by a preprocessor
+        let preprocessed = FileScanConfigBuilder::from(partitioned)
+            .with_file_preprocessor(Arc::new(|file_groups: Vec<FileGroup>| {
+                Ok(file_groups.into_iter().rev().collect())
+            }))
+            .build();
+        assert!(matches!(
+            preprocessed.output_partitioning(),
+            Partitioning::UnknownPartitioning(2)
+        ));
+
+        // a group with a value of another partition
+        let mixed = config(vec![
+            FileGroup::new(vec![file("1", "us"), file("2", "asia")]),
+            FileGroup::new(vec![file("3", "asia")]),
+        ]);
+        assert!(matches!(
+            mixed.output_partitioning(),
+            Partitioning::UnknownPartitioning(2)
+        ));
+
+        // a value in several groups
+        let split = config(vec![
+            FileGroup::new(vec![file("1", "us")]),
+            FileGroup::new(vec![file("2", "us")]),
+        ]);
+        assert!(matches!(
+            split.output_partitioning(),
+            Partitioning::UnknownPartitioning(2)
+        ));
+
+        // values in other groups than a hash partitioning would send them to
+        let swapped = config(vec![
+            FileGroup::new(vec![file("1", "us")]),
+            FileGroup::new(vec![file("2", "asia")]),
+        ]);
+        assert!(matches!(
+            swapped.output_partitioning(),
+            Partitioning::UnknownPartitioning(2)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_projection_except() -> Result<()> {
+        let builder = test_builder().with_table_partition_cols(vec![
+            Field::new("date", DataType::Utf8, false),
+            Field::new("hour", DataType::Int32, false),
+        ]);
+
+        let config = builder
+            .clone()
+            .with_projection(Some(vec![0]))
+            .with_projection_except(&["c2", "date"])?
+            .build();
+        let expected = (0..15).filter(|idx| ![1, 13].contains(idx)).collect();
+        assert_eq!(config.projection, Some(expected));
+        assert!(!columns(&config.projected_schema()).contains(&"c2".to_string()));
+
+        // the last projection wins
+        let config = builder
+            .clone()
+            .with_projection_except(&["c2", "date"])?
+            .with_projection(Some(vec![14]))
+            .build();
+        assert_eq!(config.projection, Some(vec![14]));
+
+        let err = builder.with_projection_except(&["c42"]).err().unwrap();
+        assert_contains!(
+            err.to_string(),
+            "Cannot exclude unknown column c42 from the projection"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_include_extra_columns() -> Result<()> {
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let with_extra = |name: &str| {
+            Arc::new(Schema::new(vec![
+                Field::new("a", DataType::Int32, false),
+                Field::new(name, DataType::Utf8, true),
+                Field::new("b", DataType::Int32, false),
+            ]))
+        };
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+        // project "date" and "a"
+        .with_projection(Some(vec![2, 0]));
+        assert_eq!(
+            builder.clone().build().extra_column_policy,
+            ExtraColumnPolicy::Ignore
+        );
+
+        let config = builder
+            .clone()
+            .include_extra_columns(&[with_extra("c"), with_extra("c")])?
+            .build();
+        assert_eq!(config.extra_column_policy, ExtraColumnPolicy::Include);
+        assert_eq!(columns(&config.file_schema), vec!["a", "b", "c"]);
+        // the projection still refers to the partition column
+        assert_eq!(columns(&config.projected_schema()), vec!["date", "a"]);
+        assert_eq!(config.projected_stats().column_statistics.len(), 2);
+
+        let err = builder
+            .include_extra_columns(&[with_extra("c"), with_extra("d")])
+            .err()
+            .unwrap();
+        assert_contains!(
+            err.to_string(),
+            "files have different extra columns: [c] and [d]"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_distinct_partition_values() {
+        let file = |path: &str, year: ScalarValue, region: &str| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = vec![year, ScalarValue::from(region)];
+            file
+        };
+        let dictionary = |value: &str| {
+            ScalarValue::Dictionary(
+                Box::new(DataType::UInt16),
+                Box::new(ScalarValue::from(value)),
+            )
+        };
+        let mut config = test_builder()
+            .with_table_partition_cols(vec![
+                Field::new("year", DataType::Int32, false),
+                Field::new(
+                    "region",
+                    DataType::Dictionary(
+                        Box::new(DataType::UInt16),
+                        Box::new(DataType::Utf8),
+                    ),
                     false,
-                )]),
-                files: vec![
-                    File::new("0", "2023-01-01", vec![Some((0.00, 0.49))]),
-                    File::new("1", "2023-01-01", vec![Some((0.00, 0.49))]),
-                    File::new("2", "2023-01-02", vec![None]),
-                ],
-                sort: vec![col("value").sort(true, false)],
-                expected_result: Err("construct min/max statistics for split_groups_by_statistics\ncaused by\ncollect min/max values\ncaused by\nget min/max for column: 'value'\ncaused by\nError during planning: statistics not found"),
-            },
+                ),
+            ])
+            .with_file_groups(vec![
+                FileGroup::new(vec![
+                    file("1.csv", ScalarValue::from(2024), "us"),
+                    file("2.csv", ScalarValue::from(2023), "eu"),
+                ]),
+                FileGroup::new(vec![
+                    file("3.csv", ScalarValue::from(2024), "eu"),
+                    file("4.csv", ScalarValue::from(2024), "apac"),
+                ]),
+            ])
+            .build();
+        // values may also be dictionary encoded
+        config.file_groups[1][1].partition_values[1] = dictionary("apac");
+        config.file_groups[0][1].partition_values[1] = dictionary("us");
+
+        let values = config.distinct_partition_values();
+        assert_eq!(values.len(), 2);
+        assert_eq!(
+            values["year"],
+            vec![ScalarValue::from(2023), ScalarValue::from(2024)]
+        );
+        assert_eq!(
+            values["region"],
+            vec![
+                ScalarValue::from("apac"),
+                ScalarValue::from("eu"),
+                ScalarValue::from("us")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_scan_config_duplicate_file_policy() {
+        let builder = test_builder().with_file_groups(vec![
+            FileGroup::new(vec![
+                PartitionedFile::new("a.csv", 100),
+                PartitionedFile::new("b.csv", 100),
+            ]),
+            FileGroup::new(vec![
+                PartitionedFile::new("a.csv", 100),
+                PartitionedFile::new("c.csv", 100),
+            ]),
+        ]);
+
+        // duplicates are kept by default
+        let config = builder.clone().build();
+        assert_eq!(config.duplicate_file_policy, DuplicateFilePolicy::Allow);
+        assert_eq!(
+            paths(&config),
+            vec![vec!["a.csv", "b.csv"], vec!["a.csv", "c.csv"]]
+        );
+        assert!(config.validate_no_duplicate_files().is_ok());
+
+        // only the first occurrence is kept
+        let config = builder
+            .clone()
+            .with_duplicate_file_policy(DuplicateFilePolicy::Dedup)
+            .build();
+        assert_eq!(paths(&config), vec![vec!["a.csv", "b.csv"], vec!["c.csv"]]);
+
+        // the policy is kept when converting back to a builder
+        let config = FileScanConfigBuilder::from(config).build();
+        assert_eq!(config.duplicate_file_policy, DuplicateFilePolicy::Dedup);
+
+        let config = builder
+            .with_duplicate_file_policy(DuplicateFilePolicy::Error)
+            .build();
+        assert_eq!(paths(&config).len(), 2);
+        let err = config.validate_no_duplicate_files().unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "Files are listed more than once in the file groups: a.csv"
+        );
+        let err = config.open(0, Arc::new(TaskContext::default())).err();
+        assert_contains!(err.unwrap().to_string(), "a.csv");
+    }
+
+    #[test]
+    fn test_file_scan_config_duplicate_file_ranges() {
+        let ranged =
+            |start, end| PartitionedFile::new_with_range("a.csv".into(), 300, start, end);
+        let builder =
+            test_builder().with_duplicate_file_policy(DuplicateFilePolicy::Dedup);
+        let ranges = |config: &FileScanConfig| {
+            config
+                .file_groups
+                .iter()
+                .flat_map(FileGroup::iter)
+                .map(|f| f.range.as_ref().map(|r| (r.start, r.end)))
+                .collect::<Vec<_>>()
+        };
+
+        // non overlapping ranges of the same file are not duplicates
+        let config = builder
+            .clone()
+            .with_file_groups(vec![
+                FileGroup::new(vec![ranged(0, 100)]),
+                FileGroup::new(vec![ranged(100, 200), ranged(200, 300)]),
+            ])
+            .build();
+        assert_eq!(
+            ranges(&config),
+            vec![Some((0, 100)), Some((100, 200)), Some((200, 300))]
+        );
+
+        // overlapping ranges are
+        let config = builder
+            .clone()
+            .with_file_groups(vec![
+                FileGroup::new(vec![ranged(0, 150)]),
+                FileGroup::new(vec![ranged(100, 200), ranged(200, 300)]),
+            ])
+            .build();
+        assert_eq!(ranges(&config), vec![Some((0, 150)), Some((200, 300))]);
+
+        // as is any range of a file that is also read as a whole
+        let config = builder
+            .clone()
+            .with_file_groups(vec![
+                FileGroup::new(vec![PartitionedFile::new("a.csv", 300)]),
+                FileGroup::new(vec![ranged(100, 200)]),
+            ])
+            .build();
+        assert_eq!(ranges(&config), vec![None]);
+
+        // files read in several ranges are only duplicates if any of their
+        // ranges overlap
+        let multi_ranged = |ranges: &[(i64, i64)]| {
+            PartitionedFile::new("a.csv", 300).with_ranges(
+                ranges
+                    .iter()
+                    .map(|(start, end)| FileRange {
+                        start: *start,
+                        end: *end,
+                    })
+                    .collect(),
+            )
+        };
+        let config = builder
+            .clone()
+            .with_file_groups(vec![
+                FileGroup::new(vec![multi_ranged(&[(0, 50), (200, 250)])]),
+                FileGroup::new(vec![multi_ranged(&[(50, 100), (250, 300)])]),
+            ])
+            .build();
+        assert_eq!(
+            config.file_groups.iter().map(FileGroup::len).sum::<usize>(),
+            2
+        );
+        assert_eq!(file_read_size(&config.file_groups[1][0]), 100);
+
+        let config = builder
+            .with_file_groups(vec![
+                FileGroup::new(vec![multi_ranged(&[(0, 50), (200, 250)])]),
+                FileGroup::new(vec![ranged(100, 210)]),
+            ])
+            .build();
+        assert_eq!(
+            config.file_groups.iter().map(FileGroup::len).sum::<usize>(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_file_scan_config_reverse_scan() {
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let ascending = LexOrdering::new(vec![
+            PhysicalSortExpr::new_default(Arc::new(Column::new("a", 0))),
+            PhysicalSortExpr::new_default(Arc::new(Column::new("b", 1))).desc(),
+        ])
+        .unwrap();
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_file_groups(vec![FileGroup::new(vec![PartitionedFile::new(
+            "1.parquet",
+            10,
+        )])])
+        .with_output_ordering(vec![ascending.clone()]);
+
+        let config = builder.clone().build();
+        assert!(!config.reverse_scan);
+        assert_eq!(
+            config.eq_properties().output_ordering(),
+            Some(ascending.clone())
+        );
+
+        // the ordering is reversed, with and without a projection
+        let reversed = LexOrdering::new(ascending.iter().map(|e| e.reverse())).unwrap();
+        let config = builder.clone().with_reverse_scan(true).build();
+        let config = FileScanConfigBuilder::from(config).build();
+        assert!(config.reverse_scan);
+        assert_eq!(
+            config.eq_properties().output_ordering(),
+            Some(reversed.clone())
+        );
+        assert!(config.satisfies_ordering(&reversed).unwrap());
+        assert!(!config.satisfies_ordering(&ascending).unwrap());
+
+        let config = builder
+            .with_projection(Some(vec![0, 1]))
+            .with_reverse_scan(true)
+            .build();
+        assert_eq!(config.eq_properties().output_ordering(), Some(reversed));
+    }
+
+    #[test]
+    fn test_file_scan_config_new_from_batch_schema() {
+        let batch =
+            build_table_i32(("a", &vec![1, 2]), ("b", &vec![3, 4]), ("c", &vec![5, 6]));
+        let config = FileScanConfigBuilder::new_from_batch_schema(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            &batch,
+            Arc::new(MockSource::default()),
+        )
+        .build();
+        assert!(Arc::ptr_eq(&config.file_schema, &batch.schema()));
+        assert_eq!(columns(&config.projected_schema()), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_file_scan_config_partition_encoding() -> Result<()> {
+        let file_batch = build_table_i32(
+            ("a", &vec![0, 1, 2]),
+            ("b", &vec![-2, -1, 0]),
+            ("c", &vec![10, 11, 12]),
+        );
+        let builder = FileScanConfigBuilder::new_from_batch_schema(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            &file_batch,
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![
+            Field::new("year", wrap_partition_type_in_dict(DataType::Utf8), false),
+            Field::new("month", DataType::Utf8, false),
+        ])
+        .with_projection(Some(vec![0, 3, 4]));
+        let partition_values = [
+            wrap_partition_value_in_dict(ScalarValue::from("2021")),
+            ScalarValue::from("10"),
+        ];
+
+        let expected = [
+            "+---+------+-------+",
+            "| a | year | month |",
+            "+---+------+-------+",
+            "| 0 | 2021 | 10    |",
+            "| 1 | 2021 | 10    |",
+            "| 2 | 2021 | 10    |",
+            "+---+------+-------+",
+        ];
+        for encoding in [
+            None,
+            Some(PartitionEncoding::Dictionary),
+            Some(PartitionEncoding::RunEnd),
+            Some(PartitionEncoding::Decoded),
+        ] {
+            let builder = match encoding {
+                Some(encoding) => builder.clone().with_partition_encoding(encoding),
+                None => builder.clone(),
+            };
+            let config = builder.build();
+            let schema = config.projected_schema();
+            match encoding {
+                None => assert_eq!(schema.field(2).data_type(), &DataType::Utf8),
+                Some(encoding) => {
+                    for field in &schema.fields()[1..] {
+                        assert_eq!(
+                            field.data_type(),
+                            &encoding.data_type(&DataType::Utf8)
+                        );
+                    }
+                }
+            }
+
+            let mut projector = PartitionColumnProjector::new(
+                schema,
+                &["year".to_string(), "month".to_string()],
+            );
+            let batch =
+                projector.project(file_batch.project(&[0])?, &partition_values)?;
+            assert_batches_eq!(expected, &[batch]);
+
+            // empty batches are supported as well
+            let batch = projector
+                .project(file_batch.project(&[0])?.slice(0, 0), &partition_values)?;
+            assert_eq!(batch.num_rows(), 0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_list_partition_values() -> Result<()> {
+        let file_batch = build_table_i32(
+            ("a", &vec![0, 1, 2]),
+            ("b", &vec![-2, -1, 0]),
+            ("c", &vec![10, 11, 12]),
+        );
+        let tags = ScalarValue::List(ScalarValue::new_list_nullable(
+            &[ScalarValue::from("red"), ScalarValue::from("blue")],
+            &DataType::Utf8,
+        ));
+        let build_config = |tags_type: DataType| {
+            let mut file = PartitionedFile::new("tagged.parquet", 10);
+            file.partition_values = vec![tags.clone()];
+            FileScanConfigBuilder::new_from_batch_schema(
+                ObjectStoreUrl::parse("test:///").unwrap(),
+                &file_batch,
+                Arc::new(MockSource::default()),
+            )
+            .with_table_partition_cols(vec![Field::new("tags", tags_type, false)])
+            .with_projection(Some(vec![3, 0]))
+            .with_file(file)
+            .build()
+        };
+
+        let config = build_config(DataType::new_list(DataType::Utf8, true));
+        config.validate()?;
+        let mut projector = PartitionColumnProjector::new(
+            config.projected_schema(),
+            &["tags".to_string()],
+        );
+        let batch = projector.project(file_batch.project(&[0])?, &[tags.clone()])?;
+        #[rustfmt::skip]
+        assert_batches_eq!(&[
+            "+-------------+---+",
+            "| tags        | a |",
+            "+-------------+---+",
+            "| [red, blue] | 0 |",
+            "| [red, blue] | 1 |",
+            "| [red, blue] | 2 |",
+            "+-------------+---+",
+        ], &[batch]);
+
+        // list values require a list partition column of the same type
+        let err = build_config(DataType::Utf8).validate().unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains(
+            "Partition value of column tags of file tagged.parquet is a List("
+        ));
+        assert!(message.ends_with("but the column is declared as Utf8"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_decode_partition_columns() -> Result<()> {
+        use arrow::array::AsArray;
+
+        let file_batch = build_table_i32(
+            ("a", &vec![0, 1, 2]),
+            ("b", &vec![-2, -1, 0]),
+            ("c", &vec![10, 11, 12]),
+        );
+        let builder = FileScanConfigBuilder::new_from_batch_schema(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            &file_batch,
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![Field::new(
+            "year",
+            wrap_partition_type_in_dict(DataType::Utf8),
+            false,
+        )])
+        .with_projection(Some(vec![0, 3]));
+        let partition_values = [wrap_partition_value_in_dict(ScalarValue::from("2021"))];
+        let project = |config: FileScanConfig| {
+            let mut projector = PartitionColumnProjector::new(
+                config.projected_schema(),
+                &["year".to_string()],
+            );
+            projector.project(file_batch.project(&[0])?, &partition_values)
+        };
+
+        let dictionary = project(builder.clone().build())?;
+        let decoded =
+            project(builder.clone().with_decode_partition_columns(true).build())?;
+
+        // the same values, with a single dictionary value or one per row
+        let dictionary_array = dictionary.column(1).as_dictionary::<UInt16Type>();
+        assert_eq!(dictionary_array.values().len(), 1);
+        assert_eq!(decoded.column(1).data_type(), &DataType::Utf8);
+        assert_eq!(decoded.column(1).len(), 3);
+        assert_eq!(
+            &cast(dictionary.column(1), &DataType::Utf8)?,
+            decoded.column(1)
+        );
+
+        // not decoding keeps the declared type
+        let config = builder.clone().with_decode_partition_columns(false).build();
+        assert_eq!(
+            config.projected_schema().field(1).data_type(),
+            &wrap_partition_type_in_dict(DataType::Utf8)
+        );
+
+        // not decoding keeps another encoding, whichever is set first
+        let run_end = PartitionEncoding::RunEnd
+            .data_type(&wrap_partition_type_in_dict(DataType::Utf8));
+        let data_type = |builder: FileScanConfigBuilder| {
+            builder
+                .build()
+                .projected_schema()
+                .field(1)
+                .data_type()
+                .clone()
+        };
+        let encoded = builder
+            .clone()
+            .with_partition_encoding(PartitionEncoding::RunEnd)
+            .with_decode_partition_columns(false);
+        assert_eq!(data_type(encoded), run_end);
+        let encoded = builder
+            .clone()
+            .with_decode_partition_columns(false)
+            .with_partition_encoding(PartitionEncoding::RunEnd);
+        assert_eq!(data_type(encoded), run_end);
+
+        // the encoding set last applies
+        let decoded = builder
+            .clone()
+            .with_partition_encoding(PartitionEncoding::RunEnd)
+            .with_decode_partition_columns(true);
+        assert_eq!(data_type(decoded), DataType::Utf8);
+        let encoded = builder
+            .with_decode_partition_columns(true)
+            .with_partition_encoding(PartitionEncoding::RunEnd);
+        assert_eq!(data_type(encoded), run_end);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_effective_projection() {
+        let file_schema = aggr_test_schema();
+        let num_columns = file_schema.fields().len() + 1;
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            Arc::clone(&file_schema),
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![Field::new(
+            "date",
+            DataType::Utf8,
+            false,
+        )]);
+        let projected_names = |config: &FileScanConfig| {
+            config
+                .effective_projection()
+                .into_iter()
+                .map(|i| match file_schema.fields().get(i) {
+                    Some(field) => field.name().clone(),
+                    None => config.table_partition_cols[i - file_schema.fields().len()]
+                        .name()
+                        .clone(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // all file and partition columns without a projection
+        let config = builder.clone().build();
+        assert_eq!(
+            config.effective_projection(),
+            (0..num_columns).collect::<Vec<_>>()
+        );
+        let (schema, ..) = config.project();
+        assert_eq!(projected_names(&config), columns(&schema));
+
+        let config = builder
+            .with_projection(Some(vec![num_columns - 1, 2]))
+            .build();
+        assert_eq!(config.effective_projection(), vec![num_columns - 1, 2]);
+        let (schema, ..) = config.project();
+        assert_eq!(projected_names(&config), columns(&schema));
+        assert_eq!(columns(&schema), vec!["date", "c3"]);
+    }
+
+    #[test]
+    fn test_file_scan_config_validate_partition_values() {
+        let file_schema = aggr_test_schema();
+        let partition_cols = vec![
+            Field::new("year", DataType::Utf8, false),
+            Field::new("month", DataType::Utf8, false),
         ];
+        let file = |path: &str, partition_values: Vec<ScalarValue>| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = partition_values;
+            file
+        };
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(partition_cols)
+        .with_file_group(FileGroup::new(vec![
+            file("year=2024/month=01/a.csv", vec!["2024".into(), "01".into()]),
+            file("year=2024/b.csv", vec!["2024".into()]),
+        ]))
+        .build();
 
-        for case in cases {
-            let table_schema = Arc::new(Schema::new(
-                case.file_schema
-                    .fields()
-                    .clone()
-                    .into_iter()
-                    .cloned()
-                    .chain(Some(Arc::new(Field::new(
-                        "date".to_string(),
-                        DataType::Utf8,
-                        false,
-                    ))))
-                    .collect::<Vec<_>>(),
-            ));
-            let Some(sort_order) = LexOrdering::new(
-                case.sort
-                    .into_iter()
-                    .map(|expr| {
-                        create_physical_sort_expr(
-                            &expr,
-                            &DFSchema::try_from(table_schema.as_ref().clone())?,
-                            &ExecutionProps::default(),
-                        )
-                    })
-                    .collect::<Result<Vec<_>>>()?,
-            ) else {
-                return internal_err!("This test should always use an ordering");
-            };
+        let err = config.validate().unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "File year=2024/b.csv has 1 partition values, but 2 table partition columns are declared: [year, month]"
+        );
+        let err = config.open(0, Arc::new(TaskContext::default())).err();
+        assert_contains!(err.unwrap().to_string(), "year=2024/b.csv");
 
-            let partitioned_files = FileGroup::new(
-                case.files.into_iter().map(From::from).collect::<Vec<_>>(),
-            );
-            let result = FileScanConfig::split_groups_by_statistics(
-                &table_schema,
-                &[partitioned_files.clone()],
-                &sort_order,
-            );
-            let results_by_name = result
-                .as_ref()
-                .map(|file_groups| {
-                    file_groups
-                        .iter()
-                        .map(|file_group| {
-                            file_group
-                                .iter()
-                                .map(|file| {
-                                    partitioned_files
-                                        .iter()
-                                        .find_map(|f| {
-                                            if f.object_meta == file.object_meta {
-                                                Some(
-                                                    f.object_meta
-                                                        .location
-                                                        .as_ref()
-                                                        .rsplit('/')
-                                                        .next()
-                                                        .unwrap()
-                                                        .trim_end_matches(".parquet"),
-                                                )
-                                            } else {
-                                                None
-                                            }
-                                        })
-                                        .unwrap()
-                                })
-                                .collect::<Vec<_>>()
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .map_err(|e| e.strip_backtrace().leak() as &'static str);
+        let config = FileScanConfigBuilder::from(config)
+            .with_file_groups(vec![FileGroup::new(vec![file(
+                "year=2024/month=01/a.csv",
+                vec!["2024".into(), "01".into()],
+            )])])
+            .build();
+        assert!(config.validate().is_ok());
+    }
 
-            assert_eq!(results_by_name, case.expected_result, "{}", case.name);
-        }
+    #[test]
+    fn test_file_scan_config_partition_timestamp_normalization() {
+        let file = |path: &str, value: ScalarValue| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = vec![value];
+            file
+        };
+        let files = vec![
+            file("ts=2021-01-01/a.csv", ScalarValue::from("2021-01-01")),
+            file("ts=2021-01-01T00:00:00/b.csv", "2021-01-01T00:00:00".into()),
+            file(
+                "c.csv",
+                ScalarValue::TimestampMillisecond(Some(1609459200500), None),
+            ),
+            file("ts=2021-01-02/d.csv", ScalarValue::from("2021-01-02")),
+            file("ts=yesterday/e.csv", ScalarValue::from("yesterday")),
+        ];
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            Arc::new(Schema::new(vec![Field::new("c1", DataType::Int32, true)])),
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![Field::new(
+            "ts",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            true,
+        )])
+        .with_file_group(FileGroup::new(files));
+        let values = |config: &FileScanConfig| {
+            config.file_groups[0]
+                .iter()
+                .map(|file| file.partition_values[0].clone())
+                .collect::<Vec<_>>()
+        };
 
-        return Ok(());
+        // the values are left as is by default
+        let config = builder.clone().build();
+        assert_eq!(values(&config)[0], ScalarValue::from("2021-01-01"));
 
-        impl From<File> for PartitionedFile {
-            fn from(file: File) -> Self {
-                PartitionedFile {
-                    object_meta: ObjectMeta {
-                        location: Path::from(format!(
-                            "data/date={}/{}.parquet",
-                            file.date, file.name
-                        )),
-                        last_modified: chrono::Utc.timestamp_nanos(0),
-                        size: 0,
-                        e_tag: None,
-                        version: None,
-                    },
-                    partition_values: vec![ScalarValue::from(file.date)],
-                    range: None,
-                    statistics: Some(Arc::new(Statistics {
-                        num_rows: Precision::Absent,
+        let config = builder
+            .with_partition_timestamp_normalization(TimeUnit::Second)
+            .build();
+        assert_eq!(
+            config.table_partition_cols[0].data_type(),
+            &DataType::Timestamp(TimeUnit::Second, None)
+        );
+        let day = |seconds| ScalarValue::TimestampSecond(seconds, None);
+        assert_eq!(
+            values(&config),
+            [
+                day(Some(1609459200)),
+                day(Some(1609459200)),
+                day(Some(1609459200)),
+                day(Some(1609545600)),
+                // values that are not timestamps become null
+                day(None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_scan_config_derived_partition_column() -> Result<()> {
+        let file = |region: &str, zone: &str| {
+            let mut file =
+                PartitionedFile::new(format!("region={region}/zone={zone}/a.csv"), 10)
+                    .with_statistics(Arc::new(Statistics {
+                        num_rows: Precision::Exact(2),
                         total_byte_size: Precision::Absent,
-                        column_statistics: file
-                            .statistics
-                            .into_iter()
-                            .map(|stats| {
-                                stats
-                                    .map(|(min, max)| ColumnStatistics {
-                                        min_value: Precision::Exact(ScalarValue::from(
-                                            min,
-                                        )),
-                                        max_value: Precision::Exact(ScalarValue::from(
-                                            max,
-                                        )),
-                                        ..Default::default()
-                                    })
-                                    .unwrap_or_default()
-                            })
-                            .collect::<Vec<_>>(),
-                    })),
-                    extensions: None,
-                    metadata_size_hint: None,
-                }
+                        column_statistics: vec![],
+                    }));
+            file.partition_values = vec![region.into(), zone.into()];
+            file
+        };
+        let region_zone: DerivePartitionValue = Arc::new(|values| {
+            Ok(ScalarValue::from(format!("{}_{}", values[0], values[1])))
+        });
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            Arc::new(Schema::new(vec![Field::new("c1", DataType::Int32, true)])),
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![
+            Field::new("region", DataType::Utf8, false),
+            Field::new("zone", DataType::Utf8, false),
+        ])
+        .with_derived_partition_column(
+            Field::new("region_zone", DataType::Utf8, false),
+            region_zone,
+        )
+        .with_file_group(FileGroup::new(vec![file("us", "east"), file("eu", "west")]))
+        .with_projection(Some(vec![3]))
+        .build();
+
+        assert_eq!(config.table_partition_cols.len(), 3);
+        assert_eq!(
+            config.file_groups[0][1].partition_values[2],
+            ScalarValue::from("eu_west")
+        );
+        assert!(config.validate().is_ok());
+
+        let batches = datafusion_physical_plan::common::collect(
+            config.open(0, Arc::new(TaskContext::default()))?,
+        )
+        .await?;
+        assert_batches_eq!(
+            [
+                "+-------------+",
+                "| region_zone |",
+                "+-------------+",
+                "| us_east     |",
+                "| us_east     |",
+                "| eu_west     |",
+                "| eu_west     |",
+                "+-------------+",
+            ],
+            &batches
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_prune_files_by_filter() -> Result<()> {
+        use datafusion_expr::Operator;
+        use datafusion_physical_expr::expressions::{binary, col, lit};
+
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("c1", DataType::Int32, true)]));
+        let file = |name: &str, range: Option<(i32, i32)>| {
+            let file = PartitionedFile::new(name, 10);
+            match range {
+                Some((min, max)) => file.with_statistics(Arc::new(Statistics {
+                    num_rows: Precision::Exact(10),
+                    total_byte_size: Precision::Absent,
+                    column_statistics: vec![ColumnStatistics {
+                        min_value: Precision::Exact(ScalarValue::from(min)),
+                        max_value: Precision::Exact(ScalarValue::from(max)),
+                        ..Default::default()
+                    }],
+                })),
+                None => file,
+            }
+        };
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            Arc::clone(&file_schema),
+            Arc::new(MockSource::default()),
+        )
+        .with_file_groups(vec![
+            FileGroup::new(vec![file("a", Some((0, 10))), file("b", Some((20, 30)))]),
+            FileGroup::new(vec![file("c", Some((5, 15))), file("d", None)]),
+        ])
+        .build();
+        let predicate = |op, value: i32| {
+            binary(col("c1", &file_schema)?, op, lit(value), &file_schema)
+        };
+
+        // "a" and "c" can not contain values above 15, "d" has no statistics
+        let (pruned, num_pruned) =
+            config.prune_files_by_filter(&predicate(Operator::Gt, 15)?)?;
+        assert_eq!(num_pruned, 2);
+        assert_eq!(paths(&pruned), vec![vec!["b"], vec!["d"]]);
+
+        // range predicate on both bounds
+        let between = binary(
+            predicate(Operator::GtEq, 12)?,
+            Operator::And,
+            predicate(Operator::Lt, 20)?,
+            &file_schema,
+        )?;
+        let (pruned, num_pruned) = config.prune_files_by_filter(&between)?;
+        assert_eq!(num_pruned, 2);
+        assert_eq!(paths(&pruned), vec![vec![], vec!["c", "d"]]);
+
+        // nothing is pruned if every file may match
+        let (pruned, num_pruned) =
+            config.prune_files_by_filter(&predicate(Operator::GtEq, 0)?)?;
+        assert_eq!(num_pruned, 0);
+        assert_eq!(paths(&pruned), paths(&config));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_stats_columns() -> Result<()> {
+        use datafusion_expr::Operator;
+        use datafusion_physical_expr::expressions::{binary, col, lit};
+
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+            Field::new("c", DataType::Int32, true),
+        ]));
+        let column_statistics = |value: i32| ColumnStatistics {
+            min_value: Precision::Exact(ScalarValue::from(value)),
+            max_value: Precision::Exact(ScalarValue::from(value)),
+            ..Default::default()
+        };
+        let statistics = Statistics {
+            num_rows: Precision::Exact(10),
+            total_byte_size: Precision::Absent,
+            column_statistics: vec![
+                column_statistics(1),
+                column_statistics(2),
+                column_statistics(3),
+            ],
+        };
+        let file = |name: &str, date: &str| {
+            let mut file = PartitionedFile::new(name, 10)
+                .with_statistics(Arc::new(statistics.clone()));
+            file.partition_values = vec![ScalarValue::from(date)];
+            file
+        };
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            Arc::clone(&file_schema),
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+        .with_file(file("x", "2024-01-01"))
+        .with_file(file("y", "2024-01-02"))
+        .with_statistics(statistics.clone());
+
+        // only the statistics of "b" and "date" are populated
+        let config = builder.clone().with_stats_columns(Some(vec![1, 3])).build();
+        let stats = config.projected_stats();
+        assert_eq!(stats.num_rows, Precision::Exact(10));
+        assert_eq!(
+            stats.column_statistics,
+            vec![
+                ColumnStatistics::new_unknown(),
+                column_statistics(2),
+                ColumnStatistics::new_unknown(),
+                ColumnStatistics::new_unknown().with_distinct_count(Precision::Exact(2)),
+            ]
+        );
+
+        // the statistics of "a" are not used to prune files either
+        let predicate =
+            binary(col("a", &file_schema)?, Operator::Gt, lit(5), &file_schema)?;
+        let (_, num_pruned) = builder.build().prune_files_by_filter(&predicate)?;
+        assert_eq!(num_pruned, 2);
+        let (_, num_pruned) = config.prune_files_by_filter(&predicate)?;
+        assert_eq!(num_pruned, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_with_column_statistics() -> Result<()> {
+        let builder = test_builder();
+        let c3_statistics = ColumnStatistics::new_unknown()
+            .with_min_value(Precision::Exact(ScalarValue::Int8(Some(-117))))
+            .with_max_value(Precision::Exact(ScalarValue::Int8(Some(125))));
+
+        let config = builder
+            .clone()
+            .with_column_statistics("c3", c3_statistics.clone())?
+            .build();
+        let statistics = config.file_source.statistics()?;
+        assert_eq!(statistics.num_rows, Precision::Absent);
+        assert_eq!(statistics.column_statistics.len(), 13);
+        for (idx, column_statistics) in statistics.column_statistics.iter().enumerate() {
+            if idx == 2 {
+                assert_eq!(column_statistics, &c3_statistics);
+            } else {
+                assert_eq!(column_statistics, &ColumnStatistics::new_unknown());
             }
         }
+
+        // the other statistics are kept
+        let config = builder
+            .clone()
+            .with_statistics(
+                Statistics::new_unknown(&aggr_test_schema())
+                    .with_num_rows(Precision::Exact(100)),
+            )
+            .with_column_statistics("c3", c3_statistics.clone())?
+            .build();
+        let statistics = config.file_source.statistics()?;
+        assert_eq!(statistics.num_rows, Precision::Exact(100));
+        assert_eq!(statistics.column_statistics[2], c3_statistics);
+
+        let err = builder
+            .with_column_statistics("c42", c3_statistics)
+            .err()
+            .unwrap();
+        assert_contains!(
+            err.to_string(),
+            "Cannot set the statistics of unknown column c42"
+        );
+        Ok(())
     }
 
-    // sets default for configs that play no role in projections
-    fn config_for_projection(
-        file_schema: SchemaRef,
-        projection: Option<Vec<usize>>,
-        statistics: Statistics,
-        table_partition_cols: Vec<Field>,
-    ) -> FileScanConfig {
-        FileScanConfigBuilder::new(
+    #[test]
+    fn test_file_scan_config_order_preserving_repartition() -> Result<()> {
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let file = |path: &str, min: i32, max: i32| {
+            PartitionedFile::new(path, 100).with_statistics(Arc::new(Statistics {
+                num_rows: Precision::Absent,
+                total_byte_size: Precision::Absent,
+                column_statistics: vec![ColumnStatistics::new_unknown()
+                    .with_min_value(Precision::Exact(ScalarValue::from(min)))
+                    .with_max_value(Precision::Exact(ScalarValue::from(max)))],
+            }))
+        };
+        let ordering: LexOrdering =
+            [PhysicalSortExpr::new_default(Arc::new(Column::new("a", 0)))].into();
+        let config = |order_preserving: bool, files: Vec<PartitionedFile>| {
+            FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("test:///").unwrap(),
+                Arc::clone(&file_schema),
+                Arc::new(MockSource::default()),
+            )
+            .with_projection(Some(vec![0]))
+            .with_file_groups(vec![FileGroup::new(files)])
+            .with_output_ordering(vec![ordering.clone()])
+            .with_order_preserving_repartition(order_preserving)
+            .build()
+        };
+        let repartition = |config: FileScanConfig| -> Result<FileScanConfig> {
+            let repartitioned = config
+                .repartitioned(2, 0, config.eq_properties().output_ordering())?
+                .unwrap();
+            Ok(repartitioned
+                .as_any()
+                .downcast_ref::<FileScanConfig>()
+                .unwrap()
+                .clone())
+        };
+        let paths = |config: &FileScanConfig| {
+            config
+                .file_groups
+                .iter()
+                .map(|group| {
+                    group
+                        .iter()
+                        .map(|file| file.path().to_string())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let files = vec![
+            file("c", 20, 29),
+            file("a", 0, 9),
+            file("d", 30, 39),
+            file("b", 10, 19),
+        ];
+
+        // the files are split by their statistics, ordering every group
+        let repartitioned = repartition(config(true, files.clone()))?;
+        assert_eq!(paths(&repartitioned), vec![vec!["a", "c"], vec!["b", "d"]]);
+        assert!(repartitioned.eq_properties().output_ordering().is_some());
+
+        // the files are split by size, keeping the unordered file sequence
+        let repartitioned = repartition(config(false, files.clone()))?;
+        assert_eq!(paths(&repartitioned), vec![vec!["c", "a"], vec!["d", "b"]]);
+        assert!(repartitioned.eq_properties().output_ordering().is_none());
+
+        // without statistics, the files are split by size
+        let mut files = files;
+        files[0].statistics = None;
+        let repartitioned = repartition(config(true, files))?;
+        assert_eq!(paths(&repartitioned), vec![vec!["c", "a"], vec!["d", "b"]]);
+        Ok(())
+    }
+
+    fn merge_small_groups_paths(
+        config: &FileScanConfig,
+        min_group_bytes: u64,
+    ) -> Vec<Vec<String>> {
+        paths(&config.merge_small_groups(min_group_bytes))
+    }
+
+    #[test]
+    fn test_file_scan_config_merge_small_groups_unordered() {
+        let config = test_builder()
+            .with_file_groups(vec![
+                FileGroup::new(vec![PartitionedFile::new("a", 10)]),
+                FileGroup::new(vec![PartitionedFile::new("b", 20)]),
+                FileGroup::new(vec![PartitionedFile::new("c", 100)]),
+                FileGroup::new(vec![PartitionedFile::new("d", 30)]),
+                FileGroup::new(vec![PartitionedFile::new("e", 40)]),
+            ])
+            .build();
+
+        assert_eq!(
+            merge_small_groups_paths(&config, 50),
+            vec![vec!["a", "b"], vec!["c"], vec!["d"], vec!["e"]]
+        );
+        assert_eq!(
+            merge_small_groups_paths(&config, 130),
+            vec![vec!["a", "b", "c"], vec!["d", "e"]]
+        );
+        // nothing to merge
+        assert_eq!(merge_small_groups_paths(&config, 0).len(), 5);
+
+        // groups tied to a hash partitioning are kept as they are
+        let config = FileScanConfigBuilder::from(config)
+            .with_declared_partitioning(PartitionKey::Column("c1".to_string()))
+            .build();
+        assert_eq!(merge_small_groups_paths(&config, 130).len(), 5);
+    }
+
+    #[test]
+    fn test_file_scan_config_merge_small_groups_ordered() {
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let file = |path: &str, min: i32, max: i32| {
+            PartitionedFile::new(path, 10).with_statistics(Arc::new(Statistics {
+                num_rows: Precision::Absent,
+                total_byte_size: Precision::Absent,
+                column_statistics: vec![ColumnStatistics::new_unknown()
+                    .with_min_value(Precision::Exact(ScalarValue::from(min)))
+                    .with_max_value(Precision::Exact(ScalarValue::from(max)))],
+            }))
+        };
+        let ordering: LexOrdering =
+            [PhysicalSortExpr::new_default(Arc::new(Column::new("a", 0)))].into();
+        let config = FileScanConfigBuilder::new(
             ObjectStoreUrl::parse("test:///").unwrap(),
             file_schema,
             Arc::new(MockSource::default()),
         )
-        .with_projection(projection)
-        .with_statistics(statistics)
-        .with_table_partition_cols(table_partition_cols)
-        .build()
+        .with_file_groups(vec![
+            FileGroup::new(vec![file("a", 0, 9)]),
+            FileGroup::new(vec![file("b", 10, 19)]),
+            // overlaps with "b"
+            FileGroup::new(vec![file("c", 15, 29)]),
+            FileGroup::new(vec![file("d", 30, 39)]),
+        ])
+        .with_output_ordering(vec![ordering])
+        .build();
+
+        // only groups that stay ordered are merged
+        assert_eq!(
+            merge_small_groups_paths(&config, 100),
+            vec![vec!["a", "b"], vec!["c", "d"]]
+        );
     }
 
-    /// Convert partition columns from Vec<String DataType> to Vec<Field>
-    fn to_partition_cols(table_partition_cols: Vec<(String, DataType)>) -> Vec<Field> {
-        table_partition_cols
-            .iter()
-            .map(|(name, dtype)| Field::new(name, dtype.clone(), false))
-            .collect::<Vec<_>>()
+    #[test]
+    fn test_file_scan_config_output_row_bounds() {
+        let file = |path: &str, num_rows: Precision<usize>| {
+            let mut statistics = Statistics::new_unknown(&aggr_test_schema());
+            statistics.num_rows = num_rows;
+            PartitionedFile::new(path, 100).with_statistics(Arc::new(statistics))
+        };
+        let bounds = |files: Vec<PartitionedFile>, limit: Option<usize>| {
+            test_builder()
+                .with_file_group(FileGroup::new(files))
+                .with_limit(limit)
+                .build()
+                .output_row_bounds()
+        };
+        let exact = vec![
+            file("a", Precision::Exact(10)),
+            file("b", Precision::Exact(20)),
+        ];
+        let mixed = vec![
+            file("a", Precision::Exact(10)),
+            file("b", Precision::Inexact(20)),
+        ];
+        let absent = vec![
+            file("a", Precision::Exact(10)),
+            file("b", Precision::Absent),
+        ];
+
+        assert_eq!(
+            bounds(exact.clone(), None),
+            (Precision::Exact(30), Precision::Exact(30))
+        );
+        assert_eq!(
+            bounds(exact, Some(15)),
+            (Precision::Exact(15), Precision::Exact(15))
+        );
+        assert_eq!(
+            bounds(mixed.clone(), None),
+            (Precision::Inexact(30), Precision::Inexact(30))
+        );
+        assert_eq!(
+            bounds(mixed, Some(15)),
+            (Precision::Inexact(15), Precision::Inexact(15))
+        );
+        assert_eq!(
+            bounds(absent.clone(), None),
+            (Precision::Exact(10), Precision::Absent)
+        );
+        // the limit bounds an unknown number of rows
+        assert_eq!(
+            bounds(absent, Some(15)),
+            (Precision::Exact(10), Precision::Exact(15))
+        );
+        assert_eq!(
+            bounds(vec![], Some(15)),
+            (Precision::Exact(0), Precision::Exact(0))
+        );
     }
 
-    /// returns record batch with 3 columns of i32 in memory
-    pub fn build_table_i32(
-        a: (&str, &Vec<i32>),
-        b: (&str, &Vec<i32>),
-        c: (&str, &Vec<i32>),
-    ) -> RecordBatch {
-        let schema = Schema::new(vec![
-            Field::new(a.0, DataType::Int32, false),
-            Field::new(b.0, DataType::Int32, false),
-            Field::new(c.0, DataType::Int32, false),
-        ]);
+    #[test]
+    fn test_file_scan_config_widen_projection_for_filter() -> Result<()> {
+        use datafusion_expr::Operator;
+        use datafusion_physical_expr::expressions::{binary, col, lit};
 
-        RecordBatch::try_new(
-            Arc::new(schema),
-            vec![
-                Arc::new(Int32Array::from(a.1.clone())),
-                Arc::new(Int32Array::from(b.1.clone())),
-                Arc::new(Int32Array::from(c.1.clone())),
-            ],
-        )
-        .unwrap()
+        let config = test_builder()
+            .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+            .with_projection(Some(vec![2, 0]))
+            .build();
+        let table_schema = config.table_schema();
+        let predicate = binary(
+            binary(
+                col("c4", &table_schema)?,
+                Operator::Gt,
+                col("c1", &table_schema)?,
+                &table_schema,
+            )?,
+            Operator::And,
+            binary(
+                col("date", &table_schema)?,
+                Operator::Eq,
+                lit("2024-01-01"),
+                &table_schema,
+            )?,
+            &table_schema,
+        )?;
+
+        // c1 is already projected, c4 and date are appended
+        let widened = config.widen_projection_for_filter(&predicate)?;
+        assert_eq!(widened.projection, Some(vec![2, 0, 3, 13]));
+        let names = widened
+            .projected_schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["c3", "c1", "c4", "date"]);
+
+        // widening again does not add duplicates
+        let widened = widened.widen_projection_for_filter(&predicate)?;
+        assert_eq!(widened.projection, Some(vec![2, 0, 3, 13]));
+
+        let unknown = binary(
+            Arc::new(Column::new("missing", 0)),
+            Operator::Eq,
+            lit(1),
+            &Schema::new(vec![Field::new("missing", DataType::Int32, true)]),
+        )?;
+        assert!(config.widen_projection_for_filter(&unknown).is_err());
+        Ok(())
     }
 
     #[test]
-    fn test_file_scan_config_builder() {
-        let file_schema = aggr_test_schema();
-        let object_store_url = ObjectStoreUrl::parse("test:///").unwrap();
-        let file_source: Arc<dyn FileSource> = Arc::new(MockSource::default());
+    fn test_file_scan_config_filter_files_modified_after() -> Result<()> {
+        use chrono::{TimeZone, Utc};
+
+        let file = |path: &str, hour: u32| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.object_meta.last_modified =
+                Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap();
+            file
+        };
+        let config = test_builder()
+            .with_file_groups(vec![
+                FileGroup::new(vec![file("a", 1), file("b", 3)]),
+                FileGroup::new(vec![file("c", 2)]),
+            ])
+            .with_statistics(
+                Statistics::new_unknown(&aggr_test_schema())
+                    .with_num_rows(Precision::Exact(30)),
+            )
+            .build();
 
-        // Create a builder with required parameters
-        let builder = FileScanConfigBuilder::new(
-            object_store_url.clone(),
-            Arc::clone(&file_schema),
-            Arc::clone(&file_source),
-        );
+        let watermark = Utc.with_ymd_and_hms(2024, 1, 1, 2, 0, 0).unwrap();
+        let (filtered, num_retained) = config.filter_files_modified_after(watermark)?;
+        assert_eq!(num_retained, 1);
+        // files modified exactly at the watermark are dropped
+        assert_eq!(paths(&filtered), vec![vec!["b".to_string()], vec![]]);
+        assert_eq!(filtered.statistics()?.num_rows, Precision::Inexact(30));
+
+        // nothing is dropped with an earlier watermark
+        let watermark = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let (filtered, num_retained) = config.filter_files_modified_after(watermark)?;
+        assert_eq!(num_retained, 3);
+        assert_eq!(filtered.statistics()?.num_rows, Precision::Exact(30));
+        Ok(())
+    }
 
-        // Build with various configurations
-        let config = builder
-            .with_limit(Some(1000))
-            .with_projection(Some(vec![0, 1]))
-            .with_table_partition_cols(vec![Field::new(
-                "date",
-                wrap_partition_type_in_dict(DataType::Utf8),
-                false,
-            )])
-            .with_statistics(Statistics::new_unknown(&file_schema))
-            .with_file_groups(vec![FileGroup::new(vec![PartitionedFile::new(
-                "test.parquet".to_string(),
-                1024,
-            )])])
-            .with_output_ordering(vec![[PhysicalSortExpr::new_default(Arc::new(
-                Column::new("date", 0),
-            ))]
-            .into()])
-            .with_file_compression_type(FileCompressionType::UNCOMPRESSED)
-            .with_newlines_in_values(true)
+    #[test]
+    fn test_file_scan_config_retain_partitions() -> Result<()> {
+        let file = |path: &str, year: i32, region: &str| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = vec![
+                ScalarValue::Int32(Some(year)),
+                wrap_partition_value_in_dict(ScalarValue::from(region)),
+            ];
+            file
+        };
+        let config = test_builder()
+            .with_table_partition_cols(vec![
+                Field::new("year", DataType::Int32, false),
+                Field::new("region", wrap_partition_type_in_dict(DataType::Utf8), false),
+            ])
+            .with_file_groups(vec![
+                FileGroup::new(vec![file("a", 2023, "eu"), file("b", 2023, "us")]),
+                FileGroup::new(vec![file("c", 2024, "eu"), file("d", 2024, "us")]),
+            ])
             .build();
 
-        // Verify the built config has all the expected values
-        assert_eq!(config.object_store_url, object_store_url);
-        assert_eq!(config.file_schema, file_schema);
-        assert_eq!(config.limit, Some(1000));
-        assert_eq!(config.projection, Some(vec![0, 1]));
-        assert_eq!(config.table_partition_cols.len(), 1);
-        assert_eq!(config.table_partition_cols[0].name(), "date");
-        assert_eq!(config.file_groups.len(), 1);
-        assert_eq!(config.file_groups[0].len(), 1);
-        assert_eq!(
-            config.file_groups[0][0].object_meta.location.as_ref(),
-            "test.parquet"
-        );
+        // the dictionary encoded region is compared by value
+        let retained = config.retain_partitions(&[
+            vec![ScalarValue::Int32(Some(2023)), ScalarValue::from("us")],
+            vec![ScalarValue::Int32(Some(2024)), ScalarValue::from("eu")],
+        ])?;
         assert_eq!(
-            config.file_compression_type,
-            FileCompressionType::UNCOMPRESSED
+            paths(&retained),
+            vec![vec!["b".to_string()], vec!["c".to_string()]]
         );
-        assert!(config.new_lines_in_values);
-        assert_eq!(config.output_ordering.len(), 1);
+
+        let err = config
+            .retain_partitions(&[vec![ScalarValue::Int32(Some(2023))]])
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Expected 2 partition values to retain, got 1"));
+        Ok(())
     }
 
     #[test]
-    fn test_file_scan_config_builder_defaults() {
-        let file_schema = aggr_test_schema();
-        let object_store_url = ObjectStoreUrl::parse("test:///").unwrap();
-        let file_source: Arc<dyn FileSource> = Arc::new(MockSource::default());
+    fn test_file_scan_config_for_time_range() -> Result<()> {
+        let file = |path: &str, date: Option<&str>| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = vec![ScalarValue::Utf8(date.map(String::from))];
+            file
+        };
+        let config = test_builder()
+            .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, true)])
+            .with_file_groups(vec![
+                FileGroup::new(vec![
+                    file("a", Some("2024-01-30")),
+                    file("b", Some("2024-01-31")),
+                ]),
+                FileGroup::new(vec![
+                    file("c", Some("2024-02-01")),
+                    file("d", Some("2024-02-02")),
+                    file("e", None),
+                ]),
+            ])
+            .build();
+        let date = |value: &str| {
+            ScalarValue::Utf8(Some(value.to_string()))
+                .cast_to(&DataType::Date32)
+                .unwrap()
+        };
 
-        // Create a builder with only required parameters and build without any additional configurations
-        let config = FileScanConfigBuilder::new(
-            object_store_url.clone(),
-            Arc::clone(&file_schema),
-            Arc::clone(&file_source),
-        )
-        .build();
+        // the start is inclusive, the end exclusive
+        let retained =
+            config.for_time_range(&date("2024-01-31"), &date("2024-02-02"), "date")?;
+        assert_eq!(paths(&retained), vec![vec!["b"], vec!["c"]]);
+        assert_eq!(retained.file_groups.len(), 2);
 
-        // Verify default values
-        assert_eq!(config.object_store_url, object_store_url);
-        assert_eq!(config.file_schema, file_schema);
-        assert_eq!(config.limit, None);
-        assert_eq!(config.projection, None);
-        assert!(config.table_partition_cols.is_empty());
-        assert!(config.file_groups.is_empty());
+        // an empty range retains no file
+        let retained =
+            config.for_time_range(&date("2024-01-31"), &date("2024-01-31"), "date")?;
         assert_eq!(
-            config.file_compression_type,
-            FileCompressionType::UNCOMPRESSED
+            retained
+                .file_groups
+                .iter()
+                .map(FileGroup::len)
+                .sum::<usize>(),
+            0
         );
-        assert!(!config.new_lines_in_values);
-        assert!(config.output_ordering.is_empty());
-        assert!(config.constraints.is_empty());
 
-        // Verify statistics are set to unknown
-        assert_eq!(
-            config.file_source.statistics().unwrap().num_rows,
-            Precision::Absent
+        // timestamps bounds
+        let timestamp = |value: &str| {
+            ScalarValue::Utf8(Some(value.to_string()))
+                .cast_to(&DataType::Timestamp(TimeUnit::Second, None))
+                .unwrap()
+        };
+        let retained = config.for_time_range(
+            &timestamp("2024-01-30T12:00:00"),
+            &timestamp("2024-02-03T00:00:00"),
+            "date",
+        )?;
+        assert_eq!(paths(&retained), vec![vec!["b"], vec!["c", "d"]]);
+
+        let err = config
+            .for_time_range(&date("2024-01-31"), &ScalarValue::from(1), "date")
+            .unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "Time range bounds must be of the same date or timestamp type"
         );
+        let err = config
+            .for_time_range(&date("2024-01-31"), &date("2024-02-02"), "month")
+            .unwrap_err();
+        assert_contains!(err.to_string(), "month is not a partition column");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_group_files_by_partition_values() {
+        let file = |path: &str, values: &[(i32, &str)]| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = values
+                .iter()
+                .flat_map(|(year, region)| {
+                    [ScalarValue::Int32(Some(*year)), ScalarValue::from(*region)]
+                })
+                .collect();
+            file
+        };
+        let config = test_builder()
+            .with_table_partition_cols(vec![
+                Field::new("year", DataType::Int32, false),
+                Field::new("region", DataType::Utf8, false),
+            ])
+            .with_file_groups(vec![
+                FileGroup::new(vec![
+                    file("a", &[(2023, "eu")]),
+                    file("b", &[(2023, "us")]),
+                    file("unknown1", &[]),
+                ]),
+                FileGroup::new(vec![
+                    file("c", &[(2024, "eu")]),
+                    file("d", &[(2023, "eu")]),
+                    file("unknown2", &[]),
+                ]),
+            ])
+            .build();
+
+        let grouped = config.group_files_by_partition_values();
+        // one group per distinct tuple, files without values on their own
         assert_eq!(
-            config.file_source.statistics().unwrap().total_byte_size,
-            Precision::Absent
+            paths(&grouped),
+            vec![
+                vec!["a".to_string(), "d".to_string()],
+                vec!["b".to_string()],
+                vec!["unknown1".to_string()],
+                vec!["c".to_string()],
+                vec!["unknown2".to_string()],
+            ]
         );
+
+        // groups resumed from a cursor are kept as they are
+        let config = FileScanConfigBuilder::from(config)
+            .with_resume_cursor(1, ScanCursor::default())
+            .build();
         assert_eq!(
-            config
-                .file_source
-                .statistics()
-                .unwrap()
-                .column_statistics
-                .len(),
-            file_schema.fields().len()
+            paths(&config.group_files_by_partition_values()),
+            paths(&config)
         );
-        for stat in config.file_source.statistics().unwrap().column_statistics {
-            assert_eq!(stat.distinct_count, Precision::Absent);
-            assert_eq!(stat.min_value, Precision::Absent);
-            assert_eq!(stat.max_value, Precision::Absent);
-            assert_eq!(stat.null_count, Precision::Absent);
+    }
+
+    #[test]
+    fn test_file_scan_config_split_by_store() -> Result<()> {
+        let s3 = ObjectStoreUrl::parse("s3://bucket").unwrap();
+        let file = |path: &str, s3_file: bool| {
+            let file = PartitionedFile::new(path, 10);
+            if s3_file {
+                file.with_object_store_url(s3.clone())
+            } else {
+                file
+            }
+        };
+        let config = test_builder()
+            .with_file_groups(vec![
+                FileGroup::new(vec![file("a", false), file("b", true)]),
+                FileGroup::new(vec![file("c", false), file("d", false)]),
+            ])
+            .with_projection(Some(vec![2, 0]))
+            .build();
+
+        let configs = config.clone().split_by_store()?;
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].object_store_url, config.object_store_url);
+        assert_eq!(paths(&configs[0]), vec![vec!["a"], vec!["c", "d"]]);
+        assert_eq!(configs[1].object_store_url, s3);
+        assert_eq!(paths(&configs[1]), vec![vec!["b"]]);
+        for split in &configs {
+            assert!(split
+                .file_groups
+                .iter()
+                .flat_map(FileGroup::iter)
+                .all(|file| file.object_store_url.is_none()));
+            assert_eq!(split.projection, config.projection);
+            assert_eq!(split.projected_schema(), config.projected_schema());
         }
+
+        // the split groups are no longer hash partitioned
+        let configs = FileScanConfigBuilder::from(config.clone())
+            .with_declared_partitioning(PartitionKey::Column("c1".to_string()))
+            .build()
+            .split_by_store()?;
+        assert!(configs
+            .iter()
+            .all(|split| split.declared_partitioning.is_none()));
+
+        // cursors refer to the original groups
+        let err = FileScanConfigBuilder::from(config)
+            .with_resume_cursor(0, ScanCursor::default())
+            .build()
+            .split_by_store()
+            .unwrap_err();
+        assert_contains!(err.to_string(), "resumed from a cursor");
+        Ok(())
     }
 
     #[test]
-    fn test_file_scan_config_builder_new_from() {
-        let schema = aggr_test_schema();
-        let object_store_url = ObjectStoreUrl::parse("test:///").unwrap();
-        let file_source: Arc<dyn FileSource> = Arc::new(MockSource::default());
-        let partition_cols = vec![Field::new(
-            "date",
-            wrap_partition_type_in_dict(DataType::Utf8),
-            false,
-        )];
-        let file = PartitionedFile::new("test_file.parquet", 100);
+    fn test_file_scan_config_explain_projection() {
+        let builder = test_builder().with_table_partition_cols(vec![
+            Field::new("date", DataType::Utf8, false),
+            Field::new("hour", DataType::Int32, false),
+        ]);
 
-        // Create a config with non-default values
-        let original_config = FileScanConfigBuilder::new(
-            object_store_url.clone(),
-            Arc::clone(&schema),
-            Arc::clone(&file_source),
-        )
-        .with_projection(Some(vec![0, 2]))
-        .with_limit(Some(10))
-        .with_table_partition_cols(partition_cols.clone())
-        .with_file(file.clone())
-        .with_constraints(Constraints::default())
-        .with_newlines_in_values(true)
-        .build();
+        let config = builder
+            .clone()
+            .with_projection(Some(vec![14, 2, 13, 0]))
+            .build();
+        assert_eq!(
+            config.explain_projection(),
+            "0: hour (Int32) <- table_partition_cols[1]\n\
+             1: c3 (Int8) <- file_schema[2]\n\
+             2: date (Utf8) <- table_partition_cols[0]\n\
+             3: c1 (Utf8) <- file_schema[0]"
+        );
 
-        // Create a new builder from the config
-        let new_builder = FileScanConfigBuilder::from(original_config);
+        let config = builder.with_projection(Some(vec![15])).build();
+        assert_eq!(
+            config.explain_projection(),
+            "0: invalid table column index 15"
+        );
+    }
 
-        // Build a new config from this builder
-        let new_config = new_builder.build();
+    #[test]
+    fn test_file_scan_config_per_file_byte_cap() {
+        let config = test_builder()
+            .with_file_groups(vec![
+                FileGroup::new(vec![
+                    PartitionedFile::new("small.csv", 50),
+                    PartitionedFile::new("large.csv", 1000),
+                ]),
+                FileGroup::new(vec![
+                    PartitionedFile::new_with_range("ranged.csv".into(), 1000, 20, 300),
+                    PartitionedFile::new_with_range("skipped.csv".into(), 1000, 200, 300),
+                ]),
+            ])
+            .with_per_file_byte_cap(Some(100))
+            .build();
 
-        // Verify properties match
-        let partition_cols = partition_cols.into_iter().map(Arc::new).collect::<Vec<_>>();
-        assert_eq!(new_config.object_store_url, object_store_url);
-        assert_eq!(new_config.file_schema, schema);
-        assert_eq!(new_config.projection, Some(vec![0, 2]));
-        assert_eq!(new_config.limit, Some(10));
-        assert_eq!(new_config.table_partition_cols, partition_cols);
-        assert_eq!(new_config.file_groups.len(), 1);
-        assert_eq!(new_config.file_groups[0].len(), 1);
+        let ranges = config
+            .file_groups
+            .iter()
+            .flat_map(|group| group.iter())
+            .map(|file| file.range.clone().map(|r| (r.start, r.end)))
+            .collect::<Vec<_>>();
         assert_eq!(
-            new_config.file_groups[0][0].object_meta.location.as_ref(),
-            "test_file.parquet"
+            ranges,
+            vec![
+                Some((0, 50)),
+                Some((0, 100)),
+                Some((20, 100)),
+                Some((100, 100)),
+            ]
         );
-        assert_eq!(new_config.constraints, Constraints::default());
-        assert!(new_config.new_lines_in_values);
     }
 
     #[test]
@@ -0,0 +1,839 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Opening the partitions of a [`FileScanConfig`]: the stack of streams and
+//! [`FileOpener`]s applying the options of the scan to the files it reads.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "checksum")]
+use crate::checksum::ChecksumVerifyingOpener;
+use crate::file_groups::FileGroup;
+use crate::file_scan_config::{
+    get_projected_output_ordering, FileScanConfig, PartitionColumnSource,
+    PartitionEncoding, VARIABLE_WIDTH_VALUE_BYTES,
+};
+use crate::schema_adapter::{
+    ColumnDefaultsSchemaAdapterFactory, DefaultSchemaAdapterFactory, ExtraColumnPolicy,
+    ExtraColumnsSchemaAdapterFactory, SchemaMapper,
+};
+use crate::{
+    coalesced_ranges::open_coalesced_ranges,
+    file::FileSource,
+    file_meta::FileMeta,
+    file_stream::{FileOpenFuture, FileOpener, FileStream},
+    missing_file::{MissingFileOpener, MissingFilePolicy},
+    sub_file::{open_sub_files, CreateOpenerForStore},
+    trailing_bytes::{open_without_trailing_bytes, Trailer},
+    url_refresh::UrlRefreshingOpener,
+    PartitionedFile,
+};
+
+use arrow::array::{RecordBatch, RecordBatchOptions};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::row::{OwnedRow, RowConverter, SortField};
+use datafusion_common::{config_err, exec_err, ColumnStatistics, Result, ScalarValue};
+use datafusion_execution::{
+    memory_pool::MemoryConsumer, object_store::ObjectStoreUrl, SendableRecordBatchStream,
+    TaskContext,
+};
+use datafusion_physical_expr_common::sort_expr::LexOrdering;
+use datafusion_physical_plan::{
+    metrics::BaselineMetrics, sorts::streaming_merge::StreamingMergeBuilder,
+    stream::RecordBatchStreamAdapter,
+};
+
+use futures::future::BoxFuture;
+use futures::{StreamExt, TryStreamExt};
+use object_store::ObjectStore;
+
+impl FileScanConfig {
+    /// Opens `partition` of the scan, see [`DataSource::open`].
+    ///
+    /// The options of the scan are applied by wrapping the stream of the
+    /// config without them, e.g. to verify the output ordering or to append
+    /// constant columns, and by wrapping the opener of the file source, e.g.
+    /// to route files to their object store or to skip missing files.
+    ///
+    /// [`DataSource::open`]: crate::source::DataSource::open
+    pub(crate) fn open_partition(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if cfg!(debug_assertions) && self.verify_ordering {
+            let orderings = get_projected_output_ordering(self, &self.projected_schema());
+            let unverified = FileScanConfig {
+                verify_ordering: false,
+                ..self.clone()
+            };
+            let stream = unverified.open_partition(partition, context)?;
+            return verify_ordering(stream, orderings);
+        }
+
+        if self.partition_index_column.is_some() || !self.constant_columns.is_empty() {
+            self.validate_appended_columns()?;
+            let schema = self.projected_schema();
+            let unappended = FileScanConfig {
+                partition_index_column: None,
+                constant_columns: vec![],
+                ..self.clone()
+            };
+            let stream = unappended.open_partition(partition, context)?;
+            let partition_index = self
+                .partition_index_column
+                .as_ref()
+                .map(|_| ScalarValue::UInt64(Some(partition as u64)));
+            let values = partition_index
+                .into_iter()
+                .chain(self.constant_columns.iter().map(|(_, value)| value.clone()))
+                .collect::<Vec<_>>();
+            let stream = {
+                let schema = Arc::clone(&schema);
+                stream.map(move |batch| {
+                    let batch = batch?;
+                    let num_rows = batch.num_rows();
+                    let mut columns = batch.columns().to_vec();
+                    for value in &values {
+                        columns.push(value.to_array_of_size(num_rows)?);
+                    }
+                    Ok(RecordBatch::try_new_with_options(
+                        Arc::clone(&schema),
+                        columns,
+                        &RecordBatchOptions::new().with_row_count(Some(num_rows)),
+                    )?)
+                })
+            };
+            return Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)));
+        }
+
+        if let Some(ordering) = self.sort_merge_ordering() {
+            return self.open_sort_merged(partition, context, &ordering);
+        }
+
+        if !self.projection_pushdown {
+            // read all columns and project the batches afterwards
+            let unprojected = FileScanConfig {
+                projection: None,
+                projection_pushdown: true,
+                ..self.clone()
+            };
+            let stream = unprojected.open_partition(partition, context)?;
+            let indices = self.effective_projection();
+            let stream = stream.map(move |batch| Ok(batch?.project(&indices)?));
+            return Ok(Box::pin(RecordBatchStreamAdapter::new(
+                self.projected_schema(),
+                stream,
+            )));
+        }
+
+        let batch_size = self.effective_batch_size(&context)?;
+        self.validate()?;
+        if let Some(stream) = self.partition_values_stream(partition, batch_size)? {
+            return Ok(stream);
+        }
+
+        // the config the files are read with
+        let reader = if self.reads_partition_columns() {
+            Cow::Owned(self.partition_columns_reader_config()?)
+        } else {
+            Cow::Borrowed(self)
+        };
+        let source = reader
+            .file_source
+            .with_batch_size(batch_size)
+            .with_projection(&reader);
+        let source = reader.with_column_defaults(source)?;
+        let source = reader.with_extra_columns_adapter(source)?;
+
+        let create_opener = {
+            let config = reader.clone().into_owned();
+            let source = Arc::clone(&source);
+            move |object_store: Arc<dyn ObjectStore>| {
+                config.create_file_opener(&source, object_store, partition)
+            }
+        };
+        let physical_column_names = self.name_based_column_matching.then(|| {
+            let config = reader.clone().into_owned();
+            let source = Arc::clone(&source);
+            Box::new(
+                move |object_store: Arc<dyn ObjectStore>, file: &PartitionedFile| {
+                    source.physical_column_names(object_store, file, &config)
+                },
+            ) as Box<PhysicalColumnNamesForFile>
+        });
+        let file_schema = Arc::clone(&reader.file_schema);
+        let create_override_opener = {
+            let config = reader.into_owned();
+            let source = Arc::clone(&source);
+            move |object_store: Arc<dyn ObjectStore>, file_schema: &SchemaRef| {
+                config.create_schema_override_opener(
+                    &source,
+                    object_store,
+                    file_schema,
+                    partition,
+                )
+            }
+        };
+        let object_store = {
+            let runtime_env = context.runtime_env();
+            move |url: &ObjectStoreUrl| runtime_env.object_store(url)
+        };
+        let routing_opener = ObjectStoreRoutingOpener {
+            default_url: self.partition_store_url(partition).clone(),
+            openers: Mutex::new(HashMap::new()),
+            object_store: Box::new(object_store),
+            create_opener: Arc::new(create_opener),
+            create_override_opener: Arc::new(create_override_opener),
+            file_schema,
+            physical_column_names,
+            trailer: self
+                .tolerate_trailing_bytes
+                .then(|| Trailer::for_file_type(self.file_source.file_type()))
+                .flatten(),
+            range_coalescing: self.range_coalescing,
+        };
+        // fail early if the object store of the partition is not registered
+        routing_opener.opener(&routing_opener.default_url)?;
+        let mut opener: Arc<dyn FileOpener> = Arc::new(routing_opener);
+        if let Some(url_refresher) = &self.url_refresher {
+            opener =
+                Arc::new(UrlRefreshingOpener::new(opener, Arc::clone(url_refresher)));
+        }
+        if self.missing_file_policy != MissingFilePolicy::Error {
+            opener = Arc::new(MissingFileOpener::new(
+                opener,
+                self.missing_file_policy,
+                source.metrics(),
+                partition,
+            ));
+        }
+
+        let mut stream = FileStream::new(self, partition, opener, source.metrics())?;
+        if self.memory_reservation {
+            let batch_bytes = self.estimated_batch_bytes(batch_size);
+            let mut reservation =
+                MemoryConsumer::new(format!("DataSourceExec[{partition}]"))
+                    .register(&context.runtime_env().memory_pool);
+            // the batches of the scanned file and the files opened ahead of it
+            let max_files_in_flight = self.max_open_files_per_partition.get() + 1;
+            reservation.try_grow(batch_bytes * max_files_in_flight)?;
+            stream = stream.with_memory_reservation(reservation, batch_bytes);
+        }
+        if self.reverse_scan {
+            let reservation =
+                MemoryConsumer::new(format!("DataSourceExec[{partition}] reverse scan"))
+                    .register(&context.runtime_env().memory_pool);
+            stream = stream.with_reverse_scan_reservation(reservation);
+        }
+        Ok(Box::pin(stream))
+    }
+
+    /// Returns the batch size to read files with: [`Self::batch_size`] if set,
+    /// otherwise the batch size configured for the session.
+    fn effective_batch_size(&self, context: &TaskContext) -> Result<usize> {
+        match self.batch_size {
+            Some(0) => config_err!("FileScanConfig batch_size must be greater than 0"),
+            Some(batch_size) => Ok(batch_size),
+            None => Ok(context.session_config().batch_size()),
+        }
+    }
+
+    /// Returns the estimated memory size of a batch of `batch_size` rows of
+    /// the projected schema, see [`FileScanConfigBuilder::with_memory_reservation`]
+    fn estimated_batch_bytes(&self, batch_size: usize) -> usize {
+        let row_bytes = self
+            .projected_schema()
+            .fields()
+            .iter()
+            .map(|field| {
+                field
+                    .data_type()
+                    .primitive_width()
+                    .unwrap_or(VARIABLE_WIDTH_VALUE_BYTES)
+            })
+            .sum::<usize>();
+        row_bytes * batch_size
+    }
+
+    /// Wraps the schema adapter factory of `source` so that columns missing
+    /// from a file are filled using [`FileScanConfig::column_default_exprs`].
+    fn with_column_defaults(
+        &self,
+        source: Arc<dyn FileSource>,
+    ) -> Result<Arc<dyn FileSource>> {
+        if self.column_default_exprs.is_empty() {
+            return Ok(source);
+        }
+        let inner = source
+            .schema_adapter_factory()
+            .unwrap_or_else(|| Arc::new(DefaultSchemaAdapterFactory));
+        source.with_schema_adapter_factory(Arc::new(
+            ColumnDefaultsSchemaAdapterFactory::new(
+                inner,
+                self.column_default_exprs.clone(),
+            ),
+        ))
+    }
+
+    /// Returns the config the files are read with if the partition columns
+    /// are also read from the files: the partition columns, with their value
+    /// types, are appended to the file schema, and all of them to the
+    /// projected file columns, so that they follow the projected file
+    /// columns in the batches of the files.
+    fn partition_columns_reader_config(&self) -> Result<FileScanConfig> {
+        let num_file_columns = self.file_schema.fields().len();
+        let fields = self
+            .file_schema
+            .fields()
+            .iter()
+            .cloned()
+            .chain(self.table_partition_cols.iter().map(|field| {
+                let data_type = PartitionEncoding::Decoded.data_type(field.data_type());
+                Arc::new(Field::new(field.name(), data_type, true))
+            }))
+            .collect::<Vec<_>>();
+        let file_schema = Arc::new(Schema::new_with_metadata(
+            fields,
+            self.file_schema.metadata().clone(),
+        ));
+        let projection = self
+            .file_column_projection_indices()
+            .unwrap_or_else(|| (0..num_file_columns).collect())
+            .into_iter()
+            .chain(num_file_columns..file_schema.fields().len())
+            .collect();
+
+        let mut statistics = self.file_source.statistics()?;
+        statistics
+            .column_statistics
+            .resize(file_schema.fields().len(), ColumnStatistics::new_unknown());
+        let file_source = self
+            .file_source
+            .with_statistics(statistics)
+            .with_schema(Arc::clone(&file_schema));
+        Ok(FileScanConfig {
+            file_schema,
+            table_partition_cols: vec![],
+            projection: Some(projection),
+            file_source,
+            partition_column_source: PartitionColumnSource::PathOnly,
+            ..self.clone()
+        })
+    }
+
+    /// Wrap the schema adapter factory of `source` to apply the
+    /// [`FileScanConfig::extra_column_policy`]
+    fn with_extra_columns_adapter(
+        &self,
+        source: Arc<dyn FileSource>,
+    ) -> Result<Arc<dyn FileSource>> {
+        if self.extra_column_policy == ExtraColumnPolicy::Ignore {
+            return Ok(source);
+        }
+        let inner = source
+            .schema_adapter_factory()
+            .unwrap_or_else(|| Arc::new(DefaultSchemaAdapterFactory));
+        source.with_schema_adapter_factory(Arc::new(
+            ExtraColumnsSchemaAdapterFactory::new(inner, self.extra_column_policy),
+        ))
+    }
+
+    /// Creates the opener for files with a
+    /// [`PartitionedFile::file_schema_override`] of `file_schema`.
+    ///
+    /// The projected file columns present in `file_schema` are read with
+    /// `file_schema`, and the batches are then adapted to the projected file
+    /// schema of the scan by the schema adapter of `source`.
+    fn create_schema_override_opener(
+        &self,
+        source: &Arc<dyn FileSource>,
+        object_store: Arc<dyn ObjectStore>,
+        file_schema: &SchemaRef,
+        partition: usize,
+    ) -> Result<Arc<dyn FileOpener>> {
+        let projected_file_schema = self.projected_file_schema();
+        let projection = projected_file_schema
+            .fields()
+            .iter()
+            .filter_map(|field| file_schema.index_of(field.name()).ok())
+            .collect::<Vec<_>>();
+        let read_schema = Arc::new(file_schema.project(&projection)?);
+        let config = FileScanConfig {
+            file_schema: Arc::clone(file_schema),
+            projection: Some(projection),
+            ..self.clone()
+        };
+        let file_source = source
+            .with_schema(Arc::clone(file_schema))
+            .with_projection(&config);
+        let opener = config.create_file_opener(&file_source, object_store, partition);
+
+        let (mapper, _) = source
+            .schema_adapter_factory()
+            .unwrap_or_else(|| Arc::new(DefaultSchemaAdapterFactory))
+            .create(projected_file_schema, Arc::clone(&self.file_schema))
+            .map_schema(&read_schema)?;
+        Ok(Arc::new(SchemaMappingOpener {
+            inner: opener,
+            mapper,
+        }))
+    }
+
+    /// Creates the opener of `source` reading files from `object_store`,
+    /// verifying their checksums if [`FileScanConfig::checksum_verification`] is set
+    fn create_file_opener(
+        &self,
+        source: &Arc<dyn FileSource>,
+        object_store: Arc<dyn ObjectStore>,
+        partition: usize,
+    ) -> Arc<dyn FileOpener> {
+        let opener =
+            source.create_file_opener(Arc::clone(&object_store), self, partition);
+        #[cfg(feature = "checksum")]
+        if let Some(algorithm) = self.checksum_verification {
+            return Arc::new(ChecksumVerifyingOpener::new(
+                opener,
+                object_store,
+                algorithm,
+            ));
+        }
+        opener
+    }
+
+    /// Opens every file as a stream of its own and merges them by `ordering`
+    /// into the single output partition, see
+    /// [`FileScanConfigBuilder::with_sort_merge_on_read`]
+    fn open_sort_merged(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+        ordering: &LexOrdering,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return exec_err!(
+                "Invalid partition {partition} of a sort merged file scan with a single partition"
+            );
+        }
+        if !self.resume_cursors.is_empty() {
+            return exec_err!("A sort merged file scan can not be resumed from a cursor");
+        }
+
+        let task_context = &context;
+        let streams = self
+            .file_groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group, files)| {
+                files.iter().map(move |file| {
+                    let config = FileScanConfig {
+                        object_store_url: self.partition_store_url(group).clone(),
+                        file_groups: vec![FileGroup::new(vec![file.clone()])],
+                        partition_store_urls: BTreeMap::new(),
+                        group_labels: vec![],
+                        sort_merge_on_read: false,
+                        ..self.clone()
+                    };
+                    config.open_partition(0, Arc::clone(task_context))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let reservation = MemoryConsumer::new("DataSourceExec sort merge")
+            .register(&context.runtime_env().memory_pool);
+        StreamingMergeBuilder::new()
+            .with_streams(streams)
+            .with_schema(self.projected_schema())
+            .with_expressions(ordering)
+            .with_metrics(BaselineMetrics::new(self.file_source.metrics(), partition))
+            .with_batch_size(self.effective_batch_size(&context)?)
+            .with_fetch(self.limit)
+            .with_reservation(reservation)
+            .build()
+    }
+}
+
+/// Returns the object store registered for a [`ObjectStoreUrl`]
+type ObjectStoreForUrl =
+    dyn Fn(&ObjectStoreUrl) -> Result<Arc<dyn ObjectStore>> + Send + Sync;
+
+/// Creates the opener reading files from an object store with a
+/// [`PartitionedFile::file_schema_override`]
+type CreateOpenerForSchema =
+    dyn Fn(Arc<dyn ObjectStore>, &SchemaRef) -> Result<Arc<dyn FileOpener>> + Send + Sync;
+
+/// Returns a future resolving to the names of the columns of a file in the
+/// order they are stored, if they are read by position, see
+/// [`FileSource::physical_column_names`]
+type PhysicalColumnNamesForFile = dyn Fn(
+        Arc<dyn ObjectStore>,
+        &PartitionedFile,
+    ) -> Option<BoxFuture<'static, Result<Vec<String>>>>
+    + Send
+    + Sync;
+
+/// A [`FileOpener`] opening each file with the opener for its
+/// [`PartitionedFile::object_store_url`], or the default URL if not set.
+///
+/// The openers of the object stores are created on first use, as the files
+/// are only known once they have been preprocessed. Files with
+/// [`PartitionedFile::sub_files`] are opened with an opener per sub file, and
+/// files with a [`PartitionedFile::file_schema_override`] with an opener per
+/// file. Files with [`PartitionedFile::ranges`] are opened once per range.
+/// Files are opened with an opener per file as well when their trailing
+/// bytes are ignored, to read them up to the end of their data, or when their
+/// columns are matched by name and stored in another order than the file
+/// schema.
+struct ObjectStoreRoutingOpener {
+    default_url: ObjectStoreUrl,
+    openers: Mutex<HashMap<ObjectStoreUrl, Arc<dyn FileOpener>>>,
+    object_store: Box<ObjectStoreForUrl>,
+    create_opener: Arc<CreateOpenerForStore>,
+    create_override_opener: Arc<CreateOpenerForSchema>,
+    /// The schema the files are read with
+    file_schema: SchemaRef,
+    /// Names of the columns of a file, if
+    /// [`FileScanConfig::name_based_column_matching`]
+    physical_column_names: Option<Box<PhysicalColumnNamesForFile>>,
+    /// Trailer of the files if [`FileScanConfig::tolerate_trailing_bytes`]
+    trailer: Option<Trailer>,
+    /// See [`FileScanConfig::range_coalescing`]
+    range_coalescing: Option<u64>,
+}
+
+impl ObjectStoreRoutingOpener {
+    /// Returns the opener for the object store of `url`
+    fn opener(&self, url: &ObjectStoreUrl) -> Result<Arc<dyn FileOpener>> {
+        let mut openers = self.openers.lock().unwrap();
+        if let Some(opener) = openers.get(url) {
+            return Ok(Arc::clone(opener));
+        }
+        let opener = (self.create_opener)((self.object_store)(url)?);
+        openers.insert(url.clone(), Arc::clone(&opener));
+        Ok(opener)
+    }
+}
+
+impl FileOpener for ObjectStoreRoutingOpener {
+    fn open(&self, file_meta: FileMeta, file: PartitionedFile) -> Result<FileOpenFuture> {
+        if !file.ranges.is_empty() {
+            return self.open_ranges(file_meta, file);
+        }
+        let url = file.object_store_url.as_ref().unwrap_or(&self.default_url);
+        if let Some(names) = self.physical_column_names(url, &file)? {
+            let url = url.clone();
+            return self.open_by_column_names(&url, names, file_meta, file);
+        }
+        if let Some(file_schema) = &file.file_schema_override {
+            if !file.sub_files.is_empty() {
+                return exec_err!(
+                    "Cannot override the schema of file {} with sub files",
+                    file.path()
+                );
+            }
+            let object_store = (self.object_store)(url)?;
+            let opener = (self.create_override_opener)(object_store, file_schema)?;
+            return opener.open(file_meta, file);
+        }
+        if file.sub_files.is_empty() {
+            if let Some(trailer) = self.trailer {
+                return open_without_trailing_bytes(
+                    (self.object_store)(url)?,
+                    Arc::clone(&self.create_opener),
+                    trailer,
+                    file_meta,
+                    file,
+                );
+            }
+            return self.opener(url)?.open(file_meta, file);
+        }
+        let object_store = (self.object_store)(url)?;
+        open_sub_files(
+            object_store,
+            Arc::clone(&self.create_opener),
+            file_meta,
+            file,
+        )
+    }
+}
+
+impl ObjectStoreRoutingOpener {
+    /// Returns a future resolving to the names of the columns of `file`, read
+    /// from the object store of `url`, if they are matched by name. Files
+    /// with a schema override or sub files are read as they are.
+    fn physical_column_names(
+        &self,
+        url: &ObjectStoreUrl,
+        file: &PartitionedFile,
+    ) -> Result<Option<BoxFuture<'static, Result<Vec<String>>>>> {
+        match &self.physical_column_names {
+            Some(physical_column_names)
+                if file.file_schema_override.is_none() && file.sub_files.is_empty() =>
+            {
+                Ok(physical_column_names((self.object_store)(url)?, file))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Opens `file` once the `names` of its columns are known, with the
+    /// opener of the object store of `url` if they are in the order of the
+    /// file schema, and otherwise as if the file had a schema override with
+    /// the columns of the file schema in that order. Columns of the file that
+    /// are not in the file schema are read as strings, and not projected.
+    fn open_by_column_names(
+        &self,
+        url: &ObjectStoreUrl,
+        names: BoxFuture<'static, Result<Vec<String>>>,
+        file_meta: FileMeta,
+        file: PartitionedFile,
+    ) -> Result<FileOpenFuture> {
+        let opener = self.opener(url)?;
+        let object_store = (self.object_store)(url)?;
+        let create_override_opener = Arc::clone(&self.create_override_opener);
+        let file_schema = Arc::clone(&self.file_schema);
+        Ok(Box::pin(async move {
+            let names = names.await?;
+            let in_order = names.len() == file_schema.fields().len()
+                && names
+                    .iter()
+                    .zip(file_schema.fields())
+                    .all(|(name, field)| name == field.name());
+            if in_order {
+                return opener.open(file_meta, file)?.await;
+            }
+
+            let fields = names
+                .iter()
+                .map(|name| match file_schema.field_with_name(name) {
+                    Ok(field) => field.clone(),
+                    Err(_) => Field::new(name, DataType::Utf8, true),
+                })
+                .collect::<Vec<_>>();
+            let physical_schema = Arc::new(Schema::new(fields));
+            create_override_opener(object_store, &physical_schema)?
+                .open(file_meta, file)?
+                .await
+        }))
+    }
+
+    /// Opens each of the [`PartitionedFile::ranges`] of `file` as a file
+    /// split into that range, and concatenates their batches. The ranges are
+    /// fetched with coalesced requests if [`FileScanConfig::range_coalescing`]
+    /// is set.
+    fn open_ranges(
+        &self,
+        file_meta: FileMeta,
+        mut file: PartitionedFile,
+    ) -> Result<FileOpenFuture> {
+        if file.range.is_some() || !file.sub_files.is_empty() {
+            return exec_err!(
+                "Cannot read ranges of file {} with a range or sub files",
+                file.path()
+            );
+        }
+        let url = file.object_store_url.as_ref().unwrap_or(&self.default_url);
+        if let Some(max_gap) = self.range_coalescing {
+            if self.physical_column_names.is_none()
+                && self.trailer.is_none()
+                && file.file_schema_override.is_none()
+            {
+                return open_coalesced_ranges(
+                    (self.object_store)(url)?,
+                    Arc::clone(&self.create_opener),
+                    max_gap,
+                    file_meta,
+                    file,
+                );
+            }
+        }
+        let ranges = mem::take(&mut file.ranges);
+        let opens = ranges
+            .into_iter()
+            .map(|range| {
+                let file_meta = FileMeta {
+                    range: Some(range.clone()),
+                    ..file_meta.clone()
+                };
+                let file = PartitionedFile {
+                    range: Some(range),
+                    ..file.clone()
+                };
+                self.open(file_meta, file)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let stream = futures::stream::iter(opens)
+            .then(|open| open)
+            .map_err(ArrowError::from)
+            .try_flatten();
+        Ok(Box::pin(async move { Ok(stream.boxed()) }))
+    }
+}
+
+/// A [`FileOpener`] mapping the batches of the inner opener with a
+/// [`SchemaMapper`]
+struct SchemaMappingOpener {
+    inner: Arc<dyn FileOpener>,
+    mapper: Arc<dyn SchemaMapper>,
+}
+
+impl FileOpener for SchemaMappingOpener {
+    fn open(&self, file_meta: FileMeta, file: PartitionedFile) -> Result<FileOpenFuture> {
+        let open = self.inner.open(file_meta, file)?;
+        let mapper = Arc::clone(&self.mapper);
+        Ok(Box::pin(async move {
+            let stream = open
+                .await?
+                .map(move |batch| mapper.map_batch(batch?).map_err(ArrowError::from));
+            Ok(stream.boxed())
+        }))
+    }
+}
+
+/// Wraps `stream` to fail with an error at the first row that is not sorted
+/// by one of `orderings` relative to the previous row, within a batch or
+/// across batches
+fn verify_ordering(
+    stream: SendableRecordBatchStream,
+    orderings: Vec<LexOrdering>,
+) -> Result<SendableRecordBatchStream> {
+    let schema = stream.schema();
+    let mut checks = orderings
+        .into_iter()
+        .map(|ordering| {
+            let sort_fields = ordering
+                .iter()
+                .map(|sort_expr| {
+                    let data_type = sort_expr.expr.data_type(&schema)?;
+                    Ok(SortField::new_with_options(data_type, sort_expr.options))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let converter = RowConverter::new(sort_fields)?;
+            Ok((ordering, converter, None::<OwnedRow>))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let stream = stream.map(move |batch| {
+        let batch = batch?;
+        for (ordering, converter, last_row) in &mut checks {
+            let columns = ordering
+                .iter()
+                .map(|sort_expr| {
+                    sort_expr
+                        .expr
+                        .evaluate(&batch)?
+                        .into_array(batch.num_rows())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let rows = converter.convert_columns(&columns)?;
+            let previous_rows = last_row.iter().map(|row| row.row()).chain(rows.iter());
+            if previous_rows
+                .zip(rows.iter().skip(usize::from(last_row.is_none())))
+                .any(|(previous, row)| previous > row)
+            {
+                return exec_err!(
+                    "Batch is not sorted by the declared output ordering [{ordering}]"
+                );
+            }
+            if let Some(last) = rows.num_rows().checked_sub(1) {
+                *last_row = Some(rows.row(last).owned());
+            }
+        }
+        Ok(batch)
+    });
+    Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_scan_config::FileScanConfigBuilder;
+    use crate::source::DataSource;
+    use crate::{test_util::MockSource, tests::aggr_test_schema};
+
+    use datafusion_common::assert_contains;
+
+    #[test]
+    fn test_file_scan_config_batch_size() -> Result<()> {
+        let context = Arc::new(TaskContext::default().with_session_config(
+            datafusion_execution::config::SessionConfig::new().with_batch_size(1024),
+        ));
+        let url = ObjectStoreUrl::parse("test:///").unwrap();
+        context.runtime_env().register_object_store(
+            url.as_ref(),
+            Arc::new(object_store::memory::InMemory::new()),
+        );
+        // returns the batch size of the opener the first partition is read with
+        let opener_batch_size = |batch_size: Option<usize>| -> Result<Option<usize>> {
+            let source = MockSource::default();
+            let batch_sizes = source.opener_batch_sizes();
+            FileScanConfigBuilder::new(url.clone(), aggr_test_schema(), Arc::new(source))
+                .with_file(PartitionedFile::new("a.csv", 10))
+                .with_batch_size(batch_size)
+                .build()
+                .open(0, Arc::clone(&context))?;
+            let batch_sizes = batch_sizes.lock().unwrap();
+            Ok(batch_sizes.last().copied().flatten())
+        };
+
+        // falls back to the session batch size
+        assert_eq!(opener_batch_size(None)?, Some(1024));
+
+        // the per scan batch size takes precedence
+        assert_eq!(opener_batch_size(Some(16))?, Some(16));
+
+        // and it must not be zero
+        let err = opener_batch_size(Some(0)).unwrap_err();
+        assert_contains!(err.to_string(), "batch_size must be greater than 0");
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_scan_config_column_default_exprs() {
+        let file_schema = aggr_test_schema();
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            Arc::clone(&file_schema),
+            Arc::new(MockSource::default()),
+        );
+
+        // without defaults the source is left untouched
+        let config = builder.clone().build();
+        let source = config
+            .with_column_defaults(Arc::clone(&config.file_source))
+            .unwrap();
+        assert!(source.schema_adapter_factory().is_none());
+
+        let defaults = HashMap::from([(
+            "c2".to_string(),
+            crate::test_util::col("c3", &file_schema).unwrap(),
+        )]);
+        let config = builder.with_column_default_exprs(defaults).build();
+        let config = FileScanConfigBuilder::from(config).build();
+        assert_eq!(config.column_default_exprs.len(), 1);
+
+        let source = config
+            .with_column_defaults(Arc::clone(&config.file_source))
+            .unwrap();
+        assert!(source.schema_adapter_factory().is_some());
+    }
+}
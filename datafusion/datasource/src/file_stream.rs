@@ -28,23 +28,31 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use crate::file_meta::FileMeta;
-use crate::file_scan_config::{FileScanConfig, PartitionColumnProjector};
+use crate::file_scan_config::{
+    file_read_size, FileScanConfig, PartitionColumnProjector, PartitionColumnSource,
+};
 use crate::PartitionedFile;
-use arrow::datatypes::SchemaRef;
+use arrow::datatypes::{Schema, SchemaRef};
 use datafusion_common::error::Result;
+use datafusion_execution::memory_pool::MemoryReservation;
 use datafusion_execution::RecordBatchStream;
 use datafusion_physical_plan::metrics::{
     BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, Time,
 };
 
+use arrow::array::UInt32Array;
+use arrow::compute::kernels::cmp::neq;
+use arrow::compute::kernels::zip::zip;
+use arrow::compute::{cast, is_not_null, take_record_batch};
 use arrow::error::ArrowError;
 use arrow::record_batch::RecordBatch;
 use datafusion_common::instant::Instant;
-use datafusion_common::ScalarValue;
+use datafusion_common::{exec_err, ScalarValue};
 
 use futures::future::BoxFuture;
 use futures::stream::BoxStream;
-use futures::{ready, FutureExt as _, Stream, StreamExt as _};
+use futures::{ready, FutureExt as _, Stream, StreamExt as _, TryStreamExt as _};
+use log::debug;
 
 /// A stream that iterates record batch by record batch, file over file.
 pub struct FileStream {
@@ -55,11 +63,60 @@ pub struct FileStream {
     projected_schema: SchemaRef,
     /// The remaining number of records to parse, None if no limit
     remain: Option<usize>,
+    /// The maximum number of records to read from each file, None if no limit
+    per_file_row_limit: Option<usize>,
+    /// If true, the rows of each file are emitted in reverse
+    reverse_scan: bool,
+    /// Reservation of the memory of the batches buffered to reverse a file,
+    /// if any, see [`Self::with_reverse_scan_reservation`]
+    reverse_scan_reservation: Option<MemoryReservation>,
+    /// If true, the schema metadata of the first file read is merged into
+    /// the schema metadata of the output
+    merge_file_metadata: bool,
+    /// The output schema including the metadata of the first file read, set
+    /// once it has been read if `merge_file_metadata` is true
+    merged_schema: Option<SchemaRef>,
+    /// The maximum estimated memory size of the batches read from each file,
+    /// None if no limit
+    max_batch_bytes: Option<usize>,
+    /// The schema of the empty batch produced for files without batches,
+    /// None if they produce no batches
+    empty_file_schema: Option<SchemaRef>,
+    /// Function the progress of the scan is reported to, if any
+    progress_callback: Option<ProgressCallback>,
+    /// The progress of the scan so far
+    progress: ScanProgress,
+    /// Number of batches produced since the progress was last reported
+    batches_since_progress: usize,
+    /// Reservation of the memory of the batches in flight, if any, see
+    /// [`Self::with_memory_reservation`]
+    reservation: Option<MemoryReservation>,
+    /// The estimated memory size of a batch, the largest batch read so far
+    /// if it is larger than the initial estimate
+    batch_bytes: usize,
+    /// The files opened ahead of the next file, in order, see
+    /// [`FileScanConfig::max_open_files_per_partition`]
+    prefetched: VecDeque<(NextOpen, Vec<ScalarValue>)>,
+    /// The maximum number of files opened ahead of the scanned file,
+    /// including the next file
+    max_files_ahead: usize,
+    /// The number of bytes of files to open ahead of the scanned file, if
+    /// any, see [`FileScanConfig::read_ahead_bytes`]
+    read_ahead_bytes: Option<u64>,
+    /// The bytes read from each file opened and not scanned yet, in order
+    opened_file_bytes: VecDeque<u64>,
+    /// The sum of [`Self::opened_file_bytes`]
+    bytes_ahead: u64,
+    /// Number of rows of the next file opened to skip, see
+    /// [`Self::with_resume_cursor`]
+    resume_row_offset: usize,
     /// A dynamic [`FileOpener`]. Calling `open()` returns a [`FileOpenFuture`],
     /// which can be resolved to a stream of `RecordBatch`.
     file_opener: Arc<dyn FileOpener>,
     /// The partition column projector
     pc_projector: PartitionColumnProjector,
+    /// The partition columns also read from the files, if any
+    file_partition_columns: Option<FilePartitionColumns>,
     /// The stream state
     state: FileStreamState,
     /// File stream specific metrics
@@ -84,25 +141,127 @@ impl FileStream {
             &config
                 .table_partition_cols
                 .iter()
-                .map(|x| x.name().clone())
+                .map(|x| config.output_name(x.name()).to_string())
                 .collect::<Vec<_>>(),
         );
 
-        let file_group = config.file_groups[partition].clone();
+        let empty_file_schema = config.emit_empty_file_batches.then(|| {
+            let fields = projected_schema
+                .fields()
+                .iter()
+                .filter(|field| {
+                    !config
+                        .table_partition_cols
+                        .iter()
+                        .any(|col| config.output_name(col.name()) == field.name())
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            Arc::new(Schema::new(fields))
+        });
+
+        let file_partition_columns = config.reads_partition_columns().then(|| {
+            let num_file_columns = config.file_schema.fields().len();
+            let projection = config.effective_projection();
+            FilePartitionColumns {
+                source: config.partition_column_source,
+                num_file_columns: projection
+                    .iter()
+                    .filter(|idx| **idx < num_file_columns)
+                    .count(),
+                columns: config
+                    .table_partition_cols
+                    .iter()
+                    .enumerate()
+                    .map(|(partition_idx, field)| {
+                        let output_idx = projection
+                            .iter()
+                            .position(|idx| *idx == num_file_columns + partition_idx);
+                        (field.name().clone(), output_idx)
+                    })
+                    .collect(),
+            }
+        });
+
+        let file_groups = config.file_groups_in_read_order();
+        let files_within_budget = config.files_within_byte_scan_budget(&file_groups);
+        let file_group = file_groups[partition].clone();
+        let mut file_iter: VecDeque<_> = file_group.into_inner().into_iter().collect();
+        if config.reverse_scan {
+            file_iter.make_contiguous().reverse();
+        }
+        if let Some(files_within_budget) = files_within_budget {
+            let num_skipped = file_iter.len() - files_within_budget[partition];
+            if num_skipped > 0 {
+                debug!(
+                    "Skipping {num_skipped} files of partition {partition} exceeding the byte scan budget"
+                );
+                file_iter.truncate(files_within_budget[partition]);
+            }
+            MetricBuilder::new(metrics)
+                .counter("files_skipped_by_byte_budget", partition)
+                .add(num_skipped);
+        }
 
-        Ok(Self {
-            file_iter: file_group.into_inner().into_iter().collect(),
+        let stream = Self {
+            file_iter,
             projected_schema,
             remain: config.limit,
+            per_file_row_limit: config.per_file_row_limit,
+            reverse_scan: config.reverse_scan,
+            reverse_scan_reservation: None,
+            merge_file_metadata: config.merge_file_metadata,
+            merged_schema: None,
+            max_batch_bytes: config.max_batch_bytes,
+            empty_file_schema,
+            progress_callback: config.progress_callback.clone(),
+            progress: ScanProgress {
+                partition,
+                ..Default::default()
+            },
+            batches_since_progress: 0,
+            reservation: None,
+            batch_bytes: 0,
+            prefetched: VecDeque::new(),
+            max_files_ahead: config.max_open_files_per_partition.get(),
+            read_ahead_bytes: config.read_ahead_bytes.map(|bytes| bytes as u64),
+            opened_file_bytes: VecDeque::new(),
+            bytes_ahead: 0,
+            resume_row_offset: 0,
             file_opener,
             pc_projector,
+            file_partition_columns,
             state: FileStreamState::Idle,
             file_stream_metrics: FileStreamMetrics::new(metrics, partition),
             baseline_metrics: BaselineMetrics::new(metrics, partition),
             on_error: OnError::Fail,
+        };
+        Ok(match config.resume_cursors.get(&partition) {
+            Some(cursor) => stream.with_resume_cursor(*cursor),
+            None => stream,
         })
     }
 
+    /// Resume the scan from `cursor`: the files before
+    /// [`ScanCursor::file_index`] are not read, and the first
+    /// [`ScanCursor::row_offset`] rows of that file are skipped. The skipped
+    /// files count as completed in the reported progress, and the
+    /// [`ScanCursor::rows_read`] rows count towards the limit, if any.
+    pub fn with_resume_cursor(mut self, cursor: ScanCursor) -> Self {
+        let num_skipped = cursor.file_index.min(self.file_iter.len());
+        self.file_iter.drain(..num_skipped);
+        self.progress.files_completed += num_skipped;
+        self.progress.rows_read += cursor.rows_read;
+        self.resume_row_offset = cursor.row_offset;
+        if let Some(remain) = &mut self.remain {
+            *remain = remain.saturating_sub(cursor.rows_read);
+            if *remain == 0 {
+                self.state = FileStreamState::Limit;
+            }
+        }
+        self
+    }
+
     /// Specify the behavior when an error occurs opening or scanning a file
     ///
     /// If `OnError::Skip` the stream will skip files which encounter an error and continue
@@ -112,11 +271,116 @@ impl FileStream {
         self
     }
 
+    /// Account for the memory of the batches in flight with `reservation`,
+    /// estimating the size of a batch as `batch_bytes` until a larger batch
+    /// is read.
+    ///
+    /// While a file is scanned, the next files are opened concurrently, so
+    /// the reservation is resized to a batch of every file in flight each
+    /// time a file is opened, a batch is read or a file is scanned to
+    /// completion. It shrinks as the last files drain, and is freed once the
+    /// stream is exhausted. The stream fails if the reservation cannot be
+    /// grown.
+    pub fn with_memory_reservation(
+        mut self,
+        reservation: MemoryReservation,
+        batch_bytes: usize,
+    ) -> Self {
+        self.reservation = Some(reservation);
+        self.batch_bytes = batch_bytes;
+        self
+    }
+
+    /// Account for the memory of the batches of each file buffered to emit
+    /// its rows in reverse with `reservation`, see
+    /// [`FileScanConfig::reverse_scan`].
+    ///
+    /// The reservation grows with every batch buffered and shrinks as the
+    /// reversed batches are emitted. The stream fails if the reservation
+    /// cannot be grown.
+    pub fn with_reverse_scan_reservation(
+        mut self,
+        reservation: MemoryReservation,
+    ) -> Self {
+        self.reverse_scan_reservation = Some(reservation);
+        self
+    }
+
+    /// Returns the number of files open or being opened: the scanned file,
+    /// the next file and the files opened ahead of it
+    fn files_in_flight(&self) -> usize {
+        let current = match &self.state {
+            FileStreamState::Idle => 0,
+            FileStreamState::Open { .. } => 1,
+            FileStreamState::Scan { next, .. } => 1 + usize::from(next.is_some()),
+            FileStreamState::Error | FileStreamState::Limit => return 0,
+        };
+        current + self.prefetched.len()
+    }
+
+    /// Resizes the reservation, if any, for a batch of every file in flight
+    fn resize_reservation(&mut self) -> Result<()> {
+        let files_in_flight = self.files_in_flight();
+        let Some(reservation) = &mut self.reservation else {
+            return Ok(());
+        };
+        reservation.try_resize(self.batch_bytes * files_in_flight)
+    }
+
+    /// Frees the reservation, if any, once the stream is exhausted
+    fn free_reservation(&mut self) {
+        if let Some(reservation) = &mut self.reservation {
+            reservation.free();
+        }
+    }
+
+    /// Reports the progress so far to the progress callback, if any
+    fn report_progress(&mut self) {
+        if let Some(callback) = &self.progress_callback {
+            self.batches_since_progress = 0;
+            callback(self.progress);
+        }
+    }
+
+    /// Records that `batch` was produced, reporting the progress every
+    /// [`PROGRESS_BATCH_INTERVAL`] batches
+    fn record_batch_progress(&mut self, batch: &RecordBatch) {
+        if self.progress_callback.is_none() {
+            return;
+        }
+        self.progress.rows_read += batch.num_rows();
+        self.batches_since_progress += 1;
+        if self.batches_since_progress >= PROGRESS_BATCH_INTERVAL {
+            self.report_progress();
+        }
+    }
+
+    /// Records that a file was read to completion and reports the progress
+    fn record_file_progress(&mut self) {
+        self.progress.files_completed += 1;
+        self.report_progress();
+    }
+
     /// Begin opening the next file in parallel while decoding the current file in FileStream.
     ///
     /// Since file opening is mostly IO (and may involve a
     /// bunch of sequential IO), it can be parallelized with decoding.
+    ///
+    /// Files already opened ahead of time by [`Self::prefetch_files`] are
+    /// returned first.
     fn start_next_file(&mut self) -> Option<Result<(FileOpenFuture, Vec<ScalarValue>)>> {
+        if let Some((next, partition_values)) = self.prefetched.pop_front() {
+            let future = match next {
+                NextOpen::Pending(future) => future,
+                NextOpen::Ready(reader) => Box::pin(std::future::ready(reader)),
+            };
+            return Some(Ok((future, partition_values)));
+        }
+        self.open_file()
+    }
+
+    /// Begin opening the next file of [`Self::file_iter`], if any
+    fn open_file(&mut self) -> Option<Result<(FileOpenFuture, Vec<ScalarValue>)>> {
         let part_file = self.file_iter.pop_front()?;
 
         let file_meta = FileMeta {
@@ -127,6 +391,9 @@ impl FileStream {
         };
 
         let partition_values = part_file.partition_values.clone();
+        let bytes = file_read_size(&part_file);
+        self.opened_file_bytes.push_back(bytes);
+        self.bytes_ahead += bytes;
         Some(
             self.file_opener
                 .open(file_meta, part_file)
@@ -134,8 +401,49 @@ impl FileStream {
         )
     }
 
+    /// Records that the file opened first is no longer ahead of the scan,
+    /// once it is opened
+    fn pop_opened_file(&mut self) {
+        if let Some(bytes) = self.opened_file_bytes.pop_front() {
+            self.bytes_ahead -= bytes;
+        }
+    }
+
+    /// Returns true if another file can be opened ahead of the scan
+    fn can_prefetch(&self) -> bool {
+        // the next file is open
+        self.prefetched.len() + 1 < self.max_files_ahead
+            && self
+                .read_ahead_bytes
+                .is_none_or(|read_ahead_bytes| self.bytes_ahead < read_ahead_bytes)
+    }
+
+    /// Begin opening the files following the next file, so that at most
+    /// [`Self::max_files_ahead`] files, including the next file, are open
+    /// ahead of the scanned file, and, if set, until [`Self::read_ahead_bytes`]
+    /// bytes of files are opened ahead of the scan. A file that fails to open
+    /// fails once it is scanned.
+    fn prefetch_files(&mut self) {
+        while self.can_prefetch() {
+            let Some(next) = self.open_file() else {
+                break;
+            };
+            self.prefetched.push_back(match next {
+                Ok((future, partition_values)) => {
+                    (NextOpen::Pending(future), partition_values)
+                }
+                Err(e) => (NextOpen::Ready(Err(e)), vec![]),
+            });
+        }
+    }
+
     fn poll_inner(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<RecordBatch>>> {
         loop {
+            // the files in flight change with every transition of the state
+            if let Err(e) = self.resize_reservation() {
+                self.state = FileStreamState::Error;
+                return Poll::Ready(Some(Err(e)));
+            }
             match &mut self.state {
                 FileStreamState::Idle => {
                     self.file_stream_metrics.time_opening.start();
@@ -147,7 +455,10 @@ impl FileStream {
                                 partition_values,
                             }
                         }
-                        Ok(None) => return Poll::Ready(None),
+                        Ok(None) => {
+                            self.free_reservation();
+                            return Poll::Ready(None);
+                        }
                         Err(e) => {
                             self.state = FileStreamState::Error;
                             return Poll::Ready(Some(Err(e)));
@@ -160,6 +471,32 @@ impl FileStream {
                 } => match ready!(future.poll_unpin(cx)) {
                     Ok(reader) => {
                         let partition_values = mem::take(partition_values);
+                        self.pop_opened_file();
+                        let reader = match &self.empty_file_schema {
+                            Some(schema) => emit_empty_batch(reader, Arc::clone(schema)),
+                            None => reader,
+                        };
+                        let reader = if self.reverse_scan {
+                            let reservation = self
+                                .reverse_scan_reservation
+                                .as_ref()
+                                .map(MemoryReservation::new_empty);
+                            reverse_file_rows(reader, reservation)
+                        } else {
+                            reader
+                        };
+                        let reader = match self.per_file_row_limit {
+                            Some(limit) => limit_file_rows(reader, limit),
+                            None => reader,
+                        };
+                        let reader = match mem::take(&mut self.resume_row_offset) {
+                            0 => reader,
+                            offset => skip_file_rows(reader, offset),
+                        };
+                        let reader = match self.max_batch_bytes {
+                            Some(max_bytes) => split_file_batches(reader, max_bytes),
+                            None => reader,
+                        };
 
                         // include time needed to start opening in `start_next_file`
                         self.file_stream_metrics.time_opening.stop();
@@ -169,6 +506,7 @@ impl FileStream {
 
                         match next {
                             Ok(Some((next_future, next_partition_values))) => {
+                                self.prefetch_files();
                                 self.state = FileStreamState::Scan {
                                     partition_values,
                                     reader,
@@ -192,10 +530,13 @@ impl FileStream {
                         }
                     }
                     Err(e) => {
+                        self.pop_opened_file();
                         self.file_stream_metrics.file_open_errors.add(1);
                         match self.on_error {
                             OnError::Skip => {
                                 self.file_stream_metrics.time_opening.stop();
+                                // the rows to skip belong to the skipped file
+                                self.resume_row_offset = 0;
                                 self.state = FileStreamState::Idle
                             }
                             OnError::Fail => {
@@ -210,8 +551,11 @@ impl FileStream {
                     partition_values,
                     next,
                 } => {
-                    // We need to poll the next `FileOpenFuture` here to drive it forward
-                    if let Some((next_open_future, _)) = next {
+                    // We need to poll the next `FileOpenFuture`, and those of the
+                    // files opened ahead of time, here to drive them forward
+                    for (next_open_future, _) in
+                        next.iter_mut().chain(&mut self.prefetched)
+                    {
                         if let NextOpen::Pending(f) = next_open_future {
                             if let Poll::Ready(reader) = f.as_mut().poll(cx) {
                                 *next_open_future = NextOpen::Ready(reader);
@@ -222,9 +566,29 @@ impl FileStream {
                         Some(Ok(batch)) => {
                             self.file_stream_metrics.time_scanning_until_data.stop();
                             self.file_stream_metrics.time_scanning_total.stop();
-                            let result = self
-                                .pc_projector
-                                .project(batch, partition_values)
+                            if self.merge_file_metadata && self.merged_schema.is_none() {
+                                self.merged_schema = Some(merge_metadata(
+                                    &self.projected_schema,
+                                    batch.schema_ref(),
+                                ));
+                            }
+                            let result = match &self.file_partition_columns {
+                                Some(columns) => columns.project(
+                                    batch,
+                                    partition_values,
+                                    &mut self.pc_projector,
+                                ),
+                                None => {
+                                    self.pc_projector.project(batch, partition_values)
+                                }
+                            };
+                            let result = result
+                                .and_then(|batch| match &self.merged_schema {
+                                    Some(schema) => {
+                                        Ok(batch.with_schema(Arc::clone(schema))?)
+                                    }
+                                    None => Ok(batch),
+                                })
                                 .map_err(|e| ArrowError::ExternalError(e.into()))
                                 .map(|batch| match &mut self.remain {
                                     Some(remain) => {
@@ -241,10 +605,20 @@ impl FileStream {
                                     None => batch,
                                 });
 
-                            if result.is_err() {
+                            match &result {
+                                Ok(batch) => {
+                                    self.record_batch_progress(batch);
+                                    self.batch_bytes = self
+                                        .batch_bytes
+                                        .max(batch.get_array_memory_size());
+                                    if let Err(e) = self.resize_reservation() {
+                                        self.state = FileStreamState::Error;
+                                        return Poll::Ready(Some(Err(e)));
+                                    }
+                                }
                                 // If the partition value projection fails, this is not governed by
                                 // the `OnError` behavior
-                                self.state = FileStreamState::Error
+                                Err(_) => self.state = FileStreamState::Error,
                             }
                             self.file_stream_metrics.time_scanning_total.start();
                             return Poll::Ready(Some(result.map_err(Into::into)));
@@ -277,7 +651,7 @@ impl FileStream {
                                             }
                                         }
                                     }
-                                    None => return Poll::Ready(None),
+                                    None => self.state = FileStreamState::Idle,
                                 },
                                 OnError::Fail => {
                                     self.state = FileStreamState::Error;
@@ -288,8 +662,10 @@ impl FileStream {
                         None => {
                             self.file_stream_metrics.time_scanning_until_data.stop();
                             self.file_stream_metrics.time_scanning_total.stop();
+                            let next = mem::take(next);
+                            self.record_file_progress();
 
-                            match mem::take(next) {
+                            match next {
                                 Some((future, partition_values)) => {
                                     self.file_stream_metrics.time_opening.start();
 
@@ -310,19 +686,159 @@ impl FileStream {
                                         }
                                     }
                                 }
-                                None => return Poll::Ready(None),
+                                None => self.state = FileStreamState::Idle,
                             }
                         }
                     }
                 }
                 FileStreamState::Error | FileStreamState::Limit => {
-                    return Poll::Ready(None)
+                    self.free_reservation();
+                    return Poll::Ready(None);
                 }
             }
         }
     }
 }
 
+/// Produces a batch without rows with `schema` if `reader` produces no batches
+fn emit_empty_batch(
+    reader: BoxStream<'static, Result<RecordBatch, ArrowError>>,
+    schema: SchemaRef,
+) -> BoxStream<'static, Result<RecordBatch, ArrowError>> {
+    futures::stream::unfold(Some((reader, true)), move |state| {
+        let schema = Arc::clone(&schema);
+        async move {
+            let (mut reader, empty) = state?;
+            match reader.next().await {
+                Some(batch) => Some((batch, Some((reader, false)))),
+                None if empty => Some((Ok(RecordBatch::new_empty(schema)), None)),
+                None => None,
+            }
+        }
+    })
+    .boxed()
+}
+
+/// Stops `reader` after it produced `limit` rows, without polling it further
+fn limit_file_rows(
+    reader: BoxStream<'static, Result<RecordBatch, ArrowError>>,
+    limit: usize,
+) -> BoxStream<'static, Result<RecordBatch, ArrowError>> {
+    futures::stream::unfold((reader, limit), |(mut reader, remain)| async move {
+        if remain == 0 {
+            return None;
+        }
+        match reader.next().await? {
+            Ok(batch) => {
+                let num_rows = batch.num_rows().min(remain);
+                Some((Ok(batch.slice(0, num_rows)), (reader, remain - num_rows)))
+            }
+            Err(e) => Some((Err(e), (reader, remain))),
+        }
+    })
+    .boxed()
+}
+
+/// Skips the first `offset` rows produced by `reader`
+fn skip_file_rows(
+    reader: BoxStream<'static, Result<RecordBatch, ArrowError>>,
+    offset: usize,
+) -> BoxStream<'static, Result<RecordBatch, ArrowError>> {
+    let mut remain = offset;
+    reader
+        .try_filter_map(move |batch| {
+            let num_skipped = batch.num_rows().min(remain);
+            remain -= num_skipped;
+            let batch = (num_skipped < batch.num_rows())
+                .then(|| batch.slice(num_skipped, batch.num_rows() - num_skipped));
+            futures::future::ready(Ok(batch))
+        })
+        .boxed()
+}
+
+/// Splits the batches of `reader` whose estimated memory size exceeds
+/// `max_bytes` into batches of as many rows as fit, but at least one
+fn split_file_batches(
+    reader: BoxStream<'static, Result<RecordBatch, ArrowError>>,
+    max_bytes: usize,
+) -> BoxStream<'static, Result<RecordBatch, ArrowError>> {
+    reader
+        .map_ok(move |batch| {
+            let num_rows = batch.num_rows();
+            let rows_per_batch = match batch_memory_size(&batch) {
+                Ok(size) if size > max_bytes && num_rows > 1 => {
+                    (max_bytes / size.div_ceil(num_rows)).max(1)
+                }
+                _ => num_rows,
+            };
+            let batches = if rows_per_batch >= num_rows {
+                vec![batch]
+            } else {
+                (0..num_rows)
+                    .step_by(rows_per_batch)
+                    .map(|offset| {
+                        batch.slice(offset, rows_per_batch.min(num_rows - offset))
+                    })
+                    .collect()
+            };
+            futures::stream::iter(batches).map(Ok)
+        })
+        .try_flatten()
+        .boxed()
+}
+
+/// Returns the memory size of the data referenced by `batch`, excluding the
+/// parts of the buffers outside of its slice
+fn batch_memory_size(batch: &RecordBatch) -> Result<usize, ArrowError> {
+    batch
+        .columns()
+        .iter()
+        .map(|array| array.to_data().get_slice_memory_size())
+        .sum()
+}
+
+/// Returns `projected_schema` with the schema metadata of `file_schema` added,
+/// keeping the value of `projected_schema` for keys present in both
+fn merge_metadata(projected_schema: &SchemaRef, file_schema: &SchemaRef) -> SchemaRef {
+    let mut metadata = file_schema.metadata().clone();
+    metadata.extend(projected_schema.metadata().clone());
+    Arc::new(Schema::new_with_metadata(
+        projected_schema.fields().clone(),
+        metadata,
+    ))
+}
+
+/// Emits the rows of `reader` in reverse, which requires buffering all of its
+/// batches, accounted for with `reservation` if any
+fn reverse_file_rows(
+    mut reader: BoxStream<'static, Result<RecordBatch, ArrowError>>,
+    mut reservation: Option<MemoryReservation>,
+) -> BoxStream<'static, Result<RecordBatch, ArrowError>> {
+    futures::stream::once(async move {
+        let mut batches = vec![];
+        while let Some(batch) = reader.try_next().await? {
+            if let Some(reservation) = &mut reservation {
+                reservation
+                    .try_grow(batch.get_array_memory_size())
+                    .map_err(|e| ArrowError::ExternalError(e.into()))?;
+            }
+            batches.push(batch);
+        }
+        let reversed = batches.into_iter().rev().map(move |batch| {
+            let indices =
+                UInt32Array::from_iter_values((0..batch.num_rows() as u32).rev());
+            let reversed = take_record_batch(&batch, &indices);
+            if let Some(reservation) = &mut reservation {
+                reservation.shrink(batch.get_array_memory_size());
+            }
+            reversed
+        });
+        Ok::<_, ArrowError>(futures::stream::iter(reversed))
+    })
+    .try_flatten()
+    .boxed()
+}
+
 impl Stream for FileStream {
     type Item = Result<RecordBatch>;
 
@@ -339,7 +855,11 @@ impl Stream for FileStream {
 
 impl RecordBatchStream for FileStream {
     fn schema(&self) -> SchemaRef {
-        Arc::clone(&self.projected_schema)
+        Arc::clone(
+            self.merged_schema
+                .as_ref()
+                .unwrap_or(&self.projected_schema),
+        )
     }
 }
 
@@ -361,6 +881,42 @@ impl Default for OnError {
     }
 }
 
+/// Progress of the scan of a partition, reported to a [`ProgressCallback`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanProgress {
+    /// The partition being scanned
+    pub partition: usize,
+    /// Number of files of the partition read to completion so far
+    pub files_completed: usize,
+    /// Number of rows produced by the partition so far
+    pub rows_read: usize,
+}
+
+/// Position in the scan of a partition to resume it from, see
+/// [`FileStream::with_resume_cursor`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ScanCursor {
+    /// Index of the file to resume from among the files of the partition, in
+    /// the order they are scanned
+    pub file_index: usize,
+    /// Number of rows of that file already processed
+    pub row_offset: usize,
+    /// Number of rows produced by the partition before the cursor, including
+    /// the [`Self::row_offset`] rows of the file, as reported by
+    /// [`ScanProgress::rows_read`]. They count towards the limit of the scan.
+    pub rows_read: usize,
+}
+
+/// Function receiving the [`ScanProgress`] of a partition, see
+/// [`FileScanConfigBuilder::with_progress_callback`]
+///
+/// [`FileScanConfigBuilder::with_progress_callback`]: crate::file_scan_config::FileScanConfigBuilder::with_progress_callback
+pub type ProgressCallback = Arc<dyn Fn(ScanProgress) + Send + Sync>;
+
+/// Number of batches after which the progress is reported, if no file is
+/// completed in the meantime
+const PROGRESS_BATCH_INTERVAL: usize = 16;
+
 /// Generic API for opening a file using an [`ObjectStore`] and resolving to a
 /// stream of [`RecordBatch`]
 ///
@@ -371,6 +927,71 @@ pub trait FileOpener: Unpin + Send + Sync {
     fn open(&self, file_meta: FileMeta, file: PartitionedFile) -> Result<FileOpenFuture>;
 }
 
+/// The partition columns read from the files as well as from the
+/// [`PartitionedFile::partition_values`], which follow the projected file
+/// columns in the batches of the files, see [`PartitionColumnSource`]
+struct FilePartitionColumns {
+    source: PartitionColumnSource,
+    /// Number of projected file columns in the batches of the files
+    num_file_columns: usize,
+    /// The name of each partition column and its index in the output, if
+    /// projected
+    columns: Vec<(String, Option<usize>)>,
+}
+
+impl FilePartitionColumns {
+    /// Projects `file_batch` of a file with `partition_values` to the output
+    /// schema with `projector`, after validating or before replacing the
+    /// partition values with the columns of the file
+    fn project(
+        &self,
+        file_batch: RecordBatch,
+        partition_values: &[ScalarValue],
+        projector: &mut PartitionColumnProjector,
+    ) -> Result<RecordBatch> {
+        let file_columns = file_batch.columns()[self.num_file_columns..].to_vec();
+        let file_batch =
+            file_batch.project(&(0..self.num_file_columns).collect::<Vec<_>>())?;
+
+        // the values of the partition columns, and the columns of the file
+        let mut columns = vec![];
+        for (((name, output_idx), file_column), value) in
+            self.columns.iter().zip(&file_columns).zip(partition_values)
+        {
+            let value = match value {
+                ScalarValue::Dictionary(_, value) => value.as_ref(),
+                value => value,
+            }
+            .cast_to(file_column.data_type())?;
+            if self.source == PartitionColumnSource::Validate {
+                let differs = neq(file_column, &value.to_scalar()?)?;
+                if let Some(row) = differs.iter().position(|d| d == Some(true)) {
+                    return exec_err!(
+                        "Partition column {name} has the value {} in the file, but {value} in its partition values",
+                        ScalarValue::try_from_array(file_column, row)?
+                    );
+                }
+            }
+            if let Some(output_idx) = output_idx {
+                columns.push((*output_idx, file_column, value));
+            }
+        }
+
+        let batch = projector.project(file_batch, partition_values)?;
+        if self.source != PartitionColumnSource::FilePreferred {
+            return Ok(batch);
+        }
+        let mut output = batch.columns().to_vec();
+        for (output_idx, file_column, value) in columns {
+            let column =
+                zip(&is_not_null(file_column)?, file_column, &value.to_scalar()?)?;
+            output[output_idx] =
+                cast(&column, batch.schema().field(output_idx).data_type())?;
+        }
+        Ok(RecordBatch::try_new(batch.schema(), output)?)
+    }
+}
+
 /// Represents the state of the next `FileOpenFuture`. Since we need to poll
 /// this future while scanning the current file, we need to store the result if it
 /// is ready
@@ -523,21 +1144,27 @@ impl FileStreamMetrics {
 
 #[cfg(test)]
 mod tests {
-    use crate::file_scan_config::FileScanConfigBuilder;
+    use crate::file_groups::FileGroup;
+    use crate::file_scan_config::{FileScanConfig, FileScanConfigBuilder};
     use crate::tests::make_partition;
     use crate::PartitionedFile;
     use arrow::error::ArrowError;
     use datafusion_common::error::Result;
     use datafusion_execution::object_store::ObjectStoreUrl;
     use datafusion_physical_plan::metrics::ExecutionPlanMetricsSet;
-    use futures::{FutureExt as _, StreamExt as _};
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::Arc;
+    use futures::{FutureExt as _, StreamExt as _, TryStreamExt as _};
+    use std::collections::HashMap;
+    use std::num::NonZeroUsize;
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
 
     use crate::file_meta::FileMeta;
-    use crate::file_stream::{FileOpenFuture, FileOpener, FileStream, OnError};
+    use crate::file_stream::{
+        batch_memory_size, FileOpenFuture, FileOpener, FileStream, OnError, ScanCursor,
+    };
     use crate::test_util::MockSource;
-    use arrow::array::RecordBatch;
+    use arrow::array::{ArrayRef, RecordBatch, StringArray};
+    use arrow::compute::concat_batches;
     use arrow::datatypes::Schema;
 
     use datafusion_common::{assert_batches_eq, internal_err};
@@ -585,6 +1212,12 @@ mod tests {
         num_files: usize,
         /// Global limit of records emitted by the stream
         limit: Option<usize>,
+        /// Limit of records read from each file
+        per_file_row_limit: Option<usize>,
+        /// Whether the files are read in reverse
+        reverse_scan: bool,
+        /// Position to resume the scan from
+        resume_cursor: Option<ScanCursor>,
         /// Error-handling behavior of the stream
         on_error: OnError,
         /// Mock `FileOpener`
@@ -608,6 +1241,24 @@ mod tests {
             self
         }
 
+        /// Specify the limit of records read from each file
+        pub fn with_per_file_row_limit(mut self, limit: Option<usize>) -> Self {
+            self.per_file_row_limit = limit;
+            self
+        }
+
+        /// Specify whether the files are read in reverse
+        pub fn with_reverse_scan(mut self, reverse_scan: bool) -> Self {
+            self.reverse_scan = reverse_scan;
+            self
+        }
+
+        /// Specify the position to resume the scan from
+        pub fn with_resume_cursor(mut self, cursor: ScanCursor) -> Self {
+            self.resume_cursor = Some(cursor);
+            self
+        }
+
         /// Specify the index of files in the stream which should
         /// throw an error when opening
         pub fn with_open_errors(mut self, idx: Vec<usize>) -> Self {
@@ -661,14 +1312,19 @@ mod tests {
 
             let on_error = self.on_error;
 
-            let config = FileScanConfigBuilder::new(
+            let mut builder = FileScanConfigBuilder::new(
                 ObjectStoreUrl::parse("test:///").unwrap(),
                 file_schema,
                 Arc::new(MockSource::default()),
             )
             .with_file_group(file_group)
             .with_limit(self.limit)
-            .build();
+            .with_per_file_row_limit(self.per_file_row_limit)
+            .with_reverse_scan(self.reverse_scan);
+            if let Some(cursor) = self.resume_cursor {
+                builder = builder.with_resume_cursor(0, cursor);
+            }
+            let config = builder.build();
             let metrics_set = ExecutionPlanMetricsSet::new();
             let file_stream =
                 FileStream::new(&config, 0, Arc::new(self.opener), &metrics_set)
@@ -988,4 +1644,794 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn with_resume_cursor() -> Result<()> {
+        // each file has batches [0,1,2] and [0,1], the scan resumes from the
+        // last row of the second file
+        let batches = FileStreamTest::new()
+            .with_records(vec![make_partition(3), make_partition(2)])
+            .with_num_files(3)
+            .with_resume_cursor(ScanCursor {
+                file_index: 1,
+                row_offset: 4,
+                rows_read: 9,
+            })
+            .result()
+            .await?;
+        #[rustfmt::skip]
+        assert_batches_eq!(&[
+            "+---+",
+            "| i |",
+            "+---+",
+            "| 1 |",
+            "| 0 |",
+            "| 1 |",
+            "| 2 |",
+            "| 0 |",
+            "| 1 |",
+            "+---+",
+        ], &batches);
+
+        // nothing is left after the last file
+        let batches = FileStreamTest::new()
+            .with_records(vec![make_partition(3), make_partition(2)])
+            .with_num_files(3)
+            .with_resume_cursor(ScanCursor {
+                file_index: 3,
+                row_offset: 0,
+                rows_read: 15,
+            })
+            .result()
+            .await?;
+        assert!(batches.is_empty());
+
+        // the rows read before the cursor count towards the limit
+        let batches = FileStreamTest::new()
+            .with_records(vec![make_partition(3), make_partition(2)])
+            .with_num_files(3)
+            .with_limit(Some(7))
+            .with_resume_cursor(ScanCursor {
+                file_index: 1,
+                row_offset: 1,
+                rows_read: 6,
+            })
+            .result()
+            .await?;
+        #[rustfmt::skip]
+        assert_batches_eq!(&[
+            "+---+",
+            "| i |",
+            "+---+",
+            "| 1 |",
+            "+---+",
+        ], &batches);
+
+        // nothing is left once the rows read reach the limit, e.g. when the
+        // first file produced all rows of the limit
+        let batches = FileStreamTest::new()
+            .with_records(vec![make_partition(3), make_partition(2)])
+            .with_num_files(3)
+            .with_limit(Some(3))
+            .with_resume_cursor(ScanCursor {
+                file_index: 1,
+                row_offset: 1,
+                rows_read: 3,
+            })
+            .result()
+            .await?;
+        assert!(batches.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_per_file_row_limit() -> Result<()> {
+        // each file has batches [0,1,2] and [0,1], only its first 4 rows are read
+        let batches = FileStreamTest::new()
+            .with_records(vec![make_partition(3), make_partition(2)])
+            .with_num_files(3)
+            .with_per_file_row_limit(Some(4))
+            .result()
+            .await?;
+        #[rustfmt::skip]
+        assert_batches_eq!(&[
+            "+---+",
+            "| i |",
+            "+---+",
+            "| 0 |",
+            "| 1 |",
+            "| 2 |",
+            "| 0 |",
+            "| 0 |",
+            "| 1 |",
+            "| 2 |",
+            "| 0 |",
+            "| 0 |",
+            "| 1 |",
+            "| 2 |",
+            "| 0 |",
+            "+---+",
+        ], &batches);
+
+        // combined with the global limit
+        let batches = FileStreamTest::new()
+            .with_records(vec![make_partition(3), make_partition(2)])
+            .with_num_files(3)
+            .with_limit(Some(5))
+            .with_per_file_row_limit(Some(2))
+            .result()
+            .await?;
+        #[rustfmt::skip]
+        assert_batches_eq!(&[
+            "+---+",
+            "| i |",
+            "+---+",
+            "| 0 |",
+            "| 1 |",
+            "| 0 |",
+            "| 1 |",
+            "| 0 |",
+            "+---+",
+        ], &batches);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_reverse_scan() -> Result<()> {
+        // each file has batches [0,1,2] and [0,1], whose rows are read in reverse
+        let batches = FileStreamTest::new()
+            .with_records(vec![make_partition(3), make_partition(2)])
+            .with_num_files(2)
+            .with_reverse_scan(true)
+            .result()
+            .await?;
+        #[rustfmt::skip]
+        assert_batches_eq!(&[
+            "+---+",
+            "| i |",
+            "+---+",
+            "| 1 |",
+            "| 0 |",
+            "| 2 |",
+            "| 1 |",
+            "| 0 |",
+            "| 1 |",
+            "| 0 |",
+            "| 2 |",
+            "| 1 |",
+            "| 0 |",
+            "+---+",
+        ], &batches);
+
+        // the last file is read first
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            Arc::new(Schema::empty()),
+            Arc::new(MockSource::default()),
+        )
+        .with_file_group(FileGroup::new(vec![
+            PartitionedFile::new("a.csv", 10),
+            PartitionedFile::new("b.csv", 10),
+        ]))
+        .with_reverse_scan(true)
+        .build();
+        let stream = FileStream::new(
+            &config,
+            0,
+            Arc::new(TestOpener::default()),
+            &ExecutionPlanMetricsSet::new(),
+        )?;
+        let paths = stream.file_iter.iter().map(|f| f.path().as_ref());
+        assert_eq!(paths.collect::<Vec<_>>(), vec!["b.csv", "a.csv"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_reverse_scan_reservation() -> Result<()> {
+        use datafusion_execution::memory_pool::{
+            GreedyMemoryPool, MemoryConsumer, MemoryPool,
+        };
+
+        let records = vec![make_partition(100), make_partition(3)];
+        let file_bytes: usize = records.iter().map(|b| b.get_array_memory_size()).sum();
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            records[0].schema(),
+            Arc::new(MockSource::default()),
+        )
+        .with_file_group(FileGroup::new(vec![PartitionedFile::new("a", 10)]))
+        .with_reverse_scan(true)
+        .build();
+        let stream = |pool: &Arc<dyn MemoryPool>| {
+            let opener = TestOpener {
+                records: records.clone(),
+                ..Default::default()
+            };
+            let reservation = MemoryConsumer::new("test").register(pool);
+            FileStream::new(
+                &config,
+                0,
+                Arc::new(opener),
+                &ExecutionPlanMetricsSet::new(),
+            )
+            .map(|stream| stream.with_reverse_scan_reservation(reservation))
+        };
+
+        // all batches of the file are reserved until they are emitted
+        let pool: Arc<dyn MemoryPool> = Arc::new(GreedyMemoryPool::new(file_bytes));
+        let mut stream = stream(&pool)?;
+        assert_eq!(stream.next().await.unwrap()?.num_rows(), 3);
+        assert_eq!(pool.reserved(), records[0].get_array_memory_size());
+        assert_eq!(stream.next().await.unwrap()?.num_rows(), 100);
+        assert!(stream.next().await.is_none());
+        assert_eq!(pool.reserved(), 0);
+
+        // the stream fails if the batches of the file cannot be reserved
+        let pool: Arc<dyn MemoryPool> = Arc::new(GreedyMemoryPool::new(file_bytes - 1));
+        let err = stream(&pool)?.try_collect::<Vec<_>>().await.unwrap_err();
+        assert!(err.to_string().contains("Resources exhausted"), "{err}");
+        assert_eq!(pool.reserved(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_max_batch_bytes() -> Result<()> {
+        // 100 rows of about 1000 bytes each
+        let values = (0..100).map(|i| format!("{i:0>1000}"));
+        let batch = RecordBatch::try_from_iter(vec![(
+            "s",
+            Arc::new(StringArray::from_iter_values(values)) as ArrayRef,
+        )])?;
+        let max_bytes = 10_000;
+
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            batch.schema(),
+            Arc::new(MockSource::default()),
+        )
+        .with_file_group(FileGroup::new(vec![PartitionedFile::new("a.csv", 10)]))
+        .with_max_batch_bytes(Some(max_bytes))
+        .build();
+        let opener = TestOpener {
+            records: vec![batch.clone()],
+            ..Default::default()
+        };
+        let batches = FileStream::new(
+            &config,
+            0,
+            Arc::new(opener),
+            &ExecutionPlanMetricsSet::new(),
+        )?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        assert!(batches.len() > 1);
+        for batch in &batches {
+            assert!(batch_memory_size(batch)? <= max_bytes);
+        }
+        assert_eq!(concat_batches(&batch.schema(), &batches)?, batch);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_memory_reservation() -> Result<()> {
+        use datafusion_execution::memory_pool::{
+            GreedyMemoryPool, MemoryConsumer, MemoryPool,
+        };
+
+        // each file has a batch of 100 rows and a smaller batch of 3 rows
+        let records = vec![make_partition(100), make_partition(3)];
+        let batch_bytes = records[0].get_array_memory_size();
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            records[0].schema(),
+            Arc::new(MockSource::default()),
+        )
+        .with_file_group(FileGroup::new(vec![
+            PartitionedFile::new("a", 10),
+            PartitionedFile::new("b", 10),
+            PartitionedFile::new("c", 10),
+        ]));
+        let stream = |config: &FileScanConfig, pool: &Arc<dyn MemoryPool>| {
+            let opener = TestOpener {
+                records: records.clone(),
+                ..Default::default()
+            };
+            let reservation = MemoryConsumer::new("test").register(pool);
+            FileStream::new(config, 0, Arc::new(opener), &ExecutionPlanMetricsSet::new())
+                .map(|stream| stream.with_memory_reservation(reservation, 8))
+        };
+        let reserved_files = |stream: &FileStream| {
+            let size = stream.reservation.as_ref().unwrap().size();
+            assert_eq!(size % batch_bytes, 0);
+            size / batch_bytes
+        };
+
+        // the largest batch is reserved for every file in flight after each
+        // poll, and released as the last files drain
+        for (max_files_ahead, expected) in
+            [(1, [2, 2, 2, 2, 1, 1, 0]), (2, [3, 3, 2, 2, 1, 1, 0])]
+        {
+            let config = builder
+                .clone()
+                .with_max_open_files_per_partition(
+                    NonZeroUsize::new(max_files_ahead).unwrap(),
+                )
+                .build();
+            let pool: Arc<dyn MemoryPool> = Arc::new(GreedyMemoryPool::new(1 << 20));
+            let mut stream = stream(&config, &pool)?;
+            let mut reserved = vec![];
+            while let Some(batch) = stream.next().await {
+                batch?;
+                reserved.push(reserved_files(&stream));
+            }
+            reserved.push(reserved_files(&stream));
+            assert_eq!(reserved, expected);
+            assert_eq!(pool.reserved(), 0);
+        }
+
+        // the stream fails if the memory cannot be reserved
+        let pool: Arc<dyn MemoryPool> = Arc::new(GreedyMemoryPool::new(batch_bytes));
+        let err = stream(&builder.build(), &pool)?
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Resources exhausted"), "{err}");
+        Ok(())
+    }
+
+    /// Test `FileOpener` recording the largest number of files open at the
+    /// same time, from the call to `open` until their stream is dropped
+    #[derive(Default)]
+    struct CountingOpener {
+        records: Vec<RecordBatch>,
+        open: Arc<AtomicUsize>,
+        max_open: Arc<AtomicUsize>,
+    }
+
+    /// Decrements the number of open files when the stream of a file is
+    /// dropped
+    struct OpenFile(Arc<AtomicUsize>);
+
+    impl Drop for OpenFile {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    impl FileOpener for CountingOpener {
+        fn open(
+            &self,
+            _file_meta: FileMeta,
+            _file: PartitionedFile,
+        ) -> Result<FileOpenFuture> {
+            let open = self.open.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_open.fetch_max(open, Ordering::SeqCst);
+            let file = OpenFile(Arc::clone(&self.open));
+            let records = self.records.clone();
+            Ok(Box::pin(async move {
+                tokio::task::yield_now().await;
+                Ok(futures::stream::iter(records.into_iter().map(Ok))
+                    .map(move |batch| {
+                        let _file = &file;
+                        batch
+                    })
+                    .boxed())
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn with_max_open_files_per_partition() -> Result<()> {
+        let records = vec![make_partition(3), make_partition(2)];
+        for max_files_ahead in [1, 2, 3] {
+            let config = FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("test:///").unwrap(),
+                records[0].schema(),
+                Arc::new(MockSource::default()),
+            )
+            .with_file_group(FileGroup::new(
+                (0..10)
+                    .map(|idx| PartitionedFile::new(format!("file{idx}"), 10))
+                    .collect(),
+            ))
+            .with_max_open_files_per_partition(
+                NonZeroUsize::new(max_files_ahead).unwrap(),
+            )
+            .build();
+            let opener = CountingOpener {
+                records: records.clone(),
+                ..Default::default()
+            };
+            let max_open = Arc::clone(&opener.max_open);
+
+            let batches = FileStream::new(
+                &config,
+                0,
+                Arc::new(opener),
+                &ExecutionPlanMetricsSet::new(),
+            )?
+            .try_collect::<Vec<_>>()
+            .await?;
+            assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 50);
+            // the scanned file is open in addition to the files opened ahead
+            assert_eq!(max_open.load(Ordering::SeqCst), max_files_ahead + 1);
+        }
+        Ok(())
+    }
+
+    /// Test `FileOpener` recording the bytes of the files opened ahead of
+    /// each file when it starts being scanned
+    #[derive(Default)]
+    struct ReadAheadOpener {
+        records: Vec<RecordBatch>,
+        opened_bytes: Arc<AtomicU64>,
+        scanned_bytes: Arc<AtomicU64>,
+        bytes_ahead: Arc<Mutex<Vec<u64>>>,
+    }
+
+    impl FileOpener for ReadAheadOpener {
+        fn open(
+            &self,
+            file_meta: FileMeta,
+            _file: PartitionedFile,
+        ) -> Result<FileOpenFuture> {
+            let size = file_meta.object_meta.size;
+            self.opened_bytes.fetch_add(size, Ordering::SeqCst);
+            let opened_bytes = Arc::clone(&self.opened_bytes);
+            let scanned_bytes = Arc::clone(&self.scanned_bytes);
+            let bytes_ahead = Arc::clone(&self.bytes_ahead);
+            let records = self.records.clone();
+            Ok(Box::pin(async move {
+                let start = futures::stream::once(async move {
+                    let scanned = scanned_bytes.fetch_add(size, Ordering::SeqCst) + size;
+                    let opened = opened_bytes.load(Ordering::SeqCst);
+                    bytes_ahead.lock().unwrap().push(opened - scanned);
+                    None
+                });
+                let batches = futures::stream::iter(records.into_iter().map(Some));
+                Ok(start
+                    .chain(batches)
+                    .filter_map(|b| async { b.map(Ok) })
+                    .boxed())
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn with_read_ahead_bytes() -> Result<()> {
+        let records = vec![make_partition(3)];
+        let scan = |read_ahead_bytes: Option<usize>| {
+            let records = records.clone();
+            async move {
+                let builder = FileScanConfigBuilder::new(
+                    ObjectStoreUrl::parse("test:///").unwrap(),
+                    records[0].schema(),
+                    Arc::new(MockSource::default()),
+                )
+                .with_file_group(FileGroup::new(
+                    (0..10)
+                        .map(|idx| PartitionedFile::new(format!("file{idx}"), 100))
+                        .collect(),
+                ));
+                let config = match read_ahead_bytes {
+                    Some(bytes) => builder
+                        .with_max_open_files_per_partition(NonZeroUsize::new(10).unwrap())
+                        .with_read_ahead_bytes(bytes),
+                    None => builder,
+                }
+                .build();
+                let opener = ReadAheadOpener {
+                    records,
+                    ..Default::default()
+                };
+                let bytes_ahead = Arc::clone(&opener.bytes_ahead);
+                let batches = FileStream::new(
+                    &config,
+                    0,
+                    Arc::new(opener),
+                    &ExecutionPlanMetricsSet::new(),
+                )?
+                .try_collect::<Vec<_>>()
+                .await?;
+                assert_eq!(batches.len(), 10);
+                let bytes_ahead = bytes_ahead.lock().unwrap().clone();
+                Ok::<_, datafusion_common::DataFusionError>(bytes_ahead)
+            }
+        };
+
+        // only the next file is opened ahead by default
+        assert_eq!(
+            scan(None).await?,
+            [100, 100, 100, 100, 100, 100, 100, 100, 100, 0]
+        );
+
+        // files are opened until the window is reached, and then as files
+        // are scanned, until the last file
+        assert_eq!(
+            scan(Some(350)).await?,
+            [400, 400, 400, 400, 400, 400, 300, 200, 100, 0]
+        );
+        // a window smaller than a file still opens the next file
+        assert_eq!(scan(Some(1)).await?, scan(None).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_max_open_files_and_read_ahead_bytes() -> Result<()> {
+        let records = vec![make_partition(3)];
+        for (max_files_ahead, read_ahead_bytes, expected) in
+            [(2, 1000, 3), (10, 250, 4), (1, 1000, 2)]
+        {
+            let config = FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("test:///").unwrap(),
+                records[0].schema(),
+                Arc::new(MockSource::default()),
+            )
+            .with_file_group(FileGroup::new(
+                (0..10)
+                    .map(|idx| PartitionedFile::new(format!("file{idx}"), 100))
+                    .collect(),
+            ))
+            .with_max_open_files_per_partition(
+                NonZeroUsize::new(max_files_ahead).unwrap(),
+            )
+            .with_read_ahead_bytes(read_ahead_bytes)
+            .build();
+            let opener = CountingOpener {
+                records: records.clone(),
+                ..Default::default()
+            };
+            let max_open = Arc::clone(&opener.max_open);
+
+            let batches = FileStream::new(
+                &config,
+                0,
+                Arc::new(opener),
+                &ExecutionPlanMetricsSet::new(),
+            )?
+            .try_collect::<Vec<_>>()
+            .await?;
+            assert_eq!(batches.len(), 10);
+            // the tighter of the two limits applies
+            assert_eq!(max_open.load(Ordering::SeqCst), expected);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_byte_scan_budget() -> Result<()> {
+        let records = vec![make_partition(3)];
+        let file_group = |prefix: &str| {
+            FileGroup::new(
+                (0..4)
+                    .map(|idx| PartitionedFile::new(format!("{prefix}{idx}"), 100))
+                    .collect(),
+            )
+        };
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            records[0].schema(),
+            Arc::new(MockSource::default()),
+        )
+        .with_file_groups(vec![file_group("a"), file_group("b")]);
+        let scan = |config: FileScanConfig| {
+            let records = records.clone();
+            async move {
+                let metrics = ExecutionPlanMetricsSet::new();
+                let mut num_batches = vec![];
+                for partition in 0..2 {
+                    let opener = ReadAheadOpener {
+                        records: records.clone(),
+                        ..Default::default()
+                    };
+                    let batches =
+                        FileStream::new(&config, partition, Arc::new(opener), &metrics)?
+                            .try_collect::<Vec<_>>()
+                            .await?;
+                    num_batches.push(batches.len());
+                }
+                let skipped = metrics
+                    .clone_inner()
+                    .sum_by_name("files_skipped_by_byte_budget")
+                    .map(|skipped| skipped.as_usize());
+                Ok::<_, datafusion_common::DataFusionError>((num_batches, skipped))
+            }
+        };
+
+        // every file is read by default
+        assert_eq!(scan(builder.clone().build()).await?, (vec![4, 4], None));
+
+        // a0, b0, a1, b1 and a2, which reaches the budget, are read
+        let config = builder.clone().with_byte_scan_budget(450).build();
+        assert_eq!(scan(config).await?, (vec![3, 2], Some(3)));
+
+        // the budget does not depend on the files opened ahead of time
+        let config = builder
+            .with_byte_scan_budget(450)
+            .with_max_open_files_per_partition(NonZeroUsize::new(4).unwrap())
+            .with_read_ahead_bytes(1000)
+            .build();
+        assert_eq!(scan(config).await?, (vec![3, 2], Some(3)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_progress_callback() -> Result<()> {
+        use crate::file_stream::ScanProgress;
+        use std::sync::Mutex;
+
+        let reported = Arc::new(Mutex::new(vec![]));
+        let callback = {
+            let reported = Arc::clone(&reported);
+            Arc::new(move |progress: ScanProgress| {
+                reported.lock().unwrap().push(progress)
+            })
+        };
+
+        // two files of 17 batches with a single row each
+        let records = vec![make_partition(1); 17];
+        let config = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            records[0].schema(),
+            Arc::new(MockSource::default()),
+        )
+        .with_file_group(FileGroup::new(vec![
+            PartitionedFile::new("a", 10),
+            PartitionedFile::new("b", 10),
+        ]))
+        .with_progress_callback(callback)
+        .build();
+        let opener = TestOpener {
+            records,
+            ..Default::default()
+        };
+        let stream = FileStream::new(
+            &config,
+            0,
+            Arc::new(opener),
+            &ExecutionPlanMetricsSet::new(),
+        )?;
+        let batches = stream.try_collect::<Vec<_>>().await?;
+        assert_eq!(batches.len(), 34);
+
+        // reported every 16 batches and at the end of every file
+        let progress = |files_completed, rows_read| ScanProgress {
+            partition: 0,
+            files_completed,
+            rows_read,
+        };
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![
+                progress(0, 16),
+                progress(1, 17),
+                progress(1, 33),
+                progress(2, 34)
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_emit_empty_file_batches() -> Result<()> {
+        use arrow::datatypes::{DataType, Field};
+        use datafusion_common::ScalarValue;
+
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let file = |path: &str, date: &str| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = vec![ScalarValue::from(date)];
+            file
+        };
+        let read = |emit_empty_file_batches: bool| {
+            let config = FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("test:///").unwrap(),
+                Arc::clone(&file_schema),
+                Arc::new(MockSource::default()),
+            )
+            .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+            .with_file_group(FileGroup::new(vec![
+                file("a.csv", "2024-01-01"),
+                file("b.csv", "2024-01-02"),
+            ]))
+            .with_emit_empty_file_batches(emit_empty_file_batches)
+            .build();
+            let stream = FileStream::new(
+                &config,
+                0,
+                Arc::new(TestOpener::default()),
+                &ExecutionPlanMetricsSet::new(),
+            );
+            async move { stream?.try_collect::<Vec<_>>().await }
+        };
+
+        // every empty file produces exactly one empty batch
+        let batches = read(true).await?;
+        assert_eq!(batches.len(), 2);
+        for batch in &batches {
+            assert_eq!(batch.num_rows(), 0);
+            let fields = batch
+                .schema()
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect::<Vec<_>>();
+            assert_eq!(fields, vec!["a", "date"]);
+        }
+
+        assert!(read(false).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn with_merge_file_metadata() -> Result<()> {
+        let metadata = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+        let batch = make_partition(3);
+        let file_batch = batch.clone().with_schema(Arc::new(
+            batch
+                .schema()
+                .as_ref()
+                .clone()
+                .with_metadata(metadata(&[("tag", "custom"), ("owner", "file")])),
+        ))?;
+        let table_schema = Arc::new(
+            batch
+                .schema()
+                .as_ref()
+                .clone()
+                .with_metadata(metadata(&[("owner", "table")])),
+        );
+
+        let collect = |merge_file_metadata| {
+            let config = FileScanConfigBuilder::new(
+                ObjectStoreUrl::parse("test:///").unwrap(),
+                Arc::clone(&table_schema),
+                Arc::new(MockSource::default()),
+            )
+            .with_file_group(FileGroup::new(vec![PartitionedFile::new("a.arrow", 10)]))
+            .with_merge_file_metadata(merge_file_metadata)
+            .build();
+            let opener = TestOpener {
+                records: vec![file_batch.clone()],
+                ..Default::default()
+            };
+            let stream = FileStream::new(
+                &config,
+                0,
+                Arc::new(opener),
+                &ExecutionPlanMetricsSet::new(),
+            )
+            .unwrap();
+            stream.collect::<Vec<_>>()
+        };
+
+        // the metadata of the table wins on conflict
+        let batches = collect(true)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            batches[0].schema().metadata(),
+            &metadata(&[("tag", "custom"), ("owner", "table")])
+        );
+
+        // by default only the metadata of the table is kept
+        let batches = collect(false)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            batches[0].schema().metadata(),
+            &metadata(&[("owner", "table")])
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,330 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Handling of files that no longer exist when they are opened, see
+//! [`FileScanConfigBuilder::with_missing_file_policy`]
+//!
+//! [`FileScanConfigBuilder::with_missing_file_policy`]: crate::file_scan_config::FileScanConfigBuilder::with_missing_file_policy
+
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::file_meta::FileMeta;
+use crate::file_stream::{FileOpenFuture, FileOpener};
+use crate::PartitionedFile;
+
+use datafusion_common::Result;
+use datafusion_physical_plan::metrics::{Count, ExecutionPlanMetricsSet, MetricBuilder};
+use futures::StreamExt;
+use log::debug;
+
+/// What to do when a file is not found when it is opened, e.g. because it was
+/// deleted from an eventually consistent store after it was listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingFilePolicy {
+    /// Fail the scan
+    #[default]
+    Error,
+    /// Skip the file, counting it in the `missing_files_skipped` metric
+    Skip,
+    /// Open the file again up to `max_retries` times, waiting `backoff`
+    /// before each attempt, and fail the scan if it is still not found
+    Retry {
+        /// Maximum number of times the file is opened again
+        max_retries: usize,
+        /// Time to wait before opening the file again
+        backoff: Duration,
+    },
+}
+
+/// Returns true if `error` is caused by an [`object_store::Error::NotFound`]
+pub fn is_not_found(error: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(object_store::Error::NotFound { .. }) =
+            error.downcast_ref::<object_store::Error>()
+        {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+/// A [`FileOpener`] that applies a [`MissingFilePolicy`] to the files the
+/// inner opener fails to open because they are not found.
+///
+/// Only errors opening a file are handled, a file deleted while it is read
+/// fails the scan regardless of the policy.
+pub struct MissingFileOpener {
+    inner: Arc<dyn FileOpener>,
+    policy: MissingFilePolicy,
+    missing_files_skipped: Count,
+}
+
+impl MissingFileOpener {
+    /// Create a new opener opening files with `inner`, handling missing files
+    /// according to `policy` and counting the skipped files of `partition` in
+    /// `metrics`
+    pub fn new(
+        inner: Arc<dyn FileOpener>,
+        policy: MissingFilePolicy,
+        metrics: &ExecutionPlanMetricsSet,
+        partition: usize,
+    ) -> Self {
+        Self {
+            inner,
+            policy,
+            missing_files_skipped: MetricBuilder::new(metrics)
+                .counter("missing_files_skipped", partition),
+        }
+    }
+}
+
+impl FileOpener for MissingFileOpener {
+    fn open(&self, file_meta: FileMeta, file: PartitionedFile) -> Result<FileOpenFuture> {
+        let inner = Arc::clone(&self.inner);
+        let policy = self.policy;
+        let missing_files_skipped = self.missing_files_skipped.clone();
+        Ok(Box::pin(async move {
+            let mut retries = 0;
+            loop {
+                let error = match inner.open(file_meta.clone(), file.clone()) {
+                    Ok(future) => match future.await {
+                        Ok(stream) => return Ok(stream),
+                        Err(e) => e,
+                    },
+                    Err(e) => e,
+                };
+                if !is_not_found(&error) {
+                    return Err(error);
+                }
+                match policy {
+                    MissingFilePolicy::Error => return Err(error),
+                    MissingFilePolicy::Skip => {
+                        debug!("Skipping missing file {}: {error}", file.path());
+                        missing_files_skipped.add(1);
+                        return Ok(futures::stream::empty().boxed());
+                    }
+                    MissingFilePolicy::Retry {
+                        max_retries,
+                        backoff,
+                    } => {
+                        if retries == max_retries {
+                            return Err(error);
+                        }
+                        retries += 1;
+                        debug!(
+                            "File {} not found, opening it again ({retries}/{max_retries})",
+                            file.path()
+                        );
+                        tokio::time::sleep(backoff).await;
+                    }
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fmt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use arrow::array::{ArrayRef, RecordBatch, UInt64Array};
+    use async_trait::async_trait;
+    use datafusion_common::assert_contains;
+    use futures::stream::BoxStream;
+    use futures::TryStreamExt;
+    use object_store::memory::InMemory;
+    use object_store::path::Path;
+    use object_store::{
+        GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+        PutMultipartOpts, PutOptions, PutPayload, PutResult,
+    };
+
+    /// Mock store that does not find an object for a number of requests, as
+    /// an eventually consistent store may
+    #[derive(Debug)]
+    struct LaggingStore {
+        inner: InMemory,
+        missing_requests: AtomicUsize,
+    }
+
+    impl fmt::Display for LaggingStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "LaggingStore")
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for LaggingStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> object_store::Result<PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOpts,
+        ) -> object_store::Result<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: GetOptions,
+        ) -> object_store::Result<GetResult> {
+            let missing = self
+                .missing_requests
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if missing {
+                return Err(object_store::Error::NotFound {
+                    path: location.to_string(),
+                    source: "not yet visible".into(),
+                });
+            }
+            self.inner.get_opts(location, options).await
+        }
+
+        async fn delete(&self, location: &Path) -> object_store::Result<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> BoxStream<'static, object_store::Result<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(
+            &self,
+            from: &Path,
+            to: &Path,
+        ) -> object_store::Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    /// Opener fetching each file when it is opened, returning a batch with
+    /// its size
+    struct SizeOpener {
+        store: Arc<dyn ObjectStore>,
+    }
+
+    impl FileOpener for SizeOpener {
+        fn open(
+            &self,
+            file_meta: FileMeta,
+            _: PartitionedFile,
+        ) -> Result<FileOpenFuture> {
+            let store = Arc::clone(&self.store);
+            Ok(Box::pin(async move {
+                let bytes = store.get(file_meta.location()).await?.bytes().await?;
+                let array: ArrayRef =
+                    Arc::new(UInt64Array::from(vec![bytes.len() as u64]));
+                let batch = RecordBatch::try_from_iter([("size", array)])?;
+                Ok(futures::stream::iter([Ok(batch)]).boxed())
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_opener() -> Result<()> {
+        let location = Path::from("data.csv");
+        let retry = |max_retries| MissingFilePolicy::Retry {
+            max_retries,
+            backoff: Duration::from_millis(1),
+        };
+        let open = |missing_requests: usize, policy: MissingFilePolicy| {
+            let location = location.clone();
+            async move {
+                let inner = InMemory::new();
+                inner.put(&location, "a,1\n".into()).await?;
+                let store = LaggingStore {
+                    inner,
+                    missing_requests: AtomicUsize::new(missing_requests),
+                };
+                let metrics = ExecutionPlanMetricsSet::new();
+                let opener = MissingFileOpener::new(
+                    Arc::new(SizeOpener {
+                        store: Arc::new(store),
+                    }),
+                    policy,
+                    &metrics,
+                    0,
+                );
+                let file = PartitionedFile::new(location.to_string(), 4);
+                let file_meta = FileMeta::from(file.object_meta.clone());
+                let result = match opener.open(file_meta, file)?.await {
+                    Ok(stream) => {
+                        stream.try_collect::<Vec<_>>().await.map_err(Into::into)
+                    }
+                    Err(e) => Err(e),
+                };
+                let skipped = metrics
+                    .clone_inner()
+                    .sum_by_name("missing_files_skipped")
+                    .map(|skipped| skipped.as_usize());
+                Ok::<_, datafusion_common::DataFusionError>((result, skipped))
+            }
+        };
+
+        // the file is found
+        for policy in [MissingFilePolicy::Error, MissingFilePolicy::Skip, retry(2)] {
+            let (batches, _) = open(0, policy).await?;
+            assert_eq!(batches?.len(), 1);
+        }
+
+        let (result, _) = open(1, MissingFilePolicy::Error).await?;
+        assert_contains!(result.unwrap_err().to_string(), "not yet visible");
+
+        let (batches, skipped) = open(1, MissingFilePolicy::Skip).await?;
+        assert!(batches?.is_empty());
+        assert_eq!(skipped, Some(1));
+
+        // the file is found when it is opened again
+        let (batches, _) = open(2, retry(2)).await?;
+        assert_eq!(batches?.len(), 1);
+
+        let (result, _) = open(3, retry(2)).await?;
+        assert_contains!(result.unwrap_err().to_string(), "not yet visible");
+        Ok(())
+    }
+}
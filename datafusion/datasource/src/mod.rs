@@ -27,6 +27,10 @@
 //! A table that uses the `ObjectStore` listing capability
 //! to get the list of files to process.
 
+#[cfg(feature = "checksum")]
+pub mod checksum;
+pub mod coalesced_ranges;
+pub mod column_encryption;
 pub mod decoder;
 pub mod display;
 pub mod file;
@@ -35,26 +39,34 @@ pub mod file_format;
 pub mod file_groups;
 pub mod file_meta;
 pub mod file_scan_config;
+mod file_scan_open;
 pub mod file_sink_config;
 pub mod file_stream;
 pub mod memory;
+pub mod missing_file;
 pub mod schema_adapter;
 pub mod sink;
 pub mod source;
 mod statistics;
+mod statistics_fast_path;
+pub mod sub_file;
+pub mod trailing_bytes;
 
 #[cfg(test)]
 pub mod test_util;
 
 pub mod url;
+pub mod url_refresh;
 pub mod write;
 pub use self::file::as_file_source;
 pub use self::url::ListingTableUrl;
 use crate::file_groups::FileGroup;
+use arrow::datatypes::SchemaRef;
 use chrono::TimeZone;
 use datafusion_common::stats::Precision;
 use datafusion_common::{exec_datafusion_err, ColumnStatistics, Result};
 use datafusion_common::{ScalarValue, Statistics};
+use datafusion_execution::object_store::ObjectStoreUrl;
 use file_meta::FileMeta;
 use futures::{Stream, StreamExt};
 use object_store::{path::Path, ObjectMeta};
@@ -63,6 +75,7 @@ use object_store::{GetOptions, GetRange, ObjectStore};
 #[allow(deprecated)]
 pub use statistics::add_row_stats;
 pub use statistics::compute_all_files_statistics;
+pub use statistics::SplitGroupsError;
 use std::ops::Range;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -117,6 +130,31 @@ pub struct PartitionedFile {
     pub extensions: Option<Arc<dyn std::any::Any + Send + Sync>>,
     /// The estimated size of the parquet metadata, in bytes
     pub metadata_size_hint: Option<usize>,
+    /// URL of the object store the file is read from, overriding the object
+    /// store of the `FileScanConfig` if set
+    pub object_store_url: Option<ObjectStoreUrl>,
+    /// Byte ranges of independent files embedded in this object, e.g. several
+    /// Parquet files packed into a single object. Empty if the object is a
+    /// single file. See [`Self::with_sub_files`]
+    pub sub_files: Vec<FileRange>,
+    /// Schema to read this file with, if it differs from the file schema of
+    /// the scan. See [`Self::with_file_schema_override`]
+    pub file_schema_override: Option<SchemaRef>,
+    /// Optional statistics of each row group of this file, in the order of
+    /// the row groups, for formats like Parquet. See
+    /// [`Self::with_row_group_statistics`]
+    pub row_group_statistics: Option<Vec<Arc<Statistics>>>,
+    /// Byte ranges of the file to read one after the other, e.g. the parts
+    /// of the file an external index selected. Empty if the file is read
+    /// whole or according to [`Self::range`]. See [`Self::with_ranges`]
+    pub ranges: Vec<FileRange>,
+    /// Whether the file is logically deleted as of the current snapshot of
+    /// the table, but may be read by time travel queries. See
+    /// [`Self::with_tombstoned`]
+    pub tombstoned: bool,
+    /// The expected checksum of the whole file, verified before the file is
+    /// read if the scan verifies checksums. See [`Self::with_expected_checksum`]
+    pub expected_checksum: Option<Vec<u8>>,
 }
 
 impl PartitionedFile {
@@ -135,6 +173,13 @@ impl PartitionedFile {
             statistics: None,
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         }
     }
 
@@ -153,6 +198,13 @@ impl PartitionedFile {
             statistics: None,
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         }
         .with_range(start, end)
     }
@@ -165,6 +217,93 @@ impl PartitionedFile {
         self
     }
 
+    /// Read this file from the object store registered for `object_store_url`
+    /// instead of the object store of the `FileScanConfig`, for scans mixing
+    /// files from several stores
+    pub fn with_object_store_url(mut self, object_store_url: ObjectStoreUrl) -> Self {
+        self.object_store_url = Some(object_store_url);
+        self
+    }
+
+    /// Read this object as the independent files embedded at `sub_files`,
+    /// e.g. Parquet files packed into a single object at known offsets.
+    ///
+    /// Each sub file is opened as if it was a separate object of its own
+    /// size, so the format's opener reads e.g. the Parquet footer at the end
+    /// of the sub file. If the file has a [`Self::range`], only the sub files
+    /// starting within the range are read, so that repartitioning the object
+    /// by byte ranges reads every sub file once. The [`Self::statistics`]
+    /// describe the whole object, and are not used to read the sub files.
+    pub fn with_sub_files(mut self, sub_files: Vec<FileRange>) -> Self {
+        self.sub_files = sub_files;
+        self
+    }
+
+    /// Read this file with `schema` instead of the file schema of the
+    /// `FileScanConfig`, e.g. the schema recorded for it in a manifest of a
+    /// lake whose files do not share a single schema.
+    ///
+    /// The batches read with `schema` are adapted to the file schema of the
+    /// scan with the schema adapter of the file source, which fills columns
+    /// missing from the file with nulls and casts columns of another type.
+    pub fn with_file_schema_override(mut self, schema: SchemaRef) -> Self {
+        self.file_schema_override = Some(schema);
+        self
+    }
+
+    /// Only read the byte `ranges` of this file, e.g. the parts of the file
+    /// selected by an external index.
+    ///
+    /// Each range is read as if the file was split with [`Self::with_range`],
+    /// and the batches of the ranges are concatenated, so the format decides
+    /// which records belong to a range, e.g. the lines starting in it for
+    /// CSV. The ranges must not overlap, and the file can not also have a
+    /// [`Self::range`].
+    pub fn with_ranges(mut self, ranges: Vec<FileRange>) -> Self {
+        self.ranges = ranges;
+        self
+    }
+
+    /// Mark this file as logically deleted as of the current snapshot of the
+    /// table, e.g. by a table format like Delta Lake or Iceberg.
+    ///
+    /// Tombstoned files are only read by scans configured with
+    /// [`FileScanConfigBuilder::with_include_tombstoned`], e.g. to query a
+    /// past snapshot in which the file was valid.
+    ///
+    /// [`FileScanConfigBuilder::with_include_tombstoned`]: crate::file_scan_config::FileScanConfigBuilder::with_include_tombstoned
+    pub fn with_tombstoned(mut self, tombstoned: bool) -> Self {
+        self.tombstoned = tombstoned;
+        self
+    }
+
+    /// Set the expected checksum of the whole file, e.g. as recorded in the
+    /// manifest of a table, in the encoding of its checksum algorithm.
+    ///
+    /// The checksum is only verified by scans configured with
+    /// `FileScanConfigBuilder::with_checksum_verification`, which requires the
+    /// `checksum` feature and fails to open the file if its contents do not
+    /// match.
+    pub fn with_expected_checksum(mut self, checksum: Vec<u8>) -> Self {
+        self.expected_checksum = Some(checksum);
+        self
+    }
+
+    /// Set the statistics of each row group of this file, e.g. from an index
+    /// built once from the Parquet footers of a table.
+    ///
+    /// The Parquet file source prunes the row groups of the file with these
+    /// statistics instead of the statistics of its footer. Like
+    /// [`Self::statistics`], they follow the file schema of the scan and must
+    /// be correct, or incorrect answers may result.
+    pub fn with_row_group_statistics(
+        mut self,
+        row_group_statistics: Vec<Arc<Statistics>>,
+    ) -> Self {
+        self.row_group_statistics = Some(row_group_statistics);
+        self
+    }
+
     /// Return a file reference from the given path
     pub fn from_path(path: String) -> Result<Self> {
         let size = std::fs::metadata(path.clone())?.len();
@@ -209,6 +348,13 @@ impl From<ObjectMeta> for PartitionedFile {
             statistics: None,
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         }
     }
 }
@@ -323,6 +469,91 @@ async fn find_first_newline(
     Ok(index)
 }
 
+/// Like [`calculate_range`], but aligns the range with the record boundaries
+/// of a CSV file whose quoted values may contain the `terminator`.
+///
+/// A terminator only ends a record if it is not inside a quoted value, which
+/// can only be determined by scanning the object from its start. The object is
+/// therefore streamed from its start up to the first record boundary at or
+/// after the end of the range, tracking only the `quote` and `escape`
+/// characters, which is much cheaper than parsing the skipped records.
+///
+/// Each range starts at the first record starting at or after its start and
+/// ends with the record containing its last byte, so adjacent ranges of a file
+/// read every record exactly once.
+///
+/// As every range scans the object from its start, splitting an object into
+/// `n` ranges scans it about `n / 2` times. `max_probe_bytes` bounds the scan
+/// of each range: the range containing that offset reads the rest of the
+/// object, and the ranges after it are empty.
+pub async fn calculate_quoted_range(
+    file_meta: &FileMeta,
+    store: &Arc<dyn ObjectStore>,
+    terminator: Option<u8>,
+    quote: u8,
+    escape: Option<u8>,
+    max_probe_bytes: Option<u64>,
+) -> Result<RangeCalculation> {
+    let Some(FileRange { start, end }) = file_meta.range else {
+        return Ok(RangeCalculation::Range(None));
+    };
+    let start: u64 = start.try_into().map_err(|_| {
+        exec_datafusion_err!("Expect start range to fit in u64, got {start}")
+    })?;
+    let end: u64 = end
+        .try_into()
+        .map_err(|_| exec_datafusion_err!("Expect end range to fit in u64, got {end}"))?;
+    let file_size = file_meta.object_meta.size;
+    let newline = terminator.unwrap_or(b'\n');
+    // A quote doubled inside a quoted value toggles the quote state twice
+    let escape = escape.filter(|escape| *escape != quote);
+    let max_probe_bytes = max_probe_bytes.unwrap_or(u64::MAX);
+    if start > max_probe_bytes {
+        return Ok(RangeCalculation::TerminateEarly);
+    }
+
+    let mut range_start = (start == 0).then_some(0);
+    let mut range_end = (end >= file_size || end > max_probe_bytes).then_some(file_size);
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut offset = 0;
+
+    if range_start.is_none() || range_end.is_none() {
+        let mut stream = store.get(file_meta.location()).await?.into_stream();
+        'scan: while let Some(chunk) = stream.next().await.transpose()? {
+            for (index, &byte) in chunk.iter().enumerate() {
+                if escaped {
+                    escaped = false;
+                } else if in_quotes && Some(byte) == escape {
+                    escaped = true;
+                } else if byte == quote {
+                    in_quotes = !in_quotes;
+                } else if byte == newline && !in_quotes {
+                    // The record ending here contains `position`, the next
+                    // one starts right after it
+                    let position = offset + index as u64;
+                    if range_start.is_none() && position + 1 >= start {
+                        range_start = Some(position + 1);
+                    }
+                    if range_end.is_none() && position + 1 >= end {
+                        range_end = Some(position + 1);
+                    }
+                    if range_start.is_some() && range_end.is_some() {
+                        break 'scan;
+                    }
+                }
+            }
+            offset += chunk.len() as u64;
+        }
+    }
+
+    let range = range_start.unwrap_or(file_size)..range_end.unwrap_or(file_size);
+    if range.start >= range.end {
+        return Ok(RangeCalculation::TerminateEarly);
+    }
+    Ok(RangeCalculation::Range(Some(range)))
+}
+
 /// Generates test files with min-max statistics in different overlap patterns.
 ///
 /// Used by tests and benchmarks.
@@ -401,6 +632,13 @@ pub fn generate_test_files(num_files: usize, overlap_factor: f64) -> Vec<FileGro
             })),
             extensions: None,
             metadata_size_hint: None,
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
         };
         files.push(file);
     }
@@ -574,4 +812,66 @@ mod tests {
         // testing an empty path with `ignore_subdirectory` set to false
         assert!(url.contains(&Path::parse("/var/data/mytable/").unwrap(), false));
     }
+
+    #[tokio::test]
+    async fn test_calculate_quoted_range() {
+        use super::{calculate_quoted_range, FileRange, RangeCalculation};
+        use crate::file_meta::FileMeta;
+        use object_store::{memory::InMemory, ObjectStore};
+
+        let data = "a,\"x\ny\"\nb,\"\"\"\n\"\"\nc,\"z\\\"\n\"\nd,e\n";
+        let records = [
+            "a,\"x\ny\"\n",
+            "b,\"\"\"\n\"\"\n",
+            "c,\"z\\\"\n\"\n",
+            "d,e\n",
+        ];
+        assert_eq!(records.concat(), data);
+
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let location = Path::from("data.csv");
+        store.put(&location, data.into()).await.unwrap();
+        let object_meta = store.head(&location).await.unwrap();
+
+        // Every split of the file reads each record exactly once, however far
+        // the ranges may scan the file
+        let max_probe_bytes = [None, Some(0), Some(5), Some(12), Some(100)];
+        for (split_size, max_probe_bytes) in
+            (1..data.len()).flat_map(|size| max_probe_bytes.map(|max| (size, max)))
+        {
+            let mut read = String::new();
+            for start in (0..data.len()).step_by(split_size) {
+                let end = (start + split_size).min(data.len());
+                let file_meta = FileMeta {
+                    range: Some(FileRange {
+                        start: start as i64,
+                        end: end as i64,
+                    }),
+                    ..FileMeta::from(object_meta.clone())
+                };
+                let range = calculate_quoted_range(
+                    &file_meta,
+                    &store,
+                    None,
+                    b'"',
+                    Some(b'\\'),
+                    max_probe_bytes,
+                )
+                .await
+                .unwrap();
+                if let RangeCalculation::Range(Some(range)) = range {
+                    let chunk = &data[range.start as usize..range.end as usize];
+                    assert!(
+                        records.iter().any(|record| chunk.starts_with(record)),
+                        "split {split_size} starts within a record: {chunk:?}"
+                    );
+                    if let Some(max_probe_bytes) = max_probe_bytes {
+                        assert!(start as u64 <= max_probe_bytes);
+                    }
+                    read.push_str(chunk);
+                }
+            }
+            assert_eq!(read, data, "split {split_size}, max {max_probe_bytes:?}");
+        }
+    }
 }
@@ -25,6 +25,9 @@ use arrow::array::{new_null_array, RecordBatch, RecordBatchOptions};
 use arrow::compute::{can_cast_types, cast};
 use arrow::datatypes::{Field, Schema, SchemaRef};
 use datafusion_common::{plan_err, ColumnStatistics};
+use datafusion_physical_expr::utils::reassign_predicate_columns;
+use datafusion_physical_expr_common::physical_expr::PhysicalExpr;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 
@@ -419,6 +422,229 @@ impl SchemaMapper for SchemaMapping {
     }
 }
 
+/// [`SchemaAdapterFactory`] that fills columns missing from a file by
+/// evaluating an expression rather than with nulls.
+///
+/// Each default expression is keyed by the name of the table column it
+/// produces and is written against the table schema. When a file does not
+/// contain that column, the expression is evaluated against the batch
+/// produced by the `inner` adapter (in which the missing column is null), so
+/// a default can be derived from other columns, e.g. `total = price * qty`.
+///
+/// Columns referenced by a default expression must be part of the projection,
+/// and the expression must produce the declared type of its column.
+#[derive(Debug)]
+pub struct ColumnDefaultsSchemaAdapterFactory {
+    inner: Arc<dyn SchemaAdapterFactory>,
+    defaults: HashMap<String, Arc<dyn PhysicalExpr>>,
+}
+
+impl ColumnDefaultsSchemaAdapterFactory {
+    /// Create a new factory that wraps `inner` and fills missing columns with
+    /// the given `defaults`.
+    pub fn new(
+        inner: Arc<dyn SchemaAdapterFactory>,
+        defaults: HashMap<String, Arc<dyn PhysicalExpr>>,
+    ) -> Self {
+        Self { inner, defaults }
+    }
+}
+
+impl SchemaAdapterFactory for ColumnDefaultsSchemaAdapterFactory {
+    fn create(
+        &self,
+        projected_table_schema: SchemaRef,
+        table_schema: SchemaRef,
+    ) -> Box<dyn SchemaAdapter> {
+        Box::new(ColumnDefaultsSchemaAdapter {
+            inner: self
+                .inner
+                .create(Arc::clone(&projected_table_schema), table_schema),
+            projected_table_schema,
+            defaults: self.defaults.clone(),
+        })
+    }
+}
+
+/// [`SchemaAdapter`] created by [`ColumnDefaultsSchemaAdapterFactory`]
+struct ColumnDefaultsSchemaAdapter {
+    inner: Box<dyn SchemaAdapter>,
+    projected_table_schema: SchemaRef,
+    defaults: HashMap<String, Arc<dyn PhysicalExpr>>,
+}
+
+impl SchemaAdapter for ColumnDefaultsSchemaAdapter {
+    fn map_column_index(&self, index: usize, file_schema: &Schema) -> Option<usize> {
+        self.inner.map_column_index(index, file_schema)
+    }
+
+    fn map_schema(
+        &self,
+        file_schema: &Schema,
+    ) -> datafusion_common::Result<(Arc<dyn SchemaMapper>, Vec<usize>)> {
+        let (inner, projection) = self.inner.map_schema(file_schema)?;
+
+        let mut defaults = vec![];
+        for (idx, field) in self.projected_table_schema.fields().iter().enumerate() {
+            if file_schema.field_with_name(field.name()).is_ok() {
+                continue;
+            }
+            let Some(expr) = self.defaults.get(field.name()) else {
+                continue;
+            };
+            let expr = reassign_predicate_columns(
+                Arc::clone(expr),
+                &self.projected_table_schema,
+                false,
+            )?;
+            let data_type = expr.data_type(&self.projected_table_schema)?;
+            if &data_type != field.data_type() {
+                return plan_err!(
+                    "Default expression for column {} has type {:?}, expected {:?}",
+                    field.name(),
+                    data_type,
+                    field.data_type()
+                );
+            }
+            defaults.push((idx, expr));
+        }
+
+        if defaults.is_empty() {
+            return Ok((inner, projection));
+        }
+        Ok((
+            Arc::new(ColumnDefaultsSchemaMapping { inner, defaults }),
+            projection,
+        ))
+    }
+}
+
+/// [`SchemaMapper`] that replaces the columns missing from a file with the
+/// result of their default expressions
+#[derive(Debug)]
+struct ColumnDefaultsSchemaMapping {
+    inner: Arc<dyn SchemaMapper>,
+    /// Index in the projected table schema and the expression to fill it with
+    defaults: Vec<(usize, Arc<dyn PhysicalExpr>)>,
+}
+
+impl SchemaMapper for ColumnDefaultsSchemaMapping {
+    fn map_batch(&self, batch: RecordBatch) -> datafusion_common::Result<RecordBatch> {
+        let batch = self.inner.map_batch(batch)?;
+        let num_rows = batch.num_rows();
+
+        let mut cols = batch.columns().to_vec();
+        for (idx, expr) in &self.defaults {
+            cols[*idx] = expr.evaluate(&batch)?.into_array(num_rows)?;
+        }
+
+        let options = RecordBatchOptions::new().with_row_count(Some(num_rows));
+        Ok(RecordBatch::try_new_with_options(
+            batch.schema(),
+            cols,
+            &options,
+        )?)
+    }
+
+    fn map_column_statistics(
+        &self,
+        file_col_statistics: &[ColumnStatistics],
+    ) -> datafusion_common::Result<Vec<ColumnStatistics>> {
+        self.inner.map_column_statistics(file_col_statistics)
+    }
+}
+
+/// How columns of a file that are not part of the table schema are handled,
+/// see [`ExtraColumnsSchemaAdapterFactory`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExtraColumnPolicy {
+    /// Extra columns are not read
+    #[default]
+    Ignore,
+    /// Reading a file with extra columns fails
+    Error,
+    /// Extra columns are added to the table schema, see
+    /// [`FileScanConfigBuilder::include_extra_columns`]. As all files must
+    /// agree on their extra columns, reading a file with a column that was not
+    /// included fails.
+    ///
+    /// [`FileScanConfigBuilder::include_extra_columns`]: crate::file_scan_config::FileScanConfigBuilder::include_extra_columns
+    Include,
+}
+
+/// [`SchemaAdapterFactory`] that applies an [`ExtraColumnPolicy`] to the
+/// columns of a file that are not part of the table schema, before mapping
+/// the file with the `inner` adapter.
+#[derive(Debug)]
+pub struct ExtraColumnsSchemaAdapterFactory {
+    inner: Arc<dyn SchemaAdapterFactory>,
+    policy: ExtraColumnPolicy,
+}
+
+impl ExtraColumnsSchemaAdapterFactory {
+    /// Create a new factory that wraps `inner` and handles extra file columns
+    /// according to `policy`.
+    pub fn new(inner: Arc<dyn SchemaAdapterFactory>, policy: ExtraColumnPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl SchemaAdapterFactory for ExtraColumnsSchemaAdapterFactory {
+    fn create(
+        &self,
+        projected_table_schema: SchemaRef,
+        table_schema: SchemaRef,
+    ) -> Box<dyn SchemaAdapter> {
+        Box::new(ExtraColumnsSchemaAdapter {
+            inner: self
+                .inner
+                .create(projected_table_schema, Arc::clone(&table_schema)),
+            table_schema,
+            policy: self.policy,
+        })
+    }
+}
+
+/// [`SchemaAdapter`] created by [`ExtraColumnsSchemaAdapterFactory`]
+struct ExtraColumnsSchemaAdapter {
+    inner: Box<dyn SchemaAdapter>,
+    table_schema: SchemaRef,
+    policy: ExtraColumnPolicy,
+}
+
+impl SchemaAdapter for ExtraColumnsSchemaAdapter {
+    fn map_column_index(&self, index: usize, file_schema: &Schema) -> Option<usize> {
+        self.inner.map_column_index(index, file_schema)
+    }
+
+    fn map_schema(
+        &self,
+        file_schema: &Schema,
+    ) -> datafusion_common::Result<(Arc<dyn SchemaMapper>, Vec<usize>)> {
+        let extra = file_schema
+            .fields()
+            .iter()
+            .find(|field| self.table_schema.field_with_name(field.name()).is_err());
+        match (self.policy, extra) {
+            (ExtraColumnPolicy::Error, Some(field)) => {
+                return plan_err!(
+                    "File column {} is not part of the table schema",
+                    field.name()
+                );
+            }
+            (ExtraColumnPolicy::Include, Some(field)) => {
+                return plan_err!(
+                    "File column {} is not part of the table schema, but extra \
+                     columns are included and must be the same in all files",
+                    field.name()
+                );
+            }
+            _ => {}
+        }
+        self.inner.map_schema(file_schema)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use arrow::datatypes::{DataType, Field};
@@ -700,4 +926,153 @@ mod tests {
         assert_eq!(c_array.len(), 2);
         assert_eq!(c_array.null_count(), 2);
     }
+
+    #[test]
+    fn test_column_defaults_schema_adapter() {
+        use datafusion_expr::Operator;
+        use datafusion_physical_expr::expressions::{binary, col};
+
+        let table_schema = Arc::new(Schema::new(vec![
+            Field::new("price", DataType::Int64, true),
+            Field::new("qty", DataType::Int64, true),
+            Field::new("total", DataType::Int64, true),
+        ]));
+        let total = binary(
+            col("price", &table_schema).unwrap(),
+            Operator::Multiply,
+            col("qty", &table_schema).unwrap(),
+            &table_schema,
+        )
+        .unwrap();
+        let factory = ColumnDefaultsSchemaAdapterFactory::new(
+            Arc::new(DefaultSchemaAdapterFactory),
+            HashMap::from([("total".to_string(), total)]),
+        );
+        let adapter =
+            factory.create(Arc::clone(&table_schema), Arc::clone(&table_schema));
+
+        // "total" is missing from the file and is computed from the other columns
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("qty", DataType::Int64, true),
+            Field::new("price", DataType::Int64, true),
+        ]));
+        let (mapper, projection) = adapter.map_schema(&file_schema).unwrap();
+        assert_eq!(projection, vec![0, 1]);
+
+        let file_batch = RecordBatch::try_new(
+            Arc::clone(&file_schema),
+            vec![
+                Arc::new(arrow::array::Int64Array::from(vec![2, 3])),
+                Arc::new(arrow::array::Int64Array::from(vec![10, 20])),
+            ],
+        )
+        .unwrap();
+        let mapped_batch = mapper.map_batch(file_batch).unwrap();
+        assert_eq!(mapped_batch.schema(), table_schema);
+        assert_eq!(
+            mapped_batch.column(2).as_ref(),
+            &arrow::array::Int64Array::from(vec![20, 60])
+        );
+
+        // Files that contain the column keep their own values
+        let (mapper, _) = adapter.map_schema(&table_schema).unwrap();
+        let file_batch = RecordBatch::try_new(
+            Arc::clone(&table_schema),
+            vec![
+                Arc::new(arrow::array::Int64Array::from(vec![1])),
+                Arc::new(arrow::array::Int64Array::from(vec![1])),
+                Arc::new(arrow::array::Int64Array::from(vec![5])),
+            ],
+        )
+        .unwrap();
+        let mapped_batch = mapper.map_batch(file_batch).unwrap();
+        assert_eq!(
+            mapped_batch.column(2).as_ref(),
+            &arrow::array::Int64Array::from(vec![5])
+        );
+
+        // The default expression must produce the declared column type
+        let mismatched = ColumnDefaultsSchemaAdapterFactory::new(
+            Arc::new(DefaultSchemaAdapterFactory),
+            HashMap::from([("total".to_string(), col("price", &table_schema).unwrap())]),
+        );
+        let table_schema = Arc::new(Schema::new(vec![
+            Field::new("price", DataType::Int64, true),
+            Field::new("total", DataType::Utf8, true),
+        ]));
+        let adapter =
+            mismatched.create(Arc::clone(&table_schema), Arc::clone(&table_schema));
+        let file_schema = Schema::new(vec![Field::new("price", DataType::Int64, true)]);
+        let err = adapter.map_schema(&file_schema).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Default expression for column total has type Int64"));
+    }
+
+    #[test]
+    fn test_extra_columns_schema_adapter() {
+        let table_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        // the file has an extra column "c"
+        let file_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+            Field::new("c", DataType::Int64, true),
+        ]));
+        let file_batch = RecordBatch::try_new(
+            Arc::clone(&file_schema),
+            vec![
+                Arc::new(arrow::array::Int32Array::from(vec![1])),
+                Arc::new(arrow::array::StringArray::from(vec!["x"])),
+                Arc::new(arrow::array::Int64Array::from(vec![2])),
+            ],
+        )
+        .unwrap();
+        let adapter = |policy, table_schema: &SchemaRef| {
+            ExtraColumnsSchemaAdapterFactory::new(
+                Arc::new(DefaultSchemaAdapterFactory),
+                policy,
+            )
+            .create(Arc::clone(table_schema), Arc::clone(table_schema))
+        };
+
+        // Ignore drops the extra column
+        let (mapper, projection) = adapter(ExtraColumnPolicy::Ignore, &table_schema)
+            .map_schema(&file_schema)
+            .unwrap();
+        assert_eq!(projection, vec![0, 1]);
+        let mapped_batch = mapper
+            .map_batch(file_batch.project(&projection).unwrap())
+            .unwrap();
+        assert_eq!(mapped_batch.schema(), table_schema);
+
+        // Error fails naming the extra column
+        let err = adapter(ExtraColumnPolicy::Error, &table_schema)
+            .map_schema(&file_schema)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("File column c is not part of the table schema"));
+
+        // Include reads the extra column once it is part of the table schema
+        let (mapper, projection) = adapter(ExtraColumnPolicy::Include, &file_schema)
+            .map_schema(&file_schema)
+            .unwrap();
+        assert_eq!(projection, vec![0, 1, 2]);
+        let mapped_batch = mapper.map_batch(file_batch).unwrap();
+        assert_eq!(mapped_batch.schema(), file_schema);
+        assert_eq!(
+            mapped_batch.column(2).as_ref(),
+            &arrow::array::Int64Array::from(vec![2])
+        );
+        // but fails for files with other extra columns
+        let err = adapter(ExtraColumnPolicy::Include, &table_schema)
+            .map_schema(&file_schema)
+            .unwrap_err();
+        assert!(err.to_string().contains(
+            "File column c is not part of the table schema, but extra columns are included"
+        ));
+    }
 }
@@ -20,25 +20,120 @@
 //! Currently, this module houses code to sort file groups if they are non-overlapping with
 //! respect to the required sort order. See [`MinMaxStatistics`]
 
+use std::fmt;
 use std::sync::Arc;
 
 use crate::file_groups::FileGroup;
 use crate::PartitionedFile;
 
-use arrow::array::RecordBatch;
-use arrow::compute::SortColumn;
+use arrow::array::{ArrayRef, RecordBatch};
+use arrow::compute::{cast, SortColumn};
 use arrow::datatypes::SchemaRef;
 use arrow::row::{Row, Rows};
 use datafusion_common::stats::Precision;
-use datafusion_common::{
-    plan_datafusion_err, plan_err, DataFusionError, Result, ScalarValue,
-};
+use datafusion_common::{plan_datafusion_err, DataFusionError, Result, ScalarValue};
 use datafusion_physical_expr::expressions::Column;
 use datafusion_physical_expr_common::sort_expr::{LexOrdering, PhysicalSortExpr};
 use datafusion_physical_plan::{ColumnStatistics, Statistics};
 
 use futures::{Stream, StreamExt};
 
+/// Reason why files can not be split into groups ordered by their statistics,
+/// see [`FileScanConfig::split_groups_by_statistics`].
+///
+/// It is converted to a [`DataFusionError::Plan`] with the same message,
+/// possibly with additional context, and can be retrieved from it with
+/// [`SplitGroupsError::find`].
+///
+/// [`FileScanConfig::split_groups_by_statistics`]: crate::file_scan_config::FileScanConfig::split_groups_by_statistics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SplitGroupsError {
+    /// A sort expression is not a column
+    NonColumnSortExpression {
+        /// The sort expression
+        expr: String,
+    },
+    /// A sort column is nullable
+    NullableSortColumn {
+        /// Name of the column
+        column: String,
+    },
+    /// A file has no statistics
+    MissingFileStatistics {
+        /// Path of the file
+        file: String,
+    },
+    /// The min/max statistics of a sort column are not known for a file
+    MissingStatistics {
+        /// Name of the column
+        column: String,
+        /// Path of the file
+        file: String,
+    },
+    /// A file has no value for a partition column used as sort column
+    MissingPartitionValue {
+        /// Name of the column
+        column: String,
+        /// Path of the file
+        file: String,
+    },
+}
+
+impl SplitGroupsError {
+    /// Returns the [`SplitGroupsError`] that caused `error`, if any
+    pub fn find(mut error: &DataFusionError) -> Option<&Self> {
+        loop {
+            match error {
+                DataFusionError::Context(_, e) | DataFusionError::Diagnostic(_, e) => {
+                    error = e
+                }
+                DataFusionError::Shared(e) => error = e,
+                DataFusionError::Collection(errors) => {
+                    return errors.iter().find_map(|e| match e {
+                        DataFusionError::External(e) => e.downcast_ref::<Self>(),
+                        _ => None,
+                    })
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+impl fmt::Display for SplitGroupsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonColumnSortExpression { .. } => {
+                write!(f, "sort expression must be on column")
+            }
+            Self::NullableSortColumn { .. } => {
+                write!(f, "cannot sort by nullable column")
+            }
+            Self::MissingFileStatistics { .. } => {
+                write!(f, "Parquet file missing statistics")
+            }
+            Self::MissingStatistics { .. } => write!(f, "statistics not found"),
+            Self::MissingPartitionValue { .. } => {
+                write!(f, "partition value not found")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SplitGroupsError {}
+
+impl From<SplitGroupsError> for DataFusionError {
+    /// Returns a [`DataFusionError::Plan`] with the message of `e`, collected
+    /// with `e` itself so that [`SplitGroupsError::find`] can retrieve it.
+    /// The collection is displayed as, and has the root of, its first error.
+    fn from(e: SplitGroupsError) -> Self {
+        DataFusionError::Collection(vec![
+            DataFusionError::Plan(e.to_string()),
+            DataFusionError::External(Box::new(e)),
+        ])
+    }
+}
+
 /// A normalized representation of file min/max statistics that allows for efficient sorting & comparison.
 /// The min/max values are ordered by [`Self::sort_order`].
 /// Furthermore, any columns that are reversed in the sort order have their min/max values swapped.
@@ -66,38 +161,64 @@ impl MinMaxStatistics {
         self.max_by_sort_order.row(idx)
     }
 
+    /// Create min/max statistics of `files` for the columns of
+    /// `projected_sort_order`.
+    ///
+    /// File columns use the min/max of the file statistics, while partition
+    /// columns, which follow the file columns in the table schema, use the
+    /// partition value of each file as both its min and max. This allows
+    /// ordering files by e.g. `date ASC, ts ASC` where `date` is a partition
+    /// column.
     pub fn new_from_files<'a>(
         projected_sort_order: &LexOrdering, // Sort order with respect to projected schema
         projected_schema: &SchemaRef,       // Projected schema
         projection: Option<&[usize]>, // Indices of projection in full table schema (None = all columns)
         files: impl IntoIterator<Item = &'a PartitionedFile>,
     ) -> Result<Self> {
-        let Some(statistics_and_partition_values) = files
-            .into_iter()
+        let files = files.into_iter().collect::<Vec<_>>();
+        let statistics = files
+            .iter()
             .map(|file| {
-                file.statistics
-                    .as_ref()
-                    .zip(Some(file.partition_values.as_slice()))
+                file.statistics.as_deref().ok_or_else(|| {
+                    SplitGroupsError::MissingFileStatistics {
+                        file: file.path().to_string(),
+                    }
+                    .into()
+                })
             })
-            .collect::<Option<Vec<_>>>()
-        else {
-            return plan_err!("Parquet file missing statistics");
-        };
+            .collect::<Result<Vec<_>>>()?;
 
         // Helper function to get min/max statistics for a given column of projected_schema
-        let get_min_max = |i: usize| -> Result<(Vec<ScalarValue>, Vec<ScalarValue>)> {
-            Ok(statistics_and_partition_values
+        let get_min_max = |i: usize,
+                           column: &str|
+         -> Result<(Vec<ScalarValue>, Vec<ScalarValue>)> {
+            Ok(files
                 .iter()
-                .map(|(s, pv)| {
+                .zip(&statistics)
+                .map(|(file, s)| {
                     if i < s.column_statistics.len() {
                         s.column_statistics[i]
                             .min_value
                             .get_value()
                             .cloned()
                             .zip(s.column_statistics[i].max_value.get_value().cloned())
-                            .ok_or_else(|| plan_datafusion_err!("statistics not found"))
+                            .ok_or_else(|| {
+                                SplitGroupsError::MissingStatistics {
+                                    column: column.to_string(),
+                                    file: file.path().to_string(),
+                                }
+                                .into()
+                            })
                     } else {
-                        let partition_value = &pv[i - s.column_statistics.len()];
+                        // Partition columns come after the file columns and
+                        // have a single value in each file
+                        let partition_value = file
+                            .partition_values
+                            .get(i - s.column_statistics.len())
+                            .ok_or_else(|| SplitGroupsError::MissingPartitionValue {
+                                column: column.to_string(),
+                                file: file.path().to_string(),
+                            })?;
                         Ok((partition_value.clone(), partition_value.clone()))
                     }
                 })
@@ -106,11 +227,7 @@ impl MinMaxStatistics {
                 .unzip())
         };
 
-        let Some(sort_columns) =
-            sort_columns_from_physical_sort_exprs(projected_sort_order)
-        else {
-            return plan_err!("sort expression must be on column");
-        };
+        let sort_columns = sort_columns(projected_sort_order)?;
 
         // Project the schema & sort order down to just the relevant columns
         let min_max_schema = Arc::new(
@@ -131,7 +248,8 @@ impl MinMaxStatistics {
 
         let (min_values, max_values): (Vec<_>, Vec<_>) = sort_columns
             .iter()
-            .map(|c| {
+            .zip(min_max_schema.fields())
+            .map(|(c, field)| {
                 // Reverse the projection to get the index of the column in the full statistics
                 // The file statistics contains _every_ column , but the sort column's index()
                 // refers to the index in projected_schema
@@ -139,13 +257,20 @@ impl MinMaxStatistics {
                     .map(|p| p[c.index()])
                     .unwrap_or_else(|| c.index());
 
-                let (min, max) = get_min_max(i).map_err(|e| {
+                let (min, max) = get_min_max(i, c.name()).map_err(|e| {
                     e.context(format!("get min/max for column: '{}'", c.name()))
                 })?;
-                Ok((
-                    ScalarValue::iter_to_array(min)?,
-                    ScalarValue::iter_to_array(max)?,
-                ))
+                // Partition values may not have the type of their column, e.g.
+                // if it is dictionary encoded
+                let to_array = |values: Vec<ScalarValue>| -> Result<ArrayRef> {
+                    let array = ScalarValue::iter_to_array(values)?;
+                    if array.data_type() == field.data_type() {
+                        Ok(array)
+                    } else {
+                        Ok(cast(&array, field.data_type())?)
+                    }
+                };
+                Ok((to_array(min)?, to_array(max)?))
             })
             .collect::<Result<Vec<_>>>()
             .map_err(|e| e.context("collect min/max values"))?
@@ -187,9 +312,7 @@ impl MinMaxStatistics {
             .map_err(|e| e.context("create sort fields"))?;
         let converter = RowConverter::new(sort_fields)?;
 
-        let Some(sort_columns) = sort_columns_from_physical_sort_exprs(sort_order) else {
-            return plan_err!("sort expression must be on column");
-        };
+        let sort_columns = sort_columns(sort_order)?;
 
         // swap min/max if they're reversed in the ordering
         let (new_min_cols, new_max_cols): (Vec<_>, Vec<_>) = sort_order
@@ -230,7 +353,10 @@ impl MinMaxStatistics {
 
                     // check that sort columns are non-nullable
                     if field.is_nullable() {
-                        return plan_err!("cannot sort by nullable column");
+                        return Err(SplitGroupsError::NullableSortColumn {
+                            column: column.name().to_string(),
+                        }
+                        .into());
                     }
 
                     Ok(SortColumn {
@@ -275,12 +401,23 @@ impl MinMaxStatistics {
     }
 }
 
-fn sort_columns_from_physical_sort_exprs(
-    sort_order: &LexOrdering,
-) -> Option<Vec<&Column>> {
+/// Returns the columns of `sort_order`, or an error naming the first sort
+/// expression that is not a column
+fn sort_columns(sort_order: &LexOrdering) -> Result<Vec<&Column>> {
     sort_order
         .iter()
-        .map(|expr| expr.expr.as_any().downcast_ref::<Column>())
+        .map(|sort_expr| {
+            sort_expr
+                .expr
+                .as_any()
+                .downcast_ref::<Column>()
+                .ok_or_else(|| {
+                    SplitGroupsError::NonColumnSortExpression {
+                        expr: sort_expr.expr.to_string(),
+                    }
+                    .into()
+                })
+        })
         .collect()
 }
 
@@ -0,0 +1,479 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Answering a [`FileScanConfig`] from the statistics of its files without
+//! reading them, see [`FileScanConfigBuilder::with_statistics_fast_path`]
+//!
+//! [`FileScanConfigBuilder::with_statistics_fast_path`]: crate::file_scan_config::FileScanConfigBuilder::with_statistics_fast_path
+
+use std::sync::Arc;
+
+use crate::file_groups::FileGroup;
+use crate::file_scan_config::{FileScanConfig, PartitionColumnProjector};
+use crate::missing_file::MissingFilePolicy;
+use crate::PartitionedFile;
+#[allow(unused_imports)]
+use crate::{file::FileSource, file_stream::FileStream};
+
+use arrow::array::{RecordBatch, RecordBatchOptions};
+use arrow::datatypes::Schema;
+use datafusion_common::stats::Precision;
+use datafusion_common::Result;
+use datafusion_execution::SendableRecordBatchStream;
+use datafusion_physical_plan::stream::RecordBatchStreamAdapter;
+
+impl FileScanConfig {
+    /// Returns whether the projection only references partition columns, in
+    /// which case the output can be produced without reading any file data.
+    pub fn is_partition_only_projection(&self) -> bool {
+        let num_file_columns = self.file_schema.fields().len();
+        self.projection.as_ref().is_some_and(|projection| {
+            !projection.is_empty() && projection.iter().all(|i| *i >= num_file_columns)
+        })
+    }
+
+    /// Returns the number of rows this scan produces if it is known exactly
+    /// from the statistics of the files, e.g. to answer `COUNT(*)` without
+    /// scanning.
+    ///
+    /// Returns `None` unless [`FileScanConfig::statistics_fast_path`] is
+    /// set. Also returns `None` if any file does not have an exact row
+    /// count, is only read partially, if some of them may be skipped or fail
+    /// to be read, see [`Self::may_skip_or_fail_files`], or if the
+    /// [`FileSource::filter`] may drop rows.
+    pub fn exact_row_count(&self) -> Option<usize> {
+        if !self.statistics_fast_path
+            || self.may_skip_or_fail_files()
+            || self.file_source.filter().is_some()
+        {
+            return None;
+        }
+        let num_rows = self
+            .file_groups
+            .iter()
+            .flat_map(FileGroup::iter)
+            .map(|file| self.exact_file_row_count(file))
+            .sum::<Option<usize>>()?;
+        Some(self.limit.map_or(num_rows, |limit| num_rows.min(limit)))
+    }
+
+    /// Returns true if the scan may skip some of the files, or fail to read
+    /// them, in ways the statistics of the files do not account for: files
+    /// skipped by the [`FileScanConfig::byte_scan_budget`], missing files
+    /// skipped by [`MissingFilePolicy::Skip`], files rejected by the
+    /// [`FileScanConfig::file_admission`], or files whose checksum does not
+    /// match.
+    fn may_skip_or_fail_files(&self) -> bool {
+        self.byte_scan_budget.is_some()
+            || self.missing_file_policy == MissingFilePolicy::Skip
+            || self.file_admission.is_some()
+            || self.verifies_checksums()
+    }
+
+    /// Returns the number of rows read from `file` if it is known exactly.
+    ///
+    /// The statistics describe the whole file, so this returns `None` if only
+    /// parts of the file are read, because of a [`PartitionedFile::range`] or
+    /// [`PartitionedFile::ranges`], or if the file is read according
+    /// to its [`PartitionedFile::extensions`], e.g. a Parquet access plan
+    /// skipping row groups, or is made of several
+    /// [`PartitionedFile::sub_files`].
+    fn exact_file_row_count(&self, file: &PartitionedFile) -> Option<usize> {
+        let reads_whole_file = file.range.is_none()
+            && file.extensions.is_none()
+            && file.ranges.is_empty()
+            && file.sub_files.is_empty();
+        match file.statistics.as_ref().map(|s| s.num_rows) {
+            Some(Precision::Exact(num_rows)) if reads_whole_file => Some(
+                self.per_file_row_limit
+                    .map_or(num_rows, |limit| num_rows.min(limit)),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns a stream producing the output of `partition` from the
+    /// [`PartitionedFile::partition_values`] of its files, without opening
+    /// them, if the projection only references partition columns. This
+    /// includes an empty projection, e.g. for `COUNT(*)`, in which case only
+    /// the row counts are produced.
+    ///
+    /// Only used if [`FileScanConfig::statistics_fast_path`] is set. Every
+    /// file must have an exact row count in its statistics and be read as a
+    /// whole, so that each partition value can be repeated once per row.
+    /// Returns `None` otherwise, if the [`FileSource::filter`] may drop rows,
+    /// or if files may be skipped or fail to be read, see
+    /// [`Self::may_skip_or_fail_files`], which is only accounted for when
+    /// they are opened. Also returns `None` if an option of the
+    /// [`FileStream`] changes the batches produced or observes them: the
+    /// [`FileScanConfig::emit_empty_file_batches`],
+    /// [`FileScanConfig::max_batch_bytes`],
+    /// [`FileScanConfig::progress_callback`] and
+    /// [`FileScanConfig::memory_reservation`], so that the output does not
+    /// depend on whether the fast path is used.
+    pub(crate) fn partition_values_stream(
+        &self,
+        partition: usize,
+        batch_size: usize,
+    ) -> Result<Option<SendableRecordBatchStream>> {
+        let num_file_columns = self.file_schema.fields().len();
+        let partition_only = self
+            .projection
+            .as_ref()
+            .is_some_and(|projection| projection.iter().all(|i| *i >= num_file_columns));
+        if !self.statistics_fast_path
+            || !partition_only
+            || self.may_skip_or_fail_files()
+            || self.file_source.filter().is_some()
+            || self.emit_empty_file_batches
+            || self.max_batch_bytes.is_some()
+            || self.progress_callback.is_some()
+            || self.memory_reservation
+            || self.reads_partition_columns()
+            || self.resume_cursors.contains_key(&partition)
+        {
+            return Ok(None);
+        }
+
+        let mut file_group = self.file_groups_in_read_order()[partition]
+            .clone()
+            .into_inner();
+        if self.reverse_scan {
+            file_group.reverse();
+        }
+        let mut files = Vec::with_capacity(file_group.len());
+        for file in file_group {
+            let Some(num_rows) = self.exact_file_row_count(&file) else {
+                return Ok(None);
+            };
+            files.push((file.partition_values, num_rows));
+        }
+
+        let schema = self.projected_schema();
+        let mut projector = PartitionColumnProjector::new(
+            Arc::clone(&schema),
+            &self
+                .table_partition_cols
+                .iter()
+                .map(|field| self.output_name(field.name()).to_string())
+                .collect::<Vec<_>>(),
+        );
+        let batches = files
+            .into_iter()
+            .flat_map(move |(values, num_rows)| {
+                (0..num_rows).step_by(batch_size).map(move |offset| {
+                    (values.clone(), (num_rows - offset).min(batch_size))
+                })
+            })
+            .scan(
+                self.limit.unwrap_or(usize::MAX),
+                |remaining, (values, n)| {
+                    let n = n.min(*remaining);
+                    *remaining -= n;
+                    (n > 0).then_some((values, n))
+                },
+            )
+            .map(move |(values, n)| {
+                // the projector only adds partition columns to the empty batch
+                let file_batch = RecordBatch::try_new_with_options(
+                    Arc::new(Schema::empty()),
+                    vec![],
+                    &RecordBatchOptions::new().with_row_count(Some(n)),
+                )?;
+                projector.project(file_batch, &values)
+            });
+        Ok(Some(Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::iter(batches),
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_scan_config::{FileAdmission, FileScanConfigBuilder};
+    use crate::file_stream::ProgressCallback;
+    use crate::source::DataSource;
+    use crate::{test_util::MockSource, tests::aggr_test_schema, FileRange};
+
+    #[cfg(feature = "checksum")]
+    use crate::checksum::ChecksumAlgorithm;
+    use arrow::datatypes::{DataType, Field};
+    use datafusion_common::{
+        assert_batches_eq, assert_contains, ScalarValue, Statistics,
+    };
+    use datafusion_execution::object_store::ObjectStoreUrl;
+    use datafusion_execution::TaskContext;
+    use object_store::ObjectMeta;
+
+    /// Returns a builder over [`aggr_test_schema`] reading from `test:///`
+    fn test_builder() -> FileScanConfigBuilder {
+        FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            aggr_test_schema(),
+            Arc::new(MockSource::default()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_file_scan_config_partition_only_projection() -> Result<()> {
+        let file = |path: &str, date: &str, num_rows: Option<usize>| {
+            let mut file = PartitionedFile::new(path, 10);
+            file.partition_values = vec![ScalarValue::from(date)];
+            if let Some(num_rows) = num_rows {
+                file = file.with_statistics(Arc::new(
+                    Statistics::new_unknown(&aggr_test_schema())
+                        .with_num_rows(Precision::Exact(num_rows)),
+                ));
+            }
+            file
+        };
+        let file_schema = aggr_test_schema();
+        let num_file_columns = file_schema.fields().len();
+        let builder = FileScanConfigBuilder::new(
+            ObjectStoreUrl::parse("test:///").unwrap(),
+            file_schema,
+            Arc::new(MockSource::default()),
+        )
+        .with_table_partition_cols(vec![Field::new("date", DataType::Utf8, false)])
+        .with_projection(Some(vec![num_file_columns]))
+        .with_batch_size(Some(2))
+        .with_file_groups(vec![FileGroup::new(vec![
+            file("a.csv", "2024-01-01", Some(3)),
+            file("b.csv", "2024-01-02", Some(1)),
+        ])]);
+        let context = Arc::new(TaskContext::default());
+
+        // the files are read unless the fast path is enabled, which fails as
+        // the test object store is not registered
+        let config = builder.clone().build();
+        assert!(config.is_partition_only_projection());
+        let err = config.open(0, Arc::clone(&context)).err().unwrap();
+        assert_contains!(err.to_string(), "No suitable object store found");
+
+        // neither the object store nor the file opener of the MockSource are
+        // used, so no file is read
+        let builder = builder.with_statistics_fast_path(true);
+        let config = builder.clone().build();
+        let batches = datafusion_physical_plan::common::collect(
+            config.open(0, Arc::clone(&context))?,
+        )
+        .await?;
+        assert_eq!(batches.len(), 3);
+        assert_batches_eq!(
+            [
+                "+------------+",
+                "| date       |",
+                "+------------+",
+                "| 2024-01-01 |",
+                "| 2024-01-01 |",
+                "| 2024-01-01 |",
+                "| 2024-01-02 |",
+                "+------------+",
+            ],
+            &batches
+        );
+
+        let config = builder.clone().with_limit(Some(2)).build();
+        let batches = datafusion_physical_plan::common::collect(
+            config.open(0, Arc::clone(&context))?,
+        )
+        .await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+
+        // without an exact row count the files have to be read
+        let config = builder
+            .clone()
+            .with_file_groups(vec![FileGroup::new(vec![file(
+                "c.csv",
+                "2024-01-03",
+                None,
+            )])])
+            .build();
+        let err = config.open(0, Arc::clone(&context)).err().unwrap();
+        assert_contains!(err.to_string(), "No suitable object store found");
+
+        // a filter pushed into the source may drop rows, so the files have to
+        // be read as well
+        let filter = crate::test_util::col("c2", &aggr_test_schema())?;
+        let config = builder
+            .clone()
+            .with_source(Arc::new(MockSource::default().with_filter(filter)))
+            .build();
+        let err = config.open(0, Arc::clone(&context)).err().unwrap();
+        assert_contains!(err.to_string(), "No suitable object store found");
+
+        // the options of the file stream apply to the batches it produces,
+        // so the files are read when any of them is set
+        let callback: ProgressCallback = Arc::new(|_| {});
+        let file_stream_options = [
+            builder.clone().with_emit_empty_file_batches(true),
+            builder.clone().with_max_batch_bytes(Some(1024)),
+            builder.clone().with_progress_callback(callback),
+            builder.clone().with_memory_reservation(true),
+        ];
+        for builder in file_stream_options {
+            let err = builder.build().open(0, Arc::clone(&context)).err().unwrap();
+            assert_contains!(err.to_string(), "No suitable object store found");
+        }
+
+        // projections including file columns are not partition-only
+        let config = FileScanConfigBuilder::from(config)
+            .with_projection(Some(vec![0, num_file_columns]))
+            .build();
+        assert!(!config.is_partition_only_projection());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_scan_config_exact_row_count() -> Result<()> {
+        let file = |path: &str, num_rows: Precision<usize>| {
+            PartitionedFile::new(path, 10).with_statistics(Arc::new(
+                Statistics::new_unknown(&aggr_test_schema()).with_num_rows(num_rows),
+            ))
+        };
+        let builder = test_builder()
+            .with_projection(Some(vec![]))
+            .with_statistics_fast_path(true)
+            .with_file_groups(vec![
+                FileGroup::new(vec![
+                    file("a.csv", Precision::Exact(3)),
+                    file("b.csv", Precision::Exact(4)),
+                ]),
+                FileGroup::new(vec![file("c.csv", Precision::Exact(5))]),
+            ]);
+        let context = Arc::new(TaskContext::default());
+
+        // the fast path is disabled by default
+        let config = builder.clone().with_statistics_fast_path(false).build();
+        assert_eq!(config.exact_row_count(), None);
+
+        let config = builder.clone().build();
+        assert_eq!(config.exact_row_count(), Some(12));
+        assert_eq!(
+            builder
+                .clone()
+                .with_limit(Some(10))
+                .build()
+                .exact_row_count(),
+            Some(10)
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .with_per_file_row_limit(Some(4))
+                .build()
+                .exact_row_count(),
+            Some(11)
+        );
+
+        // the rows are counted without opening any file, as neither the object
+        // store nor the file opener of the MockSource can be used
+        let mut num_rows = 0;
+        for partition in 0..2 {
+            let stream = config.open(partition, Arc::clone(&context))?;
+            let batches = datafusion_physical_plan::common::collect(stream).await?;
+            assert!(batches.iter().all(|b| b.num_columns() == 0));
+            num_rows += batches.iter().map(|b| b.num_rows()).sum::<usize>();
+        }
+        assert_eq!(num_rows, 12);
+
+        // a filter pushed into the source requires scanning the files
+        let filter = crate::test_util::col("c2", &aggr_test_schema())?;
+        let filtered = builder
+            .clone()
+            .with_source(Arc::new(MockSource::default().with_filter(filter)))
+            .build();
+        assert_eq!(filtered.exact_row_count(), None);
+        let err = filtered.open(0, Arc::clone(&context)).err().unwrap();
+        assert_contains!(err.to_string(), "No suitable object store found");
+
+        // so does a byte budget, which may skip some of the files
+        let budgeted = builder.clone().with_byte_scan_budget(15).build();
+        assert_eq!(budgeted.exact_row_count(), None);
+        let err = budgeted.open(0, Arc::clone(&context)).err().unwrap();
+        assert_contains!(err.to_string(), "No suitable object store found");
+
+        // as well as skipping missing files
+        let skipping = builder
+            .clone()
+            .with_missing_file_policy(MissingFilePolicy::Skip)
+            .build();
+        assert_eq!(skipping.exact_row_count(), None);
+        let err = skipping.open(0, Arc::clone(&context)).err().unwrap();
+        assert_contains!(err.to_string(), "No suitable object store found");
+
+        // or an admission policy, which may reject some of the files
+        let admitting = builder
+            .clone()
+            .with_file_admission(Arc::new(|_: &ObjectMeta| FileAdmission::Admit))
+            .build();
+        assert_eq!(admitting.exact_row_count(), None);
+        let err = admitting.open(0, Arc::clone(&context)).err().unwrap();
+        assert_contains!(err.to_string(), "No suitable object store found");
+
+        // or verifying checksums, which may fail reading the files
+        #[cfg(feature = "checksum")]
+        {
+            let verifying = builder
+                .clone()
+                .with_checksum_verification(ChecksumAlgorithm::Crc32c)
+                .build();
+            assert_eq!(verifying.exact_row_count(), None);
+            let err = verifying.open(0, Arc::clone(&context)).err().unwrap();
+            assert_contains!(err.to_string(), "No suitable object store found");
+        }
+
+        // an inexact count requires scanning the files
+        let config = builder
+            .clone()
+            .with_file_groups(vec![FileGroup::new(vec![
+                file("a.csv", Precision::Exact(3)),
+                file("b.csv", Precision::Inexact(4)),
+            ])])
+            .build();
+        assert_eq!(config.exact_row_count(), None);
+        let err = config.open(0, Arc::clone(&context)).err().unwrap();
+        assert_contains!(err.to_string(), "No suitable object store found");
+
+        // as do files of which only some rows are read: the statistics
+        // describe the whole file
+        let partially_read = [
+            file("b.csv", Precision::Exact(4)).with_extensions(Arc::new(())),
+            file("b.csv", Precision::Exact(4))
+                .with_ranges(vec![FileRange { start: 0, end: 5 }]),
+            file("b.csv", Precision::Exact(4)).with_sub_files(vec![
+                FileRange { start: 0, end: 5 },
+                FileRange { start: 5, end: 10 },
+            ]),
+        ];
+        for partial in partially_read {
+            let config = builder
+                .clone()
+                .with_file_groups(vec![FileGroup::new(vec![
+                    file("a.csv", Precision::Exact(3)),
+                    partial,
+                ])])
+                .build();
+            assert_eq!(config.exact_row_count(), None);
+            let err = config.open(0, Arc::clone(&context)).err().unwrap();
+            assert_contains!(err.to_string(), "No suitable object store found");
+        }
+        Ok(())
+    }
+}
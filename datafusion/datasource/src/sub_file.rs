@@ -0,0 +1,249 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reading independent files embedded in a single object, see
+//! [`PartitionedFile::with_sub_files`]
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::file_meta::FileMeta;
+use crate::file_stream::{FileOpenFuture, FileOpener};
+use crate::{FileRange, PartitionedFile};
+
+use arrow::error::ArrowError;
+use async_trait::async_trait;
+use datafusion_common::Result;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetRange, GetResult, GetResultPayload, ListResult, MultipartUpload,
+    ObjectMeta, ObjectStore, PutMultipartOpts, PutOptions, PutPayload, PutResult,
+};
+
+/// A read-only view of the byte range of a file embedded in an object, which
+/// is presented as an object of its own at the location of the object.
+///
+/// Reads of other locations are passed to the inner store unchanged.
+#[derive(Debug)]
+pub struct SubFileObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    location: Path,
+    range: Range<u64>,
+}
+
+impl SubFileObjectStore {
+    /// Create a view of the bytes `range` of the object at `location` of
+    /// `inner`
+    pub fn new(inner: Arc<dyn ObjectStore>, location: Path, range: Range<u64>) -> Self {
+        Self {
+            inner,
+            location,
+            range,
+        }
+    }
+
+    /// Returns the range of the object to read for `range` of the sub file
+    fn object_range(&self, range: Option<&GetRange>) -> Range<u64> {
+        let Range { start, end } = self.range;
+        match range {
+            None => start..end,
+            Some(GetRange::Bounded(range)) => {
+                (start + range.start).min(end)..(start + range.end).min(end)
+            }
+            Some(GetRange::Offset(offset)) => (start + offset).min(end)..end,
+            Some(GetRange::Suffix(suffix)) => end.saturating_sub(*suffix).max(start)..end,
+        }
+    }
+}
+
+impl fmt::Display for SubFileObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SubFileObjectStore({}, {}, {}..{})",
+            self.inner, self.location, self.range.start, self.range.end
+        )
+    }
+}
+
+#[async_trait]
+impl ObjectStore for SubFileObjectStore {
+    async fn put_opts(
+        &self,
+        _location: &Path,
+        _payload: PutPayload,
+        _opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        _location: &Path,
+        _opts: PutMultipartOpts,
+    ) -> object_store::Result<Box<dyn MultipartUpload>> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        mut options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        if location != &self.location {
+            return self.inner.get_opts(location, options).await;
+        }
+
+        let start = self.range.start;
+        let size = self.range.end - self.range.start;
+        let object_range = self.object_range(options.range.as_ref());
+        if !options.head {
+            options.range = Some(GetRange::Bounded(object_range.clone()));
+        }
+        let result = self.inner.get_opts(location, options).await?;
+        let meta = ObjectMeta {
+            size,
+            ..result.meta.clone()
+        };
+        let attributes = result.attributes.clone();
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(result.into_stream()),
+            meta,
+            range: object_range.start - start..object_range.end - start,
+            attributes,
+        })
+    }
+
+    async fn delete(&self, _location: &Path) -> object_store::Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    fn list(
+        &self,
+        prefix: Option<&Path>,
+    ) -> BoxStream<'static, object_store::Result<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(
+        &self,
+        prefix: Option<&Path>,
+    ) -> object_store::Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, _from: &Path, _to: &Path) -> object_store::Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn copy_if_not_exists(
+        &self,
+        _from: &Path,
+        _to: &Path,
+    ) -> object_store::Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+}
+
+/// Creates the opener reading files from an object store
+pub(crate) type CreateOpenerForStore =
+    dyn Fn(Arc<dyn ObjectStore>) -> Arc<dyn FileOpener> + Send + Sync;
+
+/// Opens the [`PartitionedFile::sub_files`] of `file` one after the other,
+/// each with an opener created by `create_opener` for a
+/// [`SubFileObjectStore`] of `object_store`, and concatenates their batches.
+pub(crate) fn open_sub_files(
+    object_store: Arc<dyn ObjectStore>,
+    create_opener: Arc<CreateOpenerForStore>,
+    file_meta: FileMeta,
+    file: PartitionedFile,
+) -> Result<FileOpenFuture> {
+    let sub_files = file
+        .sub_files
+        .iter()
+        .filter(|sub_file| match &file.range {
+            Some(range) => range.contains(sub_file.start),
+            None => true,
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let stream = futures::stream::iter(sub_files)
+        .then(move |FileRange { start, end }| {
+            let location = file.object_meta.location.clone();
+            let store = SubFileObjectStore::new(
+                Arc::clone(&object_store),
+                location,
+                start as u64..end as u64,
+            );
+            let opener = create_opener(Arc::new(store));
+            let sub_file = PartitionedFile {
+                object_meta: ObjectMeta {
+                    size: (end - start) as u64,
+                    ..file.object_meta.clone()
+                },
+                range: None,
+                statistics: None,
+                metadata_size_hint: None,
+                sub_files: vec![],
+                file_schema_override: None,
+                row_group_statistics: None,
+                ranges: vec![],
+                ..file.clone()
+            };
+            let sub_file_meta = FileMeta {
+                object_meta: sub_file.object_meta.clone(),
+                range: None,
+                extensions: file_meta.extensions.clone(),
+                metadata_size_hint: None,
+            };
+            async move { opener.open(sub_file_meta, sub_file)?.await }
+        })
+        .map_err(ArrowError::from)
+        .try_flatten();
+    Ok(Box::pin(async move { Ok(stream.boxed()) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use object_store::memory::InMemory;
+
+    #[tokio::test]
+    async fn test_sub_file_object_store() -> object_store::Result<()> {
+        let inner: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let location = Path::from("packed");
+        inner.put(&location, "aaabbbbcc".into()).await?;
+
+        let store = SubFileObjectStore::new(Arc::clone(&inner), location.clone(), 3..7);
+        assert_eq!(store.head(&location).await?.size, 4);
+        assert_eq!(store.get(&location).await?.bytes().await?, "bbbb");
+        assert_eq!(store.get_range(&location, 1..3).await?, "bb");
+        let suffix = GetOptions {
+            range: Some(GetRange::Suffix(2)),
+            ..Default::default()
+        };
+        let result = store.get_opts(&location, suffix).await?;
+        assert_eq!(result.range, 2..4);
+        assert_eq!(result.bytes().await?, "bb");
+        Ok(())
+    }
+}
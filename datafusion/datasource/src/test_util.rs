@@ -16,16 +16,21 @@
 // under the License.
 
 use crate::{
-    file::FileSource, file_scan_config::FileScanConfig, file_stream::FileOpener,
+    file::FileSource,
+    file_meta::FileMeta,
+    file_scan_config::FileScanConfig,
+    file_stream::{FileOpenFuture, FileOpener},
     schema_adapter::SchemaAdapterFactory,
+    PartitionedFile,
 };
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use arrow::datatypes::{Schema, SchemaRef};
 use datafusion_common::{Result, Statistics};
 use datafusion_physical_expr::{expressions::Column, PhysicalExpr};
 use datafusion_physical_plan::metrics::ExecutionPlanMetricsSet;
+use futures::StreamExt;
 use object_store::ObjectStore;
 
 /// Minimal [`crate::file::FileSource`] implementation for use in tests.
@@ -34,6 +39,37 @@ pub(crate) struct MockSource {
     metrics: ExecutionPlanMetricsSet,
     projected_statistics: Option<Statistics>,
     schema_adapter_factory: Option<Arc<dyn SchemaAdapterFactory>>,
+    filter: Option<Arc<dyn PhysicalExpr>>,
+    batch_size: Option<usize>,
+    /// Batch sizes the openers created by this source were created with
+    opener_batch_sizes: Arc<Mutex<Vec<Option<usize>>>>,
+}
+
+impl MockSource {
+    /// Returns a source reporting `filter` as pushed down into it
+    pub(crate) fn with_filter(mut self, filter: Arc<dyn PhysicalExpr>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Returns the batch sizes of the openers created by this source and the
+    /// sources derived from it, in the order they were created
+    pub(crate) fn opener_batch_sizes(&self) -> Arc<Mutex<Vec<Option<usize>>>> {
+        Arc::clone(&self.opener_batch_sizes)
+    }
+}
+
+/// [`FileOpener`] of a [`MockSource`], reading no rows from any file
+struct MockOpener;
+
+impl FileOpener for MockOpener {
+    fn open(
+        &self,
+        _file_meta: FileMeta,
+        _file: PartitionedFile,
+    ) -> Result<FileOpenFuture> {
+        Ok(Box::pin(async { Ok(futures::stream::empty().boxed()) }))
+    }
 }
 
 impl FileSource for MockSource {
@@ -43,15 +79,22 @@ impl FileSource for MockSource {
         _base_config: &FileScanConfig,
         _partition: usize,
     ) -> Arc<dyn FileOpener> {
-        unimplemented!()
+        self.opener_batch_sizes
+            .lock()
+            .unwrap()
+            .push(self.batch_size);
+        Arc::new(MockOpener)
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 
-    fn with_batch_size(&self, _batch_size: usize) -> Arc<dyn FileSource> {
-        Arc::new(Self { ..self.clone() })
+    fn with_batch_size(&self, batch_size: usize) -> Arc<dyn FileSource> {
+        Arc::new(Self {
+            batch_size: Some(batch_size),
+            ..self.clone()
+        })
     }
 
     fn with_schema(&self, _schema: SchemaRef) -> Arc<dyn FileSource> {
@@ -97,6 +140,10 @@ impl FileSource for MockSource {
     fn schema_adapter_factory(&self) -> Option<Arc<dyn SchemaAdapterFactory>> {
         self.schema_adapter_factory.clone()
     }
+
+    fn filter(&self) -> Option<Arc<dyn PhysicalExpr>> {
+        self.filter.clone()
+    }
 }
 
 /// Create a column expression
@@ -0,0 +1,142 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reading files followed by bytes that are not part of their data, see
+//! [`FileScanConfigBuilder::with_tolerate_trailing_bytes`]
+//!
+//! [`FileScanConfigBuilder::with_tolerate_trailing_bytes`]: crate::file_scan_config::FileScanConfigBuilder::with_tolerate_trailing_bytes
+
+use std::sync::Arc;
+
+use crate::file_meta::FileMeta;
+use crate::file_stream::FileOpenFuture;
+use crate::sub_file::{CreateOpenerForStore, SubFileObjectStore};
+use crate::PartitionedFile;
+
+use datafusion_common::Result;
+use log::debug;
+use object_store::{ObjectMeta, ObjectStore};
+
+/// Number of bytes at the end of a file searched for the end of its data
+const TRAILER_SEARCH_LEN: u64 = 64 * 1024;
+
+/// The end of a file format whose data ends with a footer, its length as a
+/// 4 bytes little endian integer and a magic number, as Parquet and Arrow IPC
+/// files do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trailer {
+    /// Magic number ending the data
+    magic: &'static [u8],
+    /// Length of the header preceding the footer
+    header_len: u64,
+}
+
+impl Trailer {
+    /// Returns the trailer of the files of `file_type`, as returned by
+    /// [`FileSource::file_type`], if they have one
+    ///
+    /// [`FileSource::file_type`]: crate::file::FileSource::file_type
+    pub fn for_file_type(file_type: &str) -> Option<Self> {
+        match file_type {
+            "parquet" => Some(Self {
+                magic: b"PAR1",
+                header_len: 4,
+            }),
+            "arrow" => Some(Self {
+                magic: b"ARROW1",
+                header_len: 8,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the end of the data of a file whose bytes from `tail_start`
+    /// are `tail`, i.e. the end of the last magic number preceded by a
+    /// footer length that fits in the file, if any
+    pub fn data_end(&self, tail: &[u8], tail_start: u64) -> Option<u64> {
+        let magic_len = self.magic.len();
+        (4..=tail.len().saturating_sub(magic_len))
+            .rev()
+            .filter(|&i| &tail[i..i + magic_len] == self.magic)
+            .find(|&i| {
+                let footer_len = u32::from_le_bytes(tail[i - 4..i].try_into().unwrap());
+                tail_start + i as u64 >= self.header_len + 4 + footer_len as u64
+            })
+            .map(|i| tail_start + (i + magic_len) as u64)
+    }
+}
+
+/// Opens `file` with an opener created by `create_opener`, ignoring the
+/// bytes following the end of its data according to `trailer`.
+///
+/// The end of the data is searched for in the last bytes of the file. If it
+/// is not found, the file is opened unchanged and fails to be read as usual.
+pub(crate) fn open_without_trailing_bytes(
+    object_store: Arc<dyn ObjectStore>,
+    create_opener: Arc<CreateOpenerForStore>,
+    trailer: Trailer,
+    file_meta: FileMeta,
+    file: PartitionedFile,
+) -> Result<FileOpenFuture> {
+    Ok(Box::pin(async move {
+        let ObjectMeta { location, size, .. } = file.object_meta.clone();
+        let tail_start = size.saturating_sub(TRAILER_SEARCH_LEN);
+        let tail = object_store.get_range(&location, tail_start..size).await?;
+        let end = trailer.data_end(&tail, tail_start).unwrap_or(size);
+        if end == size {
+            return create_opener(object_store).open(file_meta, file)?.await;
+        }
+
+        debug!("Ignoring {} trailing bytes of file {location}", size - end);
+        let store = SubFileObjectStore::new(object_store, location, 0..end);
+        let object_meta = ObjectMeta {
+            size: end,
+            ..file.object_meta.clone()
+        };
+        let file_meta = FileMeta {
+            object_meta: object_meta.clone(),
+            ..file_meta
+        };
+        let file = PartitionedFile {
+            object_meta,
+            ..file
+        };
+        create_opener(Arc::new(store)).open(file_meta, file)?.await
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailer_data_end() {
+        let trailer = Trailer::for_file_type("parquet").unwrap();
+        // header, 2 bytes of footer, footer length and magic
+        let data = b"PAR1ff\x02\x00\x00\x00PAR1";
+
+        assert_eq!(trailer.data_end(data, 0), Some(14));
+        let mut padded = data.to_vec();
+        padded.extend_from_slice(b"\0\0garbagePAR1");
+        assert_eq!(trailer.data_end(&padded, 0), Some(14));
+        // the tail starts after the header
+        assert_eq!(trailer.data_end(&padded[4..], 4), Some(14));
+
+        assert_eq!(trailer.data_end(b"garbage", 0), None);
+        assert_eq!(Trailer::for_file_type("csv"), None);
+    }
+}
@@ -0,0 +1,412 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Refreshing the object store URL of files whose access expires while they
+//! are read, see [`FileScanConfigBuilder::with_url_refresher`]
+//!
+//! [`FileScanConfigBuilder::with_url_refresher`]: crate::file_scan_config::FileScanConfigBuilder::with_url_refresher
+
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::file_meta::FileMeta;
+use crate::file_stream::{FileOpenFuture, FileOpener};
+use crate::PartitionedFile;
+
+use arrow::array::RecordBatch;
+use arrow::error::ArrowError;
+use datafusion_common::{DataFusionError, Result};
+use datafusion_execution::object_store::ObjectStoreUrl;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use log::debug;
+
+/// Function returning a fresh object store URL to read a file from, e.g. with
+/// a newly pre-signed URL, after its access has expired
+pub type UrlRefresher =
+    Arc<dyn Fn(&PartitionedFile) -> Result<ObjectStoreUrl> + Send + Sync>;
+
+/// Returns true if `error` is caused by an [`object_store::Error::Unauthenticated`],
+/// which is how object stores report expired credentials or pre-signed URLs
+pub fn is_auth_expired(error: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(object_store::Error::Unauthenticated { .. }) =
+            error.downcast_ref::<object_store::Error>()
+        {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+/// A [`FileOpener`] that refreshes the URL of a file with a [`UrlRefresher`]
+/// when reading it fails because its access expired, and resumes reading it
+/// from the fresh URL.
+///
+/// The refreshed URL is set as the [`PartitionedFile::object_store_url`] of
+/// the file, so the inner opener must open files from the object store of
+/// their URL. As the file is read again from its start, the rows already
+/// returned are skipped, which requires the inner opener to return the same
+/// rows in the same order each time it opens a file.
+///
+/// The URL is only refreshed again if rows were read since the last refresh,
+/// so that a refresher returning expired URLs does not loop forever.
+pub struct UrlRefreshingOpener {
+    inner: Arc<dyn FileOpener>,
+    refresher: UrlRefresher,
+}
+
+impl UrlRefreshingOpener {
+    /// Create a new opener opening files with `inner`, refreshing their URL
+    /// with `refresher` when their access expired
+    pub fn new(inner: Arc<dyn FileOpener>, refresher: UrlRefresher) -> Self {
+        Self { inner, refresher }
+    }
+}
+
+impl FileOpener for UrlRefreshingOpener {
+    fn open(&self, file_meta: FileMeta, file: PartitionedFile) -> Result<FileOpenFuture> {
+        let mut state = ResumeState {
+            inner: Arc::clone(&self.inner),
+            refresher: Arc::clone(&self.refresher),
+            file_meta,
+            file,
+            stream: futures::stream::empty().boxed(),
+            emitted: 0,
+            skip: 0,
+            can_refresh: true,
+        };
+        Ok(Box::pin(async move {
+            state.stream = match state.open()?.await {
+                Ok(stream) => stream,
+                Err(e) if is_auth_expired(&e) => {
+                    state.refresh(e)?;
+                    state.open()?.await?
+                }
+                Err(e) => return Err(e),
+            };
+            Ok(futures::stream::try_unfold(state, ResumeState::next).boxed())
+        }))
+    }
+}
+
+/// The state of a file read by a [`UrlRefreshingOpener`]
+struct ResumeState {
+    inner: Arc<dyn FileOpener>,
+    refresher: UrlRefresher,
+    file_meta: FileMeta,
+    file: PartitionedFile,
+    stream: BoxStream<'static, Result<RecordBatch, ArrowError>>,
+    /// Number of rows returned so far
+    emitted: usize,
+    /// Number of rows of the current stream still to skip, as they were
+    /// returned before the URL was refreshed
+    skip: usize,
+    /// Whether rows were read since the last refresh
+    can_refresh: bool,
+}
+
+impl ResumeState {
+    fn open(&self) -> Result<FileOpenFuture> {
+        self.inner.open(self.file_meta.clone(), self.file.clone())
+    }
+
+    /// Sets a fresh URL for the file after `error`, or returns `error` if
+    /// the URL can not be refreshed again yet
+    fn refresh<E: Error + From<DataFusionError>>(&mut self, error: E) -> Result<(), E> {
+        if !self.can_refresh {
+            return Err(error);
+        }
+        let url = (self.refresher)(&self.file)?;
+        debug!(
+            "Access to file {} expired, resuming from {}: {error}",
+            self.file.path(),
+            url.as_str()
+        );
+        self.file.object_store_url = Some(url);
+        self.can_refresh = false;
+        Ok(())
+    }
+
+    async fn next(mut self) -> Result<Option<(RecordBatch, Self)>, ArrowError> {
+        loop {
+            match self.stream.next().await {
+                Some(Ok(batch)) => {
+                    if batch.num_rows() > 0 {
+                        self.can_refresh = true;
+                    }
+                    let skipped = self.skip.min(batch.num_rows());
+                    if skipped > 0 {
+                        self.skip -= skipped;
+                        if skipped == batch.num_rows() {
+                            continue;
+                        }
+                    }
+                    let batch = batch.slice(skipped, batch.num_rows() - skipped);
+                    self.emitted += batch.num_rows();
+                    return Ok(Some((batch, self)));
+                }
+                Some(Err(e)) if is_auth_expired(&e) => {
+                    self.refresh(e)?;
+                    self.stream = self.open()?.await?;
+                    self.skip = self.emitted;
+                }
+                Some(Err(e)) => return Err(e),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::ops::Range;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use arrow::array::{ArrayRef, StringArray};
+    use async_trait::async_trait;
+    use datafusion_common::assert_contains;
+    use object_store::memory::InMemory;
+    use object_store::path::Path;
+    use object_store::{
+        GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+        PutMultipartOpts, PutOptions, PutPayload, PutResult,
+    };
+
+    /// Mock store whose access expires after a number of requests
+    #[derive(Debug)]
+    struct ExpiringStore {
+        inner: InMemory,
+        remaining_requests: AtomicUsize,
+    }
+
+    impl fmt::Display for ExpiringStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "ExpiringStore")
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for ExpiringStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> object_store::Result<PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOpts,
+        ) -> object_store::Result<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: GetOptions,
+        ) -> object_store::Result<GetResult> {
+            let expired = self
+                .remaining_requests
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_err();
+            if expired {
+                return Err(object_store::Error::Unauthenticated {
+                    path: location.to_string(),
+                    source: "pre-signed URL expired".into(),
+                });
+            }
+            self.inner.get_opts(location, options).await
+        }
+
+        async fn delete(&self, location: &Path) -> object_store::Result<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> BoxStream<'static, object_store::Result<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(
+            &self,
+            from: &Path,
+            to: &Path,
+        ) -> object_store::Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    /// Opener reading files from the store of their URL in chunks of 2
+    /// bytes, returning a batch with a row per byte for each chunk
+    struct ChunkedOpener {
+        stores: HashMap<ObjectStoreUrl, Arc<dyn ObjectStore>>,
+    }
+
+    impl FileOpener for ChunkedOpener {
+        fn open(
+            &self,
+            file_meta: FileMeta,
+            file: PartitionedFile,
+        ) -> Result<FileOpenFuture> {
+            let store = Arc::clone(&self.stores[file.object_store_url.as_ref().unwrap()]);
+            let location = file_meta.location().clone();
+            let size = file.object_meta.size;
+            let stream = futures::stream::try_unfold(0, move |offset| {
+                let store = Arc::clone(&store);
+                let location = location.clone();
+                async move {
+                    if offset >= size {
+                        return Ok(None);
+                    }
+                    let range: Range<u64> = offset..(offset + 2).min(size);
+                    let bytes = store
+                        .get_range(&location, range.clone())
+                        .await
+                        .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+                    let values = bytes.iter().map(|b| (*b as char).to_string());
+                    let array: ArrayRef = Arc::new(StringArray::from_iter_values(values));
+                    let batch = RecordBatch::try_from_iter([("c", array)])?;
+                    Ok(Some((batch, range.end)))
+                }
+            });
+            Ok(Box::pin(async move { Ok(stream.boxed()) }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_url_refreshing_opener() -> Result<()> {
+        let expiring_url = ObjectStoreUrl::parse("signed://expiring")?;
+        let fresh_url = ObjectStoreUrl::parse("signed://fresh")?;
+        let location = Path::from("data.txt");
+        let open = |remaining_requests: usize, refreshed_url: &ObjectStoreUrl| {
+            let store = |remaining_requests| {
+                let store = ExpiringStore {
+                    inner: InMemory::new(),
+                    remaining_requests: AtomicUsize::new(remaining_requests),
+                };
+                Arc::new(store) as Arc<dyn ObjectStore>
+            };
+            let stores = HashMap::from([
+                (expiring_url.clone(), store(remaining_requests)),
+                (fresh_url.clone(), store(usize::MAX)),
+            ]);
+            let refreshes = Arc::new(AtomicUsize::new(0));
+            let refresher: UrlRefresher = {
+                let refreshes = Arc::clone(&refreshes);
+                let url = refreshed_url.clone();
+                Arc::new(move |_| {
+                    refreshes.fetch_add(1, Ordering::SeqCst);
+                    Ok(url.clone())
+                })
+            };
+            let stores_for_put = stores.clone();
+            let location = location.clone();
+            let expiring_url = expiring_url.clone();
+            async move {
+                for store in stores_for_put.values() {
+                    store.put(&location, "abcdef".into()).await?;
+                }
+                let file = PartitionedFile::new(location.to_string(), 6)
+                    .with_object_store_url(expiring_url);
+                let opener = UrlRefreshingOpener::new(
+                    Arc::new(ChunkedOpener { stores }),
+                    refresher,
+                );
+                let result = opener
+                    .open(file.object_meta.clone().into(), file)?
+                    .await?
+                    .map(|batch| {
+                        let batch = batch?;
+                        let values =
+                            batch.column(0).as_any().downcast_ref::<StringArray>();
+                        Ok(values.unwrap().iter().flatten().collect::<String>())
+                    })
+                    .collect::<Vec<Result<String>>>()
+                    .await
+                    .into_iter()
+                    .collect::<Result<Vec<_>>>();
+                Ok::<_, DataFusionError>((result, refreshes.load(Ordering::SeqCst)))
+            }
+        };
+
+        // not expired
+        let (result, refreshes) = open(usize::MAX, &fresh_url).await?;
+        assert_eq!(result?, vec!["ab", "cd", "ef"]);
+        assert_eq!(refreshes, 0);
+
+        // expired before the first request
+        let (result, refreshes) = open(0, &fresh_url).await?;
+        assert_eq!(result?, vec!["ab", "cd", "ef"]);
+        assert_eq!(refreshes, 1);
+
+        // expired mid-read, resumed from the fresh URL
+        let (result, refreshes) = open(2, &fresh_url).await?;
+        assert_eq!(result?, vec!["ab", "cd", "ef"]);
+        assert_eq!(refreshes, 1);
+
+        // the refreshed URL expired as well
+        let (result, refreshes) = open(1, &expiring_url).await?;
+        assert_contains!(result.unwrap_err().to_string(), "pre-signed URL expired");
+        assert_eq!(refreshes, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_auth_expired() {
+        let expired = object_store::Error::Unauthenticated {
+            path: "data.txt".to_string(),
+            source: "expired".into(),
+        };
+        let error = DataFusionError::ObjectStore(expired);
+        assert!(is_auth_expired(&error));
+        let error = ArrowError::ExternalError(Box::new(error));
+        assert!(is_auth_expired(&error));
+
+        let not_found = object_store::Error::NotFound {
+            path: "data.txt".to_string(),
+            source: "not found".into(),
+        };
+        assert!(!is_auth_expired(&DataFusionError::ObjectStore(not_found)));
+        assert!(!is_auth_expired(&DataFusionError::Execution(
+            "expired".to_string()
+        )));
+    }
+}
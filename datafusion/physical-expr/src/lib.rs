@@ -36,6 +36,7 @@ pub mod intervals;
 mod partitioning;
 mod physical_expr;
 pub mod planner;
+pub mod pruning;
 mod scalar_function;
 pub mod statistics;
 pub mod utils;
@@ -40,11 +40,12 @@ use datafusion_common::{
     ScalarValue,
 };
 use datafusion_common::{Column, DFSchema};
+use datafusion_expr_common::columnar_value::ColumnarValue;
 use datafusion_expr_common::operator::Operator;
-use datafusion_physical_expr::utils::{collect_columns, Guarantee, LiteralGuarantee};
-use datafusion_physical_expr::{expressions as phys_expr, PhysicalExprRef};
 use datafusion_physical_expr_common::physical_expr::snapshot_physical_expr;
-use datafusion_physical_plan::{ColumnarValue, PhysicalExpr};
+
+use crate::utils::{collect_columns, Guarantee, LiteralGuarantee};
+use crate::{expressions as phys_expr, PhysicalExpr, PhysicalExprRef};
 
 /// Used to prove that arbitrary predicates (boolean expression) can not
 /// possibly evaluate to `true` given information about a column provided by
@@ -1828,6 +1829,8 @@ mod tests {
     use datafusion_expr::{and, col, lit, or};
     use insta::assert_snapshot;
 
+    use crate::expressions as phys_expr;
+    use crate::planner::logical2physical;
     use arrow::array::Decimal128Array;
     use arrow::{
         array::{BinaryArray, Int32Array, Int64Array, StringArray, UInt64Array},
@@ -1836,8 +1839,6 @@ mod tests {
     use datafusion_expr::expr::InList;
     use datafusion_expr::{cast, is_null, try_cast, Expr};
     use datafusion_functions_nested::expr_fn::{array_has, make_array};
-    use datafusion_physical_expr::expressions as phys_expr;
-    use datafusion_physical_expr::planner::logical2physical;
 
     #[derive(Debug, Default)]
     /// Mock statistic provider for tests
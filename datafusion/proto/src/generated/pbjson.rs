@@ -13216,6 +13216,27 @@ impl serde::Serialize for PartitionedFile {
         if self.statistics.is_some() {
             len += 1;
         }
+        if !self.sub_files.is_empty() {
+            len += 1;
+        }
+        if !self.ranges.is_empty() {
+            len += 1;
+        }
+        if !self.object_store_url.is_empty() {
+            len += 1;
+        }
+        if self.file_schema_override.is_some() {
+            len += 1;
+        }
+        if self.tombstoned {
+            len += 1;
+        }
+        if !self.row_group_statistics.is_empty() {
+            len += 1;
+        }
+        if !self.expected_checksum.is_empty() {
+            len += 1;
+        }
         let mut struct_ser = serializer.serialize_struct("datafusion.PartitionedFile", len)?;
         if !self.path.is_empty() {
             struct_ser.serialize_field("path", &self.path)?;
@@ -13239,6 +13260,29 @@ impl serde::Serialize for PartitionedFile {
         if let Some(v) = self.statistics.as_ref() {
             struct_ser.serialize_field("statistics", v)?;
         }
+        if !self.sub_files.is_empty() {
+            struct_ser.serialize_field("subFiles", &self.sub_files)?;
+        }
+        if !self.ranges.is_empty() {
+            struct_ser.serialize_field("ranges", &self.ranges)?;
+        }
+        if !self.object_store_url.is_empty() {
+            struct_ser.serialize_field("objectStoreUrl", &self.object_store_url)?;
+        }
+        if let Some(v) = self.file_schema_override.as_ref() {
+            struct_ser.serialize_field("fileSchemaOverride", v)?;
+        }
+        if self.tombstoned {
+            struct_ser.serialize_field("tombstoned", &self.tombstoned)?;
+        }
+        if !self.row_group_statistics.is_empty() {
+            struct_ser.serialize_field("rowGroupStatistics", &self.row_group_statistics)?;
+        }
+        if !self.expected_checksum.is_empty() {
+            #[allow(clippy::needless_borrow)]
+            #[allow(clippy::needless_borrows_for_generic_args)]
+            struct_ser.serialize_field("expectedChecksum", pbjson::private::base64::encode(&self.expected_checksum).as_str())?;
+        }
         struct_ser.end()
     }
 }
@@ -13257,6 +13301,18 @@ impl<'de> serde::Deserialize<'de> for PartitionedFile {
             "partitionValues",
             "range",
             "statistics",
+            "sub_files",
+            "subFiles",
+            "ranges",
+            "object_store_url",
+            "objectStoreUrl",
+            "file_schema_override",
+            "fileSchemaOverride",
+            "tombstoned",
+            "row_group_statistics",
+            "rowGroupStatistics",
+            "expected_checksum",
+            "expectedChecksum",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -13267,6 +13323,13 @@ impl<'de> serde::Deserialize<'de> for PartitionedFile {
             PartitionValues,
             Range,
             Statistics,
+            SubFiles,
+            Ranges,
+            ObjectStoreUrl,
+            FileSchemaOverride,
+            Tombstoned,
+            RowGroupStatistics,
+            ExpectedChecksum,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -13294,6 +13357,13 @@ impl<'de> serde::Deserialize<'de> for PartitionedFile {
                             "partitionValues" | "partition_values" => Ok(GeneratedField::PartitionValues),
                             "range" => Ok(GeneratedField::Range),
                             "statistics" => Ok(GeneratedField::Statistics),
+                            "subFiles" | "sub_files" => Ok(GeneratedField::SubFiles),
+                            "ranges" => Ok(GeneratedField::Ranges),
+                            "objectStoreUrl" | "object_store_url" => Ok(GeneratedField::ObjectStoreUrl),
+                            "fileSchemaOverride" | "file_schema_override" => Ok(GeneratedField::FileSchemaOverride),
+                            "tombstoned" => Ok(GeneratedField::Tombstoned),
+                            "rowGroupStatistics" | "row_group_statistics" => Ok(GeneratedField::RowGroupStatistics),
+                            "expectedChecksum" | "expected_checksum" => Ok(GeneratedField::ExpectedChecksum),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -13319,6 +13389,13 @@ impl<'de> serde::Deserialize<'de> for PartitionedFile {
                 let mut partition_values__ = None;
                 let mut range__ = None;
                 let mut statistics__ = None;
+                let mut sub_files__ = None;
+                let mut ranges__ = None;
+                let mut object_store_url__ = None;
+                let mut file_schema_override__ = None;
+                let mut tombstoned__ = None;
+                let mut row_group_statistics__ = None;
+                let mut expected_checksum__ = None;
                 while let Some(k) = map_.next_key()? {
                     match k {
                         GeneratedField::Path => {
@@ -13361,6 +13438,50 @@ impl<'de> serde::Deserialize<'de> for PartitionedFile {
                             }
                             statistics__ = map_.next_value()?;
                         }
+                        GeneratedField::SubFiles => {
+                            if sub_files__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("subFiles"));
+                            }
+                            sub_files__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::Ranges => {
+                            if ranges__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("ranges"));
+                            }
+                            ranges__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::ObjectStoreUrl => {
+                            if object_store_url__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("objectStoreUrl"));
+                            }
+                            object_store_url__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::FileSchemaOverride => {
+                            if file_schema_override__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("fileSchemaOverride"));
+                            }
+                            file_schema_override__ = map_.next_value()?;
+                        }
+                        GeneratedField::Tombstoned => {
+                            if tombstoned__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("tombstoned"));
+                            }
+                            tombstoned__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::RowGroupStatistics => {
+                            if row_group_statistics__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("rowGroupStatistics"));
+                            }
+                            row_group_statistics__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::ExpectedChecksum => {
+                            if expected_checksum__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("expectedChecksum"));
+                            }
+                            expected_checksum__ = 
+                                Some(map_.next_value::<::pbjson::private::BytesDeserialize<_>>()?.0)
+                            ;
+                        }
                     }
                 }
                 Ok(PartitionedFile {
@@ -13370,6 +13491,13 @@ impl<'de> serde::Deserialize<'de> for PartitionedFile {
                     partition_values: partition_values__.unwrap_or_default(),
                     range: range__,
                     statistics: statistics__,
+                    sub_files: sub_files__.unwrap_or_default(),
+                    ranges: ranges__.unwrap_or_default(),
+                    object_store_url: object_store_url__.unwrap_or_default(),
+                    file_schema_override: file_schema_override__,
+                    tombstoned: tombstoned__.unwrap_or_default(),
+                    row_group_statistics: row_group_statistics__.unwrap_or_default(),
+                    expected_checksum: expected_checksum__.unwrap_or_default(),
                 })
             }
         }
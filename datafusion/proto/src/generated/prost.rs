@@ -1904,6 +1904,20 @@ pub struct PartitionedFile {
     pub range: ::core::option::Option<FileRange>,
     #[prost(message, optional, tag = "6")]
     pub statistics: ::core::option::Option<super::datafusion_common::Statistics>,
+    #[prost(message, repeated, tag = "7")]
+    pub sub_files: ::prost::alloc::vec::Vec<FileRange>,
+    #[prost(message, repeated, tag = "8")]
+    pub ranges: ::prost::alloc::vec::Vec<FileRange>,
+    #[prost(string, tag = "9")]
+    pub object_store_url: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "10")]
+    pub file_schema_override: ::core::option::Option<super::datafusion_common::Schema>,
+    #[prost(bool, tag = "11")]
+    pub tombstoned: bool,
+    #[prost(message, repeated, tag = "12")]
+    pub row_group_statistics: ::prost::alloc::vec::Vec<super::datafusion_common::Statistics>,
+    #[prost(bytes = "vec", tag = "13")]
+    pub expected_checksum: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct FileRange {
@@ -554,7 +554,7 @@ impl TryFrom<&protobuf::PartitionedFile> for PartitionedFile {
     type Error = DataFusionError;
 
     fn try_from(val: &protobuf::PartitionedFile) -> Result<Self, Self::Error> {
-        Ok(PartitionedFile {
+        let file = PartitionedFile {
             object_meta: ObjectMeta {
                 location: Path::from(val.path.as_str()),
                 last_modified: Utc.timestamp_nanos(val.last_modified_ns as i64),
@@ -575,7 +575,51 @@ impl TryFrom<&protobuf::PartitionedFile> for PartitionedFile {
                 .transpose()?,
             extensions: None,
             metadata_size_hint: None,
-        })
+            object_store_url: None,
+            sub_files: vec![],
+            file_schema_override: None,
+            row_group_statistics: None,
+            ranges: vec![],
+            tombstoned: false,
+            expected_checksum: None,
+        };
+        let file = match val.object_store_url.is_empty() {
+            false => {
+                file.with_object_store_url(ObjectStoreUrl::parse(&val.object_store_url)?)
+            }
+            true => file,
+        };
+        let file = match &val.file_schema_override {
+            Some(schema) => file.with_file_schema_override(Arc::new(schema.try_into()?)),
+            None => file,
+        };
+        let file = match val.row_group_statistics.is_empty() {
+            false => file.with_row_group_statistics(
+                val.row_group_statistics
+                    .iter()
+                    .map(|v| v.try_into().map(Arc::new))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            true => file,
+        };
+        let file = match val.expected_checksum.is_empty() {
+            false => file.with_expected_checksum(val.expected_checksum.clone()),
+            true => file,
+        };
+        Ok(file
+            .with_tombstoned(val.tombstoned)
+            .with_sub_files(
+                val.sub_files
+                    .iter()
+                    .map(|v| v.try_into())
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+            .with_ranges(
+                val.ranges
+                    .iter()
+                    .map(|v| v.try_into())
+                    .collect::<Result<Vec<_>, _>>()?,
+            ))
     }
 }
 
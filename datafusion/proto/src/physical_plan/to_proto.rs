@@ -452,6 +452,34 @@ impl TryFrom<&PartitionedFile> for protobuf::PartitionedFile {
                 .collect::<Result<Vec<_>, _>>()?,
             range: pf.range.as_ref().map(|r| r.try_into()).transpose()?,
             statistics: pf.statistics.as_ref().map(|s| s.as_ref().into()),
+            sub_files: pf
+                .sub_files
+                .iter()
+                .map(|r| r.try_into())
+                .collect::<Result<Vec<_>, _>>()?,
+            ranges: pf
+                .ranges
+                .iter()
+                .map(|r| r.try_into())
+                .collect::<Result<Vec<_>, _>>()?,
+            object_store_url: pf
+                .object_store_url
+                .as_ref()
+                .map(|url| url.to_string())
+                .unwrap_or_default(),
+            file_schema_override: pf
+                .file_schema_override
+                .as_ref()
+                .map(|schema| schema.as_ref().try_into())
+                .transpose()?,
+            tombstoned: pf.tombstoned,
+            row_group_statistics: pf
+                .row_group_statistics
+                .iter()
+                .flatten()
+                .map(|s| s.as_ref().into())
+                .collect(),
+            expected_checksum: pf.expected_checksum.clone().unwrap_or_default(),
         })
     }
 }
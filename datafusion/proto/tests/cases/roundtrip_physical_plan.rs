@@ -44,7 +44,7 @@ use datafusion::datasource::empty::EmptyTable;
 use datafusion::datasource::file_format::csv::CsvSink;
 use datafusion::datasource::file_format::json::JsonSink;
 use datafusion::datasource::file_format::parquet::ParquetSink;
-use datafusion::datasource::listing::{ListingTableUrl, PartitionedFile};
+use datafusion::datasource::listing::{FileRange, ListingTableUrl, PartitionedFile};
 use datafusion::datasource::object_store::ObjectStoreUrl;
 use datafusion::datasource::physical_plan::{
     wrap_partition_type_in_dict, wrap_partition_value_in_dict, FileGroup,
@@ -808,6 +808,66 @@ async fn roundtrip_parquet_exec_with_table_partition_cols() -> Result<()> {
     roundtrip_test(DataSourceExec::from_data_source(scan_config))
 }
 
+#[test]
+fn roundtrip_partitioned_file_options() -> Result<()> {
+    let file_schema = Arc::new(Schema::new(vec![
+        Field::new("a", DataType::Int64, true),
+        Field::new("b", DataType::Utf8, false),
+    ]));
+    let row_group_statistics = |num_rows| {
+        Arc::new(
+            Statistics::new_unknown(&file_schema)
+                .with_num_rows(Precision::Exact(num_rows)),
+        )
+    };
+    let file = PartitionedFile::new("/path/to/packed.parquet".to_string(), 1024)
+        .with_object_store_url(ObjectStoreUrl::parse("s3://other-bucket")?)
+        .with_sub_files(vec![
+            FileRange { start: 0, end: 512 },
+            FileRange {
+                start: 512,
+                end: 1024,
+            },
+        ])
+        .with_ranges(vec![
+            FileRange { start: 0, end: 128 },
+            FileRange {
+                start: 512,
+                end: 640,
+            },
+        ])
+        .with_file_schema_override(Arc::clone(&file_schema))
+        .with_row_group_statistics(vec![
+            row_group_statistics(10),
+            row_group_statistics(20),
+        ])
+        .with_tombstoned(true)
+        .with_expected_checksum(vec![0xe3, 0x06, 0x92, 0x83]);
+
+    let proto = protobuf::PartitionedFile::try_from(&file)?;
+    let result = PartitionedFile::try_from(&proto)?;
+    assert_eq!(result.object_store_url, file.object_store_url);
+    assert_eq!(result.sub_files, file.sub_files);
+    assert_eq!(result.ranges, file.ranges);
+    assert_eq!(result.file_schema_override, Some(file_schema));
+    assert_eq!(result.row_group_statistics, file.row_group_statistics);
+    assert!(result.tombstoned);
+    assert_eq!(result.expected_checksum, file.expected_checksum);
+
+    // options that are not set are not set after a roundtrip either
+    let file = PartitionedFile::new("/path/to/file.parquet".to_string(), 1024);
+    let proto = protobuf::PartitionedFile::try_from(&file)?;
+    let result = PartitionedFile::try_from(&proto)?;
+    assert_eq!(result.object_store_url, None);
+    assert!(result.sub_files.is_empty());
+    assert!(result.ranges.is_empty());
+    assert_eq!(result.file_schema_override, None);
+    assert_eq!(result.row_group_statistics, None);
+    assert!(!result.tombstoned);
+    assert_eq!(result.expected_checksum, None);
+    Ok(())
+}
+
 #[test]
 fn roundtrip_parquet_exec_with_custom_predicate_expr() -> Result<()> {
     let file_schema =
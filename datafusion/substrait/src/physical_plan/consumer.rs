@@ -132,6 +132,13 @@ pub async fn from_substrait_rel(
                             statistics: None,
                             extensions: None,
                             metadata_size_hint: None,
+                            object_store_url: None,
+                            sub_files: vec![],
+                            file_schema_override: None,
+                            row_group_statistics: None,
+                            ranges: vec![],
+                            tombstoned: false,
+                            expected_checksum: None,
                         };
 
                         let part_index = file.partition_index as usize;